@@ -1,206 +1,1316 @@
 mod action;
+mod archive;
+pub(crate) mod atomic_file;
+mod clipboard;
 mod component;
-mod config;
+pub(crate) mod config;
+mod decode;
+mod diff;
+pub mod embed;
+mod format;
+mod input;
 mod job;
+mod journal;
+mod jq;
+mod lock;
 mod math;
+mod paths;
+mod register;
+mod remote;
+mod replay;
+#[cfg(feature = "testing")]
+pub mod testing;
+mod tool;
 
 use std::{
-    fs::File,
-    io::{Write, stdout},
-    process::Command,
-    time::Duration,
+    borrow::Cow,
+    collections::BTreeMap,
+    fs::{self, File},
+    io::{ErrorKind, IsTerminal, Read, Seek, SeekFrom, Stderr, Stdout, Write, stderr, stdout},
+    path::{Path, PathBuf},
+    process::Stdio,
+    sync::{Arc, Mutex},
+    thread,
+    time::{Duration, Instant, SystemTime},
 };
 
 use action::{
     Action, Actions, ConfirmAction, EditJobAction, JobAction, NavigationAction, WorkSpaceAction,
 };
-use component::workspace::{WorkSpace, WorkSpaceState};
+use atomic_file::AtomicFile;
+use component::{
+    tab_bar::TabBar,
+    workspace::{WorkSpace, WorkSpaceState, WorkTreeNode},
+};
 use config::Config;
 use crossterm::{
     ExecutableCommand,
-    event::{self, Event, KeyCode},
+    event::{
+        DisableBracketedPaste, DisableMouseCapture, EnableBracketedPaste, EnableMouseCapture,
+        Event, KeyCode,
+    },
     terminal::{EnterAlternateScreen, LeaveAlternateScreen, disable_raw_mode, enable_raw_mode},
 };
-use job::Job;
-use ratatui::{DefaultTerminal, Frame};
+use indexmap::IndexMap;
+use input::Input;
+use job::{Job, Progress, ProgressReader};
+use journal::Journal;
+use lock::DocumentLock;
+use ratatui::{
+    Frame,
+    layout::{Constraint, Layout},
+    prelude::CrosstermBackend,
+};
+use remote::Remote;
+use replay::Replay;
+use tempfile::TempPath;
 
-use crate::{container::node::Node, error::LoadError};
+use crate::{
+    container::{
+        encoding::{Encoding, Indent, LineEnding},
+        gron,
+        join::{self, JoinKind},
+        merge,
+        ndjson::RecordReader,
+        node::Node,
+        parser::{ParseOptions, Repairs},
+        pointer::KeyPath,
+        validation::Schema,
+    },
+    error::{JobError, LoadError},
+};
 
 struct GlobalState {
     exit: bool,
 }
 
-pub struct CliApp {
-    state: GlobalState,
+/// A document open in its own tab, with everything needed to save it.
+struct Tab {
     worktree_state: WorkSpaceState,
     worktree: WorkSpace,
-    output_file_name: String,
+    output_file_name: Option<String>,
+    output_encoding: Encoding,
+    output_line_ending: LineEnding,
+    output_indent: Indent,
+    /// Tells other instances this one has the input open, when it is a local file.
+    lock: Option<DocumentLock>,
+    /// The output file as it was when loaded or last saved, to notice other programs writing it.
+    output_stamp: Option<FileStamp>,
+    /// Where unsaved changes are autosaved, when enabled for a local output file.
+    journal: Option<Journal>,
+    /// Scratch file of the edit in `$EDITOR`, kept for retries until the edit lands.
+    editor_buffer: Option<TempPath>,
+    /// Jobs started from this tab, whose actions wait while another tab is shown.
     jobs: Vec<Job>,
 }
 
-impl CliApp {
-    pub fn new(input_file_name: String, output_file_name: String) -> std::io::Result<Self> {
-        let initial_load_job = Job::new(move || {
-            let file = File::open(&input_file_name)?;
-            let file_root = Node::load(file).map_err(|error| {
-                std::io::Error::new(std::io::ErrorKind::InvalidData, error.to_string())
+impl Tab {
+    /// Opens `input_file_name`, or else an empty scratch document.
+    fn open(
+        input_file_name: Option<String>,
+        output_file_name: Option<String>,
+        follow: bool,
+        mut config: Config,
+        remotes: &BTreeMap<String, Remote>,
+    ) -> std::io::Result<Self> {
+        let archive_member = input_file_name.as_deref().and_then(archive::split_member);
+        if archive_member.is_some() && config.no_shell {
+            return Err(std::io::Error::other(
+                "Opening archive members runs unzip or tar, which no_shell disables",
+            ));
+        }
+        // Defaults to overwriting the input, unless it is a followed log or inside an archive.
+        let output_file_name = output_file_name.or_else(|| {
+            input_file_name
+                .clone()
+                .filter(|_| !follow && archive_member.is_none())
+        });
+        let archive_member =
+            archive_member.map(|(archive, member)| (archive.to_string(), member.to_string()));
+        let input_remote = input_file_name
+            .as_deref()
+            .and_then(|input| remote::find(&config.remotes, input))
+            .cloned();
+        if input_remote.is_some() && config.no_shell {
+            return Err(std::io::Error::other(
+                "Opening URLs runs a fetch helper, which no_shell disables",
+            ));
+        }
+        let parse_options = config.parse_options();
+        let line_ending = config.line_ending;
+        let autosave = config.autosave;
+        let Some(input_file_name) = input_file_name else {
+            return Ok(Self {
+                output_indent: config.indent.resolve(&[]),
+                worktree: WorkSpace::new(Node::from_object(IndexMap::new()), config),
+                worktree_state: WorkSpaceState::default(),
+                output_file_name,
+                output_encoding: Encoding::default(),
+                output_line_ending: line_ending.resolve(&[]),
+                lock: None,
+                output_stamp: None,
+                journal: None,
+                editor_buffer: None,
+                jobs: Vec::new(),
+            });
+        };
+
+        if follow {
+            return Ok(Self {
+                output_indent: config.indent.resolve(&[]),
+                worktree: WorkSpace::new(Node::from_array(Vec::new()), config),
+                worktree_state: WorkSpaceState::default(),
+                output_file_name,
+                output_encoding: Encoding::default(),
+                output_line_ending: line_ending.resolve(&[]),
+                lock: None,
+                output_stamp: None,
+                journal: None,
+                editor_buffer: None,
+                jobs: vec![follow_job(
+                    input_file_name,
+                    RecordReader::default(),
+                    parse_options,
+                )],
+            });
+        }
+
+        let (encoding, head) = read_file_head(&input_file_name);
+        let output_encoding = if config.preserve_encoding {
+            encoding
+        } else {
+            Encoding::default()
+        };
+        let output_line_ending = line_ending.resolve(&head);
+        // Sizes and previews follow the indentation the document is saved with.
+        config.indent = config.indent.resolve(&head);
+        let output_indent = config.indent;
+
+        let mut worktree = WorkSpace::new(Node::null(), config);
+        let mut worktree_state = WorkSpaceState::default();
+        let mut lock = output_file_name
+            .as_ref()
+            .filter(|&output| *output == input_file_name)
+            .and_then(|_| DocumentLock::new(&input_file_name));
+        let mut is_shared = false;
+        if let Some(lock) = &mut lock {
+            let holder = lock.holder();
+            is_shared = holder.is_some();
+            match holder {
+                Some(holder) if holder.is_edited => worktree.handle_action(
+                    &mut worktree_state,
+                    &mut Actions::new(),
+                    WorkSpaceAction::ReadOnly(ConfirmAction::Request(format!(
+                        "Another jedit (pid {}) has unsaved changes to {input_file_name}.",
+                        holder.pid
+                    ))),
+                )?,
+                Some(holder) => {
+                    worktree.push_message(format!("Also open in jedit (pid {})", holder.pid));
+                    let _ = lock.acquire(false);
+                }
+                None => {
+                    let _ = lock.acquire(false);
+                }
+            }
+        }
+
+        let local_output = output_file_name
+            .as_deref()
+            .filter(|output| *output != STDOUT_OUTPUT && remote::find(remotes, output).is_none());
+        let output_stamp = local_output.and_then(FileStamp::read);
+        // Another instance of the same document writes the same journal.
+        let journal = autosave
+            .zip(local_output)
+            .filter(|_| !is_shared)
+            .map(|(interval, output)| Journal::new(output, interval));
+        if let Some(journal) = &journal
+            && journal.is_newer_than(&input_file_name)
+        {
+            worktree.handle_action(
+                &mut worktree_state,
+                &mut Actions::new(),
+                WorkSpaceAction::Recover(ConfirmAction::Request(journal.path().to_string())),
+            )?;
+        }
+        let progress = Arc::new(Progress::default());
+        let initial_load_job = Job::with_progress("Load", progress.clone(), move || {
+            let loaded = match (archive_member, input_remote) {
+                (Some((archive, member)), _) => Node::load_with_repairs(
+                    archive::read_member(&archive, &member)?.as_slice(),
+                    &parse_options,
+                ),
+                (None, Some(remote)) => Node::load_with_repairs(
+                    remote.fetch(&input_file_name)?.as_slice(),
+                    &parse_options,
+                ),
+                (None, None) => {
+                    let file = File::open(&input_file_name)?;
+                    progress.set_total(file.metadata()?.len() as usize);
+                    let reader = ProgressReader::new(file, progress.clone());
+                    Node::load_with_repairs(reader, &parse_options)
+                }
+            };
+            // Nothing is shown yet, so the document is closed.
+            if progress.is_cancelled() {
+                return Ok(Action::Exit(ConfirmAction::Request(())));
+            }
+            let (file_root, repaired) = loaded.map_err(|error| match error {
+                LoadError::IO(error) => error,
+                error => std::io::Error::new(std::io::ErrorKind::InvalidData, error.to_string()),
             })?;
 
             Ok(WorkSpaceAction::Load {
                 node: file_root,
                 is_edit: false,
+                repaired,
             }
             .into())
         });
 
-        let cli_app = Self {
-            worktree: WorkSpace::new(Node::null(), Config::load()),
-            worktree_state: WorkSpaceState::default(),
-            state: GlobalState { exit: false },
+        Ok(Self {
+            worktree,
+            worktree_state,
             output_file_name,
+            output_encoding,
+            output_line_ending,
+            output_indent,
+            lock,
+            output_stamp,
+            journal,
+            editor_buffer: None,
             jobs: vec![initial_load_job],
+        })
+    }
+
+    /// Name shown in the tab bar.
+    fn name(&self) -> &str {
+        self.output_file_name.as_deref().unwrap_or("untitled")
+    }
+}
+
+pub struct CliApp {
+    state: GlobalState,
+    /// The document shown.
+    tab: Tab,
+    /// The other open documents, in tab order with the shown one left out.
+    tabs: Vec<Tab>,
+    /// Position of the shown document among all tabs.
+    active: usize,
+    no_shell: bool,
+    parse_options: ParseOptions,
+    /// Fetch and put helpers by URL scheme, for saving to URLs.
+    remotes: BTreeMap<String, Remote>,
+    frame_time: Duration,
+    /// Whether to capture the mouse while the editor is shown.
+    mouse: bool,
+    /// Whether to copy the output file to `file.json.bak` before saving over it.
+    backup: bool,
+    /// Whether to save without any whitespace, rather than indented.
+    minify: bool,
+    editor_buffer_dir: Option<PathBuf>,
+    /// Recorded keys still to be fed in, from `--replay`.
+    replay: Option<Replay>,
+    /// Set when an event, action or running job may have changed the screen; frames are only
+    /// drawn then, so an idle editor sends nothing over the wire. Within a frame, panes showing
+    /// the same thing as before reuse their last render, and the terminal only gets the cells
+    /// that differ from the previous frame.
+    needs_redraw: bool,
+    /// Document last saved to `-`, written to stdout on exit.
+    stdout_document: Arc<Mutex<Option<Vec<u8>>>>,
+}
+
+impl CliApp {
+    pub fn new(
+        input_file_name: Option<String>,
+        output_file_name: Option<String>,
+        no_shell: bool,
+        follow: bool,
+    ) -> std::io::Result<Self> {
+        let mut config = Config::load();
+        config.no_shell |= no_shell;
+        // Without helpers, saving to a URL-like name writes a local file.
+        let remotes = if config.no_shell {
+            BTreeMap::new()
+        } else {
+            config.remotes.clone()
         };
+        let parse_options = config.parse_options();
+        let frame_time = config.frame_time;
+        let mouse = config.mouse;
+        let backup = config.backup;
+        let minify = config.minify;
+        let editor_buffer_dir = config.editor_buffer_dir.clone();
+        let tab = Tab::open(input_file_name, output_file_name, follow, config, &remotes)?;
+
+        Ok(Self {
+            state: GlobalState { exit: false },
+            tab,
+            tabs: Vec::new(),
+            active: 0,
+            no_shell,
+            parse_options,
+            remotes,
+            frame_time,
+            mouse,
+            backup,
+            minify,
+            editor_buffer_dir,
+            replay: None,
+            needs_redraw: true,
+            stdout_document: Arc::default(),
+        })
+    }
+
+    /// Opens `input_file_name` in a new last tab, saving over it, and reloads it on changes when
+    /// `watch` is set.
+    pub fn open(&mut self, input_file_name: String, watch: bool) -> std::io::Result<()> {
+        let mut config = Config::load();
+        config.no_shell |= self.no_shell;
+        let mut tab = Tab::open(
+            Some(input_file_name.clone()),
+            None,
+            false,
+            config,
+            &self.remotes,
+        )?;
+        if watch {
+            tab.jobs.push(watch_job(input_file_name));
+        }
+        self.tabs.push(tab);
+        Ok(())
+    }
+
+    /// Opens the document read from `reader`, such as stdin. It can only be saved to
+    /// `output_file_name`, as there is no input file to overwrite.
+    pub fn from_reader(
+        reader: Box<dyn Read + Send + Sync>,
+        output_file_name: String,
+        no_shell: bool,
+    ) -> std::io::Result<Self> {
+        let mut cli_app = Self::new(None, Some(output_file_name), no_shell, false)?;
+        let parse_options = cli_app.parse_options;
+        cli_app.tab.jobs.push(Job::new("Load", move || {
+            let (node, repaired) =
+                Node::load_with_repairs(reader, &parse_options).map_err(|error| match error {
+                    LoadError::IO(error) => error,
+                    error => {
+                        std::io::Error::new(std::io::ErrorKind::InvalidData, error.to_string())
+                    }
+                })?;
+            Ok(WorkSpaceAction::Load {
+                node,
+                is_edit: false,
+                repaired,
+            }
+            .into())
+        }));
+        Ok(cli_app)
+    }
+
+    /// Opens the three-way merge of `ours` and `theirs` as an edited scratch document.
+    pub fn merge(
+        base: String,
+        ours: String,
+        theirs: String,
+        output_file_name: Option<String>,
+    ) -> std::io::Result<Self> {
+        let output_file_name = output_file_name.unwrap_or_else(|| ours.clone());
+        let mut cli_app = Self::new(None, Some(output_file_name), false, false)?;
+        let parse_options = cli_app.parse_options;
+        cli_app.tab.jobs.push(Job::new("Merge", move || {
+            let load = |path: &str| {
+                Node::load_file(path, &parse_options).map_err(|error| match error {
+                    LoadError::IO(error) => error,
+                    error => std::io::Error::new(
+                        std::io::ErrorKind::InvalidData,
+                        LoadError::InFile(path.to_string(), Box::new(error)).to_string(),
+                    ),
+                })
+            };
+            let (node, conflicts) = merge::merge(&load(&base)?, &load(&ours)?, &load(&theirs)?);
+            Ok(WorkSpaceAction::Merged { node, conflicts }.into())
+        }));
         Ok(cli_app)
     }
 
+    /// Compares the tree against the document in `path` once it is loaded.
+    pub fn compare(&mut self, path: String) {
+        self.tab.jobs.push(compare_job(path, self.parse_options));
+    }
+
+    /// Reloads the document whenever another program changes `path`.
+    pub fn watch(&mut self, path: String) {
+        self.tab.jobs.push(watch_job(path));
+    }
+
+    /// Saves without copying the output file to `file.json.bak` first, whatever the config says.
+    pub fn disable_backup(&mut self) {
+        self.backup = false;
+    }
+
+    /// Saves without any whitespace, whatever the config says.
+    pub fn minify(&mut self) {
+        self.minify = true;
+    }
+
+    /// Validates the document against the JSON Schema in `path` when pressing `V`.
+    pub fn schema(&mut self, path: &str) -> std::io::Result<()> {
+        let schema = Schema::load_file(path).map_err(|error| match error {
+            LoadError::IO(error) => error,
+            error => std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                LoadError::InFile(path.to_string(), Box::new(error)).to_string(),
+            ),
+        })?;
+        self.tab.worktree.set_schema(schema);
+        Ok(())
+    }
+
+    /// Feeds the keys recorded in `path` in, one every `interval`.
+    pub fn replay(&mut self, path: &str, interval: Duration) -> std::io::Result<()> {
+        self.replay = Some(Replay::load(path, interval)?);
+        Ok(())
+    }
+
     pub fn run(&mut self) -> std::io::Result<()> {
-        let mut terminal = Terminal::new();
+        let mut terminal = Terminal::new(self.mouse);
 
-        self.worktree.handle_action(
-            &mut self.worktree_state,
-            &mut Actions::new(),
-            NavigationAction::TogglePreview.into(),
-        )?;
+        for tab in std::iter::once(&mut self.tab).chain(&mut self.tabs) {
+            tab.worktree.handle_action(
+                &mut tab.worktree_state,
+                &mut Actions::new(),
+                NavigationAction::TogglePreview.into(),
+            )?;
+        }
 
         while !self.state.exit {
-            terminal.0.draw(|frame| self.draw(frame))?;
+            if self.needs_redraw {
+                terminal.terminal.draw(|frame| self.draw(frame))?;
+                self.needs_redraw = false;
+            }
             self.handle_event(&mut terminal)?;
+            if let Some(lock) = &mut self.tab.lock {
+                let _ = lock.update(self.tab.worktree.is_edited());
+            }
+        }
+
+        for tab in std::iter::once(&self.tab).chain(&self.tabs) {
+            if let Some(lock) = &tab.lock {
+                lock.release();
+            }
         }
 
+        drop(terminal);
+        let document = self
+            .stdout_document
+            .lock()
+            .expect("poisoned stdout document")
+            .take();
+        if let Some(document) = document {
+            let mut stdout = stdout();
+            stdout.write_all(&document)?;
+            stdout.flush()?;
+        }
         Ok(())
     }
 
     fn draw(&mut self, frame: &mut Frame) {
-        frame.render_stateful_widget(&self.worktree, frame.area(), &mut self.worktree_state);
+        let mut area = frame.area();
+        if !self.tabs.is_empty() {
+            let [tab_bar, rest] =
+                Layout::vertical([Constraint::Length(1), Constraint::Fill(1)]).areas(area);
+            let (before, after) = self.tabs.split_at(self.active);
+            let tabs = before
+                .iter()
+                .chain(std::iter::once(&self.tab))
+                .chain(after)
+                .map(|tab| (tab.name().to_string(), tab.worktree.is_edited()))
+                .collect();
+            frame.render_widget(
+                &TabBar {
+                    tabs,
+                    selected: self.active,
+                },
+                tab_bar,
+            );
+            area = rest;
+        }
+        frame.render_stateful_widget(&self.tab.worktree, area, &mut self.tab.worktree_state);
+    }
+
+    /// Shows the next tab, or the previous one when `previous`, wrapping around.
+    fn switch_tab(&mut self, previous: bool) {
+        if self.tabs.is_empty() {
+            return;
+        }
+        let n_tabs = self.tabs.len() + 1;
+        let target = if previous {
+            (self.active + n_tabs - 1) % n_tabs
+        } else {
+            (self.active + 1) % n_tabs
+        };
+        // Parked tabs leave out the shown one, so those after it sit one place earlier.
+        let shown = self.tabs.remove(if target < self.active {
+            target
+        } else {
+            target - 1
+        });
+        let hidden = std::mem::replace(&mut self.tab, shown);
+        let hidden_index = if target < self.active {
+            self.active - 1
+        } else {
+            self.active
+        };
+        self.tabs.insert(hidden_index, hidden);
+        self.active = target;
+    }
+
+    /// Closes the shown tab in favour of the next one, or the previous one for the last tab.
+    fn close_tab(&mut self) {
+        let next = if self.active < self.tabs.len() {
+            self.tabs.remove(self.active)
+        } else {
+            self.active -= 1;
+            self.tabs.remove(self.active)
+        };
+        let closed = std::mem::replace(&mut self.tab, next);
+        if let Some(lock) = &closed.lock {
+            lock.release();
+        }
+        self.tab
+            .worktree
+            .push_message(format!("Closed {}", closed.name()));
+    }
+
+    /// How long to sleep without input: a frame while the loading popup animates or keys are
+    /// replayed, until the next autosave while there are unsaved changes, or for good, as jobs
+    /// wake the editor when they finish.
+    fn wait_time(&self) -> Option<Duration> {
+        let is_replaying = self.replay.as_ref().is_some_and(|replay| !replay.is_done());
+        if is_replaying || self.tab.jobs.iter().any(|job| !job.is_background()) {
+            return Some(self.frame_time);
+        }
+        self.tab
+            .journal
+            .as_ref()
+            .filter(|_| self.tab.worktree.is_edited())
+            .and_then(|journal| journal.due_in(Instant::now()))
+    }
+
+    /// Replayed keys wait for running jobs, so that they land where they did when recorded.
+    fn next_replayed_event(&mut self) -> Option<Event> {
+        if self.tab.jobs.iter().any(|job| !job.is_background()) {
+            return None;
+        }
+        self.replay.as_mut()?.next(Instant::now())
     }
 
     fn handle_event(&mut self, terminal: &mut Terminal) -> std::io::Result<()> {
         let mut actions = Actions::new();
-        if event::poll(FRAME_TIME)? {
-            let event = event::read()?;
+        let event = match terminal.input.next(self.wait_time())? {
+            Some(event) => Some(event),
+            None => self.next_replayed_event(),
+        };
+        if let Some(event) = event {
             if global_exit_handler(&event) {
                 self.state.exit = true;
                 return Ok(());
             }
 
-            self.worktree.handle_event(&mut actions, event);
+            self.tab
+                .worktree
+                .handle_event(&self.tab.worktree_state, &mut actions, event);
+            self.needs_redraw = true;
         }
 
         let mut jobs = Vec::new();
-        std::mem::swap(&mut jobs, &mut self.jobs);
-        jobs.into_iter()
-            .filter_map(|job| {
-                if job.is_done() {
-                    Some(job.action())
-                } else {
-                    self.jobs.push(job);
-                    None
+        std::mem::swap(&mut jobs, &mut self.tab.jobs);
+        let mut updates = Vec::new();
+        for job in jobs {
+            let is_done = job.is_done();
+            updates.extend(job.updates());
+            if !is_done {
+                self.tab.jobs.push(job);
+                continue;
+            }
+            match job.action() {
+                Ok(action) => actions.push(action),
+                Err(JobError::IO(error)) => return Err(error),
+                // Without a document, saving would overwrite the input with null.
+                Err(error @ JobError::Panicked { name: "Load", .. }) => {
+                    return Err(std::io::Error::other(error.to_string()));
                 }
-            })
-            .collect::<Result<Vec<_>, _>>()?
-            .into_iter()
-            .for_each(|action| actions.push(action));
+                Err(error) => actions.push(WorkSpaceAction::Error(error.to_string()).into()),
+            }
+        }
+        // Streamed actions go last, so that they see what finished jobs such as saves did.
+        for update in updates {
+            actions.push(update);
+        }
 
         while let Some(action) = actions.next() {
+            self.needs_redraw = true;
             match action {
                 Action::Exit(confirm_action) => {
-                    self.state.exit = self.worktree.maybe_exit(confirm_action);
+                    if self.tab.worktree.maybe_exit(confirm_action) {
+                        if let Some(journal) = &self.tab.journal {
+                            journal.remove();
+                        }
+                        if self.tabs.is_empty() {
+                            self.state.exit = true;
+                        } else {
+                            self.close_tab();
+                        }
+                    }
                     return Ok(());
                 }
-                Action::Workspace(workspace_action) => self.worktree.handle_action(
-                    &mut self.worktree_state,
-                    &mut actions,
-                    workspace_action,
-                )?,
+                Action::SwitchTab(previous) => self.switch_tab(previous),
+                Action::CancelJobs => self.tab.jobs.iter().for_each(Job::cancel),
+                Action::Workspace(workspace_action) => {
+                    match &workspace_action {
+                        WorkSpaceAction::SaveDone(_) => {
+                            self.tab.output_stamp = self
+                                .tab
+                                .output_file_name
+                                .as_deref()
+                                .and_then(FileStamp::read);
+                            if let Some(journal) = &self.tab.journal {
+                                journal.remove();
+                            }
+                        }
+                        WorkSpaceAction::Autosaved(error) => {
+                            if error.is_some() {
+                                self.tab.journal = None;
+                            } else if let Some(journal) = &mut self.tab.journal {
+                                journal.finish_write();
+                                // Saved while writing the journal.
+                                if !self.tab.worktree.is_edited() {
+                                    journal.remove();
+                                }
+                            }
+                        }
+                        WorkSpaceAction::InputChanged(ConfirmAction::Request(path))
+                            if self.is_own_write(path) =>
+                        {
+                            continue;
+                        }
+                        WorkSpaceAction::Reloaded { path, .. }
+                            if self.tab.output_file_name.as_ref() == Some(path) =>
+                        {
+                            self.tab.output_stamp = FileStamp::read(path);
+                        }
+                        // The edit is read, or given up on.
+                        WorkSpaceAction::ApplyEdit(ConfirmAction::Request(_))
+                        | WorkSpaceAction::EditError(ConfirmAction::Confirm(false)) => {
+                            self.tab.editor_buffer = None;
+                        }
+                        _ => {}
+                    }
+                    self.tab.worktree.handle_action(
+                        &mut self.tab.worktree_state,
+                        &mut actions,
+                        workspace_action,
+                    )?;
+                }
                 Action::ExecuteJob(job) => {
-                    if let Some(job) = self.execute_job(terminal, job)? {
-                        self.jobs.push(job);
+                    if let Some(job) = self.execute_job(terminal, &mut actions, job)? {
+                        self.tab.jobs.push(job);
                     }
                 }
             }
         }
 
-        self.worktree.set_loading(!self.jobs.is_empty());
+        let is_loading = self.tab.jobs.iter().any(|job| !job.is_background());
+        if let Some(journal) = &mut self.tab.journal
+            && self.tab.worktree.is_edited()
+            && !is_loading
+            && journal.start_write(Instant::now())
+        {
+            self.tab.jobs.push(journal_job(
+                journal.path().to_string(),
+                self.tab.worktree.file_root().clone(),
+            ));
+        }
+
+        // The loading popup animates while jobs run.
+        self.needs_redraw |= is_loading;
+        self.tab.worktree.set_loading(is_loading);
+        self.tab.worktree.set_has_tabs(!self.tabs.is_empty());
+        self.tab
+            .worktree
+            .set_progress(self.tab.jobs.iter().find_map(Job::progress));
         Ok(())
     }
 
-    fn execute_job(&self, terminal: &mut Terminal, job: JobAction) -> std::io::Result<Option<Job>> {
+    /// Whether `path` changed because this instance is saving it or saved it last.
+    fn is_own_write(&self, path: &str) -> bool {
+        self.tab.output_file_name.as_deref() == Some(path)
+            && (self.tab.jobs.iter().any(|job| !job.is_background())
+                || FileStamp::read(path) == self.tab.output_stamp)
+    }
+
+    fn execute_job(
+        &mut self,
+        terminal: &mut Terminal,
+        actions: &mut Actions,
+        job: JobAction,
+    ) -> std::io::Result<Option<Job>> {
         let job = match job {
-            JobAction::Edit(EditJobAction::Init) => {
-                let Some(node) = self.worktree.selected_node(&self.worktree_state) else {
+            JobAction::Edit(EditJobAction::Init(command)) => {
+                let Some(node) = self.tab.worktree.selected_node(&self.tab.worktree_state) else {
                     return Ok(None);
                 };
-                let node = NodeJob(node);
-                Job::new(move || {
-                    let mut file = File::create(EDITOR_BUFFER)?;
-                    let _ = &node;
-                    let node = unsafe { node.0.as_ref().expect("invalid pointer to node") };
+                let (mut file, path) = match paths::editor_buffer(self.editor_buffer_dir.as_deref())
+                {
+                    Ok(buffer) => buffer,
+                    Err(error) => {
+                        actions.push(
+                            WorkSpaceAction::Error(format!(
+                                "Failed to create the editor buffer: {error}"
+                            ))
+                            .into(),
+                        );
+                        return Ok(None);
+                    }
+                };
+                self.tab.editor_buffer = Some(path);
+                // A snapshot sharing the tree, so that later edits leave the buffer as it was.
+                let node = node.clone();
+                let indent = self.tab.output_indent;
+                let progress = Arc::new(Progress::default());
+                Job::with_progress("Edit", progress.clone(), move || {
                     let content = node
-                        .to_string_pretty()
+                        .to_string_indented(indent)
                         .expect("invalid internal representation");
-                    file.write_all(content.as_bytes())?;
-                    Ok(JobAction::Edit(EditJobAction::Open).into())
+                    progress.set_total(content.len());
+                    match write_chunked(&mut file, content.as_bytes(), &progress) {
+                        Err(_) if progress.is_cancelled() => {
+                            return Ok(
+                                WorkSpaceAction::Error(String::from("Edit cancelled")).into()
+                            );
+                        }
+                        res => res?,
+                    }
+                    Ok(JobAction::Edit(EditJobAction::Open(command)).into())
                 })
             }
-            JobAction::Edit(EditJobAction::Open) => {
-                terminal.run_editor(EDITOR_BUFFER)?;
-                Job::new(|| {
-                    let file = File::open(EDITOR_BUFFER)?;
+            JobAction::Edit(EditJobAction::Open(command)) => {
+                let Some(editor_buffer) = &self.tab.editor_buffer else {
+                    return Ok(None);
+                };
+                let editor_buffer = editor_buffer.to_path_buf();
+                terminal.run_editor(command.as_deref(), &editor_buffer)?;
+                let parse_options = self.parse_options;
+                Job::new("Edit", move || {
+                    let file = File::open(editor_buffer)?;
 
-                    match Node::load(file) {
+                    match Node::load_with_repairs(file, &parse_options) {
                         Err(LoadError::IO(error)) => Err(error),
-                        Err(LoadError::SerdeJson(error)) => Ok(WorkSpaceAction::EditError(
-                            ConfirmAction::Request(error.to_string()),
+                        Err(error) => Ok(WorkSpaceAction::EditError(ConfirmAction::Request(
+                            error.to_string(),
+                        ))
+                        .into()),
+                        Ok((node, repaired)) => Ok(WorkSpaceAction::ApplyEdit(
+                            ConfirmAction::Request((node, repaired)),
                         )
                         .into()),
-                        Err(LoadError::DeserializationError(error)) => Ok(
-                            WorkSpaceAction::EditError(ConfirmAction::Request(error.to_string()))
-                                .into(),
-                        ),
-                        Ok(node) => Ok(WorkSpaceAction::Load {
-                            node,
-                            is_edit: true,
-                        }
+                    }
+                })
+            }
+            JobAction::Page { content, command } => {
+                terminal.run_pager(command.as_deref(), &content)?;
+                return Ok(None);
+            }
+            JobAction::Pipe { command, content } => {
+                let output = match terminal.run_filter(&command, &content) {
+                    Ok(output) => output,
+                    Err(error) => {
+                        actions.push(WorkSpaceAction::Error(error.to_string()).into());
+                        return Ok(None);
+                    }
+                };
+                let parse_options = self.parse_options;
+                Job::new("Pipe", move || {
+                    match Node::load_with_repairs(output.as_slice(), &parse_options) {
+                        Ok((node, repaired)) => Ok(WorkSpaceAction::ApplyEdit(
+                            ConfirmAction::Request((node, repaired)),
+                        )
+                        .into()),
+                        Err(error) => Ok(WorkSpaceAction::Error(format!(
+                            "Output of `{command}` is not JSON: {error}"
+                        ))
                         .into()),
                     }
                 })
             }
+            JobAction::ReadOnly(read_only) => {
+                if read_only {
+                    self.tab.output_file_name = None;
+                } else if let Some(lock) = &mut self.tab.lock {
+                    let _ = lock.acquire(self.tab.worktree.is_edited());
+                }
+                return Ok(None);
+            }
+            JobAction::Follow { path, reader } => follow_job(path, reader, self.parse_options),
+            JobAction::Reload(path) => reload_job(path, self.parse_options),
+            JobAction::Recover(path) => recover_job(path, self.parse_options),
+            JobAction::Restat => restat_job(self.tab.worktree.file_root().clone()),
+            JobAction::ExpandAll { selector, keep } => {
+                expand_all_job(self.tab.worktree.file_root().clone(), selector, keep)
+            }
+            JobAction::Compact => compact_job(self.tab.worktree.file_root().clone()),
+            JobAction::Copy { content, run_tools } => {
+                if let Err(error) = clipboard::copy(&content, run_tools) {
+                    actions.push(WorkSpaceAction::Error(format!("Failed to copy: {error}")).into());
+                }
+                return Ok(None);
+            }
+            JobAction::ReadClipboard { replace, run_tools } => {
+                Job::new("Paste", move || match clipboard::paste(run_tools) {
+                    Ok(content) => Ok(WorkSpaceAction::ClipboardRead { content, replace }.into()),
+                    Err(error) => Ok(WorkSpaceAction::Error(error.to_string()).into()),
+                })
+            }
+            JobAction::SaveAs(output_file_name) => {
+                self.tab.output_file_name = Some(output_file_name);
+                self.tab.output_stamp = None;
+                return self.execute_job(terminal, actions, JobAction::Save);
+            }
+            JobAction::Overwrite => {
+                self.tab.output_stamp = None;
+                return self.execute_job(terminal, actions, JobAction::Save);
+            }
             JobAction::Save => {
-                let mut output_file = File::create(&self.output_file_name)?;
-                let content: *const Node = self.worktree.file_root();
-                let content = NodeJob(content);
-                Job::new(move || {
-                    let _ = &content;
-                    let content =
-                        unsafe { content.0.as_ref().expect("invalid pointer to content") };
-                    output_file.write_all(
-                        content
-                            .to_string_pretty()
-                            .expect("invalid internal representation")
-                            .as_bytes(),
-                    )?;
-                    Ok(WorkSpaceAction::SaveDone.into())
+                let Some(output_file_name) = &self.tab.output_file_name else {
+                    actions.push(WorkSpaceAction::SaveAs(ConfirmAction::Request(())).into());
+                    return Ok(None);
+                };
+                if let Some(stamp) = self.tab.output_stamp
+                    && FileStamp::read(output_file_name).is_some_and(|current| current != stamp)
+                {
+                    actions.push(
+                        WorkSpaceAction::Overwrite(ConfirmAction::Request(
+                            output_file_name.clone(),
+                        ))
+                        .into(),
+                    );
+                    return Ok(None);
+                }
+                let remote = remote::find(&self.remotes, output_file_name).cloned();
+                let stdout_document =
+                    (output_file_name == STDOUT_OUTPUT).then(|| self.stdout_document.clone());
+                if self.backup
+                    && remote.is_none()
+                    && stdout_document.is_none()
+                    && let Err(error) = back_up(output_file_name)
+                {
+                    actions.push(
+                        WorkSpaceAction::Error(format!(
+                            "Not saved, backing up {output_file_name} failed: {error}"
+                        ))
+                        .into(),
+                    );
+                    return Ok(None);
+                }
+                let output_file = match (&remote, &stdout_document) {
+                    (None, None) => match AtomicFile::create(output_file_name) {
+                        Ok(output_file) => Some(output_file),
+                        Err(error) => {
+                            actions.push(
+                                WorkSpaceAction::Error(format!(
+                                    "Not saved, {output_file_name} is unchanged: {error}"
+                                ))
+                                .into(),
+                            );
+                            return Ok(None);
+                        }
+                    },
+                    _ => None,
+                };
+                let output_file_name = output_file_name.clone();
+                let content = self.tab.worktree.file_root().clone();
+                let output_encoding = self.tab.output_encoding;
+                let output_line_ending = self.tab.output_line_ending;
+                let output_indent = (!self.minify).then_some(self.tab.output_indent);
+                let progress = Arc::new(Progress::default());
+                Job::with_progress("Save", progress.clone(), move || {
+                    let started = Instant::now();
+                    let content = match output_indent {
+                        Some(indent) => content.to_string_indented(indent),
+                        None => content.to_string_compact(),
+                    }
+                    .expect("invalid internal representation");
+                    let content = output_encoding.encode(&output_line_ending.apply(&content));
+                    progress.set_total(content.len());
+                    match (output_file, remote, stdout_document) {
+                        (Some(mut output_file), ..) => {
+                            let res = write_chunked(&mut output_file, &content, &progress)
+                                .and_then(|()| output_file.commit());
+                            if let Err(error) = res {
+                                return Ok(WorkSpaceAction::Error(format!(
+                                    "Not saved, {output_file_name} is unchanged: {error}"
+                                ))
+                                .into());
+                            }
+                        }
+                        (None, Some(remote), _) => {
+                            let res = remote.put(&output_file_name).and_then(|mut upload| {
+                                write_chunked(&mut upload, &content, &progress)?;
+                                upload.finish()
+                            });
+                            if let Err(error) = res {
+                                return Ok(WorkSpaceAction::Error(error.to_string()).into());
+                            }
+                        }
+                        (None, None, Some(stdout_document)) => {
+                            let size = format::bytes(content.len());
+                            *stdout_document.lock().expect("poisoned stdout document") =
+                                Some(content);
+                            return Ok(WorkSpaceAction::SaveDone(format!(
+                                "Saved {size}, written to stdout on exit"
+                            ))
+                            .into());
+                        }
+                        (None, None, None) => unreachable!("no output for save"),
+                    }
+                    Ok(WorkSpaceAction::SaveDone(format!(
+                        "Saved {} to {output_file_name} in {:.1}s",
+                        format::bytes(content.len()),
+                        started.elapsed().as_secs_f64()
+                    ))
+                    .into())
                 })
             }
+            JobAction::WriteFiles(files) => write_files_job(files),
+            JobAction::ImportFolder(dir) => import_folder_job(dir, self.parse_options),
+            JobAction::ImportFile(path) => import_file_job(path, self.parse_options),
+            JobAction::Compare(path) => compare_job(path, self.parse_options),
+            JobAction::SavePatch { path, content } => save_patch_job(path, content),
+            JobAction::ExportGron { path, content } => export_gron_job(path, content),
+            JobAction::ImportGron(path) => import_gron_job(path),
+            JobAction::Jq { filter, content } => jq_job(filter, content),
         };
 
         Ok(Some(job))
     }
 }
 
-struct NodeJob(*const Node);
-unsafe impl Send for NodeJob {}
-unsafe impl Sync for NodeJob {}
+/// Size and modification time of a file, which change whenever it is written.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct FileStamp {
+    len: u64,
+    modified: SystemTime,
+}
+
+impl FileStamp {
+    fn read(path: &str) -> Option<Self> {
+        let metadata = fs::metadata(path).ok()?;
+        Some(Self {
+            len: metadata.len(),
+            modified: metadata.modified().ok()?,
+        })
+    }
+}
+
+/// Output name saving to stdout on exit.
+const STDOUT_OUTPUT: &str = "-";
+const FILE_HEAD_SIZE: u64 = 4096;
+const SAVE_CHUNK_SIZE: usize = 1 << 16;
+const FOLLOW_INTERVAL: Duration = Duration::from_millis(250);
+const WATCH_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Writes `content` a chunk at a time, advancing `progress` and stopping once it is cancelled.
+fn write_chunked(
+    writer: &mut impl Write,
+    content: &[u8],
+    progress: &Progress,
+) -> std::io::Result<()> {
+    for chunk in content.chunks(SAVE_CHUNK_SIZE) {
+        progress.check()?;
+        writer.write_all(chunk)?;
+        progress.advance(chunk.len());
+    }
+    Ok(())
+}
+
+fn restat_job(node: Node) -> Job {
+    Job::new("Restat", move || {
+        Ok(WorkSpaceAction::Restated(node.restat()).into())
+    })
+}
+
+fn expand_all_job(node: Node, selector: KeyPath, keep: Option<usize>) -> Job {
+    Job::new("Expand", move || {
+        let subtree = node.subtree(&selector).expect("broken selector");
+        let tree = WorkTreeNode::expanded(String::new(), subtree, keep);
+        Ok(WorkSpaceAction::ExpandedAll {
+            node,
+            selector,
+            tree,
+        }
+        .into())
+    })
+}
+
+fn compact_job(mut node: Node) -> Job {
+    Job::new("Compact", move || {
+        let reclaimed = node.compact();
+        Ok(WorkSpaceAction::Compacted { node, reclaimed }.into())
+    })
+}
+
+fn write_files_job(files: Vec<(String, String)>) -> Job {
+    Job::new("Split", move || {
+        let n_files = files.len();
+        let res = files.into_iter().try_for_each(|(path, content)| {
+            if let Some(parent) = Path::new(&path).parent() {
+                fs::create_dir_all(parent)?;
+            }
+            fs::write(&path, content)
+        });
+        match res {
+            Ok(()) => Ok(WorkSpaceAction::SplitDone(n_files).into()),
+            Err(error) => Ok(WorkSpaceAction::Error(error.to_string()).into()),
+        }
+    })
+}
+
+fn import_folder_job(dir: String, parse_options: ParseOptions) -> Job {
+    Job::new("Import", move || {
+        let res = join::json_files_in(&dir)
+            .map_err(LoadError::from)
+            .and_then(|paths| join::join(&paths, JoinKind::Object, &parse_options));
+        match res {
+            Ok(node) => Ok(WorkSpaceAction::Load {
+                node,
+                is_edit: true,
+                repaired: Repairs::default(),
+            }
+            .into()),
+            Err(error) => Ok(WorkSpaceAction::Error(error.to_string()).into()),
+        }
+    })
+}
+
+fn import_file_job(path: String, parse_options: ParseOptions) -> Job {
+    Job::new("Import", move || {
+        let res = File::open(&path)
+            .map_err(LoadError::from)
+            .and_then(|file| Node::load_with_repairs(file, &parse_options))
+            .map_err(|error| LoadError::InFile(path, Box::new(error)));
+        match res {
+            Ok((node, repaired)) => Ok(WorkSpaceAction::Load {
+                node,
+                is_edit: true,
+                repaired,
+            }
+            .into()),
+            Err(error) => Ok(WorkSpaceAction::Error(error.to_string()).into()),
+        }
+    })
+}
+
+fn save_patch_job(path: String, content: String) -> Job {
+    Job::new("Save patch", move || match fs::write(&path, content) {
+        Ok(()) => Ok(WorkSpaceAction::PatchSaved(format!("Saved patch to {path}")).into()),
+        Err(error) => Ok(WorkSpaceAction::Error(error.to_string()).into()),
+    })
+}
+
+fn export_gron_job(path: String, content: String) -> Job {
+    Job::new("Export", move || match fs::write(&path, content) {
+        Ok(()) => Ok(WorkSpaceAction::Exported(format!("Exported gron to {path}")).into()),
+        Err(error) => Ok(WorkSpaceAction::Error(error.to_string()).into()),
+    })
+}
+
+fn import_gron_job(path: String) -> Job {
+    Job::new("Import", move || {
+        let res = fs::read_to_string(&path)
+            .map_err(LoadError::from)
+            .and_then(|gron| gron::from_gron(&gron))
+            .map_err(|error| LoadError::InFile(path, Box::new(error)));
+        match res {
+            Ok(node) => Ok(WorkSpaceAction::Load {
+                node,
+                is_edit: true,
+                repaired: Repairs::default(),
+            }
+            .into()),
+            Err(error) => Ok(WorkSpaceAction::Error(error.to_string()).into()),
+        }
+    })
+}
+
+fn jq_job(filter: String, content: String) -> Job {
+    Job::new("jq", move || {
+        let res = jq::run(&filter, &content)
+            .map_err(LoadError::from)
+            .and_then(|output| jq::values(&output));
+        match res {
+            Ok(values) => {
+                Ok(WorkSpaceAction::JqOutput(ConfirmAction::Request((filter, values))).into())
+            }
+            Err(error) => Ok(WorkSpaceAction::Error(error.to_string()).into()),
+        }
+    })
+}
+
+fn compare_job(path: String, parse_options: ParseOptions) -> Job {
+    Job::new("Compare", move || {
+        match Node::load_file(&path, &parse_options) {
+            Ok(node) => Ok(WorkSpaceAction::Compared { name: path, node }.into()),
+            Err(error) => Ok(WorkSpaceAction::Error(
+                LoadError::InFile(path, Box::new(error)).to_string(),
+            )
+            .into()),
+        }
+    })
+}
+
+/// Polls `path` until it has new complete records.
+fn follow_job(path: String, mut reader: RecordReader, parse_options: ParseOptions) -> Job {
+    Job::background("Follow", move || {
+        loop {
+            // Errors stop following rather than the editor, as logs get rotated or deleted.
+            let input = match appended(&path, reader.offset()) {
+                Ok(input) => input,
+                Err(error) => {
+                    return Ok(WorkSpaceAction::Error(format!(
+                        "Stopped following {path}: {error}"
+                    ))
+                    .into());
+                }
+            };
+            if let Some(input) = input {
+                match reader.parse(&input, &parse_options) {
+                    Ok(records) if records.is_empty() => {}
+                    Ok(records) => {
+                        return Ok(WorkSpaceAction::Followed {
+                            path,
+                            reader,
+                            records,
+                        }
+                        .into());
+                    }
+                    Err(error) => {
+                        return Ok(WorkSpaceAction::Error(format!(
+                            "Stopped following {path}: {error}"
+                        ))
+                        .into());
+                    }
+                }
+            }
+            std::thread::sleep(FOLLOW_INTERVAL);
+        }
+    })
+}
+
+/// Bytes written to `path` past `offset`, or `None` when there are none yet.
+fn appended(path: &str, offset: u64) -> std::io::Result<Option<Vec<u8>>> {
+    let mut file = File::open(path)?;
+    let len = file.metadata()?.len();
+    if len < offset {
+        return Err(std::io::Error::other("truncated"));
+    }
+    if len == offset {
+        return Ok(None);
+    }
+    let mut input = Vec::new();
+    file.seek(SeekFrom::Start(offset))?;
+    file.read_to_end(&mut input)?;
+    Ok(Some(input))
+}
+
+/// Polls `path` until the editor exits, reporting changes once they stop for an interval, so
+/// that files being written are not reloaded halfway.
+fn watch_job(path: String) -> Job {
+    Job::stream("Watch", move |updates| {
+        let mut reported = FileStamp::read(&path);
+        let mut seen = reported;
+        loop {
+            std::thread::sleep(WATCH_INTERVAL);
+            let current = FileStamp::read(&path);
+            if current.is_some() && current == seen && current != reported {
+                reported = current;
+                let changed = WorkSpaceAction::InputChanged(ConfirmAction::Request(path.clone()));
+                if !updates.send(changed.into()) {
+                    return Ok(WorkSpaceAction::Error(format!("Stopped watching {path}")).into());
+                }
+            }
+            seen = current;
+        }
+    })
+}
+
+/// Writes the unsaved document to its journal without blocking the editor.
+fn journal_job(path: String, node: Node) -> Job {
+    Job::background("Autosave", move || {
+        let content = node
+            .to_string_pretty()
+            .expect("invalid internal representation");
+        let error = fs::write(&path, content).err();
+        Ok(WorkSpaceAction::Autosaved(error.map(|error| error.to_string())).into())
+    })
+}
+
+fn recover_job(path: String, parse_options: ParseOptions) -> Job {
+    Job::new("Recover", move || {
+        match Node::load_file(&path, &parse_options) {
+            Ok(node) => Ok(WorkSpaceAction::Load {
+                node,
+                is_edit: true,
+                repaired: Repairs::default(),
+            }
+            .into()),
+            Err(error) => Ok(WorkSpaceAction::Error(
+                LoadError::InFile(path, Box::new(error)).to_string(),
+            )
+            .into()),
+        }
+    })
+}
+
+fn reload_job(path: String, parse_options: ParseOptions) -> Job {
+    Job::new("Reload", move || {
+        match Node::load_file(&path, &parse_options) {
+            Ok(node) => Ok(WorkSpaceAction::Reloaded { path, node }.into()),
+            Err(error) => Ok(WorkSpaceAction::Error(
+                LoadError::InFile(path, Box::new(error)).to_string(),
+            )
+            .into()),
+        }
+    })
+}
+
+/// Copies `path` to `path.bak`, unless there is nothing to back up yet.
+pub(crate) fn back_up(path: &str) -> std::io::Result<()> {
+    match fs::copy(path, format!("{path}.bak")) {
+        Ok(_) => Ok(()),
+        Err(error) if error.kind() == ErrorKind::NotFound && !Path::new(path).exists() => Ok(()),
+        Err(error) => Err(error),
+    }
+}
+
+/// Sniffs the encoding from the first bytes of the input and returns them decoded, falling back
+/// to UTF-8 when the file can't be read; the load job reports that error.
+pub(crate) fn read_file_head(path: &str) -> (Encoding, Vec<u8>) {
+    let mut head = Vec::new();
+    if File::open(path)
+        .and_then(|file| file.take(FILE_HEAD_SIZE).read_to_end(&mut head))
+        .is_err()
+    {
+        return (Encoding::default(), Vec::new());
+    }
+
+    let encoding = Encoding::detect(&head);
+    let head = encoding
+        .decode(&head, true)
+        .map(Cow::into_owned)
+        .unwrap_or_default();
+    (encoding, head)
+}
 
 fn global_exit_handler(event: &Event) -> bool {
     let Some(key_event) = event.as_key_event() else {
@@ -214,32 +1324,197 @@ fn global_exit_handler(event: &Event) -> bool {
     key_event.code == KeyCode::F(5)
 }
 
-pub struct Terminal(DefaultTerminal);
+/// Where the editor is drawn: stdout, or stderr when stdout is piped, such as into a program
+/// reading the document saved to `-`.
+enum Tty {
+    Stdout(Stdout),
+    Stderr(Stderr),
+}
 
-impl Terminal {
+impl Tty {
     fn new() -> Self {
-        Self(ratatui::init())
+        if stdout().is_terminal() {
+            Self::Stdout(stdout())
+        } else {
+            Self::Stderr(stderr())
+        }
+    }
+}
+
+impl Write for Tty {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        match self {
+            Self::Stdout(stdout) => stdout.write(buf),
+            Self::Stderr(stderr) => stderr.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        match self {
+            Self::Stdout(stdout) => stdout.flush(),
+            Self::Stderr(stderr) => stderr.flush(),
+        }
     }
+}
+
+/// Leaves the alternate screen and raw mode, also when panicking.
+fn restore_tty() {
+    let _ = disable_raw_mode();
+    let _ = Tty::new().execute(LeaveAlternateScreen);
+}
+
+pub struct Terminal {
+    terminal: ratatui::Terminal<CrosstermBackend<Tty>>,
+    input: Input,
+    mouse: bool,
+}
 
-    fn run_editor(&mut self, path: &str) -> std::io::Result<()> {
-        let editor = std::env::var("EDITOR")
-            .ok()
-            .unwrap_or_else(|| String::from("vi"));
-        stdout().execute(LeaveAlternateScreen)?;
+impl Terminal {
+    fn new(mouse: bool) -> Self {
+        let hook = std::panic::take_hook();
+        std::panic::set_hook(Box::new(move |info| {
+            restore_tty();
+            hook(info);
+        }));
+        enable_raw_mode().expect("failed to enable raw mode");
+        let mut tty = Tty::new();
+        tty.execute(EnterAlternateScreen)
+            .expect("failed to enter the alternate screen");
+        let terminal = ratatui::Terminal::new(CrosstermBackend::new(Tty::new()))
+            .expect("failed to initialize the terminal");
+        job::isolate_job_panics();
+        let _ = tty.execute(EnableBracketedPaste);
+        if mouse {
+            let _ = tty.execute(EnableMouseCapture);
+        }
+        let input = Input::spawn();
+        job::wake_with(input.waker());
+        Self {
+            terminal,
+            input,
+            mouse,
+        }
+    }
+
+    /// Hands the terminal over to an external program, then takes it back.
+    fn suspend(&mut self, run: impl FnOnce() -> std::io::Result<()>) -> std::io::Result<()> {
+        self.input.pause();
+        let mut tty = Tty::new();
+        tty.execute(DisableBracketedPaste)?;
+        if self.mouse {
+            tty.execute(DisableMouseCapture)?;
+        }
+        tty.execute(LeaveAlternateScreen)?;
         disable_raw_mode()?;
-        Command::new(&editor).arg(path).status()?;
-        stdout().execute(EnterAlternateScreen)?;
+        let res = run();
+        tty.execute(EnterAlternateScreen)?;
         enable_raw_mode()?;
-        self.0.clear()?;
-        Ok(())
+        tty.execute(EnableBracketedPaste)?;
+        if self.mouse {
+            tty.execute(EnableMouseCapture)?;
+        }
+        self.terminal.clear()?;
+        self.input.resume();
+        res
+    }
+
+    fn run_editor(&mut self, command: Option<&str>, path: &Path) -> std::io::Result<()> {
+        let editor = command.map(String::from).or(std::env::var("EDITOR").ok());
+        self.suspend(|| {
+            paths::editor_command(editor.as_deref(), path).status()?;
+            Ok(())
+        })
+    }
+
+    /// Shows `content` in `$PAGER`, first piping it through `viewer` when given.
+    fn run_pager(&mut self, viewer: Option<&str>, content: &str) -> std::io::Result<()> {
+        let pager = std::env::var("PAGER").ok();
+        self.suspend(|| pipe_into_pager(viewer, pager.as_deref(), content))
+    }
+
+    /// Pipes `content` through the shell `command`, which shares the terminal for its prompts
+    /// and errors, and returns what it printed.
+    fn run_filter(&mut self, command: &str, content: &str) -> std::io::Result<Vec<u8>> {
+        let mut output = Vec::new();
+        self.suspend(|| {
+            output = pipe_through(command, content)?;
+            Ok(())
+        })?;
+        Ok(output)
     }
 }
 
+fn pipe_through(command: &str, content: &str) -> std::io::Result<Vec<u8>> {
+    let mut child = paths::shell_command(command)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()?;
+    let mut stdin = child.stdin.take().expect("piped stdin");
+    let content = content.to_owned();
+    // Written from another thread, so that the command never blocks on a full stdout.
+    let writer = thread::spawn(move || stdin.write_all(content.as_bytes()));
+    let output = child.wait_with_output()?;
+    match writer.join() {
+        // The command quit before reading everything.
+        Ok(Err(error)) if error.kind() != ErrorKind::BrokenPipe => return Err(error),
+        _ => {}
+    }
+    if !output.status.success() {
+        return Err(std::io::Error::other(format!(
+            "`{command}` failed with {}",
+            output.status
+        )));
+    }
+    Ok(output.stdout)
+}
+
+fn pipe_into_pager(
+    viewer: Option<&str>,
+    pager: Option<&str>,
+    content: &str,
+) -> std::io::Result<()> {
+    let mut pager = paths::pager_command(pager);
+    let mut viewer = match viewer {
+        Some(viewer) => {
+            let mut viewer = paths::tool_command(viewer)
+                .stdin(Stdio::piped())
+                .stdout(Stdio::piped())
+                .spawn()?;
+            pager.stdin(viewer.stdout.take().expect("piped stdout"));
+            Some(viewer)
+        }
+        None => {
+            pager.stdin(Stdio::piped());
+            None
+        }
+    };
+    let mut pager = pager.spawn()?;
+
+    let stdin = match &mut viewer {
+        Some(viewer) => viewer.stdin.take(),
+        None => pager.stdin.take(),
+    };
+    let mut stdin = stdin.expect("piped stdin");
+    match stdin.write_all(content.as_bytes()) {
+        // The program quit before reading everything.
+        Err(error) if error.kind() == ErrorKind::BrokenPipe => {}
+        res => res?,
+    }
+    drop(stdin);
+    if let Some(mut viewer) = viewer {
+        viewer.wait()?;
+    }
+    pager.wait()?;
+    Ok(())
+}
+
 impl Drop for Terminal {
     fn drop(&mut self) {
-        ratatui::restore();
+        let mut tty = Tty::new();
+        let _ = tty.execute(DisableBracketedPaste);
+        if self.mouse {
+            let _ = tty.execute(DisableMouseCapture);
+        }
+        restore_tty();
     }
 }
-
-const FRAME_TIME: Duration = Duration::from_millis(16);
-const EDITOR_BUFFER: &str = "/tmp/jedit-buffer.json";
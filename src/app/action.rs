@@ -1,8 +1,12 @@
 use std::collections::VecDeque;
 
-use crate::container::node::Node;
+use crossterm::event::MouseEvent;
 
-use super::math::Op;
+use crate::container::{
+    merge::Conflict, ndjson::RecordReader, node::Node, parser::Repairs, pointer::KeyPath,
+};
+
+use super::{component::workspace::WorkTreeNode, math::Op};
 
 #[derive(Debug, Clone, Copy)]
 #[cfg_attr(test, derive(PartialEq))]
@@ -11,6 +15,8 @@ pub enum PreviewNavigationAction {
     Down(u16),
     Left,
     Right,
+    Top,
+    Bottom,
 }
 
 impl From<PreviewNavigationAction> for Action {
@@ -34,7 +40,24 @@ pub enum NavigationAction {
     Bottom,
     Expand,
     Close,
+    /// Collapses every level below the selection, leaving its children shown.
+    CollapseAll,
     TogglePreview,
+    /// Gives the whole window to the previews, hiding the tree, or brings the tree back.
+    ToggleFullscreenPreview,
+    /// Moves the previews between beside and below the tree.
+    TogglePreviewLayout,
+    /// Shows the selected string decoded the next way it can be in the preview, then as JSON
+    /// again.
+    CycleDecoding,
+    /// Previews arrays of flat objects as a table, or as JSON again.
+    ToggleTableView,
+    /// Lists every leaf with its full path instead of the tree, or brings the tree back.
+    ToggleFlatView,
+    /// Moves the keyboard focus from the tree to the preview, then the pinned preview.
+    SwitchFocus,
+    /// Gives the keyboard focus back to the tree.
+    FocusTree,
     PreviewNavigation(PreviewNavigationAction),
     PreviewWindowResize(Op),
 }
@@ -70,15 +93,172 @@ impl<T, C> ConfirmAction<T, C> {
 #[cfg_attr(test, derive(PartialEq, Clone))]
 pub(crate) enum WorkSpaceAction {
     Navigation(NavigationAction),
+    /// Clicks, scrolls and drags on the tree, the preview or the border between them.
+    Mouse(MouseEvent),
     Edit,
+    Page,
     EditError(ConfirmAction<String>),
+    /// Applies an edited node with its repaired paths, after confirming a summary of its
+    /// structural changes when it replaces a container.
+    ApplyEdit(ConfirmAction<(Node, Repairs)>),
+    /// Edits the selection in a text dialog, for scalars edited inline or when external programs
+    /// are disabled. Strings edited inline are typed without quotes.
+    InlineEdit(ConfirmAction<(), Option<String>>),
+    /// Converts the selected scalar to the type picked by its index in `ScalarType::ALL`.
+    SetType(ConfirmAction<(), Option<usize>>),
+    /// Replaces the selected string with the JSON document it holds.
+    ExpandJson,
+    /// Replaces the selected node with its compact JSON as a string.
+    Stringify,
+    /// Sorts the selected container in the order picked by its index in the sort menu.
+    Sort(ConfirmAction<(), Option<usize>>),
+    /// Hides nodes not matching the query, or shows them all again for an empty one.
+    Filter(ConfirmAction<(), Option<String>>),
     Save(ConfirmAction<()>),
-    SaveDone,
+    SaveAs(ConfirmAction<(), Option<String>>),
+    SaveDone(String),
+    /// Asks whether to overwrite the output file another program changed since it was loaded.
+    Overwrite(ConfirmAction<String>),
+    /// Writes the changes made since loading as a JSON Patch, leaving the input untouched.
+    SavePatch(ConfirmAction<(), Option<String>>),
+    PatchSaved(String),
+    /// Asks whether to load the changes a crashed session left in its journal.
+    Recover(ConfirmAction<String, Option<String>>),
+    /// A journal write finished, with the error if it failed.
+    Autosaved(Option<String>),
     ErrorConfirmed,
-    Load { node: Node, is_edit: bool },
+    Load {
+        node: Node,
+        is_edit: bool,
+        /// What had to be repaired to load the node.
+        repaired: Repairs,
+    },
     Rename(ConfirmAction<(), Option<String>>),
-    Delete(ConfirmAction<()>),
+    /// Deletes the selection, cutting it into the given register.
+    Delete(ConfirmAction<char>),
     Add(ConfirmAction<(), Option<String>>),
+    JumpToIndex(ConfirmAction<(), Option<String>>),
+    Search(ConfirmAction<(), Option<String>>),
+    /// Selects the node named by a JSON Pointer or JSONPath.
+    GoTo(ConfirmAction<(), Option<String>>),
+    /// Creates a missing path as `null`, with the objects and arrays leading to it, once
+    /// confirmed.
+    CreatePath(ConfirmAction<Vec<String>, Option<Vec<String>>>),
+    /// Selects the first match of the query typed so far.
+    SearchInput(String),
+    /// Selects the next match of the last search, or the previous one when `true`.
+    SearchNext(bool),
+    /// Scrolls the focused preview to a line containing the query.
+    PreviewSearch(ConfirmAction<(), Option<String>>),
+    /// Scrolls the focused preview to the next match of its search, or the previous one when
+    /// `true`.
+    PreviewSearchNext(bool),
+    Info,
+    LargestSubtrees,
+    /// Lists where the selected subtree breaks the schema.
+    Validate,
+    SelectPath(Option<KeyPath>),
+    Split(ConfirmAction<(), Option<String>>),
+    SplitDone(usize),
+    ImportFolder(ConfirmAction<(), Option<String>>),
+    /// Replaces the selected node with the document in a file.
+    ImportFile(ConfirmAction<(), Option<String>>),
+    /// Writes the selected node as gron assignments to a file, or to the clipboard.
+    ExportGron(ConfirmAction<(), Option<String>>),
+    /// Replaces the selected node with the document listed in a gron file.
+    ImportGron(ConfirmAction<(), Option<String>>),
+    Exported(String),
+    Error(String),
+    Yank(char),
+    /// Inserts a copy of the selection right after it.
+    Duplicate,
+    /// Swaps the selection with its next sibling, or the previous one when `true`.
+    MoveWithin(bool),
+    Paste {
+        register: char,
+        key: ConfirmAction<(), Option<String>>,
+    },
+    ListRegisters,
+    RegisterPicked(Option<char>),
+    /// Remembers the selection under a letter.
+    Bookmark(char),
+    JumpToBookmark(char),
+    ListBookmarks,
+    ListSnippets,
+    SnippetPicked(Option<String>),
+    /// Text read from the system clipboard, pasted after the selection or replacing it.
+    ClipboardRead {
+        content: String,
+        replace: bool,
+    },
+    InsertSnippet {
+        name: String,
+        key: ConfirmAction<(), Option<String>>,
+    },
+    ListMessages,
+    /// Previews how the selection changed since the last checkpoint.
+    Diff,
+    /// Keeps the preview of the selection beside the tree, or drops the pinned one.
+    Pin,
+    /// Runs a jq filter on the selection.
+    Jq(ConfirmAction<(), Option<String>>),
+    /// Pins what a jq filter printed, then replaces the selection with it once confirmed.
+    JqOutput(ConfirmAction<(String, Vec<Node>)>),
+    /// Replaces the selection with what a shell command prints when fed it.
+    Pipe(ConfirmAction<(), Option<String>>),
+    /// Compares the tree against another file, or stops comparing on an empty path.
+    Compare(ConfirmAction<(), Option<String>>),
+    Compared {
+        name: String,
+        node: Node,
+    },
+    ListTrash,
+    /// Asks whether to open read-only a document another instance has unsaved changes to.
+    ReadOnly(ConfirmAction<String>),
+    /// Restores the trashed node at this position, counted from the oldest.
+    Restore(Option<usize>),
+    /// Expands every level below the selection, asking first for huge subtrees.
+    ExpandAll(ConfirmAction<()>),
+    /// Puts back the document after the rows of the subtree at `selector` were built in the
+    /// background.
+    ExpandedAll {
+        node: Node,
+        selector: KeyPath,
+        tree: WorkTreeNode,
+    },
+    /// Puts back the document after its sizes were recomputed in the background.
+    Restated(Node),
+    /// Puts back the document after its capacities were shrunk in the background.
+    Compacted {
+        node: Node,
+        reclaimed: usize,
+    },
+    /// Another program changed the watched input, which reloads it, after confirming when there
+    /// are unsaved changes.
+    InputChanged(ConfirmAction<String, Option<String>>),
+    /// Replaces the whole document with the input as reloaded from disk.
+    Reloaded {
+        path: String,
+        node: Node,
+    },
+    /// Appends the records newly read from a followed file to the root array.
+    Followed {
+        path: String,
+        reader: RecordReader,
+        records: Vec<Node>,
+    },
+    /// Loads a merged document, keeping its conflicts for resolving.
+    Merged {
+        node: Node,
+        conflicts: Vec<Conflict>,
+    },
+    ListConflicts,
+    ConflictPicked(Option<usize>),
+    /// Resolves a conflict with our side (0) or their side (1).
+    ResolveConflict {
+        conflict: usize,
+        side: Option<usize>,
+    },
 }
 
 impl From<WorkSpaceAction> for Action {
@@ -90,15 +270,79 @@ impl From<WorkSpaceAction> for Action {
 #[derive(Debug)]
 #[cfg_attr(test, derive(PartialEq))]
 pub enum EditJobAction {
-    Init,
-    Open,
+    /// Writes the selection to the editor buffer, then opens it with the given command, or
+    /// `$EDITOR`.
+    Init(Option<String>),
+    Open(Option<String>),
 }
 
 #[derive(Debug)]
 #[cfg_attr(test, derive(PartialEq))]
 pub enum JobAction {
     Edit(EditJobAction),
+    /// Pipes the content through a viewer command, if any, into `$PAGER`.
+    Page {
+        content: String,
+        command: Option<String>,
+    },
+    /// Recomputes the sizes of the whole document in the background.
+    Restat,
+    /// Builds the rows of the subtree at `selector` with every level expanded, hiding all but the
+    /// first and last `keep` elements of longer arrays.
+    ExpandAll {
+        selector: KeyPath,
+        keep: Option<usize>,
+    },
+    /// Shrinks the capacities left behind by deletions in the background.
+    Compact,
+    /// Opens the document read-only, or else takes over its lock from another instance.
+    ReadOnly(bool),
+    /// Waits for `path` to grow past the records read so far.
+    Follow {
+        path: String,
+        reader: RecordReader,
+    },
+    /// Loads the unsaved changes kept in a journal as an edit.
+    Recover(String),
+    /// Loads the input again after another program changed it.
+    Reload(String),
+    /// Puts yanked JSON on the system clipboard, through external tools when allowed.
+    Copy {
+        content: String,
+        run_tools: bool,
+    },
+    /// Reads JSON from the system clipboard, to paste after the selection or replace it.
+    ReadClipboard {
+        replace: bool,
+        run_tools: bool,
+    },
     Save,
+    /// Saves even if another program changed the output file since it was loaded.
+    Overwrite,
+    SaveAs(String),
+    WriteFiles(Vec<(String, String)>),
+    ImportFolder(String),
+    ImportFile(String),
+    /// Loads a file to compare the tree against.
+    Compare(String),
+    SavePatch {
+        path: String,
+        content: String,
+    },
+    ExportGron {
+        path: String,
+        content: String,
+    },
+    ImportGron(String),
+    Jq {
+        filter: String,
+        content: String,
+    },
+    /// Hands the terminal to a shell command reading the content, and loads what it prints.
+    Pipe {
+        command: String,
+        content: String,
+    },
 }
 
 impl From<JobAction> for Action {
@@ -111,7 +355,12 @@ impl From<JobAction> for Action {
 #[derive(Debug)]
 #[cfg_attr(test, derive(PartialEq))]
 pub(crate) enum Action {
+    /// Closes the shown tab, or the editor with the last one.
     Exit(ConfirmAction<()>),
+    /// Shows the next tab, or the previous one when `true`.
+    SwitchTab(bool),
+    /// Asks the running jobs to stop, from the loading popup.
+    CancelJobs,
     Workspace(WorkSpaceAction),
     ExecuteJob(JobAction),
 }
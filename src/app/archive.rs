@@ -0,0 +1,87 @@
+use std::process::Command;
+
+const ZIP_EXTENSIONS: &[&str] = &[".zip", ".jar"];
+const TAR_EXTENSIONS: &[&str] = &[".tar", ".tar.gz", ".tgz", ".tar.bz2", ".tar.xz", ".tar.zst"];
+
+/// Splits `archive.zip!/path/inner.json` into the archive and the member path.
+pub fn split_member(input: &str) -> Option<(&str, &str)> {
+    let (archive, member) = input.split_once("!/")?;
+    extract_command(archive, member).map(|_| (archive, member))
+}
+
+/// Builds the command printing `member` of `archive` to stdout, picked by the archive extension.
+pub fn extract_command(archive: &str, member: &str) -> Option<Command> {
+    let has_extension = |extensions: &[&str]| {
+        let archive = archive.to_ascii_lowercase();
+        extensions
+            .iter()
+            .any(|extension| archive.ends_with(extension))
+    };
+    let mut command = if has_extension(ZIP_EXTENSIONS) {
+        let mut command = Command::new("unzip");
+        command.arg("-p");
+        command
+    } else if has_extension(TAR_EXTENSIONS) {
+        let mut command = Command::new("tar");
+        command.arg("-xOf");
+        command
+    } else {
+        return None;
+    };
+    command.arg(archive).arg(member);
+    Some(command)
+}
+
+/// Reads `member` out of `archive` with the system `unzip` or `tar`.
+pub fn read_member(archive: &str, member: &str) -> std::io::Result<Vec<u8>> {
+    let output = extract_command(archive, member)
+        .expect("unsupported archive")
+        .output()?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(std::io::Error::other(format!(
+            "Failed to read {member} from {archive}: {}",
+            stderr.trim()
+        )));
+    }
+    Ok(output.stdout)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn args(command: &Command) -> Vec<String> {
+        std::iter::once(command.get_program())
+            .chain(command.get_args())
+            .map(|arg| arg.to_string_lossy().into_owned())
+            .collect()
+    }
+
+    #[test]
+    fn split_member_test() {
+        assert_eq!(
+            split_member("data.zip!/nested/inner.json"),
+            Some(("data.zip", "nested/inner.json"))
+        );
+        assert_eq!(
+            split_member("data.TAR.GZ!/inner.json"),
+            Some(("data.TAR.GZ", "inner.json"))
+        );
+        assert_eq!(split_member("data.json"), None);
+        assert_eq!(split_member("weird!/name.json"), None);
+    }
+
+    #[test]
+    fn extract_command_test() {
+        assert_eq!(
+            args(&extract_command("data.zip", "a.json").unwrap()),
+            ["unzip", "-p", "data.zip", "a.json"]
+        );
+        assert_eq!(
+            args(&extract_command("data.tgz", "a.json").unwrap()),
+            ["tar", "-xOf", "data.tgz", "a.json"]
+        );
+        assert!(extract_command("data.rar", "a.json").is_none());
+    }
+}
@@ -0,0 +1,129 @@
+use std::{
+    fs::{self, File, OpenOptions},
+    io::Write,
+    path::PathBuf,
+};
+
+/// File written under a temporary name next to its destination, then renamed over it, so that a
+/// crash or a full disk never leaves the destination half written.
+#[derive(Debug)]
+pub struct AtomicFile {
+    file: File,
+    temp_path: PathBuf,
+    path: PathBuf,
+    is_committed: bool,
+}
+
+impl AtomicFile {
+    pub fn create(path: &str) -> std::io::Result<Self> {
+        // Writes through symlinks rather than replacing them.
+        let path = fs::canonicalize(path).unwrap_or_else(|_| PathBuf::from(path));
+        let Some(file_name) = path.file_name() else {
+            return Err(std::io::Error::other(format!(
+                "{} is not a file",
+                path.display()
+            )));
+        };
+        let temp_path = path.with_file_name(format!(
+            ".{}.{}.tmp",
+            file_name.to_string_lossy(),
+            std::process::id()
+        ));
+        // The destination's permissions are set before anything is written, so that the content
+        // of a private file is never readable by others, even for a moment.
+        let permissions = fs::metadata(&path)
+            .ok()
+            .map(|metadata| metadata.permissions());
+        let mut options = OpenOptions::new();
+        options.write(true).create_new(true);
+        #[cfg(unix)]
+        if permissions.is_some() {
+            use std::os::unix::fs::OpenOptionsExt;
+            options.mode(0o600);
+        }
+        // A leftover of a crashed save may be open elsewhere, so it is replaced, not reused.
+        let _ = fs::remove_file(&temp_path);
+        let file = options.open(&temp_path)?;
+        // Built first, so that the temporary file is removed if the rest fails.
+        let atomic_file = Self {
+            file,
+            temp_path,
+            path,
+            is_committed: false,
+        };
+        if let Some(permissions) = permissions {
+            atomic_file.file.set_permissions(permissions)?;
+        }
+        Ok(atomic_file)
+    }
+
+    /// Replaces the destination with what was written, which has its permissions.
+    pub fn commit(mut self) -> std::io::Result<()> {
+        self.file.sync_all()?;
+        fs::rename(&self.temp_path, &self.path)?;
+        self.is_committed = true;
+        Ok(())
+    }
+}
+
+impl Write for AtomicFile {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.file.write(buf)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.file.flush()
+    }
+}
+
+impl Drop for AtomicFile {
+    fn drop(&mut self) {
+        if !self.is_committed {
+            let _ = fs::remove_file(&self.temp_path);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn atomic_file_test() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("atomic-file-test.json");
+        let path = path.to_str().unwrap();
+        fs::write(path, "{\"a\": 1}").unwrap();
+
+        let mut file = AtomicFile::create(path).unwrap();
+        let temp_path = file.temp_path.clone();
+        file.write_all(b"{\"a\": ").unwrap();
+        assert_eq!(fs::read_to_string(path).unwrap(), "{\"a\": 1}");
+        drop(file);
+        assert!(!temp_path.exists());
+        assert_eq!(fs::read_to_string(path).unwrap(), "{\"a\": 1}");
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(path, fs::Permissions::from_mode(0o640)).unwrap();
+        }
+        let mut file = AtomicFile::create(path).unwrap();
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = fs::metadata(&temp_path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o640);
+        }
+        file.write_all(b"{\"a\": 2}").unwrap();
+        file.commit().unwrap();
+        assert!(!temp_path.exists());
+        assert_eq!(fs::read_to_string(path).unwrap(), "{\"a\": 2}");
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = fs::metadata(path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o640);
+        }
+    }
+}
@@ -0,0 +1,126 @@
+use std::{
+    env,
+    io::Write,
+    process::{Command, Stdio},
+};
+
+use super::Tty;
+
+const BASE64_ALPHABET: &[u8; 64] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// Puts `content` on the system clipboard through `pbcopy`, `wl-copy` or `xclip`, falling back to
+/// the OSC 52 escape sequence, which the terminal forwards even over SSH.
+pub fn copy(content: &str, run_tools: bool) -> std::io::Result<()> {
+    if run_tools
+        && env::var_os("SSH_TTY").is_none()
+        && let Some(mut command) = system_command(false)
+        && pipe_into(&mut command, content).is_ok()
+    {
+        return Ok(());
+    }
+    let mut tty = Tty::new();
+    tty.write_all(osc52(content).as_bytes())?;
+    tty.flush()
+}
+
+/// Text on the system clipboard, read through `pbpaste`, `wl-paste` or `xclip`. Terminals do not
+/// reliably answer OSC 52 queries, so there is no fallback.
+pub fn paste(run_tools: bool) -> std::io::Result<String> {
+    let command = run_tools.then(|| system_command(true)).flatten();
+    let Some(mut command) = command else {
+        return Err(std::io::Error::other(
+            "Reading the clipboard needs pbpaste, wl-paste or xclip",
+        ));
+    };
+    let output = command.stderr(Stdio::null()).output()?;
+    if !output.status.success() {
+        return Err(std::io::Error::other("clipboard command failed"));
+    }
+    String::from_utf8(output.stdout).map_err(std::io::Error::other)
+}
+
+fn system_command(read: bool) -> Option<Command> {
+    let (program, args): (&str, &[&str]) = match (read, cfg!(target_os = "macos")) {
+        (false, true) => ("pbcopy", &[]),
+        (true, true) => ("pbpaste", &[]),
+        _ if env::var_os("WAYLAND_DISPLAY").is_some() => {
+            if read {
+                ("wl-paste", &["--no-newline"])
+            } else {
+                ("wl-copy", &[])
+            }
+        }
+        _ if env::var_os("DISPLAY").is_some() => {
+            if read {
+                ("xclip", &["-selection", "clipboard", "-o"])
+            } else {
+                ("xclip", &["-selection", "clipboard"])
+            }
+        }
+        _ => return None,
+    };
+    let mut command = Command::new(program);
+    command.args(args);
+    Some(command)
+}
+
+fn pipe_into(command: &mut Command, content: &str) -> std::io::Result<()> {
+    let mut child = command
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()?;
+    child
+        .stdin
+        .take()
+        .expect("piped stdin")
+        .write_all(content.as_bytes())?;
+    if child.wait()?.success() {
+        Ok(())
+    } else {
+        Err(std::io::Error::other("clipboard command failed"))
+    }
+}
+
+/// Escape sequence asking the terminal to set its clipboard.
+fn osc52(content: &str) -> String {
+    format!("\x1b]52;c;{}\x07", base64(content.as_bytes()))
+}
+
+fn base64(bytes: &[u8]) -> String {
+    let mut encoded = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let n = chunk
+            .iter()
+            .enumerate()
+            .fold(0u32, |n, (i, &byte)| n | u32::from(byte) << (16 - 8 * i));
+        for i in 0..4 {
+            if i <= chunk.len() {
+                encoded.push(BASE64_ALPHABET[(n >> (18 - 6 * i) & 0x3f) as usize].into());
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+    encoded
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn base64_test() {
+        assert_eq!(base64(b""), "");
+        assert_eq!(base64(b"f"), "Zg==");
+        assert_eq!(base64(b"fo"), "Zm8=");
+        assert_eq!(base64(b"foo"), "Zm9v");
+        assert_eq!(base64(b"{\"a\": 1}"), "eyJhIjogMX0=");
+    }
+
+    #[test]
+    fn osc52_test() {
+        assert_eq!(osc52("foo"), "\x1b]52;c;Zm9v\x07");
+    }
+}
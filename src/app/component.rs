@@ -1,9 +1,13 @@
 pub mod confirm_dialog;
 pub mod loading;
+pub mod pane_cache;
 pub mod popup;
 pub mod preview;
 pub mod scrollbar;
+pub mod status_bar;
+pub mod tab_bar;
+pub mod table_preview;
 pub mod workspace;
 
-#[cfg(test)]
-mod test_render;
+#[cfg(any(test, feature = "testing"))]
+pub(crate) mod test_render;
@@ -1,5 +1,6 @@
 pub mod boolean_confirm_dialog;
 pub mod error_confirm_dialog;
+pub mod list_confirm_dialog;
 pub mod text_confirm_dialog;
 
 use crate::app::Actions;
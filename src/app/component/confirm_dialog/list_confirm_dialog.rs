@@ -0,0 +1,151 @@
+use std::cell::RefCell;
+
+use crossterm::event::{Event, KeyCode};
+use ratatui::{
+    prelude::{Buffer, Rect},
+    style::{Modifier, Style, palette::tailwind::SLATE},
+    text::Line,
+    widgets::{Block, Clear, HighlightSpacing, List, ListState, StatefulWidget, Widget, WidgetRef},
+};
+
+use crate::app::{
+    action::{Action, Actions},
+    component::popup::popup_area,
+};
+
+use super::ConfirmDialog;
+
+pub struct ListConfirmDialog {
+    items: Vec<Line<'static>>,
+    title: Option<Line<'static>>,
+    state: RefCell<ListState>,
+    response_fn: Box<dyn Fn(Option<usize>) -> Action>,
+}
+
+impl ListConfirmDialog {
+    pub fn new(
+        items: Vec<Line<'static>>,
+        response_fn: Box<dyn Fn(Option<usize>) -> Action>,
+    ) -> Self {
+        Self {
+            items,
+            title: None,
+            state: RefCell::new(ListState::default().with_selected(Some(0))),
+            response_fn,
+        }
+    }
+
+    pub fn title(mut self, title: Line<'static>) -> Self {
+        self.title = Some(title);
+        self
+    }
+}
+
+impl ConfirmDialog for ListConfirmDialog {
+    fn handle_event(&self, actions: &mut Actions, event: Event) {
+        let Some(event) = event.as_key_press_event() else {
+            return;
+        };
+
+        let mut state = self.state.borrow_mut();
+        match event.code {
+            KeyCode::Char('k') | KeyCode::Up => state.select_previous(),
+            KeyCode::Char('j') | KeyCode::Down => {
+                let last = self.items.len().saturating_sub(1);
+                let selected = state.selected().map(|index| (index + 1).min(last));
+                state.select(selected);
+            }
+            KeyCode::Enter => {
+                let selected = state.selected().filter(|&index| index < self.items.len());
+                actions.push((self.response_fn)(selected));
+            }
+            KeyCode::Esc | KeyCode::Char('q') => {
+                actions.push((self.response_fn)(None));
+            }
+            _ => {}
+        }
+    }
+}
+
+impl WidgetRef for ListConfirmDialog {
+    fn render_ref(&self, area: Rect, buf: &mut Buffer) {
+        let height = (self.items.len() as u16)
+            .saturating_add(2)
+            .min(area.height.saturating_sub(4));
+        let area = popup_area(area, height, area.width.saturating_sub(12).min(64));
+        let mut block =
+            Block::bordered().title_bottom(Line::from("[Enter] Select / [Esc] Close").centered());
+        if let Some(title) = self.title.clone() {
+            block = block.title(title);
+        }
+
+        Clear.render(area, buf);
+        let list = List::new(self.items.clone())
+            .block(block)
+            .highlight_style(Style::new().bg(SLATE.c800).add_modifier(Modifier::BOLD))
+            .highlight_symbol("> ")
+            .highlight_spacing(HighlightSpacing::Always);
+        StatefulWidget::render(list, area, buf, &mut self.state.borrow_mut());
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crossterm::event::{KeyEvent, KeyModifiers};
+    use insta::assert_snapshot;
+
+    use crate::{
+        app::{action::WorkSpaceAction, component::test_render::render_to_string},
+        container::pointer::KeyPath,
+    };
+
+    use super::*;
+
+    #[test]
+    fn event_handler_test() {
+        let dialog = ListConfirmDialog::new(
+            vec![Line::from("a"), Line::from("b")],
+            Box::new(|index| {
+                WorkSpaceAction::SelectPath(index.map(|i| KeyPath::from([i.to_string()]))).into()
+            }),
+        );
+
+        let mut actions = Actions::new();
+        for code in [
+            KeyCode::Down,
+            KeyCode::Down,
+            KeyCode::Enter,
+            KeyCode::Up,
+            KeyCode::Enter,
+            KeyCode::Esc,
+        ] {
+            dialog.handle_event(
+                &mut actions,
+                Event::Key(KeyEvent::new(code, KeyModifiers::empty())),
+            );
+        }
+        assert_eq!(
+            actions.into_vec(),
+            vec![
+                WorkSpaceAction::SelectPath(Some(KeyPath::from(["1"]))).into(),
+                WorkSpaceAction::SelectPath(Some(KeyPath::from(["0"]))).into(),
+                WorkSpaceAction::SelectPath(None).into(),
+            ]
+        );
+    }
+
+    #[test]
+    fn render_test() {
+        let dialog = ListConfirmDialog::new(
+            vec![
+                Line::from("first"),
+                Line::from("second"),
+                Line::from("third"),
+            ],
+            Box::new(|_| WorkSpaceAction::SelectPath(None).into()),
+        )
+        .title(Line::from("Pick one"));
+
+        assert_snapshot!(render_to_string(&dialog));
+    }
+}
@@ -1,8 +1,9 @@
-use std::cell::RefCell;
+use std::cell::{Cell, RefCell};
 
-use crossterm::event::{Event, KeyCode};
+use crossterm::event::{Event, KeyCode, KeyModifiers};
 use ratatui::{
     prelude::{Buffer, Rect},
+    style::{Style, Stylize},
     text::{Line, Text},
     widgets::{Block, Clear, WidgetRef},
 };
@@ -17,16 +18,21 @@ use super::ConfirmDialog;
 pub struct TextConfirmDialog {
     // Should this content be a String, and pipe the mutation through actions?
     content: RefCell<String>,
+    /// Characters before the cursor.
+    cursor: Cell<usize>,
     title: Option<Line<'static>>,
     response_fn: Box<dyn Fn(Option<String>) -> Action>,
+    change_fn: Option<Box<dyn Fn(String) -> Action>>,
 }
 
 impl TextConfirmDialog {
     pub fn new(response_fn: Box<dyn Fn(Option<String>) -> Action>) -> Self {
         Self {
             content: String::new().into(),
+            cursor: Cell::new(0),
             title: None,
             response_fn,
+            change_fn: None,
         }
     }
 
@@ -36,17 +42,106 @@ impl TextConfirmDialog {
     }
 
     pub fn content(mut self, content: String) -> Self {
+        self.cursor = Cell::new(content.chars().count());
         self.content = content.into();
         self
     }
+
+    /// Reports the content after every keystroke that edits it.
+    pub fn on_change(mut self, change_fn: Box<dyn Fn(String) -> Action>) -> Self {
+        self.change_fn = Some(change_fn);
+        self
+    }
+
+    fn changed(&self, actions: &mut Actions) {
+        if let Some(change_fn) = &self.change_fn {
+            actions.push(change_fn(self.content.borrow().clone()));
+        }
+    }
+
+    /// Byte offset of the `n`th character.
+    fn offset(&self, n: usize) -> usize {
+        let content = self.content.borrow();
+        content
+            .char_indices()
+            .nth(n)
+            .map_or(content.len(), |(offset, _)| offset)
+    }
+
+    fn len(&self) -> usize {
+        self.content.borrow().chars().count()
+    }
+
+    /// Inserts `text` at the cursor, leaving out line breaks and other control characters as the
+    /// input is a single line.
+    fn insert(&self, text: &str) {
+        let text = text.chars().filter(|c| !c.is_control()).collect::<String>();
+        let cursor = self.cursor.get();
+        let offset = self.offset(cursor);
+        self.content.borrow_mut().insert_str(offset, &text);
+        self.cursor.set(cursor + text.chars().count());
+    }
+
+    /// Removes the characters from `start` up to the cursor, leaving the cursor at `start`.
+    fn delete_back_to(&self, start: usize) {
+        let range = self.offset(start)..self.offset(self.cursor.get());
+        self.content.borrow_mut().replace_range(range, "");
+        self.cursor.set(start);
+    }
+
+    /// Start of the word before the cursor, skipping the whitespace right before it.
+    fn word_start(&self) -> usize {
+        let content = self.content.borrow();
+        let before = content.chars().take(self.cursor.get()).collect::<Vec<_>>();
+        let n_spaces = before
+            .iter()
+            .rev()
+            .take_while(|c| c.is_whitespace())
+            .count();
+        let n_word = before
+            .iter()
+            .rev()
+            .skip(n_spaces)
+            .take_while(|c| !c.is_whitespace())
+            .count();
+        before.len() - n_spaces - n_word
+    }
 }
 
 impl ConfirmDialog for TextConfirmDialog {
     fn handle_event(&self, actions: &mut Actions, event: Event) {
+        if let Event::Paste(text) = &event {
+            self.insert(text);
+            self.changed(actions);
+            return;
+        }
         let Some(event) = event.as_key_press_event() else {
             return;
         };
 
+        let cursor = self.cursor.get();
+        if event.modifiers == KeyModifiers::CONTROL {
+            match event.code {
+                KeyCode::Char('a') => self.cursor.set(0),
+                KeyCode::Char('e') => self.cursor.set(self.len()),
+                KeyCode::Char('w') => {
+                    self.delete_back_to(self.word_start());
+                    self.changed(actions);
+                }
+                KeyCode::Char('u') => {
+                    self.delete_back_to(0);
+                    self.changed(actions);
+                }
+                KeyCode::Char('k') => {
+                    let offset = self.offset(cursor);
+                    self.content.borrow_mut().truncate(offset);
+                    self.changed(actions);
+                }
+                _ => {}
+            }
+            return;
+        }
+
         match event.code {
             KeyCode::Enter => {
                 actions.push((self.response_fn)(Some(self.content.borrow().clone())));
@@ -54,11 +149,22 @@ impl ConfirmDialog for TextConfirmDialog {
             KeyCode::Esc => {
                 actions.push((self.response_fn)(None));
             }
-            KeyCode::Char(c) => {
-                self.content.borrow_mut().push(c);
+            KeyCode::Left => self.cursor.set(cursor.saturating_sub(1)),
+            KeyCode::Right => self.cursor.set((cursor + 1).min(self.len())),
+            KeyCode::Home => self.cursor.set(0),
+            KeyCode::End => self.cursor.set(self.len()),
+            KeyCode::Char(c) if !event.modifiers.contains(KeyModifiers::ALT) => {
+                self.insert(c.encode_utf8(&mut [0; 4]));
+                self.changed(actions);
+            }
+            KeyCode::Backspace if cursor > 0 => {
+                self.delete_back_to(cursor - 1);
+                self.changed(actions);
             }
-            KeyCode::Backspace => {
-                self.content.borrow_mut().pop();
+            KeyCode::Delete if cursor < self.len() => {
+                let offset = self.offset(cursor);
+                self.content.borrow_mut().remove(offset);
+                self.changed(actions);
             }
             _ => {}
         }
@@ -81,21 +187,27 @@ impl WidgetRef for TextConfirmDialog {
         content_area.x += 2;
         content_area.width -= 2;
 
-        let text_width = content_area.width - 1;
+        // Scrolls just enough to keep the cursor in view, leaving a cell for it past the end.
+        let text_width = usize::from(content_area.width - 1);
+        let cursor = self.cursor.get();
+        let start = cursor.saturating_sub(text_width);
         let content = self
             .content
             .borrow()
             .chars()
-            .rev()
-            .take(text_width.into())
-            .collect::<Vec<_>>();
-
-        Text::from(content.iter().rev().collect::<String>()).render_ref(content_area, buf);
+            .skip(start)
+            .take(text_width + 1)
+            .collect::<String>();
+        Text::from(content.as_str()).render_ref(content_area, buf);
 
-        let n_char = content.len() as u16;
-        content_area.x += n_char;
-        content_area.width -= n_char;
-        Text::from("█").render_ref(content_area, buf);
+        let cursor_x = content_area.x + (cursor - start) as u16;
+        if cursor < self.len() {
+            buf[(cursor_x, content_area.y)].set_style(Style::new().reversed());
+        } else {
+            content_area.width -= cursor_x - content_area.x;
+            content_area.x = cursor_x;
+            Text::from("█").render_ref(content_area, buf);
+        }
     }
 }
 
@@ -163,4 +275,80 @@ mod test {
 
         assert_snapshot!(render_to_string(&dialog));
     }
+
+    fn press(dialog: &TextConfirmDialog, code: KeyCode, modifiers: KeyModifiers) {
+        dialog.handle_event(
+            &mut Actions::new(),
+            Event::Key(KeyEvent::new(code, modifiers)),
+        );
+    }
+
+    #[test]
+    fn readline_test() {
+        let dialog = TextConfirmDialog::new(Box::new(ConfirmAction::action_confirmer(
+            WorkSpaceAction::Rename,
+        )))
+        .content(String::from("héllo wörld"));
+        let state = || (dialog.content.borrow().clone(), dialog.cursor.get());
+
+        press(&dialog, KeyCode::Left, KeyModifiers::NONE);
+        press(&dialog, KeyCode::Left, KeyModifiers::NONE);
+        press(&dialog, KeyCode::Char('X'), KeyModifiers::SHIFT);
+        assert_eq!(state(), (String::from("héllo wörXld"), 10));
+        press(&dialog, KeyCode::Delete, KeyModifiers::NONE);
+        press(&dialog, KeyCode::Backspace, KeyModifiers::NONE);
+        assert_eq!(state(), (String::from("héllo wörd"), 9));
+
+        press(&dialog, KeyCode::Home, KeyModifiers::NONE);
+        press(&dialog, KeyCode::Left, KeyModifiers::NONE);
+        press(&dialog, KeyCode::Backspace, KeyModifiers::NONE);
+        press(&dialog, KeyCode::Right, KeyModifiers::NONE);
+        press(&dialog, KeyCode::Char('w'), KeyModifiers::ALT);
+        assert_eq!(state(), (String::from("héllo wörd"), 1));
+
+        press(&dialog, KeyCode::End, KeyModifiers::NONE);
+        press(&dialog, KeyCode::Char(' '), KeyModifiers::NONE);
+        press(&dialog, KeyCode::Char('w'), KeyModifiers::CONTROL);
+        assert_eq!(state(), (String::from("héllo "), 6));
+        press(&dialog, KeyCode::Char('a'), KeyModifiers::CONTROL);
+        press(&dialog, KeyCode::Right, KeyModifiers::NONE);
+        press(&dialog, KeyCode::Char('k'), KeyModifiers::CONTROL);
+        assert_eq!(state(), (String::from("h"), 1));
+        press(&dialog, KeyCode::Char('e'), KeyModifiers::CONTROL);
+        press(&dialog, KeyCode::Char('u'), KeyModifiers::CONTROL);
+        assert_eq!(state(), (String::new(), 0));
+    }
+
+    #[test]
+    fn paste_test() {
+        let dialog = TextConfirmDialog::new(Box::new(ConfirmAction::action_confirmer(
+            WorkSpaceAction::Rename,
+        )))
+        .on_change(Box::new(|content| {
+            WorkSpaceAction::SearchInput(content).into()
+        }))
+        .content(String::from("ab"));
+        press(&dialog, KeyCode::Left, KeyModifiers::NONE);
+
+        let mut actions = Actions::new();
+        dialog.handle_event(&mut actions, Event::Paste(String::from("ké\ty\r\n")));
+        assert_eq!(*dialog.content.borrow(), "akéyb");
+        assert_eq!(dialog.cursor.get(), 4);
+        assert_eq!(
+            actions.into_vec(),
+            [WorkSpaceAction::SearchInput(String::from("akéyb")).into()]
+        );
+    }
+
+    #[test]
+    fn render_cursor_test() {
+        let dialog = TextConfirmDialog::new(Box::new(ConfirmAction::action_confirmer(
+            WorkSpaceAction::Rename,
+        )))
+        .content("0123456789".repeat(6));
+        assert_snapshot!(render_to_string(&dialog));
+
+        press(&dialog, KeyCode::Home, KeyModifiers::NONE);
+        assert_snapshot!(render_to_string(&dialog));
+    }
 }
@@ -1,28 +1,40 @@
 use std::time::Instant;
 
 use super::popup::popup_area;
+use crate::app::format;
 use ratatui::{
-    layout::Rect,
+    layout::{Constraint, Layout, Rect},
     prelude::Buffer,
-    text::Text,
-    widgets::{Block, Clear, Padding, Widget},
+    style::{Color, Style},
+    text::{Line, Text},
+    widgets::{Block, Clear, LineGauge, Padding, Widget},
 };
 
-pub struct Loading(Instant);
+const PROGRESS_WIDTH: u16 = 40;
+
+/// Loading popup; the dots animate from the start instant, or stay fixed when it is `None`.
+pub struct Loading(Option<Instant>, Option<(usize, usize)>);
 
 impl Default for Loading {
     fn default() -> Self {
-        Self::new()
+        Self::new(true)
     }
 }
 
 impl Loading {
-    pub fn new() -> Self {
-        Loading(Instant::now())
+    pub fn new(is_animated: bool) -> Self {
+        Loading(is_animated.then(Instant::now), None)
+    }
+
+    /// Sets the bytes done out of the total, shown as a progress bar.
+    pub fn set_progress(&mut self, progress: Option<(usize, usize)>) {
+        self.1 = progress;
     }
 
-    fn loading_text(&self) -> Text {
-        let elapsed = (self.0.elapsed().as_secs() % 4) as usize;
+    fn loading_text(&self) -> Text<'_> {
+        let elapsed = self
+            .0
+            .map_or(3, |start| (start.elapsed().as_secs() % 4) as usize);
         Text::from(String::from_iter(
             "Loading".chars().chain(std::iter::repeat_n('.', elapsed)),
         ))
@@ -36,12 +48,37 @@ impl Widget for &Loading {
         Self: Sized,
     {
         let block = Block::bordered().padding(Padding::symmetric(1, 1));
-        let area = popup_area(area, 5, 14);
+        let Some((done, total)) = self.1 else {
+            let area = popup_area(area, 5, 14);
+            Clear.render(area, buf);
+            let inner_area = block.inner(area);
+
+            block.render(area, buf);
+            self.loading_text().render(inner_area, buf);
+            return;
+        };
+
+        // Only jobs reporting their progress can be cancelled.
+        let block = block.title_bottom(Line::from(" Esc to cancel ").right_aligned());
+        let area = popup_area(area, 6, PROGRESS_WIDTH);
         Clear.render(area, buf);
-        let inner_area = block.inner(area);
+        let [text_area, gauge_area] =
+            Layout::vertical([Constraint::Length(1); 2]).areas(block.inner(area));
 
         block.render(area, buf);
-        self.loading_text().render(inner_area, buf);
+        self.loading_text().render(text_area, buf);
+        Text::from(format!(
+            "{} / {}",
+            format::bytes(done),
+            format::bytes(total)
+        ))
+        .right_aligned()
+        .render(text_area, buf);
+        LineGauge::default()
+            .filled_style(Style::new().fg(Color::Cyan))
+            .unfilled_style(Style::new().fg(Color::DarkGray))
+            .ratio((done as f64 / total as f64).clamp(0.0, 1.0))
+            .render(gauge_area, buf);
     }
 }
 
@@ -58,8 +95,26 @@ mod test {
     #[test]
     fn render_test() {
         for i in 0..5 {
-            let loading = Loading(Instant::now() - Duration::from_secs(i));
+            let loading = Loading(Some(Instant::now() - Duration::from_secs(i)), None);
             assert_snapshot!(render_to_string(&loading));
         }
     }
+
+    #[test]
+    fn render_static_test() {
+        assert_eq!(
+            render_to_string(&Loading::new(false)),
+            render_to_string(&Loading(
+                Some(Instant::now() - Duration::from_secs(3)),
+                None
+            ))
+        );
+    }
+
+    #[test]
+    fn render_progress_test() {
+        let mut loading = Loading::new(false);
+        loading.set_progress(Some((3 << 20, 12 << 20)));
+        assert_snapshot!(render_to_string(&loading));
+    }
 }
@@ -0,0 +1,124 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use ratatui::{buffer::Buffer, layout::Rect};
+
+static NEXT_VERSION: AtomicU64 = AtomicU64::new(1);
+
+/// Number telling apart every version of what a pane shows, such as each list built for the
+/// tree, so that a [`PaneCache`] knows when it changed without comparing it.
+pub fn next_version() -> u64 {
+    NEXT_VERSION.fetch_add(1, Ordering::Relaxed)
+}
+
+/// Cells a pane was last rendered to, copied as they are while the pane shows the same thing in
+/// the same area. Only the panes that changed since the last frame get rendered again.
+#[derive(Debug)]
+pub struct PaneCache<K, S> {
+    last: Option<Rendered<K, S>>,
+}
+
+#[derive(Debug)]
+struct Rendered<K, S> {
+    key: K,
+    area: Rect,
+    /// State of the pane before rendering, which the key covers.
+    state: S,
+    /// State as rendering left it, such as scrolled to keep the selection in view.
+    rendered_state: S,
+    buffer: Buffer,
+}
+
+impl<K, S> Default for PaneCache<K, S> {
+    fn default() -> Self {
+        Self { last: None }
+    }
+}
+
+impl<K: PartialEq, S: Clone + PartialEq> PaneCache<K, S> {
+    /// Renders the pane through `render`, unless `key`, `area` and `state` are those of the last
+    /// render, whose cells and resulting state are reused instead.
+    pub fn render(
+        &mut self,
+        key: K,
+        area: Rect,
+        buf: &mut Buffer,
+        state: &mut S,
+        render: impl FnOnce(Rect, &mut Buffer, &mut S),
+    ) {
+        let is_unchanged = self
+            .last
+            .as_ref()
+            .is_some_and(|last| last.key == key && last.area == area && last.state == *state);
+        if !is_unchanged {
+            let before = state.clone();
+            let mut buffer = Buffer::empty(area);
+            render(area, &mut buffer, state);
+            self.last = Some(Rendered {
+                key,
+                area,
+                state: before,
+                rendered_state: state.clone(),
+                buffer,
+            });
+        }
+
+        let last = self.last.as_ref().expect("just rendered");
+        state.clone_from(&last.rendered_state);
+        let area = area.intersection(buf.area);
+        for y in area.top()..area.bottom() {
+            for x in area.left()..area.right() {
+                buf[(x, y)] = last.buffer[(x, y)].clone();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::cell::Cell;
+
+    use ratatui::widgets::{Paragraph, Widget};
+
+    use super::*;
+
+    #[test]
+    fn render_test() {
+        let mut cache = PaneCache::default();
+        let n_renders = Cell::new(0);
+        let render = |cache: &mut PaneCache<&str, u16>, key, state: &mut u16| {
+            let mut buf = Buffer::empty(Rect::new(0, 0, 6, 2));
+            cache.render(
+                key,
+                Rect::new(1, 1, 5, 1),
+                &mut buf,
+                state,
+                |area, buf, state| {
+                    n_renders.set(n_renders.get() + 1);
+                    Paragraph::new(format!("{key}{state}")).render(area, buf);
+                    *state = (*state).min(3);
+                },
+            );
+            buf
+        };
+
+        let mut state = 1;
+        let first = render(&mut cache, "a", &mut state);
+        assert_eq!(first, Buffer::with_lines(["      ", " a1   "]));
+        assert_eq!(render(&mut cache, "a", &mut state), first);
+        assert_eq!(n_renders.get(), 1);
+
+        render(&mut cache, "b", &mut state);
+        assert_eq!(n_renders.get(), 2);
+
+        // The state left by the render comes back along with the cells.
+        state = 9;
+        render(&mut cache, "b", &mut state);
+        assert_eq!(state, 3);
+        state = 9;
+        assert_eq!(
+            render(&mut cache, "b", &mut state),
+            Buffer::with_lines(["      ", " b9   "])
+        );
+        assert_eq!((state, n_renders.get()), (3, 3));
+    }
+}
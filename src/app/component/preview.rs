@@ -1,3 +1,5 @@
+use std::ops::Range;
+
 use ratatui::{
     buffer::Buffer,
     layout::{Constraint, Flex, Layout, Rect},
@@ -10,15 +12,59 @@ use ratatui::{
 
 use crate::app::math::Op;
 
-use super::scrollbar::scrollbar;
+use super::{pane_cache::next_version, scrollbar::scrollbar};
 
-#[derive(Debug, Default)]
+#[derive(Debug, Default, Clone, PartialEq)]
 pub struct PreviewState {
     x_offset: u16,
     y_offset: u16,
+    /// Whether the preview has the keyboard focus.
+    is_focused: bool,
+    search: Option<Search>,
+}
+
+/// Query highlighted in the preview, with the position of the match stepped to with `n` and `N`.
+#[derive(Debug, Clone, PartialEq)]
+struct Search {
+    query: String,
+    current: usize,
 }
 
 impl PreviewState {
+    pub fn set_focused(&mut self, is_focused: bool) {
+        self.is_focused = is_focused;
+    }
+
+    pub fn is_focused(&self) -> bool {
+        self.is_focused
+    }
+
+    /// Index of the first line shown.
+    pub fn line(&self) -> usize {
+        self.y_offset.into()
+    }
+
+    /// Index of the first column shown.
+    pub fn column(&self) -> usize {
+        self.x_offset.into()
+    }
+
+    /// Scrolls back so that the first line and column shown are at most `max_line` and
+    /// `max_column`.
+    pub fn limit(&mut self, max_line: usize, max_column: usize) {
+        self.y_offset = self.y_offset.min(max_line.try_into().unwrap_or(u16::MAX));
+        self.x_offset = self.x_offset.min(max_column.try_into().unwrap_or(u16::MAX));
+    }
+
+    /// Scrolls so that the line at `index` comes first, or as close as the content allows.
+    pub fn scroll_to(&mut self, index: usize) {
+        self.y_offset = index.try_into().unwrap_or(u16::MAX);
+    }
+
+    pub fn clear_search(&mut self) {
+        self.search = None;
+    }
+
     pub fn scroll_up(&mut self, n: u16) {
         self.y_offset = Op::Sub(n).exec(self.y_offset);
     }
@@ -34,25 +80,170 @@ impl PreviewState {
     pub fn scroll_right(&mut self) {
         self.x_offset = Op::Add(1).exec(self.x_offset);
     }
+
+    /// Scrolls left by a single column, as the table view does instead of by several characters.
+    pub fn previous_column(&mut self) {
+        self.x_offset = self.x_offset.saturating_sub(1);
+    }
+
+    pub fn next_column(&mut self) {
+        self.x_offset = self.x_offset.saturating_add(1);
+    }
 }
 
 pub struct Preview {
     content: Option<Content>,
+    /// Whether the content is a `+`/`-`/`~` prefixed line diff.
+    is_diff: bool,
+    title: Option<String>,
+    version: u64,
 }
 
 impl Preview {
     pub fn new(content: Option<String>) -> Self {
         Self {
             content: content.map(Content::new),
+            is_diff: false,
+            title: None,
+            version: next_version(),
         }
     }
+
+    pub fn diff(content: String) -> Self {
+        Self {
+            content: Some(Content::new(content)),
+            is_diff: true,
+            title: None,
+            version: next_version(),
+        }
+    }
+
+    /// Number telling this preview apart from the others, for caching its render.
+    pub fn version(&self) -> u64 {
+        self.version
+    }
+
+    pub fn title(mut self, title: String) -> Self {
+        self.title = Some(title);
+        self
+    }
+
+    /// Highlights `query` and scrolls to its first match from the top line shown, returning how
+    /// many matches there are.
+    pub fn search(&self, state: &mut PreviewState, query: String) -> usize {
+        let matches = self.matches(&query);
+        let current = matches
+            .iter()
+            .position(|(line, _)| *line >= state.line())
+            .unwrap_or_default();
+        if let Some((line, _)) = matches.get(current) {
+            state.scroll_to(*line);
+        }
+        state.search = Some(Search { query, current });
+        matches.len()
+    }
+
+    /// Scrolls to the next match of the search, or the previous one when `backward`, returning
+    /// how many matches there are, or `None` without a search.
+    pub fn search_next(&self, state: &mut PreviewState, backward: bool) -> Option<usize> {
+        let search = state.search.as_mut()?;
+        let matches = self.matches(&search.query);
+        let len = matches.len();
+        if len > 0 {
+            // The content may have changed since the search, so the position may be past the end.
+            let current = search.current.min(len - 1);
+            search.current = if backward {
+                (current + len - 1) % len
+            } else {
+                (current + 1) % len
+            };
+            let (line, _) = matches[search.current];
+            state.scroll_to(line);
+        }
+        Some(len)
+    }
+
+    /// Line and byte range of every occurrence of `query`, ignoring ASCII case.
+    fn matches(&self, query: &str) -> Vec<(usize, Range<usize>)> {
+        let Some(content) = &self.content else {
+            return Vec::new();
+        };
+        content
+            .text
+            .lines()
+            .enumerate()
+            .flat_map(|(index, line)| line_matches(line, query).map(move |range| (index, range)))
+            .collect()
+    }
+}
+
+/// Byte ranges of the occurrences of `query` in `line`, ignoring ASCII case.
+fn line_matches<'a>(line: &str, query: &'a str) -> impl Iterator<Item = Range<usize>> + 'a {
+    let line = line.to_ascii_lowercase();
+    let query = query.to_ascii_lowercase();
+    let mut start = 0;
+    std::iter::from_fn(move || {
+        if query.is_empty() {
+            return None;
+        }
+        let offset = line[start..].find(&query)? + start;
+        start = offset + query.len();
+        Some(offset..start)
+    })
+}
+
+/// `line` with the occurrences of `query` highlighted, the one at `current` among them standing out.
+fn highlight<'a>(
+    line: &'a str,
+    style: Style,
+    query: &str,
+    current: Option<Range<usize>>,
+) -> Line<'a> {
+    let mut spans = Vec::new();
+    let mut end = 0;
+    for range in line_matches(line, query) {
+        spans.push(Span::styled(&line[end..range.start], style));
+        let match_style = if current.as_ref() == Some(&range) {
+            Style::new().black().on_yellow()
+        } else {
+            Style::new().reversed()
+        };
+        end = range.end;
+        spans.push(Span::styled(&line[range], match_style));
+    }
+    spans.push(Span::styled(&line[end..], style));
+    Line::from(spans)
 }
 
 impl StatefulWidget for &Preview {
     type State = PreviewState;
 
     fn render(self, area: Rect, buf: &mut Buffer, state: &mut Self::State) {
-        let block = Block::bordered().title("Preview");
+        let title = match &self.title {
+            Some(title) => title.as_str(),
+            None if self.is_diff => "Diff",
+            None => "Preview",
+        };
+        let matches = state
+            .search
+            .as_ref()
+            .map(|search| self.matches(&search.query))
+            .unwrap_or_default();
+        let current = state.search.as_ref().and_then(|search| {
+            let current = search.current.min(matches.len().checked_sub(1)?);
+            Some((current, matches[current].clone()))
+        });
+        let title = match (&state.search, &current) {
+            (Some(_), Some((current, _))) => {
+                format!("{title} - match {}/{}", current + 1, matches.len())
+            }
+            (Some(_), None) => format!("{title} - no match"),
+            (None, _) => String::from(title),
+        };
+        let mut block = Block::bordered().title(title);
+        if state.is_focused {
+            block = block.border_style(Style::new().cyan());
+        }
         let Some(content) = &self.content else {
             let content_area = block.inner(area);
             block.render(area, buf);
@@ -103,7 +294,27 @@ impl StatefulWidget for &Preview {
             .collect::<Text<'_>>()
             .render(line_number_area, buf);
 
-        let lines = content.text.lines().map(Line::from).collect::<Text>();
+        let lines = content
+            .text
+            .lines()
+            .enumerate()
+            .map(|(index, line)| {
+                let style = match line.as_bytes().first() {
+                    Some(b'+') if self.is_diff => Style::new().green(),
+                    Some(b'-') if self.is_diff => Style::new().red(),
+                    Some(b'~') if self.is_diff => Style::new().yellow(),
+                    _ => Style::new(),
+                };
+                let Some(search) = &state.search else {
+                    return Line::styled(line, style);
+                };
+                let current = current
+                    .as_ref()
+                    .filter(|(_, (current_line, _))| *current_line == index)
+                    .map(|(_, (_, range))| range.clone());
+                highlight(line, style, &search.query, current)
+            })
+            .collect::<Text>();
 
         Paragraph::new(lines)
             .scroll((state.y_offset, state.x_offset))
@@ -189,6 +400,7 @@ mod test {
                 &mut PreviewState {
                     x_offset: 0,
                     y_offset,
+                    ..Default::default()
                 }
             ));
         }
@@ -219,7 +431,8 @@ mod test {
                 &preview,
                 &mut PreviewState {
                     x_offset,
-                    y_offset: 0
+                    y_offset: 0,
+                    ..Default::default()
                 }
             ));
         }
@@ -259,6 +472,47 @@ mod test {
         assert_snapshot!(stateful_render_to_string(&preview, &mut preview_state));
     }
 
+    #[test]
+    fn search_test() {
+        let preview = Preview::new(Some(
+            (1..=30)
+                .map(|number| format!("Cat {number} cat\n"))
+                .collect(),
+        ));
+        let mut preview_state = PreviewState::default();
+        preview_state.scroll_to(3);
+
+        assert_eq!(
+            preview.search(&mut preview_state, String::from("CAT 2")),
+            11
+        );
+        assert_eq!(preview_state.line(), 19);
+        assert_snapshot!(stateful_render_to_string(&preview, &mut preview_state));
+
+        assert_eq!(preview.search_next(&mut preview_state, true), Some(11));
+        assert_eq!(preview_state.line(), 1);
+        assert_eq!(preview.search_next(&mut preview_state, false), Some(11));
+        assert_eq!(preview_state.line(), 19);
+
+        assert_eq!(preview.search(&mut preview_state, String::from("dog")), 0);
+        assert_eq!(preview.search_next(&mut preview_state, false), Some(0));
+        preview_state.clear_search();
+        assert_eq!(preview.search_next(&mut preview_state, false), None);
+    }
+
+    #[test]
+    fn line_matches_test() {
+        assert_eq!(
+            line_matches("Cat cat CAT", "cat").collect::<Vec<_>>(),
+            [0..3, 4..7, 8..11]
+        );
+        assert_eq!(
+            line_matches("aaaaa", "aa").collect::<Vec<_>>(),
+            [0..2, 2..4]
+        );
+        assert_eq!(line_matches("cat", "").count(), 0);
+    }
+
     #[test]
     fn render_empty_test() {
         let preview = Preview::new(None);
@@ -0,0 +1,77 @@
+use ratatui::{
+    layout::Rect,
+    prelude::Buffer,
+    style::{Style, palette::tailwind::SLATE},
+    text::Line,
+    widgets::Widget,
+};
+
+use crate::app::format;
+
+/// One line describing the selected node and whether the document has unsaved changes.
+pub struct StatusBar {
+    /// JSONPath of the selected node.
+    pub path: String,
+    pub type_name: &'static str,
+    pub n_bytes: usize,
+    pub is_edited: bool,
+}
+
+impl Widget for &StatusBar {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let style = Style::new().bg(SLATE.c800);
+        buf.set_style(area, style);
+
+        let modified = if self.is_edited { " [+]" } else { "" };
+        let details = format!(
+            "{} {}{modified} ",
+            self.type_name,
+            format::bytes(self.n_bytes)
+        );
+        // Leaves a space before the path and another before the details.
+        let path_width = area.width.saturating_sub(details.len() as u16 + 2);
+        // Long paths keep their end, where the selected key is.
+        let n_chars = self.path.chars().count();
+        let path = if n_chars > usize::from(path_width) {
+            let skip = n_chars + 1 - usize::from(path_width);
+            format!("…{}", self.path.chars().skip(skip).collect::<String>())
+        } else {
+            self.path.clone()
+        };
+
+        Line::from(format!(" {path}")).render(area, buf);
+        Line::from(details).right_aligned().render(area, buf);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn render(status_bar: &StatusBar, width: u16) -> String {
+        let area = Rect::new(0, 0, width, 1);
+        let mut buf = Buffer::empty(area);
+        status_bar.render(area, &mut buf);
+        buf.content().iter().map(|cell| cell.symbol()).collect()
+    }
+
+    #[test]
+    fn render_test() {
+        let mut status_bar = StatusBar {
+            path: String::from("$.servlet[2]['init-param']"),
+            type_name: "object",
+            n_bytes: 2048,
+            is_edited: false,
+        };
+        assert_eq!(
+            render(&status_bar, 50),
+            " $.servlet[2]['init-param']       object 2.00 KiB "
+        );
+
+        status_bar.is_edited = true;
+        assert_eq!(
+            render(&status_bar, 40),
+            " …[2]['init-param'] object 2.00 KiB [+] "
+        );
+    }
+}
@@ -0,0 +1,54 @@
+use ratatui::{
+    layout::Rect,
+    prelude::Buffer,
+    style::{Style, Stylize, palette::tailwind::SLATE},
+    widgets::{Tabs, Widget},
+};
+
+/// One line naming the open documents, highlighting the shown one.
+pub struct TabBar {
+    /// Names of the documents in tab order, with whether each has unsaved changes.
+    pub tabs: Vec<(String, bool)>,
+    pub selected: usize,
+}
+
+impl Widget for &TabBar {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let titles = self.tabs.iter().map(|(name, is_edited)| {
+            if *is_edited {
+                format!("{name} [+]")
+            } else {
+                name.clone()
+            }
+        });
+        Tabs::new(titles)
+            .select(self.selected)
+            .style(Style::new().bg(SLATE.c800))
+            .highlight_style(Style::new().bold().bg(SLATE.c600))
+            .render(area, buf);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn render(tab_bar: &TabBar, width: u16) -> String {
+        let area = Rect::new(0, 0, width, 1);
+        let mut buf = Buffer::empty(area);
+        tab_bar.render(area, &mut buf);
+        buf.content().iter().map(|cell| cell.symbol()).collect()
+    }
+
+    #[test]
+    fn render_test() {
+        let tab_bar = TabBar {
+            tabs: vec![
+                (String::from("a.json"), false),
+                (String::from("b.json"), true),
+            ],
+            selected: 1,
+        };
+        assert_eq!(render(&tab_bar, 30), " a.json │ b.json [+]          ");
+    }
+}
@@ -0,0 +1,260 @@
+use std::iter;
+
+use indexmap::IndexSet;
+use ratatui::{
+    buffer::Buffer,
+    layout::{Constraint, Rect},
+    style::{Style, Stylize},
+    text::Span,
+    widgets::{
+        Block, Cell, Padding, Row, ScrollbarOrientation, ScrollbarState, StatefulWidget, Table,
+        Widget,
+    },
+};
+
+use crate::container::node::Node;
+
+use super::{pane_cache::next_version, preview::PreviewState, scrollbar::scrollbar};
+
+/// Widest a column gets, longer values being cut.
+const MAX_COLUMN_WIDTH: usize = 30;
+const COLUMN_SPACING: u16 = 2;
+
+/// Array of flat objects shown with a row per object and a column per key. It scrolls by rows
+/// and by columns, keeping the header and the index column in view.
+pub struct TablePreview {
+    columns: Vec<String>,
+    /// Cells of every object, empty for the keys it lacks.
+    rows: Vec<Vec<String>>,
+    widths: Vec<u16>,
+    version: u64,
+}
+
+impl TablePreview {
+    /// Table of `node` when it is a non-empty array of objects holding only scalars.
+    pub fn new(node: &Node) -> Option<Self> {
+        let items = node.as_array().filter(|items| !items.is_empty())?;
+        let mut keys = IndexSet::new();
+        for item in items {
+            for (key, value) in item.as_object()? {
+                if value.as_array().is_some() || value.as_object().is_some() {
+                    return None;
+                }
+                keys.insert(key.as_str());
+            }
+        }
+
+        let rows = items
+            .iter()
+            .map(|item| {
+                let members = item.as_object().expect("checked to be an object");
+                keys.iter()
+                    .map(|key| members.get(*key).map(cell).unwrap_or_default())
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        let columns = keys.into_iter().map(cut).collect::<Vec<_>>();
+        let widths = columns
+            .iter()
+            .enumerate()
+            .map(|(index, column)| {
+                let width = rows
+                    .iter()
+                    .map(|row| row[index].chars().count())
+                    .fold(column.chars().count(), usize::max);
+                width.try_into().unwrap_or(u16::MAX)
+            })
+            .collect();
+        Some(Self {
+            columns,
+            rows,
+            widths,
+            version: next_version(),
+        })
+    }
+
+    /// Number telling this table apart from the others, for caching its render.
+    pub fn version(&self) -> u64 {
+        self.version
+    }
+}
+
+/// Text of a cell: strings without their quotes, escaped only when they hold control characters
+/// such as line breaks, and other scalars as JSON.
+fn cell(value: &Node) -> String {
+    let json = || {
+        value
+            .to_string_compact()
+            .expect("broken internal representation")
+    };
+    match value.as_str() {
+        Some(text) if text.chars().any(char::is_control) => {
+            let json = json();
+            cut(&json[1..json.len() - 1])
+        }
+        Some(text) => cut(text),
+        None => cut(&json()),
+    }
+}
+
+fn cut(text: &str) -> String {
+    if text.chars().count() <= MAX_COLUMN_WIDTH {
+        return String::from(text);
+    }
+    let mut text = text.chars().take(MAX_COLUMN_WIDTH - 1).collect::<String>();
+    text.push('…');
+    text
+}
+
+impl StatefulWidget for &TablePreview {
+    type State = PreviewState;
+
+    fn render(self, area: Rect, buf: &mut Buffer, state: &mut Self::State) {
+        let mut block = Block::bordered().title(format!("Table - {} rows", self.rows.len()));
+        if state.is_focused() {
+            block = block.border_style(Style::new().cyan());
+        }
+        let scrollbar_area = block.inner(area);
+        let block = block.padding(Padding::new(0, 2, 0, 2));
+        let content_area = block.inner(area);
+        block.render(area, buf);
+
+        // The header takes the first line.
+        let n_shown = usize::from(content_area.height.saturating_sub(1));
+        let max_line = self.rows.len().saturating_sub(n_shown);
+        let index_width = (self.rows.len() - 1)
+            .to_string()
+            .len()
+            .try_into()
+            .unwrap_or(u16::MAX);
+        // Scrolling right stops once the last columns are all shown.
+        let mut max_column = self.columns.len();
+        let mut used = index_width;
+        while let Some(width) = max_column.checked_sub(1).map(|index| self.widths[index]) {
+            used = used.saturating_add(COLUMN_SPACING).saturating_add(width);
+            if used > content_area.width {
+                break;
+            }
+            max_column -= 1;
+        }
+        let max_column = max_column.min(self.columns.len().saturating_sub(1));
+        state.limit(max_line, max_column);
+        let (first_line, first_column) = (state.line(), state.column());
+
+        let mut widths = vec![index_width];
+        // Columns past the right edge are left out rather than squeezed.
+        let mut n_columns = 0;
+        let mut remaining = content_area.width.saturating_sub(widths[0]);
+        for &width in &self.widths[first_column.min(self.widths.len())..] {
+            if remaining <= COLUMN_SPACING {
+                break;
+            }
+            remaining -= COLUMN_SPACING;
+            widths.push(width.min(remaining));
+            remaining -= width.min(remaining);
+            n_columns += 1;
+        }
+        let columns = first_column..first_column + n_columns;
+
+        let header = Row::new(
+            iter::once("#")
+                .chain(self.columns[columns.clone()].iter().map(String::as_str))
+                .map(|column| Cell::from(Span::from(column).bold())),
+        );
+        let rows = self
+            .rows
+            .iter()
+            .enumerate()
+            .skip(first_line)
+            .take(n_shown)
+            .map(|(index, row)| {
+                Row::new(
+                    iter::once(Cell::from(Span::from(index.to_string()).cyan())).chain(
+                        row[columns.clone()]
+                            .iter()
+                            .map(|cell| Cell::from(cell.as_str())),
+                    ),
+                )
+            });
+        Widget::render(
+            Table::new(rows, widths.into_iter().map(Constraint::Length))
+                .header(header)
+                .column_spacing(COLUMN_SPACING),
+            content_area,
+            buf,
+        );
+
+        if max_line > 0 {
+            let mut scrollbar_area = scrollbar_area;
+            scrollbar_area.height -= 1;
+            let mut scrollbar_state = ScrollbarState::new(max_line + 1).position(first_line);
+            scrollbar(ScrollbarOrientation::VerticalRight).render(
+                scrollbar_area,
+                buf,
+                &mut scrollbar_state,
+            );
+        }
+        if max_column > 0 {
+            let mut scrollbar_area = scrollbar_area;
+            scrollbar_area.width -= 1;
+            let mut scrollbar_state = ScrollbarState::new(max_column + 1).position(first_column);
+            scrollbar(ScrollbarOrientation::HorizontalBottom).render(
+                scrollbar_area,
+                buf,
+                &mut scrollbar_state,
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use insta::assert_snapshot;
+
+    use super::*;
+    use crate::app::component::test_render::stateful_render_to_string;
+
+    fn table(json: &str) -> Option<TablePreview> {
+        TablePreview::new(&Node::load(json.as_bytes()).unwrap())
+    }
+
+    #[test]
+    fn new_test() {
+        let table = table(r#"[{"id": 1, "name": "a"}, {"name": "b\nc", "ok": true}]"#).unwrap();
+        assert_eq!(table.columns, vec!["id", "name", "ok"]);
+        assert_eq!(
+            table.rows,
+            vec![vec!["1", "a", ""], vec!["", "b\\nc", "true"]]
+        );
+        assert_eq!(table.widths, vec![2, 4, 4]);
+
+        assert!(self::table("[]").is_none());
+        assert!(self::table(r#"{"a": 1}"#).is_none());
+        assert!(self::table(r#"[{"a": 1}, 2]"#).is_none());
+        assert!(self::table(r#"[{"a": [1]}]"#).is_none());
+        assert_eq!(
+            cell(&Node::string("x".repeat(40))),
+            format!("{}…", "x".repeat(29))
+        );
+    }
+
+    #[test]
+    fn render_test() {
+        let rows = (0..30)
+            .map(|index| {
+                format!(
+                    r#"{{"id": {index}, "name": "user {index}", "admin": false,
+                        "email": "user{index}@example.com", "bio": "{}", "team": "platform"}}"#,
+                    "lorem ipsum ".repeat(3)
+                )
+            })
+            .collect::<Vec<_>>();
+        let table = table(&format!("[{}]", rows.join(","))).unwrap();
+        let mut state = PreviewState::default();
+        assert_snapshot!(stateful_render_to_string(&table, &mut state));
+
+        state.scroll_down(100);
+        state.next_column();
+        assert_snapshot!(stateful_render_to_string(&table, &mut state));
+    }
+}
@@ -1,9 +1,8 @@
-use ratatui::{
-    Terminal,
-    backend::TestBackend,
-    widgets::{StatefulWidget, Widget},
-};
+#[cfg(test)]
+use ratatui::widgets::Widget;
+use ratatui::{Terminal, backend::TestBackend, widgets::StatefulWidget};
 
+#[cfg(test)]
 pub fn render_to_string<T: Widget>(widget: T) -> String {
     let mut terminal = Terminal::new(TestBackend::new(80, 20)).unwrap();
     terminal
@@ -1,19 +1,30 @@
 mod worktree_node;
 
-use std::io::Write;
+use std::{
+    cell::Cell,
+    collections::{BTreeMap, VecDeque},
+    io::Write,
+    ops::Deref,
+    time::{Duration, Instant},
+};
 
-use crossterm::event::{Event, KeyCode, KeyModifiers};
+use crossterm::event::{
+    Event, KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind,
+};
 use ratatui::{
-    layout::{Constraint, Layout},
+    layout::{Constraint, Layout, Margin, Position},
     prelude::{Buffer, Rect},
-    style::{Modifier, Style, palette::tailwind::SLATE},
-    text::{Line, Text},
+    style::{
+        Modifier, Style,
+        palette::tailwind::{BLUE, CYAN, GREEN, ORANGE, RED, SLATE, VIOLET, YELLOW},
+    },
+    text::{Line, Span, Text},
     widgets::{
-        Block, HighlightSpacing, List, ListState, ScrollbarOrientation, ScrollbarState,
+        Block, HighlightSpacing, List, ListItem, ListState, ScrollbarOrientation, ScrollbarState,
         StatefulWidget, Widget,
     },
 };
-use worktree_node::WorkTreeNode;
+pub(crate) use worktree_node::WorkTreeNode;
 
 use crate::{
     app::{
@@ -23,22 +34,83 @@ use crate::{
             WorkSpaceAction,
         },
         component::confirm_dialog::{
-            error_confirm_dialog::ErrorConfirmDialog, text_confirm_dialog::TextConfirmDialog,
+            error_confirm_dialog::ErrorConfirmDialog, list_confirm_dialog::ListConfirmDialog,
+            text_confirm_dialog::TextConfirmDialog,
         },
-        config::Config,
+        config::{Config, NumberEdits, PreviewLayout},
+        decode::Decoding,
+        diff::{self, DiffLine},
+        format,
         math::Op,
+        register::{self, CLIPBOARD_REGISTER, Registers, UNNAMED_REGISTER},
+        tool,
+    },
+    container::{
+        encoding::Indent,
+        gron,
+        merge::Conflict,
+        ndjson::RecordReader,
+        node::{AddNodeKey, Index, IndexKind, Node, NodeKind, NodeMeta, ScalarType, SortSpec},
+        parser::{DuplicateKeys, Repairs},
+        patch,
+        pointer::{self, KeyPath, to_path, to_pointer},
+        validation::Schema,
     },
-    container::node::{AddNodeKey, Index, IndexKind, Node, NodeMeta},
-    error::MutationError,
+    error::{ConversionError, IndexingError, MutationError, PointerError},
 };
 
 use super::{
     confirm_dialog::{ConfirmDialog, boolean_confirm_dialog::BooleanConfirmDialog},
     loading::Loading,
+    pane_cache::{PaneCache, next_version},
     preview::{Preview, PreviewState},
     scrollbar::scrollbar,
+    status_bar::StatusBar,
+    table_preview::TablePreview,
 };
 
+const LARGEST_SUBTREES_COUNT: usize = 20;
+/// Rows expanded at once without asking, beyond which the rows are also built in the background.
+const EXPAND_ALL_LIMIT: usize = 10_000;
+const SPLIT_KEY_PLACEHOLDER: &str = "{key}";
+const SPLIT_DEFAULT_PATTERN: &str = "./{key}.json";
+const REGISTER_PREVIEW_LEN: usize = 60;
+const MESSAGE_HISTORY_LEN: usize = 100;
+const REVIEW_PATHS_LEN: usize = 10;
+const TRASH_LEN: usize = 20;
+const MOUSE_SCROLL_LINES: usize = 3;
+const DOUBLE_CLICK_TIME: Duration = Duration::from_millis(400);
+const SORT_SPECS: [(&str, SortSpec); 4] = [
+    (
+        "Ascending",
+        SortSpec {
+            natural: false,
+            descending: false,
+        },
+    ),
+    (
+        "Descending",
+        SortSpec {
+            natural: false,
+            descending: true,
+        },
+    ),
+    (
+        "Natural ascending",
+        SortSpec {
+            natural: true,
+            descending: false,
+        },
+    ),
+    (
+        "Natural descending",
+        SortSpec {
+            natural: true,
+            descending: true,
+        },
+    ),
+];
+
 pub struct WorkSpace {
     config: Config,
     file_root: Node,
@@ -46,33 +118,233 @@ pub struct WorkSpace {
     is_edited: bool,
 
     list: List<'static>,
+    /// Changes whenever `list` is rebuilt, so that the tree is only rendered again then.
+    list_version: u64,
     // dialogs: Vec<BooleanConfirmDialog>,
     dialogs: Vec<Box<dyn ConfirmDialog>>,
     preview: Option<Preview>,
+    /// Preview of a node kept beside the other one while navigating elsewhere, as it was when
+    /// pinned.
+    pinned: Option<Preview>,
     preview_pct: u16,
+    /// Whether the previews take the whole window, hiding the tree.
+    is_preview_fullscreen: bool,
+    preview_layout: PreviewLayout,
+    /// How the preview of strings is decoded, or `None` to show their JSON.
+    decoding: Option<Decoding>,
+    /// Whether arrays of flat objects preview as a table.
+    is_table_view: bool,
+    /// Table drawn instead of the preview, which it shares the scroll position with.
+    table: Option<TablePreview>,
     loading: Option<Loading>,
+    registers: Registers,
+    pending_register: Cell<PendingRegister>,
+    /// Whether other documents are open in tabs, which makes `g` a prefix of `gt` and `gT`.
+    has_tabs: bool,
+    pending_g: Cell<bool>,
+    pending_bookmark: Cell<PendingBookmark>,
+    /// Selectors bookmarked by letter, which stay valid as the tree is reindexed.
+    bookmarks: BTreeMap<char, KeyPath>,
+    /// Register the delete being confirmed cuts into.
+    cut_register: char,
+    messages: VecDeque<String>,
+    /// Whether the latest message is still shown in the status line.
+    show_message: bool,
+    checkpoint: Option<Checkpoint>,
+    /// Editor command of the edit in progress, reused when retrying after a JSON error.
+    edit_command: Option<String>,
+    trash: VecDeque<Trashed>,
+    /// Unresolved conflicts of a merged document.
+    conflicts: Vec<Conflict>,
+    /// Edited node and its repaired paths, waiting for its changes to be confirmed.
+    pending_edit: Option<(Node, Repairs)>,
+    /// Last jq filter run, offered again by the next one.
+    jq_filter: String,
+    /// What the last jq filter printed, waiting to replace the selection.
+    jq_output: Option<Node>,
+    /// Last shell command the selection was piped through.
+    pipe_command: String,
+    /// Deletions since the document was last compacted.
+    deletes_since_compact: usize,
+    search: Option<Search>,
+    /// Query the tree is filtered by, showing only matching nodes and their ancestors.
+    filter: Option<String>,
+    /// Leaves listed with their paths, shown instead of the tree.
+    flat: Option<FlatView>,
+    /// Schema the document is validated against with `V`.
+    schema: Option<Schema>,
+    compare: Option<Comparison>,
+    /// Changes made since the document was loaded, for saving as a JSON Patch.
+    patch: Vec<patch::Operation>,
+}
+
+/// Every leaf of the document on its own row, as `json.a[0].b = 1` in the way `gron` prints
+/// documents. Selecting a row selects its node in the tree, so that actions apply to it.
+struct FlatView {
+    /// The document the rows were listed from, sharing its containers until either changes.
+    root: Node,
+    selectors: Vec<Vec<String>>,
+    list: List<'static>,
+    list_version: u64,
+}
+
+impl FlatView {
+    fn new(root: &Node) -> Self {
+        let (selectors, items): (Vec<_>, Vec<_>) = root
+            .leaves()
+            .map(|(selector, leaf)| {
+                let value = gron::value(leaf);
+                let line = Line::from(vec![
+                    Span::from(gron::path(root, &selector)),
+                    Span::styled(format!(" = {value}"), Style::new().fg(SLATE.c400)),
+                ]);
+                (selector, ListItem::new(line))
+            })
+            .unzip();
+        Self {
+            root: root.clone(),
+            selectors,
+            list: styled_list(items),
+            list_version: next_version(),
+        }
+    }
+
+    /// Row of the first leaf at or below `selector`.
+    fn row<T: Deref<Target = str>>(&self, selector: &[T], hint: Option<usize>) -> Option<usize> {
+        let is_below = |row: usize| {
+            self.selectors.get(row).is_some_and(|leaf| {
+                leaf.len() >= selector.len()
+                    && leaf
+                        .iter()
+                        .zip(selector)
+                        .all(|(key, other)| **key == **other)
+            })
+        };
+        // The selection usually is the leaf selected last.
+        if hint.is_some_and(is_below) {
+            return hint;
+        }
+        (0..self.selectors.len()).find(|row| is_below(*row))
+    }
+}
+
+/// Another document the tree is compared against, marking the rows that differ from it.
+struct Comparison {
+    name: String,
+    node: Node,
+}
+
+/// Matches of the last search, stepped through with `n` and `N`.
+struct Search {
+    query: String,
+    matches: Vec<Vec<String>>,
+    /// Position of the selected match.
+    current: usize,
+}
+
+/// The previous version of the subtree touched by the last change.
+struct Checkpoint {
+    selector: KeyPath,
+    node: Node,
+}
+
+impl Checkpoint {
+    /// Records the operations turning this version into the current one in `root`.
+    fn record(&self, root: &Node, patch: &mut Vec<patch::Operation>) {
+        patch::diff(
+            &self.selector,
+            &self.node,
+            root.subtree(&self.selector).ok(),
+            patch,
+        );
+    }
+}
+
+/// A deleted subtree kept for restoring.
+struct Trashed {
+    selector: KeyPath,
+    /// Position among its siblings when it was deleted.
+    position: usize,
+    node: Node,
+}
+
+/// Progress of a `"<name>` register prefix typed before yank, paste or delete.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum PendingRegister {
+    None,
+    Quote,
+    Named(char),
+}
+
+/// Bookmark command waiting for its letter, after `m` or `'`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum PendingBookmark {
+    None,
+    Set,
+    Jump,
 }
 
 impl WorkSpace {
     pub fn new(file_root: Node, config: Config) -> Self {
         let work_tree_root =
             WorkTreeNode::new(String::from("root"), Some(file_root.as_index().meta));
-        let list = new_list(&work_tree_root);
+        let list = new_list(&work_tree_root, &file_root, None, &config);
+        let registers = match register::default_path() {
+            Some(path) if config.persist_registers => Registers::load(path),
+            _ => Registers::default(),
+        };
         Self {
+            preview_layout: config.preview_layout,
+            decoding: None,
+            is_table_view: false,
+            table: None,
             config,
             file_root,
             work_tree_root,
             is_edited: false,
             list,
+            list_version: next_version(),
             dialogs: Vec::new(),
             preview: None,
+            pinned: None,
             preview_pct: 65,
+            is_preview_fullscreen: false,
             loading: None,
+            registers,
+            pending_register: Cell::new(PendingRegister::None),
+            has_tabs: false,
+            pending_g: Cell::new(false),
+            pending_bookmark: Cell::new(PendingBookmark::None),
+            bookmarks: BTreeMap::new(),
+            cut_register: UNNAMED_REGISTER,
+            messages: VecDeque::new(),
+            show_message: false,
+            checkpoint: None,
+            edit_command: None,
+            trash: VecDeque::new(),
+            conflicts: Vec::new(),
+            pending_edit: None,
+            jq_filter: String::from("."),
+            jq_output: None,
+            pipe_command: String::new(),
+            deletes_since_compact: 0,
+            search: None,
+            filter: None,
+            flat: None,
+            schema: None,
+            compare: None,
+            patch: Vec::new(),
         }
     }
 
-    pub fn handle_event(&self, actions: &mut Actions, event: Event) {
+    pub fn handle_event(&self, state: &WorkSpaceState, actions: &mut Actions, event: Event) {
         if self.loading.is_some() {
+            if let Some(event) = event.as_key_press_event()
+                && (event.code == KeyCode::Esc
+                    || event.code == KeyCode::Char('c') && event.modifiers == KeyModifiers::CONTROL)
+            {
+                actions.push(Action::CancelJobs);
+            }
             return;
         }
 
@@ -81,12 +353,39 @@ impl WorkSpace {
             return;
         }
 
+        if let Event::Mouse(mouse_event) = event {
+            actions.push(WorkSpaceAction::Mouse(mouse_event).into());
+            return;
+        }
+
         let Some(event) = event.as_key_press_event() else {
             return;
         };
 
+        if state.focus != Focus::Tree
+            && let Some(action) = preview_key_action(event)
+        {
+            actions.push(action);
+            return;
+        }
+
         if event.modifiers == KeyModifiers::CONTROL {
             match event.code {
+                KeyCode::Char('r') => {
+                    actions.push(JobAction::Restat.into());
+                }
+                KeyCode::Char('w') => {
+                    actions.push(WorkSpaceAction::SavePatch(ConfirmAction::Request(())).into());
+                }
+                KeyCode::Char('v') => {
+                    actions.push(
+                        JobAction::ReadClipboard {
+                            replace: true,
+                            run_tools: !self.config.no_shell,
+                        }
+                        .into(),
+                    );
+                }
                 KeyCode::Char('u') => {
                     actions.push(NavigationAction::Up(10).into());
                 }
@@ -110,7 +409,103 @@ impl WorkSpace {
             return;
         }
 
+        if event.modifiers == KeyModifiers::ALT {
+            match event.code {
+                KeyCode::Char('k') | KeyCode::Up => {
+                    actions.push(WorkSpaceAction::MoveWithin(true).into());
+                }
+                KeyCode::Char('j') | KeyCode::Down => {
+                    actions.push(WorkSpaceAction::MoveWithin(false).into());
+                }
+                _ => {}
+            }
+            return;
+        }
+
+        if self.pending_g.replace(false) {
+            match event.code {
+                KeyCode::Char('t') | KeyCode::Char('T') => {
+                    actions.push(Action::SwitchTab(event.code == KeyCode::Char('T')));
+                    return;
+                }
+                KeyCode::Char('g') => {
+                    actions.push(NavigationAction::Top.into());
+                    return;
+                }
+                _ => actions.push(NavigationAction::Top.into()),
+            }
+        }
+
+        let pending_bookmark = self.pending_bookmark.replace(PendingBookmark::None);
+        if pending_bookmark != PendingBookmark::None {
+            if let KeyCode::Char(c) = event.code
+                && c.is_ascii_alphabetic()
+            {
+                actions.push(if pending_bookmark == PendingBookmark::Set {
+                    WorkSpaceAction::Bookmark(c).into()
+                } else {
+                    WorkSpaceAction::JumpToBookmark(c).into()
+                });
+            }
+            return;
+        }
+
+        let register = match self.pending_register.replace(PendingRegister::None) {
+            PendingRegister::Quote => {
+                if let KeyCode::Char(c) = event.code
+                    && (Registers::is_named(c) || c == UNNAMED_REGISTER || c == CLIPBOARD_REGISTER)
+                {
+                    self.pending_register.set(PendingRegister::Named(c));
+                }
+                return;
+            }
+            PendingRegister::Named(c) => c,
+            PendingRegister::None => UNNAMED_REGISTER,
+        };
+
         match event.code {
+            KeyCode::Char('"') => {
+                self.pending_register.set(PendingRegister::Quote);
+            }
+            KeyCode::Char('y') => {
+                actions.push(WorkSpaceAction::Yank(register).into());
+            }
+            KeyCode::Char('P') if register == CLIPBOARD_REGISTER => {
+                actions.push(
+                    JobAction::ReadClipboard {
+                        replace: false,
+                        run_tools: !self.config.no_shell,
+                    }
+                    .into(),
+                );
+            }
+            KeyCode::Char('P') => {
+                actions.push(
+                    WorkSpaceAction::Paste {
+                        register,
+                        key: ConfirmAction::Request(()),
+                    }
+                    .into(),
+                );
+            }
+            KeyCode::Char('R') => {
+                actions.push(WorkSpaceAction::ListRegisters.into());
+            }
+            KeyCode::Char('m') => {
+                self.pending_bookmark.set(PendingBookmark::Set);
+            }
+            KeyCode::Char('\'') => {
+                self.pending_bookmark.set(PendingBookmark::Jump);
+            }
+            KeyCode::Char('`') => {
+                actions.push(WorkSpaceAction::ListBookmarks.into());
+            }
+            KeyCode::Char('A') => {
+                actions.push(WorkSpaceAction::ListSnippets.into());
+            }
+            KeyCode::Char('g') if self.has_tabs => {
+                self.pending_g.set(true);
+            }
             KeyCode::Char('g') => {
                 actions.push(NavigationAction::Top.into());
             }
@@ -129,18 +524,45 @@ impl WorkSpace {
             KeyCode::Char('h') => {
                 actions.push(NavigationAction::Close.into());
             }
+            KeyCode::Char('E') => {
+                actions.push(WorkSpaceAction::ExpandAll(ConfirmAction::Request(())).into());
+            }
+            KeyCode::Char('Z') => {
+                actions.push(NavigationAction::CollapseAll.into());
+            }
             KeyCode::Char('p') => {
                 actions.push(NavigationAction::TogglePreview.into());
             }
+            KeyCode::Char('z') => {
+                actions.push(NavigationAction::ToggleFullscreenPreview.into());
+            }
+            KeyCode::Char('o') => {
+                actions.push(NavigationAction::TogglePreviewLayout.into());
+            }
+            KeyCode::Char('b') => {
+                actions.push(NavigationAction::CycleDecoding.into());
+            }
+            KeyCode::Char('O') => {
+                actions.push(NavigationAction::ToggleTableView.into());
+            }
+            KeyCode::Char('_') => {
+                actions.push(NavigationAction::ToggleFlatView.into());
+            }
             KeyCode::Char('q') => {
                 actions.push(Action::Exit(ConfirmAction::Request(())));
             }
             KeyCode::Char('e') => {
                 actions.push(WorkSpaceAction::Edit.into());
             }
+            KeyCode::Char('v') => {
+                actions.push(WorkSpaceAction::Page.into());
+            }
             KeyCode::Char('w') => {
                 actions.push(WorkSpaceAction::Save(ConfirmAction::Request(())).into());
             }
+            KeyCode::Char('W') => {
+                actions.push(WorkSpaceAction::SaveAs(ConfirmAction::Request(())).into());
+            }
             KeyCode::Char('H') => {
                 actions.push(PreviewNavigationAction::Left.into());
             }
@@ -157,23 +579,124 @@ impl WorkSpace {
                 actions.push(WorkSpaceAction::Rename(ConfirmAction::Request(())).into());
             }
             KeyCode::Char('d') => {
-                actions.push(WorkSpaceAction::Delete(ConfirmAction::Request(())).into());
+                actions.push(WorkSpaceAction::Delete(ConfirmAction::Request(register)).into());
+            }
+            KeyCode::Char('D') => {
+                actions.push(WorkSpaceAction::Duplicate.into());
+            }
+            KeyCode::Char('s') => {
+                actions.push(WorkSpaceAction::Sort(ConfirmAction::Request(())).into());
+            }
+            KeyCode::Char('f') => {
+                actions.push(WorkSpaceAction::Filter(ConfirmAction::Request(())).into());
+            }
+            KeyCode::Char('t') => {
+                actions.push(WorkSpaceAction::SetType(ConfirmAction::Request(())).into());
+            }
+            KeyCode::Char('{') => {
+                actions.push(WorkSpaceAction::ExpandJson.into());
+            }
+            KeyCode::Char('}') => {
+                actions.push(WorkSpaceAction::Stringify.into());
             }
             KeyCode::Char('a') => {
                 actions.push(WorkSpaceAction::Add(ConfirmAction::Request(())).into());
             }
+            KeyCode::Char('i') => {
+                actions.push(WorkSpaceAction::Info.into());
+            }
+            KeyCode::Char('#') => {
+                actions.push(WorkSpaceAction::JumpToIndex(ConfirmAction::Request(())).into());
+            }
+            KeyCode::Char('B') => {
+                actions.push(WorkSpaceAction::LargestSubtrees.into());
+            }
+            KeyCode::Char('S') => {
+                actions.push(WorkSpaceAction::Split(ConfirmAction::Request(())).into());
+            }
+            KeyCode::Char('F') => {
+                actions.push(WorkSpaceAction::ImportFolder(ConfirmAction::Request(())).into());
+            }
+            KeyCode::Char('I') => {
+                actions.push(WorkSpaceAction::ImportFile(ConfirmAction::Request(())).into());
+            }
+            KeyCode::Char('X') => {
+                actions.push(WorkSpaceAction::ExportGron(ConfirmAction::Request(())).into());
+            }
+            KeyCode::Char('U') => {
+                actions.push(WorkSpaceAction::ImportGron(ConfirmAction::Request(())).into());
+            }
+            KeyCode::Char('M') => {
+                actions.push(WorkSpaceAction::ListMessages.into());
+            }
+            KeyCode::Char('c') => {
+                actions.push(WorkSpaceAction::Diff.into());
+            }
+            KeyCode::Char('|') => {
+                actions.push(WorkSpaceAction::Pin.into());
+            }
+            KeyCode::Char('Q') => {
+                actions.push(WorkSpaceAction::Jq(ConfirmAction::Request(())).into());
+            }
+            KeyCode::Char('!') => {
+                actions.push(WorkSpaceAction::Pipe(ConfirmAction::Request(())).into());
+            }
+            KeyCode::Tab => {
+                actions.push(NavigationAction::SwitchFocus.into());
+            }
+            KeyCode::Char('=') => {
+                actions.push(WorkSpaceAction::Compare(ConfirmAction::Request(())).into());
+            }
+            KeyCode::Char('C') => {
+                actions.push(JobAction::Compact.into());
+            }
+            KeyCode::Char('T') => {
+                actions.push(WorkSpaceAction::ListTrash.into());
+            }
+            KeyCode::Char('x') => {
+                actions.push(WorkSpaceAction::ListConflicts.into());
+            }
+            KeyCode::Char('V') => {
+                actions.push(WorkSpaceAction::Validate.into());
+            }
+            KeyCode::Char(':') => {
+                actions.push(WorkSpaceAction::GoTo(ConfirmAction::Request(())).into());
+            }
+            KeyCode::Char('/') => {
+                actions.push(WorkSpaceAction::Search(ConfirmAction::Request(())).into());
+            }
+            KeyCode::Char('n') => {
+                actions.push(WorkSpaceAction::SearchNext(false).into());
+            }
+            KeyCode::Char('N') => {
+                actions.push(WorkSpaceAction::SearchNext(true).into());
+            }
             _ => {}
         }
     }
 
     pub fn set_loading(&mut self, is_loading: bool) {
         if is_loading && self.loading.is_none() {
-            self.loading = Some(Loading::default());
+            self.loading = Some(Loading::new(self.config.animations));
         } else if !is_loading {
             self.loading = None;
         }
     }
 
+    pub fn set_schema(&mut self, schema: Schema) {
+        self.schema = Some(schema);
+    }
+
+    pub fn set_has_tabs(&mut self, has_tabs: bool) {
+        self.has_tabs = has_tabs;
+    }
+
+    pub fn set_progress(&mut self, progress: Option<(usize, usize)>) {
+        if let Some(loading) = &mut self.loading {
+            loading.set_progress(progress);
+        }
+    }
+
     pub fn maybe_exit(&mut self, confirm_action: ConfirmAction<()>) -> bool {
         match confirm_action {
             ConfirmAction::Request(()) => {
@@ -201,22 +724,173 @@ impl WorkSpace {
     ) -> std::io::Result<()> {
         match action {
             WorkSpaceAction::Navigation(navigation_action) => {
+                self.show_message = false;
                 self.handle_navigation_action(state, navigation_action);
             }
-            WorkSpaceAction::Edit => actions.push(JobAction::Edit(EditJobAction::Init).into()),
+            WorkSpaceAction::Mouse(mouse_event) => {
+                self.show_message = false;
+                self.handle_mouse(state, mouse_event);
+            }
+            WorkSpaceAction::Edit => {
+                let is_elided = state
+                    .list_state
+                    .selected()
+                    .is_some_and(|index| self.work_tree_root.elided(index).is_some());
+                if !is_elided {
+                    if self.edits_inline(state) || self.config.no_shell {
+                        self.handle_inline_edit(state, actions, ConfirmAction::Request(()));
+                    } else {
+                        self.edit_command = self.selected_tool(state, true);
+                        actions.push(
+                            JobAction::Edit(EditJobAction::Init(self.edit_command.clone())).into(),
+                        );
+                    }
+                }
+            }
+            WorkSpaceAction::Page => self.handle_page(state, actions),
             WorkSpaceAction::EditError(confirm_action) => {
                 if self.handle_edit_error_action(confirm_action) {
-                    actions.push(JobAction::Edit(EditJobAction::Open).into());
+                    actions.push(
+                        JobAction::Edit(EditJobAction::Open(self.edit_command.clone())).into(),
+                    );
+                }
+            }
+            WorkSpaceAction::InlineEdit(confirm_action) => {
+                self.handle_inline_edit(state, actions, confirm_action);
+            }
+            WorkSpaceAction::SetType(confirm_action) => {
+                self.handle_set_type(state, actions, confirm_action);
+            }
+            WorkSpaceAction::ExpandJson => self.handle_expand_json(state, actions),
+            WorkSpaceAction::Stringify => {
+                if self.index_for_mutation(state).is_some()
+                    && let Some(node) = self.selected_node(state)
+                {
+                    actions.push(
+                        WorkSpaceAction::Load {
+                            node: node.stringify(),
+                            is_edit: true,
+                            repaired: Repairs::default(),
+                        }
+                        .into(),
+                    );
                 }
             }
+            WorkSpaceAction::Sort(confirm_action) => {
+                self.handle_sort(state, confirm_action);
+            }
+            WorkSpaceAction::Filter(confirm_action) => {
+                self.handle_filter(state, confirm_action);
+            }
             WorkSpaceAction::Rename(confirm_action) => {
                 self.handle_rename(state, confirm_action)?;
             }
             WorkSpaceAction::Delete(confirm_action) => {
-                self.handle_delete(state, confirm_action)?;
+                self.handle_delete(state, actions, confirm_action)?;
             }
             WorkSpaceAction::Add(confirm_action) => {
-                self.handle_add(state, confirm_action)?;
+                self.handle_add(state, confirm_action);
+            }
+            WorkSpaceAction::JumpToIndex(confirm_action) => {
+                self.handle_jump_to_index(state, confirm_action);
+            }
+            WorkSpaceAction::Search(confirm_action) => {
+                self.handle_search(state, confirm_action);
+            }
+            WorkSpaceAction::GoTo(confirm_action) => self.handle_go_to(state, confirm_action),
+            WorkSpaceAction::CreatePath(confirm_action) => {
+                self.handle_create_path(state, confirm_action);
+            }
+            WorkSpaceAction::SearchInput(query) => self.search_for(state, query),
+            WorkSpaceAction::SearchNext(backward) => self.handle_search_next(state, backward),
+            WorkSpaceAction::PreviewSearch(confirm_action) => {
+                self.handle_preview_search(state, confirm_action);
+            }
+            WorkSpaceAction::PreviewSearchNext(backward) => {
+                self.handle_preview_search_next(state, backward);
+            }
+            WorkSpaceAction::Info => self.handle_info(state),
+            WorkSpaceAction::LargestSubtrees => self.handle_largest_subtrees(),
+            WorkSpaceAction::Validate => self.handle_validate(state),
+            WorkSpaceAction::Split(confirm_action) => {
+                if let Some(action) = self.handle_split(state, confirm_action) {
+                    actions.push(action);
+                }
+            }
+            WorkSpaceAction::SplitDone(n_files) => {
+                self.dialogs.push(Box::new(
+                    ErrorConfirmDialog::new(format!("Wrote {n_files} files").into())
+                        .title(Line::from("Split")),
+                ));
+            }
+            WorkSpaceAction::ImportFolder(confirm_action) => {
+                if let Some(action) = self.handle_import_folder(state, confirm_action) {
+                    actions.push(action);
+                }
+            }
+            WorkSpaceAction::ImportFile(confirm_action) => {
+                if let Some(action) = self.handle_import_file(state, confirm_action) {
+                    actions.push(action);
+                }
+            }
+            WorkSpaceAction::ExportGron(confirm_action) => {
+                if let Some(action) = self.handle_export_gron(state, confirm_action) {
+                    actions.push(action);
+                }
+            }
+            WorkSpaceAction::ImportGron(confirm_action) => {
+                if let Some(action) = self.handle_import_gron(state, confirm_action) {
+                    actions.push(action);
+                }
+            }
+            WorkSpaceAction::Exported(message) => self.push_message(message),
+            WorkSpaceAction::Yank(register) => self.handle_yank(state, actions, register),
+            WorkSpaceAction::Duplicate => self.handle_duplicate(state),
+            WorkSpaceAction::MoveWithin(up) => self.handle_move_within(state, up),
+            WorkSpaceAction::Paste { register, key } => {
+                self.handle_paste(state, register, key);
+            }
+            WorkSpaceAction::ListRegisters => self.handle_list_registers(),
+            WorkSpaceAction::RegisterPicked(register) => {
+                self.dialogs.pop();
+                if let Some(register) = register {
+                    actions.push(
+                        WorkSpaceAction::Paste {
+                            register,
+                            key: ConfirmAction::Request(()),
+                        }
+                        .into(),
+                    );
+                }
+            }
+            WorkSpaceAction::ListSnippets => self.handle_list_snippets(),
+            WorkSpaceAction::SnippetPicked(name) => {
+                self.dialogs.pop();
+                if let Some(name) = name {
+                    actions.push(
+                        WorkSpaceAction::InsertSnippet {
+                            name,
+                            key: ConfirmAction::Request(()),
+                        }
+                        .into(),
+                    );
+                }
+            }
+            WorkSpaceAction::ClipboardRead { content, replace } => {
+                self.handle_clipboard_read(actions, content, replace);
+            }
+            WorkSpaceAction::InsertSnippet { name, key } => {
+                self.handle_insert_snippet(state, name, key);
+            }
+            WorkSpaceAction::Error(message) => {
+                self.dialogs
+                    .push(Box::new(ErrorConfirmDialog::new(message.into())));
+            }
+            WorkSpaceAction::SelectPath(selector) => {
+                self.dialogs.pop();
+                if let Some(selector) = selector {
+                    self.select_path(state, &selector);
+                }
             }
             WorkSpaceAction::Save(confirm_action) => {
                 self.dialogs.pop();
@@ -224,26 +898,260 @@ impl WorkSpace {
                     actions.push(action);
                 }
             }
-            WorkSpaceAction::SaveDone => self.handle_save_done(),
-            WorkSpaceAction::Load { node, is_edit } => {
+            WorkSpaceAction::Overwrite(confirm_action) => {
+                if let Some(action) = self.handle_overwrite(confirm_action) {
+                    actions.push(action);
+                }
+            }
+            WorkSpaceAction::SaveAs(confirm_action) => {
+                if let Some(action) = self.handle_save_as(confirm_action) {
+                    actions.push(action);
+                }
+            }
+            WorkSpaceAction::SavePatch(confirm_action) => {
+                if let Some(action) = self.handle_save_patch(confirm_action) {
+                    actions.push(action);
+                }
+            }
+            WorkSpaceAction::PatchSaved(message) => self.push_message(message),
+            WorkSpaceAction::Recover(confirm_action) => {
+                if let Some(action) = self.handle_recover(confirm_action) {
+                    actions.push(action);
+                }
+            }
+            WorkSpaceAction::Autosaved(error) => {
+                if let Some(error) = error {
+                    self.push_message(format!("Stopped autosaving: {error}"));
+                }
+            }
+            WorkSpaceAction::InputChanged(confirm_action) => {
+                if let Some(action) = self.handle_input_changed(confirm_action) {
+                    actions.push(action);
+                }
+            }
+            WorkSpaceAction::Reloaded { path, node } => self.handle_reloaded(state, path, node),
+            WorkSpaceAction::SaveDone(message) => self.handle_save_done(message),
+            WorkSpaceAction::ListMessages => self.handle_list_messages(),
+            WorkSpaceAction::Bookmark(name) => self.handle_bookmark(state, name),
+            WorkSpaceAction::JumpToBookmark(name) => self.handle_jump_to_bookmark(state, name),
+            WorkSpaceAction::ListBookmarks => self.handle_list_bookmarks(),
+            WorkSpaceAction::Diff => self.handle_diff(state),
+            WorkSpaceAction::Pin => self.handle_pin(state),
+            WorkSpaceAction::Jq(confirm_action) => {
+                if let Some(action) = self.handle_jq(state, confirm_action) {
+                    actions.push(action);
+                }
+            }
+            WorkSpaceAction::JqOutput(confirm_action) => {
+                self.handle_jq_output(state, actions, confirm_action);
+            }
+            WorkSpaceAction::Pipe(confirm_action) => {
+                if let Some(action) = self.handle_pipe(state, confirm_action) {
+                    actions.push(action);
+                }
+            }
+            WorkSpaceAction::Compare(confirm_action) => {
+                if let Some(action) = self.handle_compare(state, confirm_action) {
+                    actions.push(action);
+                }
+            }
+            WorkSpaceAction::Compared { name, node } => self.handle_compared(state, name, node),
+            WorkSpaceAction::ListTrash => self.handle_list_trash(),
+            WorkSpaceAction::ReadOnly(confirm_action) => match confirm_action {
+                ConfirmAction::Request(message) => {
+                    let mut confirm_dialog = BooleanConfirmDialog::new(
+                        Text::from(vec![
+                            Line::from(message),
+                            Line::from(""),
+                            Line::from("Open read-only?").centered(),
+                        ]),
+                        Box::new(ConfirmAction::action_confirmer(WorkSpaceAction::ReadOnly)),
+                    );
+                    confirm_dialog.title(Some(Line::from("Already open").left_aligned()));
+                    self.dialogs.push(Box::new(confirm_dialog));
+                }
+                ConfirmAction::Confirm(read_only) => {
+                    self.dialogs.pop();
+                    actions.push(JobAction::ReadOnly(read_only).into());
+                }
+            },
+            WorkSpaceAction::Restore(position) => {
+                self.dialogs.pop();
+                if let Some(position) = position {
+                    self.handle_restore(state, position);
+                }
+            }
+            WorkSpaceAction::Followed {
+                path,
+                reader,
+                records,
+            } => self.handle_followed(state, actions, path, reader, records),
+            WorkSpaceAction::ApplyEdit(confirm_action) => {
+                self.handle_apply_edit(state, actions, confirm_action);
+            }
+            WorkSpaceAction::Merged { node, conflicts } => {
                 self.replace_selected(state, node);
+                self.is_edited = true;
+                if !conflicts.is_empty() {
+                    self.push_message(format!(
+                        "{} merge conflicts, press x to resolve",
+                        conflicts.len()
+                    ));
+                }
+                self.conflicts = conflicts;
+            }
+            WorkSpaceAction::ListConflicts => self.handle_list_conflicts(),
+            WorkSpaceAction::ConflictPicked(conflict) => {
+                self.dialogs.pop();
+                if let Some(conflict) = conflict {
+                    self.handle_conflict_picked(state, conflict);
+                }
+            }
+            WorkSpaceAction::ResolveConflict { conflict, side } => {
+                self.dialogs.pop();
+                if let Some(side) = side {
+                    self.handle_resolve_conflict(state, conflict, side == 1);
+                }
+            }
+            WorkSpaceAction::Compacted { node, reclaimed } => {
+                self.file_root = node;
+                let reclaimed = reclaimed
+                    + self
+                        .trash
+                        .iter_mut()
+                        .map(|trashed| trashed.node.compact())
+                        .sum::<usize>();
+                self.trash.shrink_to_fit();
+                self.messages.shrink_to_fit();
+                self.deletes_since_compact = 0;
+                self.push_message(format!("Compacted, reclaimed {}", format::bytes(reclaimed)));
+            }
+            WorkSpaceAction::ExpandAll(confirm_action) => {
+                self.handle_expand_all(state, actions, confirm_action);
+            }
+            WorkSpaceAction::ExpandedAll {
+                node,
+                selector,
+                tree,
+            } => {
+                self.file_root = node;
+                if self.select_path(state, &selector) {
+                    let index = state.list_state.selected().expect("path was just selected");
+                    self.work_tree_root.replace_subtree(index, tree);
+                    self.refresh_list();
+                }
+            }
+            WorkSpaceAction::Restated(node) => {
+                self.file_root = node;
+                self.work_tree_root.refresh_metas(&self.file_root);
+                self.refresh_list();
+            }
+            WorkSpaceAction::Load {
+                node,
+                is_edit,
+                repaired,
+            } => {
+                let checkpoint = self.replace_selected(state, node);
+                if is_edit {
+                    if let Some(checkpoint) = &checkpoint {
+                        checkpoint.record(&self.file_root, &mut self.patch);
+                    }
+                    self.checkpoint = checkpoint;
+                }
                 self.is_edited |= is_edit;
+                if !repaired.is_empty() {
+                    self.dialogs.push(Box::new(
+                        ErrorConfirmDialog::new(self.repairs_warning(&repaired).into())
+                            .title(Line::from("Warning")),
+                    ));
+                }
             }
             WorkSpaceAction::ErrorConfirmed => {
                 self.dialogs.pop();
             }
         }
 
+        self.sync_flat_view(state);
         Ok(())
     }
 
+    /// Lists the leaves again after the document changed, and selects the row of the node
+    /// selected in the tree.
+    fn sync_flat_view(&mut self, state: &mut WorkSpaceState) {
+        let Some(flat) = &mut self.flat else {
+            return;
+        };
+        if !flat.root.is_same(&self.file_root) {
+            *flat = FlatView::new(&self.file_root);
+        }
+        let index = state.list_state.selected().unwrap_or_default();
+        let selector = self.work_tree_root.selector(index);
+        let row = flat.row(&selector, state.flat_state.selected());
+        state.flat_state.select(row);
+    }
+
+    /// Moves the flat view's selection, selecting the node of the new row in the tree.
+    fn move_in_flat_view(&mut self, state: &mut WorkSpaceState, row: usize) {
+        let Some(selector) = self
+            .flat
+            .as_ref()
+            .and_then(|flat| flat.selectors.get(row).cloned())
+        else {
+            return;
+        };
+        state.flat_state.select(Some(row));
+        self.select_path(state, &selector);
+    }
+
+    fn repairs_warning(&self, repaired: &Repairs) -> String {
+        let mut sections = Vec::new();
+        if !repaired.utf8.is_empty() {
+            sections.push(format!(
+                "Replaced invalid UTF-8 in:\n{}",
+                repaired.utf8.join("\n")
+            ));
+        }
+        if !repaired.duplicate_keys.is_empty() {
+            let heading = match self.config.duplicate_keys {
+                DuplicateKeys::Rename => "Renamed duplicate keys to:",
+                DuplicateKeys::Last | DuplicateKeys::Report => {
+                    "Kept the last value of duplicate keys:"
+                }
+            };
+            sections.push(format!("{heading}\n{}", repaired.duplicate_keys.join("\n")));
+        }
+        sections.join("\n\n")
+    }
+
     fn handle_navigation_action(
         &mut self,
         state: &mut WorkSpaceState,
         navigation_action: NavigationAction,
     ) {
-        let prev_index = state.list_state.selected();
-        match navigation_action {
+        if let Some(flat) = &self.flat {
+            let current = state.flat_state.selected().unwrap_or_default();
+            let last = flat.selectors.len().saturating_sub(1);
+            let row = match navigation_action {
+                NavigationAction::Up(n) => Some(current.saturating_sub(n)),
+                NavigationAction::Down(n) => Some(current.saturating_add(n).min(last)),
+                NavigationAction::Top => Some(0),
+                NavigationAction::Bottom => Some(last),
+                _ => None,
+            };
+            if let Some(row) = row {
+                self.move_in_flat_view(state, row);
+                return;
+            }
+        }
+
+        let prev_index = state.list_state.selected();
+        match navigation_action {
+            NavigationAction::ToggleFlatView => {
+                self.flat = match self.flat {
+                    Some(_) => None,
+                    None => Some(FlatView::new(&self.file_root)),
+                };
+            }
             NavigationAction::Up(n) => {
                 let index = state.list_state.selected().unwrap().saturating_sub(n);
                 state.list_state.select(Some(index));
@@ -265,7 +1173,10 @@ impl WorkSpace {
             }
             NavigationAction::Expand => {
                 if let Some(index) = state.list_state.selected() {
-                    if self.expand(index) {
+                    if self.work_tree_root.materialize(index) {
+                        self.refresh_list();
+                        self.set_preview_to_selected(state, false);
+                    } else if self.expand(index) {
                         state.list_state.select_next();
                     }
                 }
@@ -273,18 +1184,67 @@ impl WorkSpace {
             NavigationAction::Close => {
                 if let Some(index) = state.list_state.selected() {
                     self.work_tree_root.close(index);
-                    self.list = new_list(&self.work_tree_root);
+                    self.refresh_list();
+                }
+            }
+            NavigationAction::CollapseAll => {
+                if let Some(index) = state.list_state.selected()
+                    && self.work_tree_root.elided(index).is_none()
+                {
+                    let selector = self.work_tree_root.selector(index);
+                    let node_index = self
+                        .file_root
+                        .subtree(&selector)
+                        .expect("broken selector")
+                        .as_index();
+                    self.reindex(index, node_index, true);
                 }
             }
             NavigationAction::TogglePreview => {
                 self.toggle_preview(state);
             }
-            NavigationAction::PreviewNavigation(preview_navigation) => match preview_navigation {
-                PreviewNavigationAction::Up(n) => state.preview_state.scroll_up(n),
-                PreviewNavigationAction::Down(n) => state.preview_state.scroll_down(n),
-                PreviewNavigationAction::Left => state.preview_state.scroll_left(),
-                PreviewNavigationAction::Right => state.preview_state.scroll_right(),
-            },
+            NavigationAction::TogglePreviewLayout => {
+                self.preview_layout = self.preview_layout.toggle();
+            }
+            NavigationAction::CycleDecoding => self.cycle_decoding(state),
+            NavigationAction::ToggleTableView => {
+                self.is_table_view = !self.is_table_view;
+                self.push_message(String::from(if self.is_table_view {
+                    "Previewing arrays of flat objects as tables"
+                } else {
+                    "Previewing arrays as JSON"
+                }));
+                self.set_preview_to_selected(state, true);
+            }
+            NavigationAction::ToggleFullscreenPreview => {
+                self.is_preview_fullscreen = !self.is_preview_fullscreen;
+                if self.is_preview_fullscreen && self.preview.is_none() && self.pinned.is_none() {
+                    self.set_preview_to_selected(state, true);
+                }
+            }
+            NavigationAction::SwitchFocus => {
+                state.focus = match state.focus {
+                    Focus::Tree if self.preview.is_some() => Focus::Preview,
+                    Focus::Tree | Focus::Preview if self.pinned.is_some() => Focus::Pinned,
+                    _ => Focus::Tree,
+                };
+            }
+            NavigationAction::FocusTree => state.focus = Focus::Tree,
+            NavigationAction::PreviewNavigation(preview_navigation) => {
+                let is_table = self.table.is_some() && state.focus != Focus::Pinned;
+                let preview_state = state.focused_preview_state();
+                match preview_navigation {
+                    PreviewNavigationAction::Left if is_table => preview_state.previous_column(),
+                    PreviewNavigationAction::Right if is_table => preview_state.next_column(),
+                    PreviewNavigationAction::Up(n) => preview_state.scroll_up(n),
+                    PreviewNavigationAction::Down(n) => preview_state.scroll_down(n),
+                    PreviewNavigationAction::Left => preview_state.scroll_left(),
+                    PreviewNavigationAction::Right => preview_state.scroll_right(),
+                    PreviewNavigationAction::Top => preview_state.scroll_to(0),
+                    // Rendering clamps the offset to the last page.
+                    PreviewNavigationAction::Bottom => preview_state.scroll_to(usize::MAX),
+                }
+            }
             NavigationAction::PreviewWindowResize(delta) => {
                 self.preview_pct = delta.exec(self.preview_pct).clamp(20, 80)
             }
@@ -295,6 +1255,131 @@ impl WorkSpace {
         }
     }
 
+    fn handle_mouse(&mut self, state: &mut WorkSpaceState, event: MouseEvent) {
+        let position = Position::new(event.column, event.row);
+        let over_preview = state
+            .preview_area
+            .is_some_and(|area| area.contains(position));
+        let over_pinned = state
+            .pinned_area
+            .is_some_and(|area| area.contains(position));
+        // The wheel scrolls the preview under it, whichever has the focus.
+        let hovered_state = if over_pinned {
+            Some(&mut state.pinned_state)
+        } else if over_preview {
+            Some(&mut state.preview_state)
+        } else {
+            None
+        };
+        if let Some(preview_state) = hovered_state {
+            match event.kind {
+                MouseEventKind::ScrollUp => preview_state.scroll_up(MOUSE_SCROLL_LINES as u16),
+                MouseEventKind::ScrollDown => preview_state.scroll_down(MOUSE_SCROLL_LINES as u16),
+                _ => {}
+            }
+        }
+        let over_preview = over_preview || over_pinned;
+        let navigation_action = match event.kind {
+            MouseEventKind::ScrollUp | MouseEventKind::ScrollDown if over_preview => return,
+            MouseEventKind::ScrollUp => NavigationAction::Up(MOUSE_SCROLL_LINES),
+            MouseEventKind::ScrollDown => NavigationAction::Down(MOUSE_SCROLL_LINES),
+            MouseEventKind::Down(MouseButton::Left) => {
+                // The tree's right or bottom border touches the preview's left or top one.
+                let on_border = !self.is_preview_fullscreen
+                    && state
+                        .preview_area
+                        .or(state.pinned_area)
+                        .is_some_and(|area| match self.preview_layout {
+                            PreviewLayout::Beside => {
+                                event.column + 1 == area.x || event.column == area.x
+                            }
+                            PreviewLayout::Below => event.row + 1 == area.y || event.row == area.y,
+                        });
+                if on_border {
+                    state.resizing = true;
+                    return;
+                }
+                if over_preview {
+                    state.focus = if over_pinned {
+                        Focus::Pinned
+                    } else {
+                        Focus::Preview
+                    };
+                    return;
+                }
+                match self.handle_click(state, position) {
+                    Some(navigation_action) => navigation_action,
+                    None => return,
+                }
+            }
+            MouseEventKind::Drag(MouseButton::Left) if state.resizing => {
+                let side_area = state.preview_area.or(state.pinned_area).unwrap_or_default();
+                let (tree_size, size) = match self.preview_layout {
+                    PreviewLayout::Beside => (
+                        event.column.saturating_sub(state.tree_area.x),
+                        state.tree_area.width
+                            + state.preview_area.unwrap_or_default().width
+                            + state.pinned_area.unwrap_or_default().width,
+                    ),
+                    PreviewLayout::Below => (
+                        event.row.saturating_sub(state.tree_area.y),
+                        state.tree_area.height + side_area.height,
+                    ),
+                };
+                let tree_pct = u32::from(tree_size) * 100 / u32::from(size.max(1));
+                self.preview_pct = (100 - tree_pct.min(100) as u16).clamp(20, 80);
+                return;
+            }
+            MouseEventKind::Up(MouseButton::Left) => {
+                state.resizing = false;
+                return;
+            }
+            _ => return,
+        };
+        self.handle_navigation_action(state, navigation_action);
+    }
+
+    /// Selects the clicked row. A second click on it toggles whether it is expanded.
+    fn handle_click(
+        &mut self,
+        state: &mut WorkSpaceState,
+        position: Position,
+    ) -> Option<NavigationAction> {
+        let rows = state.tree_area.inner(Margin::new(1, 1));
+        if !rows.contains(position) {
+            return None;
+        }
+        state.focus = Focus::Tree;
+        if self.flat.is_some() {
+            let row = state.flat_state.offset() + usize::from(position.y - rows.y);
+            self.move_in_flat_view(state, row);
+            return None;
+        }
+        let index = state.list_state.offset() + usize::from(position.y - rows.y);
+        if index >= self.work_tree_root.len() {
+            return None;
+        }
+
+        let now = Instant::now();
+        let is_double_click = state.last_click.is_some_and(|(at, clicked)| {
+            clicked == index && now.duration_since(at) <= DOUBLE_CLICK_TIME
+        });
+        state.last_click = (!is_double_click).then_some((now, index));
+        if is_double_click {
+            return Some(if self.work_tree_root.is_expanded(index) {
+                NavigationAction::Close
+            } else {
+                NavigationAction::Expand
+            });
+        }
+
+        if state.list_state.selected() != Some(index) {
+            state.list_state.select(Some(index));
+            self.set_preview_to_selected(state, false);
+        }
+        None
+    }
+
     fn expand(&mut self, index: usize) -> bool {
         if self.work_tree_root.is_expanded(index) {
             return false;
@@ -322,34 +1407,213 @@ impl WorkSpace {
             .file_root
             .subtree(&selector)
             .expect("broken selector")
-            .to_string_pretty()
+            .to_string_indented(self.config.indent)
             .expect("broken internal representation");
         writer.write_all(content.as_bytes())?;
         Ok(())
     }
 
-    fn replace_selected(&mut self, worktree_state: &WorkSpaceState, new_node: Node) {
-        let Some(index) = worktree_state.list_state.selected() else {
-            return;
-        };
+    /// Replaces the selected node, returning what it replaced.
+    fn replace_selected(
+        &mut self,
+        worktree_state: &WorkSpaceState,
+        new_node: Node,
+    ) -> Option<Checkpoint> {
+        let index = worktree_state.list_state.selected()?;
         let selector = self.work_tree_root.selector(index);
 
         let node_index = new_node.as_index();
-        self.file_root
+        let old_node = self
+            .file_root
             .replace(&selector, new_node)
             .expect("broken selector");
         self.reindex(index, node_index, false);
         self.set_preview_to_selected(worktree_state, false);
+        Some(Checkpoint {
+            selector,
+            node: old_node,
+        })
+    }
+
+    /// Copies the subtree at `selector` before it gets mutated.
+    fn checkpoint<T: Deref<Target = str>>(&self, selector: &[T]) -> Checkpoint {
+        Checkpoint {
+            selector: KeyPath::from(selector),
+            node: self
+                .file_root
+                .subtree(selector)
+                .expect("broken selector")
+                .clone(),
+        }
+    }
+
+    /// Shows in the preview how the selected node changed since the last checkpoint.
+    fn handle_diff(&mut self, state: &WorkSpaceState) {
+        let Some(checkpoint) = &self.checkpoint else {
+            self.push_message(String::from("No change to compare against"));
+            return;
+        };
+        let index = state.list_state.selected().unwrap_or_default();
+        if self.work_tree_root.elided(index).is_some() {
+            return;
+        }
+
+        let selector = self.work_tree_root.selector(index);
+        let current = self.file_root.subtree(&selector).expect("broken selector");
+        let old = if let Some(rest) = selector.strip_prefix(&checkpoint.selector[..]) {
+            checkpoint.node.subtree(rest).ok().cloned()
+        } else if let Some(rest) = checkpoint.selector.strip_prefix(&selector[..]) {
+            let mut old = current.clone();
+            match old.set(rest, checkpoint.node.clone(), false) {
+                Ok(_) => Some(old),
+                // The changed node was added by the last change.
+                Err(_) => {
+                    let mut old = current.clone();
+                    let _ = old.delete(rest);
+                    Some(old)
+                }
+            }
+        } else {
+            Some(current.clone())
+        };
+
+        let max_size = self.config.max_preview_size.as_u64() as usize;
+        if current.as_meta().n_bytes > max_size
+            || old
+                .as_ref()
+                .is_some_and(|old| old.as_meta().n_bytes > max_size)
+        {
+            self.push_message(String::from("Node too large to diff"));
+            return;
+        }
+
+        let old = old
+            .map(|old| {
+                old.to_string_pretty()
+                    .expect("broken internal representation")
+            })
+            .unwrap_or_default();
+        let new = current
+            .to_string_pretty()
+            .expect("broken internal representation");
+        if old == new {
+            self.push_message(String::from("No change in selection"));
+            return;
+        }
+
+        let content = diff::line_diff(&old, &new)
+            .into_iter()
+            .map(|line| match line {
+                DiffLine::Same(line) => format!("  {line}\n"),
+                DiffLine::Removed(line) => format!("- {line}\n"),
+                DiffLine::Added(line) => format!("+ {line}\n"),
+            })
+            .collect();
+        self.preview = Some(Preview::diff(content));
+        self.table = None;
+    }
+
+    fn handle_compare(
+        &mut self,
+        state: &WorkSpaceState,
+        confirm_action: ConfirmAction<(), Option<String>>,
+    ) -> Option<Action> {
+        match confirm_action {
+            ConfirmAction::Request(()) => {
+                let name = self
+                    .compare
+                    .as_ref()
+                    .map(|compare| compare.name.clone())
+                    .unwrap_or_default();
+                self.dialogs.push(Box::new(
+                    TextConfirmDialog::new(Box::new(ConfirmAction::action_confirmer(
+                        WorkSpaceAction::Compare,
+                    )))
+                    .title(Line::from("Compare with file, empty to stop"))
+                    .content(name),
+                ));
+                None
+            }
+            ConfirmAction::Confirm(path) => {
+                self.dialogs.pop();
+                let path = path?;
+                if !path.is_empty() {
+                    return Some(JobAction::Compare(path).into());
+                }
+                if let Some(compare) = self.compare.take() {
+                    self.push_message(format!("Stopped comparing with {}", compare.name));
+                    self.refresh_list();
+                    self.set_preview_to_selected(state, false);
+                }
+                None
+            }
+        }
+    }
+
+    fn handle_compared(&mut self, state: &WorkSpaceState, name: String, node: Node) {
+        let diff = diff::structural_diff::<&str>(&[], &node, &self.file_root);
+        self.push_message(format!(
+            "Compared with {name}: {} added, {} removed, {} changed",
+            diff.added.len(),
+            diff.removed.len(),
+            diff.changed.len()
+        ));
+        self.compare = Some(Comparison { name, node });
+        self.refresh_list();
+        self.set_preview_to_selected(state, false);
+    }
+
+    /// Side-by-side diff of the compared document against the document at `index`.
+    fn compare_preview(&self, index: usize, n_bytes: usize) -> Preview {
+        let compare = self.compare.as_ref().expect("comparing");
+        let selector = self.work_tree_root.selector(index);
+        let node = self.file_root.subtree(&selector).expect("broken selector");
+        let other = compare.node.subtree(&selector).ok();
+
+        let max_size = self.config.max_preview_size.as_u64() as usize;
+        if n_bytes > max_size || other.is_some_and(|other| other.as_meta().n_bytes > max_size) {
+            return Preview::new(None);
+        }
+        let pretty = |node: &Node| {
+            node.to_string_pretty()
+                .expect("broken internal representation")
+        };
+        Preview::diff(diff::side_by_side(
+            &compare.name,
+            &other.map(pretty).unwrap_or_default(),
+            "current",
+            &pretty(node),
+        ))
     }
 
     fn reindex(&mut self, index: usize, node_index: Index, force: bool) {
+        let is_array = matches!(node_index.kind, IndexKind::Array(_));
         self.work_tree_root.reindex(index, node_index, force);
-        self.list = new_list(&self.work_tree_root);
+        if let Some(keep) = self.config.array_sample_size
+            && is_array
+        {
+            self.work_tree_root.elide(index, keep);
+        }
+        self.refresh_list();
     }
 
-    fn toggle_preview(&mut self, state: &WorkSpaceState) {
+    fn refresh_list(&mut self) {
+        self.list = new_list(
+            &self.work_tree_root,
+            &self.file_root,
+            self.compare.as_ref().map(|compare| &compare.node),
+            &self.config,
+        );
+        self.list_version = next_version();
+    }
+
+    fn toggle_preview(&mut self, state: &mut WorkSpaceState) {
         if self.preview.is_some() {
             self.preview = None;
+            self.table = None;
+            if state.focus == Focus::Preview {
+                state.focus = Focus::Tree;
+            }
             return;
         }
 
@@ -360,18 +1624,229 @@ impl WorkSpace {
         if self.preview.is_none() && !force_show {
             return;
         }
+        self.table = None;
 
         let Some(index) = state.list_state.selected() else {
             return;
         };
+        if self.work_tree_root.elided(index).is_some() {
+            self.preview = Some(Preview::new(None));
+            return;
+        }
         let meta = self.meta_on_index(index);
+        if self.compare.is_some() {
+            self.preview = Some(self.compare_preview(index, meta.n_bytes));
+            return;
+        }
+
+        self.preview = Some(
+            self.decoded_preview(index, meta.n_bytes)
+                .unwrap_or_else(|| Preview::new(self.preview_content(index, meta.n_bytes))),
+        );
+        if self.is_table_view && meta.n_bytes <= self.config.max_preview_size.as_u64() as usize {
+            let selector = self.work_tree_root.selector(index);
+            self.table = self
+                .file_root
+                .subtree(&selector)
+                .ok()
+                .and_then(TablePreview::new);
+        }
+    }
 
+    /// Preview of the string at `index` decoded as picked with `b`, when it decodes that way.
+    fn decoded_preview(&self, index: usize, n_bytes: usize) -> Option<Preview> {
+        let decoding = self.decoding?;
+        if n_bytes > self.config.max_preview_size.as_u64() as usize {
+            return None;
+        }
+        let selector = self.work_tree_root.selector(index);
+        let value = self.file_root.subtree(&selector).ok()?.as_str()?;
+        let content = decoding.decode(value)?;
+        Some(Preview::new(Some(content)).title(format!("Preview ({})", decoding.name())))
+    }
+
+    /// Moves to the next decoding the selected string allows, skipping those it does not, and
+    /// back to its JSON after the last one.
+    fn cycle_decoding(&mut self, state: &WorkSpaceState) {
+        let Some(value) = self.selected_node(state).and_then(Node::as_str) else {
+            self.push_message(String::from("Only strings have decoded views"));
+            return;
+        };
+        let start = self.decoding.map_or(0, |decoding| {
+            Decoding::ALL
+                .iter()
+                .position(|&other| other == decoding)
+                .map_or(0, |position| position + 1)
+        });
+        self.decoding = Decoding::ALL[start..]
+            .iter()
+            .copied()
+            .find(|decoding| decoding.decode(value).is_some());
+        self.push_message(match self.decoding {
+            Some(decoding) => format!("Previewing strings decoded from {}", decoding.name()),
+            None => String::from("Previewing strings as JSON"),
+        });
+        self.set_preview_to_selected(state, true);
+    }
+
+    /// JSON of the node at `index`, unless it is larger than `max_preview_size`.
+    fn preview_content(&self, index: usize, n_bytes: usize) -> Option<String> {
         let mut buffer = Vec::new();
-        if meta.n_bytes <= self.config.max_preview_size.as_u64() as usize {
+        if n_bytes <= self.config.max_preview_size.as_u64() as usize {
             let _ = self.write_on_index(&mut buffer, index);
         }
-        let preview = String::from_utf8(buffer).unwrap_or_default();
-        self.preview = Some(Preview::new((!preview.is_empty()).then_some(preview)))
+        let content = String::from_utf8(buffer).unwrap_or_default();
+        (!content.is_empty()).then_some(content)
+    }
+
+    fn handle_pin(&mut self, state: &mut WorkSpaceState) {
+        if self.pinned.take().is_some() {
+            if state.focus == Focus::Pinned {
+                state.focus = Focus::Tree;
+            }
+            self.push_message(String::from("Unpinned the preview"));
+            return;
+        }
+        let Some(index) = state.list_state.selected() else {
+            return;
+        };
+        if self.work_tree_root.elided(index).is_some() {
+            return;
+        }
+
+        let path = to_path(&self.file_root, &self.work_tree_root.selector(index));
+        let meta = self.meta_on_index(index);
+        let content = self.preview_content(index, meta.n_bytes);
+        self.pinned = Some(Preview::new(content).title(format!("Pinned {path}")));
+        state.pinned_state = PreviewState::default();
+        self.push_message(format!("Pinned {path}, Tab focuses either preview"));
+    }
+
+    fn handle_jq(
+        &mut self,
+        state: &WorkSpaceState,
+        confirm_action: ConfirmAction<(), Option<String>>,
+    ) -> Option<Action> {
+        match confirm_action {
+            ConfirmAction::Request(()) => {
+                if self.config.no_shell {
+                    self.push_message(String::from("jq is disabled by no_shell"));
+                    return None;
+                }
+                let index = state.list_state.selected()?;
+                if self.work_tree_root.elided(index).is_some() {
+                    return None;
+                }
+                self.dialogs.push(Box::new(
+                    TextConfirmDialog::new(Box::new(ConfirmAction::action_confirmer(
+                        WorkSpaceAction::Jq,
+                    )))
+                    .title(Line::from("jq filter on selected node"))
+                    .content(self.jq_filter.clone()),
+                ));
+                None
+            }
+            ConfirmAction::Confirm(filter) => {
+                self.dialogs.pop();
+                let filter = filter.filter(|filter| !filter.trim().is_empty())?;
+                self.jq_filter.clone_from(&filter);
+                let content = self
+                    .selected_node(state)?
+                    .to_string_compact()
+                    .expect("broken internal representation");
+                Some(JobAction::Jq { filter, content }.into())
+            }
+        }
+    }
+
+    /// Pins the output of a jq filter, a single value or an array of the values it printed, and
+    /// asks whether to replace the selection with it.
+    fn handle_jq_output(
+        &mut self,
+        state: &mut WorkSpaceState,
+        actions: &mut Actions,
+        confirm_action: ConfirmAction<(String, Vec<Node>)>,
+    ) {
+        match confirm_action {
+            ConfirmAction::Request((filter, mut values)) => {
+                let node = match values.len() {
+                    0 => {
+                        self.push_message(format!("jq printed nothing for {filter}"));
+                        return;
+                    }
+                    1 => values.pop().expect("one value"),
+                    _ => Node::from_array(values),
+                };
+                let content = node
+                    .to_string_pretty()
+                    .expect("broken internal representation");
+                self.pinned = Some(Preview::new(Some(content)).title(format!("jq {filter}")));
+                state.pinned_state = PreviewState::default();
+                self.jq_output = Some(node);
+
+                let mut confirm_dialog = BooleanConfirmDialog::new(
+                    Text::from(vec![
+                        Line::from("Replace the selected node with the output?").centered(),
+                    ]),
+                    Box::new(ConfirmAction::action_confirmer(WorkSpaceAction::JqOutput)),
+                );
+                confirm_dialog.title(Some(Line::from("jq").left_aligned()));
+                self.dialogs.push(Box::new(confirm_dialog));
+            }
+            ConfirmAction::Confirm(ok) => {
+                self.dialogs.pop();
+                let Some(node) = self.jq_output.take() else {
+                    return;
+                };
+                if ok {
+                    actions.push(
+                        WorkSpaceAction::Load {
+                            node,
+                            is_edit: true,
+                            repaired: Repairs::default(),
+                        }
+                        .into(),
+                    );
+                }
+            }
+        }
+    }
+
+    fn handle_pipe(
+        &mut self,
+        state: &WorkSpaceState,
+        confirm_action: ConfirmAction<(), Option<String>>,
+    ) -> Option<Action> {
+        match confirm_action {
+            ConfirmAction::Request(()) => {
+                if self.config.no_shell {
+                    self.push_message(String::from("Piping is disabled by no_shell"));
+                    return None;
+                }
+                let index = state.list_state.selected()?;
+                if self.work_tree_root.elided(index).is_some() {
+                    return None;
+                }
+                self.dialogs.push(Box::new(
+                    TextConfirmDialog::new(Box::new(ConfirmAction::action_confirmer(
+                        WorkSpaceAction::Pipe,
+                    )))
+                    .title(Line::from("Pipe selected node through command"))
+                    .content(self.pipe_command.clone()),
+                ));
+                None
+            }
+            ConfirmAction::Confirm(command) => {
+                self.dialogs.pop();
+                let command = command.filter(|command| !command.trim().is_empty())?;
+                self.pipe_command.clone_from(&command);
+                let content = self
+                    .selected_node(state)?
+                    .to_string_pretty()
+                    .expect("broken internal representation");
+                Some(JobAction::Pipe { command, content }.into())
+            }
+        }
     }
 
     fn meta_on_index(&mut self, index: usize) -> NodeMeta {
@@ -390,6 +1865,37 @@ impl WorkSpace {
         meta
     }
 
+    /// Expands every node along `selector` and selects the node it points to.
+    fn select_path<T: Deref<Target = str>>(
+        &mut self,
+        state: &mut WorkSpaceState,
+        selector: &[T],
+    ) -> bool {
+        let mut index = 0;
+        for key in selector {
+            self.expand(index);
+            let Some(mut row) = self.work_tree_root.child_row(index, key) else {
+                return false;
+            };
+            if self.work_tree_root.materialize(row) {
+                row = self
+                    .work_tree_root
+                    .child_row(index, key)
+                    .expect("broken materialized row");
+            }
+            index = row;
+        }
+
+        self.refresh_list();
+        state.list_state.select(Some(index));
+        self.set_preview_to_selected(state, false);
+        true
+    }
+
+    pub fn is_edited(&self) -> bool {
+        self.is_edited
+    }
+
     pub fn file_root(&self) -> &Node {
         &self.file_root
     }
@@ -400,15 +1906,101 @@ impl WorkSpace {
         &mut self,
         state: &mut WorkSpaceState,
         confirm_action: ConfirmAction<(), Option<String>>,
-    ) -> std::io::Result<()> {
-        let Some(index) = self.index_for_mutation(state) else {
-            return Ok(());
+    ) {
+        self.append_node(
+            state,
+            confirm_action,
+            Node::null(),
+            "Append key",
+            WorkSpaceAction::Add,
+        );
+    }
+
+    fn handle_paste(
+        &mut self,
+        state: &mut WorkSpaceState,
+        register: char,
+        confirm_action: ConfirmAction<(), Option<String>>,
+    ) {
+        let Some(node) = self.registers.get(register).cloned() else {
+            self.dialogs.push(Box::new(ErrorConfirmDialog::new(
+                format!("Register \"{register} is empty").into(),
+            )));
+            return;
+        };
+
+        self.append_node(state, confirm_action, node, "Paste as key", move |key| {
+            WorkSpaceAction::Paste { register, key }
+        });
+    }
+
+    /// Pastes JSON from the clipboard through the `+` register, or replaces the selection with it.
+    fn handle_clipboard_read(&mut self, actions: &mut Actions, content: String, replace: bool) {
+        let (node, repaired) =
+            match Node::load_with_repairs(content.as_bytes(), &self.config.parse_options()) {
+                Ok(loaded) => loaded,
+                Err(error) => {
+                    self.dialogs.push(Box::new(
+                        ErrorConfirmDialog::new(error.to_string().into())
+                            .title(Line::from("Invalid JSON in clipboard")),
+                    ));
+                    return;
+                }
+            };
+        if replace {
+            actions
+                .push(WorkSpaceAction::ApplyEdit(ConfirmAction::Request((node, repaired))).into());
+            return;
+        }
+        // Only named registers are persisted, so this cannot fail.
+        let _ = self.registers.set(CLIPBOARD_REGISTER, node);
+        actions.push(
+            WorkSpaceAction::Paste {
+                register: CLIPBOARD_REGISTER,
+                key: ConfirmAction::Request(()),
+            }
+            .into(),
+        );
+    }
+
+    /// Inserts `node` after the selected node, or into it when it is an empty container, prompting
+    /// for a key when the new parent is an object.
+    fn append_node(
+        &mut self,
+        state: &mut WorkSpaceState,
+        confirm_action: ConfirmAction<(), Option<String>>,
+        node: Node,
+        title: &'static str,
+        confirmer: impl Fn(ConfirmAction<(), Option<String>>) -> WorkSpaceAction + Clone + 'static,
+    ) {
+        let selected = state.list_state.selected().unwrap_or_default();
+        let into_empty = self.work_tree_root.elided(selected).is_none()
+            && match self
+                .file_root
+                .subtree(&self.work_tree_root.selector(selected))
+                .expect("broken selector")
+                .as_index()
+                .kind
+            {
+                IndexKind::Object(keys) => keys.is_empty(),
+                IndexKind::Array(n) => n == 0,
+                IndexKind::Terminal => false,
+            };
+        let index = if into_empty {
+            selected
+        } else {
+            let Some(index) = self.index_for_mutation(state) else {
+                return;
+            };
+            index
         };
 
         let new_key = match confirm_action {
             ConfirmAction::Request(_) => {
                 let mut selector = self.work_tree_root.selector(index);
-                selector.pop();
+                if !into_empty {
+                    selector.pop();
+                }
                 let meta = self
                     .file_root
                     .subtree(&selector)
@@ -418,12 +2010,12 @@ impl WorkSpace {
                 if !matches!(meta.kind, IndexKind::Array(_)) {
                     self.dialogs.push(Box::new(
                         TextConfirmDialog::new(Box::new(ConfirmAction::action_confirmer(
-                            WorkSpaceAction::Add,
+                            confirmer.clone(),
                         )))
-                        .title(Line::from("Append key")),
+                        .title(Line::from(title)),
                     ));
 
-                    return Ok(());
+                    return;
                 }
 
                 None
@@ -431,7 +2023,7 @@ impl WorkSpace {
             ConfirmAction::Confirm(new_key) => {
                 self.dialogs.pop();
                 let Some(new_key) = new_key else {
-                    return Ok(());
+                    return;
                 };
                 Some(new_key)
             }
@@ -442,52 +2034,664 @@ impl WorkSpace {
             None => AddNodeKey::Array,
         };
         let mut selector = self.work_tree_root.selector(index);
-        match self
-            .file_root
-            .append_after(&selector, add_node_key, Node::null())
-        {
+        let checkpoint = if into_empty {
+            self.checkpoint(&selector)
+        } else {
+            self.checkpoint(&selector[..selector.len() - 1])
+        };
+        let res = if into_empty {
+            self.file_root.insert_first(&selector, add_node_key, node)
+        } else {
+            self.file_root.append_after(&selector, add_node_key, node)
+        };
+        if res.is_ok() {
+            checkpoint.record(&self.file_root, &mut self.patch);
+            self.checkpoint = Some(checkpoint);
+        }
+        match res {
             Err(MutationError::DuplicateKey) => {
                 self.dialogs.push(Box::new(
-                    TextConfirmDialog::new(Box::new(ConfirmAction::action_confirmer(
-                        WorkSpaceAction::Add,
-                    )))
-                    .title("Rename".into())
-                    .content(new_key.unwrap_or_default()),
+                    TextConfirmDialog::new(Box::new(ConfirmAction::action_confirmer(confirmer)))
+                        .title("Rename".into())
+                        .content(new_key.unwrap_or_default()),
                 ));
                 self.dialogs
                     .push(Box::new(ErrorConfirmDialog::new("Duplicate key".into())));
-                return Ok(());
+                return;
             }
             Err(err) => {
                 panic!("broken selector {err}")
             }
             Ok(_) => {}
         }
+        if into_empty {
+            let node_index = self
+                .file_root
+                .subtree(&selector)
+                .expect("broken selector")
+                .as_index();
+            self.reindex(index, node_index, true);
+        } else {
+            selector.pop();
+            let parent_metas = self.file_root.metas(&selector).expect("broken selector");
+            self.work_tree_root
+                .append_after(index, new_key, parent_metas);
+            self.refresh_list();
+        }
+        self.is_edited = true;
+        state.list_state.select(Some(index + 1));
+        self.set_preview_to_selected(state, false);
+    }
+
+    fn handle_list_snippets(&mut self) {
+        if self.config.snippets.is_empty() {
+            self.dialogs.push(Box::new(ErrorConfirmDialog::new(
+                "No snippets configured".into(),
+            )));
+            return;
+        }
+
+        let names = self.config.snippets.keys().cloned().collect::<Vec<_>>();
+        let items = names.iter().map(|name| Line::from(name.clone())).collect();
+        self.dialogs.push(Box::new(
+            ListConfirmDialog::new(
+                items,
+                Box::new(move |index| {
+                    WorkSpaceAction::SnippetPicked(index.map(|index| names[index].clone())).into()
+                }),
+            )
+            .title(Line::from("Insert snippet")),
+        ));
+    }
+
+    fn handle_insert_snippet(
+        &mut self,
+        state: &mut WorkSpaceState,
+        name: String,
+        confirm_action: ConfirmAction<(), Option<String>>,
+    ) {
+        let Some(content) = self.config.snippets.get(&name) else {
+            return;
+        };
+        let node = match Node::load(content.as_bytes()) {
+            Ok(node) => node,
+            Err(error) => {
+                self.dialogs.push(Box::new(ErrorConfirmDialog::new(
+                    format!("Invalid snippet {name}: {error}").into(),
+                )));
+                return;
+            }
+        };
+
+        self.append_node(state, confirm_action, node, "Snippet key", move |key| {
+            WorkSpaceAction::InsertSnippet {
+                name: name.clone(),
+                key,
+            }
+        });
+    }
+
+    /// Inserts a copy of the selection after it, suffixing object keys with `_copy` to keep them
+    /// unique.
+    fn handle_duplicate(&mut self, state: &mut WorkSpaceState) {
+        let Some(index) = self.index_for_mutation(state) else {
+            return;
+        };
+
+        let mut selector = self.work_tree_root.selector(index);
+        let node = self
+            .file_root
+            .subtree(&selector)
+            .expect("broken selector")
+            .clone();
+        let key = selector.pop().expect("broken selector");
+        let parent = self.file_root.subtree(&selector).expect("broken selector");
+        let new_key = parent.as_object().map(|object| {
+            (1..)
+                .map(|n| match n {
+                    1 => format!("{key}_copy"),
+                    n => format!("{key}_copy{n}"),
+                })
+                .find(|new_key| !object.contains_key(new_key))
+                .expect("unbounded suffixes")
+        });
+        let checkpoint = self.checkpoint(&selector);
+        selector.push_key(key);
+        let add_node_key = match &new_key {
+            Some(new_key) => AddNodeKey::Object(new_key.clone()),
+            None => AddNodeKey::Array,
+        };
+        self.file_root
+            .append_after(&selector, add_node_key, node)
+            .expect("broken selector");
+        checkpoint.record(&self.file_root, &mut self.patch);
+        self.checkpoint = Some(checkpoint);
+
         selector.pop();
         let parent_metas = self.file_root.metas(&selector).expect("broken selector");
+        let rows = self.work_tree_root.rows(index);
         self.work_tree_root
             .append_after(index, new_key, parent_metas);
         self.is_edited = true;
-        self.list = new_list(&self.work_tree_root);
-        state.list_state.select_next();
+        self.refresh_list();
+        state.list_state.select(Some(index + rows));
         self.set_preview_to_selected(state, false);
+    }
 
-        Ok(())
+    /// Swaps the selection with its previous or next sibling, keeping it selected.
+    fn handle_move_within(&mut self, state: &mut WorkSpaceState, up: bool) {
+        let Some(index) = self.index_for_mutation(state) else {
+            return;
+        };
+
+        let mut selector = self.work_tree_root.selector(index);
+        let key = selector.pop().expect("broken selector");
+        let parent = self.file_root.subtree(&selector).expect("broken selector");
+        let position = parent.position(&key).expect("broken selector");
+        let other = if up {
+            position
+                .checked_sub(1)
+                .and_then(|other| parent.key_at(other))
+        } else {
+            parent.key_at(position + 1)
+        };
+        let Some(other) = other else {
+            return;
+        };
+        let Some(moved_to) = self.work_tree_root.swap(index, up) else {
+            self.dialogs.push(Box::new(
+                ErrorConfirmDialog::new("Expand hidden elements first".into())
+                    .title(Line::from("Invalid selection")),
+            ));
+            return;
+        };
+
+        let checkpoint = self.checkpoint(&selector);
+        selector.push_key(key);
+        self.file_root
+            .swap(&selector, &other)
+            .expect("broken selector");
+        checkpoint.record(&self.file_root, &mut self.patch);
+        self.checkpoint = Some(checkpoint);
+        self.is_edited = true;
+        self.refresh_list();
+        state.list_state.select(Some(moved_to));
+        self.set_preview_to_selected(state, false);
+    }
+
+    /// Yanks the selection into `register`, and onto the system clipboard for the unnamed one.
+    fn handle_yank(&mut self, state: &WorkSpaceState, actions: &mut Actions, register: char) {
+        let index = state.list_state.selected().unwrap_or_default();
+        if self.work_tree_root.elided(index).is_some() {
+            self.dialogs.push(Box::new(
+                ErrorConfirmDialog::new("Expand hidden elements first".into())
+                    .title(Line::from("Invalid selection")),
+            ));
+            return;
+        }
+
+        let selector = self.work_tree_root.selector(index);
+        let node = self.file_root.subtree(&selector).expect("broken selector");
+        if let Err(error) = self.registers.set(register, node.clone()) {
+            self.dialogs.push(Box::new(ErrorConfirmDialog::new(
+                format!("Failed to save registers: {error}").into(),
+            )));
+            return;
+        }
+        if (register == UNNAMED_REGISTER && self.config.clipboard) || register == CLIPBOARD_REGISTER
+        {
+            let content = node
+                .to_string_pretty()
+                .expect("broken internal representation");
+            actions.push(
+                JobAction::Copy {
+                    content,
+                    run_tools: !self.config.no_shell,
+                }
+                .into(),
+            );
+        }
+        self.push_message(format!(
+            "Yanked {} into \"{register}",
+            to_pointer(&selector)
+        ));
+    }
+
+    fn handle_list_trash(&mut self) {
+        if self.trash.is_empty() {
+            self.dialogs.push(Box::new(
+                ErrorConfirmDialog::new("Nothing deleted".into()).title(Line::from("Trash")),
+            ));
+            return;
+        }
+
+        // Most recent first.
+        let items = self
+            .trash
+            .iter()
+            .rev()
+            .map(|trashed| {
+                let content = trashed
+                    .node
+                    .to_string_compact()
+                    .expect("broken internal representation");
+                let preview = content
+                    .chars()
+                    .take(REGISTER_PREVIEW_LEN)
+                    .collect::<String>();
+                Line::from(format!("{}  {preview}", to_pointer(&trashed.selector)))
+            })
+            .collect();
+        let n_trashed = self.trash.len();
+        self.dialogs.push(Box::new(
+            ListConfirmDialog::new(
+                items,
+                Box::new(move |index| {
+                    WorkSpaceAction::Restore(index.map(|index| n_trashed - 1 - index)).into()
+                }),
+            )
+            .title(Line::from("Trash")),
+        ));
+    }
+
+    /// Re-inserts a deleted subtree at its original selector and position.
+    fn handle_restore(&mut self, state: &mut WorkSpaceState, position: usize) {
+        let Some(trashed) = self.trash.get(position) else {
+            return;
+        };
+        let (key, parent_selector) = trashed.selector.split_last().expect("trashed root node");
+        let Some(parent) = self
+            .file_root
+            .subtree(parent_selector)
+            .ok()
+            .filter(|parent| parent.type_name() == "array" || parent.type_name() == "object")
+        else {
+            self.dialogs.push(Box::new(
+                ErrorConfirmDialog::new(
+                    format!("{} no longer exists", to_pointer(parent_selector)).into(),
+                )
+                .title(Line::from("Restore")),
+            ));
+            return;
+        };
+
+        let is_array = parent.type_name() == "array";
+        let add_node_key = if is_array {
+            AddNodeKey::Array
+        } else {
+            AddNodeKey::Object(key.to_string())
+        };
+        let n_children = parent.children().count();
+        let position_in_parent = trashed.position.min(n_children);
+        let after = position_in_parent
+            .checked_sub(1)
+            .and_then(|position| parent.key_at(position));
+        let checkpoint = self.checkpoint(parent_selector);
+
+        let node = trashed.node.clone();
+        let res = match &after {
+            Some(after) => {
+                let mut selector = parent_selector.iter().cloned().collect::<KeyPath>();
+                selector.push(after);
+                self.file_root.append_after(&selector, add_node_key, node)
+            }
+            None => self
+                .file_root
+                .insert_first(parent_selector, add_node_key, node),
+        };
+        match res {
+            Ok(()) => {}
+            Err(MutationError::DuplicateKey) => {
+                self.dialogs.push(Box::new(
+                    ErrorConfirmDialog::new(
+                        format!("{} already exists", to_pointer(&trashed.selector)).into(),
+                    )
+                    .title(Line::from("Restore")),
+                ));
+                return;
+            }
+            Err(err) => panic!("broken selector {err}"),
+        }
+
+        let trashed = self.trash.remove(position).expect("missing trashed node");
+        checkpoint.record(&self.file_root, &mut self.patch);
+        self.checkpoint = Some(checkpoint);
+        self.is_edited = true;
+
+        let (key, parent_selector) = trashed.selector.split_last().expect("trashed root node");
+        let parent_index = self
+            .file_root
+            .subtree(parent_selector)
+            .expect("broken selector")
+            .as_index();
+        self.select_path(state, parent_selector);
+        let parent_row = state.list_state.selected().unwrap_or_default();
+        self.reindex(parent_row, parent_index, true);
+        let mut selector = parent_selector.iter().cloned().collect::<KeyPath>();
+        if is_array {
+            selector.push(&position_in_parent.to_string());
+        } else {
+            selector.push_key(key.clone());
+        }
+        self.select_path(state, &selector);
+        self.push_message(format!("Restored {}", to_pointer(&selector)));
+    }
+
+    /// Asks before an edit replaces a container, listing the paths it adds, removes and changes.
+    fn handle_apply_edit(
+        &mut self,
+        state: &WorkSpaceState,
+        actions: &mut Actions,
+        confirm_action: ConfirmAction<(Node, Repairs)>,
+    ) {
+        let (node, repaired) = match confirm_action {
+            ConfirmAction::Request((node, repaired)) => {
+                let node = match (self.config.number_edits, self.selected_node(state)) {
+                    (NumberEdits::KeepKind, Some(original)) => node.keep_number_kind(original),
+                    _ => node,
+                };
+                if let Some(review) = self.edit_review(state, &node) {
+                    let mut confirm_dialog = BooleanConfirmDialog::new(
+                        review,
+                        Box::new(ConfirmAction::action_confirmer(WorkSpaceAction::ApplyEdit)),
+                    );
+                    confirm_dialog.title(Some(Line::from("Review edit").left_aligned()));
+                    self.dialogs.push(Box::new(confirm_dialog));
+                    self.pending_edit = Some((node, repaired));
+                    return;
+                }
+                (node, repaired)
+            }
+            ConfirmAction::Confirm(ok) => {
+                self.dialogs.pop();
+                let Some(edit) = self.pending_edit.take() else {
+                    return;
+                };
+                if !ok {
+                    self.push_message(String::from("Discarded edit"));
+                    return;
+                }
+                edit
+            }
+        };
+
+        actions.push(
+            WorkSpaceAction::Load {
+                node,
+                is_edit: true,
+                repaired,
+            }
+            .into(),
+        );
+    }
+
+    /// Summary of the structural changes `node` makes over the selection, when either is a
+    /// container and anything changed.
+    fn edit_review(&self, state: &WorkSpaceState, node: &Node) -> Option<Text<'static>> {
+        let index = state.list_state.selected()?;
+        let selector = self.work_tree_root.selector(index);
+        let old = self.file_root.subtree(&selector).expect("broken selector");
+        let is_container = |node: &Node| !matches!(node.as_meta().kind, NodeKind::Terminal);
+        if !is_container(old) && !is_container(node) {
+            return None;
+        }
+        let review = diff::structural_diff(&selector, old, node);
+        if review.is_empty() {
+            return None;
+        }
+
+        let mut lines = vec![
+            Line::from(format!(
+                "{} added, {} removed, {} changed",
+                review.added.len(),
+                review.removed.len(),
+                review.changed.len()
+            )),
+            Line::from(""),
+        ];
+        let paths = [
+            ('+', &review.added),
+            ('-', &review.removed),
+            ('~', &review.changed),
+        ]
+        .into_iter()
+        .flat_map(|(sign, selectors)| {
+            selectors
+                .iter()
+                .map(move |selector| format!("{sign} {}", to_pointer(selector)))
+        })
+        .collect::<Vec<_>>();
+        let n_paths = paths.len();
+        lines.extend(paths.into_iter().take(REVIEW_PATHS_LEN).map(Line::from));
+        if n_paths > REVIEW_PATHS_LEN {
+            lines.push(Line::from(format!("… {} more", n_paths - REVIEW_PATHS_LEN)));
+        }
+        lines.push(Line::from(""));
+        lines.push(Line::from("Apply edit?").centered());
+        Some(Text::from(lines))
+    }
+
+    fn handle_list_conflicts(&mut self) {
+        if self.conflicts.is_empty() {
+            self.dialogs.push(Box::new(
+                ErrorConfirmDialog::new("No merge conflicts".into()).title(Line::from("Merge")),
+            ));
+            return;
+        }
+
+        let items = self
+            .conflicts
+            .iter()
+            .map(|conflict| Line::from(to_pointer(&conflict.selector)))
+            .collect();
+        self.dialogs.push(Box::new(
+            ListConfirmDialog::new(
+                items,
+                Box::new(|index| WorkSpaceAction::ConflictPicked(index).into()),
+            )
+            .title(Line::from("Merge conflicts")),
+        ));
+    }
+
+    /// Shows the conflicting path and asks which side to keep.
+    fn handle_conflict_picked(&mut self, state: &mut WorkSpaceState, index: usize) {
+        let Some(conflict) = self.conflicts.get(index) else {
+            return;
+        };
+        let selector = conflict.selector.clone();
+        let preview = |side: &str, node: &Option<Node>| {
+            let content = match node {
+                Some(node) => node
+                    .to_string_compact()
+                    .expect("broken internal representation")
+                    .chars()
+                    .take(REGISTER_PREVIEW_LEN)
+                    .collect::<String>(),
+                None => String::from("(deleted)"),
+            };
+            Line::from(format!("{side}  {content}"))
+        };
+        let items = vec![
+            preview("Ours", &conflict.ours),
+            preview("Theirs", &conflict.theirs),
+        ];
+        self.select_path(state, &selector);
+        self.dialogs.push(Box::new(
+            ListConfirmDialog::new(
+                items,
+                Box::new(move |side| {
+                    WorkSpaceAction::ResolveConflict {
+                        conflict: index,
+                        side,
+                    }
+                    .into()
+                }),
+            )
+            .title(Line::from(format!("Resolve {}", to_pointer(&selector)))),
+        ));
+    }
+
+    /// Replaces the parent of a conflicting path with one keeping the chosen side, which also
+    /// covers sides that deleted the path.
+    fn handle_resolve_conflict(&mut self, state: &mut WorkSpaceState, index: usize, theirs: bool) {
+        let Some(conflict) = self.conflicts.get(index) else {
+            return;
+        };
+        let chosen = if theirs {
+            conflict.theirs.clone()
+        } else {
+            conflict.ours.clone()
+        };
+        let selector = conflict.selector.clone();
+        let pointer = to_pointer(&selector);
+        let (target, node) = match conflict.selector.split_last() {
+            None => (&[][..], chosen.expect("deleted root node")),
+            Some((key, parent_selector)) => {
+                let Some(mut parent) = self
+                    .file_root
+                    .subtree(parent_selector)
+                    .ok()
+                    .filter(|parent| parent.type_name() == "object")
+                    .cloned()
+                else {
+                    self.dialogs.push(Box::new(
+                        ErrorConfirmDialog::new(
+                            format!("{} no longer exists", to_pointer(parent_selector)).into(),
+                        )
+                        .title(Line::from("Merge")),
+                    ));
+                    return;
+                };
+                let res = match chosen {
+                    Some(node) => parent.set(&[key.as_str()], node, false).map(|_| ()),
+                    None => parent.delete(&[key.as_str()]).map(|_| ()),
+                };
+                match res {
+                    // Deleting a path that is already gone leaves nothing to do.
+                    Ok(()) | Err(MutationError::Indexing(_)) => {}
+                    Err(err) => panic!("broken selector {err}"),
+                }
+                (parent_selector, parent)
+            }
+        };
+
+        let target = target.to_vec();
+        self.select_path(state, &target);
+        self.checkpoint = self.replace_selected(state, node);
+        if let Some(checkpoint) = &self.checkpoint {
+            checkpoint.record(&self.file_root, &mut self.patch);
+        }
+        self.is_edited = true;
+        self.select_path(state, &selector);
+        self.conflicts.remove(index);
+        let side = if theirs { "theirs" } else { "ours" };
+        self.push_message(format!("Resolved {pointer} with {side}"));
+    }
+
+    fn handle_list_registers(&mut self) {
+        let registers = self
+            .registers
+            .iter()
+            .map(|(name, node)| {
+                let content = node
+                    .to_string_compact()
+                    .expect("broken internal representation");
+                let preview = content
+                    .chars()
+                    .take(REGISTER_PREVIEW_LEN)
+                    .collect::<String>();
+                (name, Line::from(format!("\"{name}  {preview}")))
+            })
+            .collect::<Vec<_>>();
+        if registers.is_empty() {
+            self.dialogs
+                .push(Box::new(ErrorConfirmDialog::new("No registers".into())));
+            return;
+        }
+
+        let (names, items): (Vec<_>, Vec<_>) = registers.into_iter().unzip();
+        self.dialogs.push(Box::new(
+            ListConfirmDialog::new(
+                items,
+                Box::new(move |index| {
+                    WorkSpaceAction::RegisterPicked(index.map(|index| names[index])).into()
+                }),
+            )
+            .title(Line::from("Registers")),
+        ));
+    }
+
+    fn handle_bookmark(&mut self, state: &WorkSpaceState, name: char) {
+        let Some(index) = state.list_state.selected() else {
+            return;
+        };
+        if self.work_tree_root.elided(index).is_some() {
+            return;
+        }
+        let selector = self.work_tree_root.selector(index);
+        self.push_message(format!("Bookmarked {} as {name}", node_name(&selector)));
+        self.bookmarks.insert(name, selector);
+    }
+
+    fn handle_jump_to_bookmark(&mut self, state: &mut WorkSpaceState, name: char) {
+        let Some(selector) = self.bookmarks.get(&name).cloned() else {
+            self.push_message(format!("No bookmark {name}"));
+            return;
+        };
+        if self.file_root.subtree(&selector).is_err() || !self.select_path(state, &selector) {
+            self.push_message(format!(
+                "Bookmark {name} at {} no longer exists",
+                node_name(&selector)
+            ));
+        }
+    }
+
+    fn handle_list_bookmarks(&mut self) {
+        if self.bookmarks.is_empty() {
+            self.dialogs.push(Box::new(ErrorConfirmDialog::new(
+                "No bookmarks, set one with m and a letter".into(),
+            )));
+            return;
+        }
+
+        let items = self
+            .bookmarks
+            .iter()
+            .map(|(name, selector)| Line::from(format!("'{name}  {}", node_name(selector))))
+            .collect();
+        let selectors = self.bookmarks.values().cloned().collect::<Vec<_>>();
+        self.dialogs.push(Box::new(
+            ListConfirmDialog::new(
+                items,
+                Box::new(move |index| {
+                    WorkSpaceAction::SelectPath(index.map(|index| selectors[index].clone())).into()
+                }),
+            )
+            .title(Line::from("Bookmarks")),
+        ));
     }
 
+    /// Deletes the selection after confirming, cutting it into a register for pasting elsewhere.
     fn handle_delete(
         &mut self,
         state: &mut WorkSpaceState,
-        confirm_action: ConfirmAction<()>,
+        actions: &mut Actions,
+        confirm_action: ConfirmAction<char>,
     ) -> std::io::Result<()> {
         let Some(index) = self.index_for_mutation(state) else {
             return Ok(());
         };
 
         match confirm_action {
-            ConfirmAction::Request(_) => {
+            ConfirmAction::Request(register) => {
+                self.cut_register = register;
+                let selector = self.work_tree_root.selector(index);
+                let node = self.file_root.subtree(&selector).expect("broken selector");
                 self.dialogs.push(Box::new(BooleanConfirmDialog::new(
-                    Text::from("Delete node?"),
+                    Text::from(vec![
+                        Line::from(delete_summary(&selector, node, self.config.indent)),
+                        Line::from(to_pointer(&selector)),
+                    ]),
                     Box::new(ConfirmAction::action_confirmer(WorkSpaceAction::Delete)),
                 )));
             }
@@ -498,40 +2702,381 @@ impl WorkSpace {
                 }
 
                 let mut selector = self.work_tree_root.selector(index);
-                let _ = self.file_root.delete(&selector).expect("broken selector");
+                let checkpoint = self.checkpoint(&selector[..selector.len() - 1]);
+                let position = checkpoint
+                    .node
+                    .position(selector.last().expect("broken selector"))
+                    .expect("broken selector");
+                let node = self.file_root.delete(&selector).expect("broken selector");
+                checkpoint.record(&self.file_root, &mut self.patch);
+                self.checkpoint = Some(checkpoint);
+                let message = match self.registers.set(self.cut_register, node.clone()) {
+                    Ok(()) => format!(
+                        "Cut {} into \"{}, press P to paste it",
+                        to_pointer(&selector),
+                        self.cut_register
+                    ),
+                    Err(error) => format!("Failed to save registers: {error}"),
+                };
+                if self.cut_register == CLIPBOARD_REGISTER {
+                    let content = node
+                        .to_string_pretty()
+                        .expect("broken internal representation");
+                    actions.push(
+                        JobAction::Copy {
+                            content,
+                            run_tools: !self.config.no_shell,
+                        }
+                        .into(),
+                    );
+                }
+                if self.trash.len() == TRASH_LEN {
+                    self.trash.pop_front();
+                }
+                self.trash.push_back(Trashed {
+                    selector: selector.clone(),
+                    position,
+                    node,
+                });
                 selector.pop();
                 let parent_metas = self.file_root.metas(&selector).expect("broken selector");
                 self.work_tree_root.delete(index, parent_metas);
+                self.push_message(message);
 
                 if index >= self.work_tree_root.len() {
                     state.list_state.select_previous();
                 }
                 self.is_edited = true;
-                self.list = new_list(&self.work_tree_root);
+                self.refresh_list();
                 self.set_preview_to_selected(state, false);
+
+                self.deletes_since_compact += 1;
+                if self
+                    .config
+                    .compact_after_deletes
+                    .is_some_and(|n_deletes| self.deletes_since_compact >= n_deletes)
+                {
+                    actions.push(JobAction::Compact.into());
+                }
             }
         }
 
         Ok(())
     }
 
-    fn handle_rename(
+    fn handle_followed(
         &mut self,
         state: &WorkSpaceState,
-        confirm_action: ConfirmAction<(), Option<String>>,
-    ) -> std::io::Result<()> {
-        let Some(index) = self.index_for_mutation(state) else {
-            return Ok(());
+        actions: &mut Actions,
+        path: String,
+        reader: RecordReader,
+        records: Vec<Node>,
+    ) {
+        let n_records = records.len();
+        if self.file_root.extend(records).is_err() {
+            self.push_message(format!(
+                "Stopped following {path}: the root is no longer an array"
+            ));
+            return;
+        }
+
+        // New rows only ever go below the existing ones, so the selected row stays put.
+        self.work_tree_root
+            .extend_root(n_records, self.file_root.as_meta());
+        self.refresh_list();
+        if state.list_state.selected() == Some(0) {
+            self.set_preview_to_selected(state, false);
+        }
+        actions.push(JobAction::Follow { path, reader }.into());
+    }
+
+    fn handle_page(&mut self, state: &WorkSpaceState, actions: &mut Actions) {
+        let Some(index) = state.list_state.selected() else {
+            return;
         };
-        match confirm_action {
-            ConfirmAction::Request(_) => {
-                let selector = self.work_tree_root.selector(index);
-                let index = self
-                    .file_root
-                    .subtree(&selector[..selector.len() - 1])
-                    .expect("broken selector")
-                    .as_index();
-                match index.kind {
+        if self.work_tree_root.elided(index).is_some() {
+            return;
+        }
+        if self.config.no_shell {
+            self.push_message(String::from("The pager is disabled by no_shell"));
+            return;
+        }
+
+        let command = self.selected_tool(state, false);
+        let node = self.selected_node(state).expect("broken selection");
+        let content = match command {
+            Some(_) => tool::view_content(node),
+            None => node
+                .to_string_pretty()
+                .expect("broken internal representation"),
+        };
+        actions.push(JobAction::Page { content, command }.into());
+    }
+
+    /// Command of the first configured tool matching the selection.
+    fn selected_tool(&self, state: &WorkSpaceState, edit: bool) -> Option<String> {
+        let index = state.list_state.selected()?;
+        let selector = self.work_tree_root.selector(index);
+        let node = self.file_root.subtree(&selector).expect("broken selector");
+        tool::find(&self.config.tools, edit, &to_pointer(&selector), node)
+            .map(|tool| tool.command.clone())
+    }
+
+    /// Whether the selection is a scalar to edit in a text dialog. Strings with control characters
+    /// such as newlines, and nodes with an edit tool, still go through the editor.
+    fn edits_inline(&self, state: &WorkSpaceState) -> bool {
+        let Some(node) = self.selected_node(state) else {
+            return false;
+        };
+        self.config.inline_scalar_edits
+            && matches!(node.as_index().kind, IndexKind::Terminal)
+            && !node
+                .as_str()
+                .is_some_and(|value| value.chars().any(char::is_control))
+            && self.selected_tool(state, true).is_none()
+    }
+
+    /// Edits the selection in a text dialog, in place of the external editor. Scalars edited
+    /// inline type strings as they are; anything else is typed as one line of JSON.
+    fn handle_inline_edit(
+        &mut self,
+        state: &WorkSpaceState,
+        actions: &mut Actions,
+        confirm_action: ConfirmAction<(), Option<String>>,
+    ) {
+        let Some(node) = self.selected_node(state) else {
+            return;
+        };
+        let is_raw_string = node.as_str().is_some() && self.edits_inline(state);
+        let type_name = node.type_name();
+        let dialog = |content: String| {
+            TextConfirmDialog::new(Box::new(ConfirmAction::action_confirmer(
+                WorkSpaceAction::InlineEdit,
+            )))
+            .title(Line::from(format!("Edit {type_name}")))
+            .content(content)
+        };
+
+        match confirm_action {
+            ConfirmAction::Request(_) => {
+                let content = match node.as_str().filter(|_| is_raw_string) {
+                    Some(value) => String::from(value),
+                    None => node
+                        .to_string_compact()
+                        .expect("broken internal representation"),
+                };
+                self.dialogs.push(Box::new(dialog(content)));
+            }
+            ConfirmAction::Confirm(content) => {
+                self.dialogs.pop();
+
+                let Some(content) = content else {
+                    return;
+                };
+                if is_raw_string {
+                    actions.push(
+                        WorkSpaceAction::ApplyEdit(ConfirmAction::Request((
+                            Node::string(content),
+                            Repairs::default(),
+                        )))
+                        .into(),
+                    );
+                    return;
+                }
+                match Node::load_with_repairs(content.as_bytes(), &self.config.parse_options()) {
+                    Ok((node, repaired)) => actions.push(
+                        WorkSpaceAction::ApplyEdit(ConfirmAction::Request((node, repaired))).into(),
+                    ),
+                    Err(error) => {
+                        self.dialogs.push(Box::new(dialog(content)));
+                        self.dialogs.push(Box::new(
+                            ErrorConfirmDialog::new(error.to_string().into())
+                                .title(Line::from("JSON Error")),
+                        ));
+                    }
+                }
+            }
+        }
+    }
+
+    /// Sorts the keys of the selected object or the values of the selected array.
+    fn handle_sort(
+        &mut self,
+        state: &mut WorkSpaceState,
+        confirm_action: ConfirmAction<(), Option<usize>>,
+    ) {
+        if self.refuse_while_filtered() {
+            return;
+        }
+        let index = state.list_state.selected().unwrap_or_default();
+        if self.work_tree_root.elided(index).is_some() {
+            self.dialogs.push(Box::new(
+                ErrorConfirmDialog::new("Expand hidden elements first".into())
+                    .title(Line::from("Invalid selection")),
+            ));
+            return;
+        }
+        let selector = self.work_tree_root.selector(index);
+
+        let spec = match confirm_action {
+            ConfirmAction::Request(_) => {
+                let node = self.file_root.subtree(&selector).expect("broken selector");
+                let title = match node.as_index().kind {
+                    IndexKind::Object(_) => "Sort keys",
+                    IndexKind::Array(_) => "Sort values",
+                    IndexKind::Terminal => {
+                        self.dialogs.push(Box::new(
+                            ErrorConfirmDialog::new("Select an object or array to sort".into())
+                                .title(Line::from("Sort")),
+                        ));
+                        return;
+                    }
+                };
+                let items = SORT_SPECS
+                    .iter()
+                    .map(|(label, _)| Line::from(*label))
+                    .collect();
+                self.dialogs.push(Box::new(
+                    ListConfirmDialog::new(
+                        items,
+                        Box::new(ConfirmAction::action_confirmer(WorkSpaceAction::Sort)),
+                    )
+                    .title(Line::from(title)),
+                ));
+                return;
+            }
+            ConfirmAction::Confirm(spec) => {
+                self.dialogs.pop();
+                let Some(spec) = spec else {
+                    return;
+                };
+                SORT_SPECS[spec].1
+            }
+        };
+
+        let checkpoint = self.checkpoint(&selector);
+        let order = match self.file_root.sort(&selector, spec) {
+            Ok(order) => order,
+            Err(error) => {
+                self.dialogs.push(Box::new(
+                    ErrorConfirmDialog::new(error.to_string().into()).title(Line::from("Sort")),
+                ));
+                return;
+            }
+        };
+        checkpoint.record(&self.file_root, &mut self.patch);
+        self.checkpoint = Some(checkpoint);
+        if !self.work_tree_root.permute(index, &order) {
+            let node_index = self
+                .file_root
+                .subtree(&selector)
+                .expect("broken selector")
+                .as_index();
+            self.reindex(index, node_index, false);
+        }
+        self.is_edited = true;
+        self.refresh_list();
+        self.set_preview_to_selected(state, false);
+        self.push_message(format!("Sorted {}", to_pointer(&selector)));
+    }
+
+    /// Converts the selected scalar to the picked type, such as `"42"` to `42`.
+    fn handle_set_type(
+        &mut self,
+        state: &WorkSpaceState,
+        actions: &mut Actions,
+        confirm_action: ConfirmAction<(), Option<usize>>,
+    ) {
+        if self.index_for_mutation(state).is_none() {
+            return;
+        }
+        let Some(node) = self.selected_node(state) else {
+            return;
+        };
+
+        let converted = match confirm_action {
+            ConfirmAction::Request(_) => {
+                if !matches!(node.as_index().kind, IndexKind::Terminal) {
+                    self.dialogs.push(Box::new(
+                        ErrorConfirmDialog::new(ConversionError::NotScalar.to_string().into())
+                            .title(Line::from("Change type")),
+                    ));
+                    return;
+                }
+                let items = ScalarType::ALL
+                    .iter()
+                    .map(|to| Line::from(to.name()))
+                    .collect();
+                self.dialogs.push(Box::new(
+                    ListConfirmDialog::new(
+                        items,
+                        Box::new(ConfirmAction::action_confirmer(WorkSpaceAction::SetType)),
+                    )
+                    .title(Line::from(format!("Change {} to", node.type_name()))),
+                ));
+                return;
+            }
+            ConfirmAction::Confirm(index) => {
+                let Some(index) = index else {
+                    self.dialogs.pop();
+                    return;
+                };
+                node.convert(ScalarType::ALL[index])
+            }
+        };
+
+        self.dialogs.pop();
+        match converted {
+            Ok(node) => actions.push(
+                WorkSpaceAction::ApplyEdit(ConfirmAction::Request((node, Repairs::default())))
+                    .into(),
+            ),
+            Err(error) => self.dialogs.push(Box::new(
+                ErrorConfirmDialog::new(error.to_string().into()).title(Line::from("Change type")),
+            )),
+        }
+    }
+
+    fn handle_expand_json(&mut self, state: &WorkSpaceState, actions: &mut Actions) {
+        if self.index_for_mutation(state).is_none() {
+            return;
+        }
+        let Some(node) = self.selected_node(state) else {
+            return;
+        };
+        // Skips the edit review, as `}` undoes it.
+        match node.parse_embedded() {
+            Ok(node) => actions.push(
+                WorkSpaceAction::Load {
+                    node,
+                    is_edit: true,
+                    repaired: Repairs::default(),
+                }
+                .into(),
+            ),
+            Err(error) => self.dialogs.push(Box::new(
+                ErrorConfirmDialog::new(error.to_string().into()).title(Line::from("Expand JSON")),
+            )),
+        }
+    }
+
+    fn handle_rename(
+        &mut self,
+        state: &WorkSpaceState,
+        confirm_action: ConfirmAction<(), Option<String>>,
+    ) -> std::io::Result<()> {
+        let Some(index) = self.index_for_mutation(state) else {
+            return Ok(());
+        };
+        match confirm_action {
+            ConfirmAction::Request(_) => {
+                let selector = self.work_tree_root.selector(index);
+                let index = self
+                    .file_root
+                    .subtree(&selector[..selector.len() - 1])
+                    .expect("broken selector")
+                    .as_index();
+                match index.kind {
                     IndexKind::Object(_) => {
                         self.dialogs.push(Box::new(
                             TextConfirmDialog::new(Box::new(ConfirmAction::action_confirmer(
@@ -553,15 +3098,15 @@ impl WorkSpace {
 
                 if let Some(new_key) = new_key {
                     let selector = self.work_tree_root.selector(index);
-                    if selector
-                        .last()
-                        .is_some_and(|&old_key| old_key != new_key.as_str())
-                    {
+                    if selector.last().is_some_and(|old_key| **old_key != *new_key) {
+                        let checkpoint = self.checkpoint(&selector[..selector.len() - 1]);
                         match self.file_root.rename(&selector, new_key.clone()) {
                             Ok(_) => {
+                                checkpoint.record(&self.file_root, &mut self.patch);
+                                self.checkpoint = Some(checkpoint);
                                 self.work_tree_root.rename(index, new_key);
                                 self.is_edited = true;
-                                self.list = new_list(&self.work_tree_root);
+                                self.refresh_list();
                             }
                             Err(MutationError::DuplicateKey) => {
                                 self.dialogs.push(Box::new(
@@ -587,1206 +3132,5445 @@ impl WorkSpace {
         Ok(())
     }
 
-    fn index_for_mutation(&mut self, state: &WorkSpaceState) -> Option<usize> {
+    fn handle_info(&mut self, state: &WorkSpaceState) {
         let index = state.list_state.selected().unwrap_or_default();
-        if index == 0 {
+        let selector = self.work_tree_root.selector(index);
+        if let Some(n_hidden) = self.work_tree_root.elided(index) {
+            // The row is named after the first element it hides.
+            let mut last = selector.clone();
+            let start = last.pop().expect("elided root");
+            let start = start.parse::<usize>().expect("broken elided node");
+            last.push(&(start + n_hidden - 1).to_string());
+            let lines = vec![
+                Line::from("Type:     hidden elements"),
+                Line::from(format!("Elements: {n_hidden}")),
+                Line::from(format!("Depth:    {}", selector.len())),
+                Line::from(format!(
+                    "Pointers: {} to {}",
+                    to_pointer(&selector),
+                    to_pointer(&last)
+                )),
+            ];
             self.dialogs.push(Box::new(
-                ErrorConfirmDialog::new("Index cannot be 0".into())
-                    .title(Line::from("Invalid selection")),
+                ErrorConfirmDialog::new(Text::from(lines)).title(Line::from("Node info")),
             ));
-            return None;
+            return;
         }
+        let node = self.file_root.subtree(&selector).expect("broken selector");
+        let node_index = node.as_index();
+
+        let mut lines = vec![
+            Line::from(format!("Type:     {}", node.type_name())),
+            Line::from(format!(
+                "Size:     {}",
+                format::bytes(node_index.meta.n_bytes_with(self.config.indent))
+            )),
+            Line::from(format!("Lines:    {}", node_index.meta.n_lines)),
+        ];
+        match node_index.kind {
+            IndexKind::Object(keys) => lines.push(Line::from(format!("Children: {}", keys.len()))),
+            IndexKind::Array(n) => lines.push(Line::from(format!("Children: {n}"))),
+            IndexKind::Terminal => {}
+        }
+        lines.push(Line::from(format!("Depth:    {}", selector.len())));
+        lines.push(Line::from(format!("Pointer:  {}", to_pointer(&selector))));
 
-        Some(index)
+        self.dialogs.push(Box::new(
+            ErrorConfirmDialog::new(Text::from(lines)).title(Line::from("Node info")),
+        ));
     }
-}
 
-impl WorkSpace {
-    fn handle_save_action(
+    fn handle_expand_all(
         &mut self,
+        state: &WorkSpaceState,
+        actions: &mut Actions,
         confirm_action: ConfirmAction<()>,
-    ) -> std::io::Result<Option<Action>> {
+    ) {
+        let Some(index) = state.list_state.selected() else {
+            return;
+        };
+        if self.work_tree_root.elided(index).is_some() {
+            return;
+        }
+        let selector = self.work_tree_root.selector(index);
+        let keep = self.config.array_sample_size;
+
         match confirm_action {
             ConfirmAction::Request(()) => {
+                let node = self.file_root.subtree(&selector).expect("broken selector");
+                if WorkTreeNode::expanded_len(node, keep, EXPAND_ALL_LIMIT) <= EXPAND_ALL_LIMIT {
+                    let tree = WorkTreeNode::expanded(String::new(), node, keep);
+                    self.work_tree_root.replace_subtree(index, tree);
+                    self.refresh_list();
+                    return;
+                }
                 self.dialogs.push(Box::new(BooleanConfirmDialog::new(
-                    Text::from(Line::from("Write file?").centered()),
-                    Box::new(ConfirmAction::action_confirmer(WorkSpaceAction::Save)),
+                    Text::from(vec![
+                        Line::from(format!(
+                            "{} has over {EXPAND_ALL_LIMIT} nodes.",
+                            node_name(&selector)
+                        )),
+                        Line::from("Expand all of them?"),
+                    ]),
+                    Box::new(ConfirmAction::action_confirmer(WorkSpaceAction::ExpandAll)),
                 )));
-                Ok(None)
             }
-            ConfirmAction::Confirm(ok) => {
-                if ok {
-                    Ok(Some(JobAction::Save.into()))
-                } else {
-                    self.dialogs.pop();
-                    Ok(None)
+            ConfirmAction::Confirm(is_expand) => {
+                self.dialogs.pop();
+                if is_expand {
+                    actions.push(JobAction::ExpandAll { selector, keep }.into());
                 }
             }
         }
     }
 
-    fn handle_save_done(&mut self) {
-        self.is_edited = false;
-    }
-}
-
-impl WorkSpace {
-    fn handle_edit_error_action(&mut self, confirm_action: ConfirmAction<String>) -> bool {
-        match confirm_action {
-            ConfirmAction::Request(message) => {
-                let mut confirm_dialog = BooleanConfirmDialog::new(
-                    Text::from(vec![
-                        Line::from(message),
-                        Line::from(""),
-                        Line::from("Continue to edit?").centered(),
-                    ]),
-                    Box::new(ConfirmAction::action_confirmer(WorkSpaceAction::EditError)),
-                );
-                confirm_dialog.title(Some(Line::from("JSON Error").left_aligned()));
-                self.dialogs.push(Box::new(confirm_dialog));
-                false
-            }
-            ConfirmAction::Confirm(ok) => {
-                self.dialogs.pop();
-                ok
-            }
+    fn handle_largest_subtrees(&mut self) {
+        let largest = self.file_root.largest_subtrees(LARGEST_SUBTREES_COUNT);
+        if largest.is_empty() {
+            self.dialogs.push(Box::new(ErrorConfirmDialog::new(
+                "Document has no subtrees".into(),
+            )));
+            return;
         }
+
+        let items = largest
+            .iter()
+            .map(|(selector, n_bytes)| {
+                Line::from(format!(
+                    "{:>10}  {}",
+                    format::bytes(*n_bytes),
+                    to_pointer(selector)
+                ))
+            })
+            .collect();
+        let selectors = largest
+            .into_iter()
+            .map(|(selector, _)| KeyPath::from(selector))
+            .collect::<Vec<_>>();
+
+        self.dialogs.push(Box::new(
+            ListConfirmDialog::new(
+                items,
+                Box::new(move |index| {
+                    WorkSpaceAction::SelectPath(index.map(|index| selectors[index].clone())).into()
+                }),
+            )
+            .title(Line::from("Largest subtrees")),
+        ));
     }
-}
 
-#[derive(Debug)]
-pub struct WorkSpaceState {
-    list_state: ListState,
-    preview_state: PreviewState,
-}
+    fn handle_validate(&mut self, state: &WorkSpaceState) {
+        let Some(schema) = &self.schema else {
+            self.dialogs.push(Box::new(
+                ErrorConfirmDialog::new("No schema, open the file with --schema".into())
+                    .title(Line::from("Validate")),
+            ));
+            return;
+        };
 
-impl Default for WorkSpaceState {
-    fn default() -> Self {
-        let mut list_state = ListState::default();
-        list_state.select(Some(0));
-        Self {
-            list_state,
-            preview_state: PreviewState::default(),
+        // The whole document is validated, as the schema of a subtree depends on its ancestors.
+        let index = state.list_state.selected().unwrap_or_default();
+        let selector = self.work_tree_root.selector(index);
+        let errors = schema
+            .validate(&self.file_root)
+            .into_iter()
+            .filter(|error| selector.is_prefix_of(&error.selector))
+            .collect::<Vec<_>>();
+        if errors.is_empty() {
+            self.push_message(format!("{} is valid", node_name(&selector)));
+            return;
         }
-    }
-}
-
-impl StatefulWidget for &WorkSpace {
-    type State = WorkSpaceState;
 
-    fn render(self, area: Rect, buf: &mut Buffer, state: &mut Self::State) {
-        if let Some(preview) = &self.preview {
-            let layout = Layout::horizontal([
-                Constraint::Percentage(100 - self.preview_pct),
-                Constraint::Fill(self.preview_pct),
-            ]);
-            let [tree_area, preview_area] = layout.areas(area);
+        let title = Line::from(format!("{} schema errors", errors.len()));
+        let items = errors
+            .iter()
+            .map(|error| Line::from(format!("{}  {}", node_name(&error.selector), error.message)))
+            .collect();
+        let selectors = errors
+            .into_iter()
+            .map(|error| KeyPath::from(error.selector))
+            .collect::<Vec<_>>();
+
+        self.dialogs.push(Box::new(
+            ListConfirmDialog::new(
+                items,
+                Box::new(move |index| {
+                    WorkSpaceAction::SelectPath(index.map(|index| selectors[index].clone())).into()
+                }),
+            )
+            .title(title),
+        ));
+    }
 
-            self.render_tree(tree_area, buf, state);
-            preview.render(preview_area, buf, &mut state.preview_state);
-        } else {
-            self.render_tree(area, buf, state);
+    fn handle_split(
+        &mut self,
+        state: &WorkSpaceState,
+        confirm_action: ConfirmAction<(), Option<String>>,
+    ) -> Option<Action> {
+        if self.refuse_elided(state) {
+            return None;
         }
+        let index = state.list_state.selected().unwrap_or_default();
+        let selector = self.work_tree_root.selector(index);
+        let node = self.file_root.subtree(&selector).expect("broken selector");
+        let keys = match node.as_index().kind {
+            IndexKind::Object(keys) => keys,
+            IndexKind::Array(n) => (0..n).map(|i| i.to_string()).collect(),
+            IndexKind::Terminal => {
+                self.dialogs.push(Box::new(ErrorConfirmDialog::new(
+                    "Selected node is not an object or array".into(),
+                )));
+                return None;
+            }
+        };
 
-        for dialog in &self.dialogs {
-            dialog.render_ref(area, buf);
-        }
+        match confirm_action {
+            ConfirmAction::Request(()) => {
+                self.dialogs.push(Box::new(
+                    TextConfirmDialog::new(Box::new(ConfirmAction::action_confirmer(
+                        WorkSpaceAction::Split,
+                    )))
+                    .title(Line::from(format!("Split {} children into", keys.len())))
+                    .content(String::from(SPLIT_DEFAULT_PATTERN)),
+                ));
+                None
+            }
+            ConfirmAction::Confirm(pattern) => {
+                self.dialogs.pop();
+                let pattern = pattern?;
+                if !pattern.contains(SPLIT_KEY_PLACEHOLDER) {
+                    self.dialogs.push(Box::new(ErrorConfirmDialog::new(
+                        format!("Pattern must contain {SPLIT_KEY_PLACEHOLDER}").into(),
+                    )));
+                    return None;
+                }
 
-        if let Some(loading) = &self.loading {
-            loading.render(area, buf);
+                let files = keys
+                    .iter()
+                    .map(|key| {
+                        let content = node
+                            .child(key)
+                            .expect("broken index")
+                            .to_string_pretty()
+                            .expect("broken internal representation");
+                        (split_file_name(&pattern, key), content)
+                    })
+                    .collect();
+                Some(JobAction::WriteFiles(files).into())
+            }
         }
     }
-}
-
-impl WorkSpace {
-    fn render_tree(&self, area: Rect, buf: &mut Buffer, state: &mut WorkSpaceState) {
-        let block = Block::bordered().title("Tree");
-        let inner_area = block.inner(area);
 
-        block.render(area, buf);
-        StatefulWidget::render(&self.list, inner_area, buf, &mut state.list_state);
-
-        let scrollbar = scrollbar(ScrollbarOrientation::VerticalRight);
-        StatefulWidget::render(
-            scrollbar,
-            inner_area,
-            buf,
-            &mut ScrollbarState::new(self.work_tree_root.len())
-                .position(state.list_state.selected().unwrap_or_default()),
-        );
-    }
-}
+    fn handle_import_folder(
+        &mut self,
+        state: &WorkSpaceState,
+        confirm_action: ConfirmAction<(), Option<String>>,
+    ) -> Option<Action> {
+        if self.refuse_elided(state) {
+            return None;
+        }
+        match confirm_action {
+            ConfirmAction::Request(()) => {
+                self.dialogs.push(Box::new(
+                    TextConfirmDialog::new(Box::new(ConfirmAction::action_confirmer(
+                        WorkSpaceAction::ImportFolder,
+                    )))
+                    .title(Line::from("Replace selected node with folder"))
+                    .content(String::from("./")),
+                ));
+                None
+            }
+            ConfirmAction::Confirm(dir) => {
+                self.dialogs.pop();
+                Some(JobAction::ImportFolder(dir?).into())
+            }
+        }
+    }
+
+    fn handle_import_file(
+        &mut self,
+        state: &WorkSpaceState,
+        confirm_action: ConfirmAction<(), Option<String>>,
+    ) -> Option<Action> {
+        if self.refuse_elided(state) {
+            return None;
+        }
+        match confirm_action {
+            ConfirmAction::Request(()) => {
+                self.dialogs.push(Box::new(
+                    TextConfirmDialog::new(Box::new(ConfirmAction::action_confirmer(
+                        WorkSpaceAction::ImportFile,
+                    )))
+                    .title(Line::from("Replace selected node with file"))
+                    .content(String::from("./")),
+                ));
+                None
+            }
+            ConfirmAction::Confirm(path) => {
+                self.dialogs.pop();
+                let path = path.filter(|path| !path.trim().is_empty())?;
+                Some(JobAction::ImportFile(path).into())
+            }
+        }
+    }
+
+    fn handle_export_gron(
+        &mut self,
+        state: &WorkSpaceState,
+        confirm_action: ConfirmAction<(), Option<String>>,
+    ) -> Option<Action> {
+        if self.refuse_elided(state) {
+            return None;
+        }
+        match confirm_action {
+            ConfirmAction::Request(()) => {
+                self.dialogs.push(Box::new(
+                    TextConfirmDialog::new(Box::new(ConfirmAction::action_confirmer(
+                        WorkSpaceAction::ExportGron,
+                    )))
+                    .title(Line::from(
+                        "Export selected node as gron (empty copies to clipboard)",
+                    )),
+                ));
+                None
+            }
+            ConfirmAction::Confirm(path) => {
+                self.dialogs.pop();
+                let path = path?;
+                let index = state.list_state.selected().unwrap_or_default();
+                let selector = self.work_tree_root.selector(index);
+                let node = self.file_root.subtree(&selector).expect("broken selector");
+                let content = gron::to_gron(node);
+                if path.trim().is_empty() {
+                    self.push_message(String::from("Copied gron of the selected node"));
+                    return Some(
+                        JobAction::Copy {
+                            content,
+                            run_tools: !self.config.no_shell,
+                        }
+                        .into(),
+                    );
+                }
+                Some(JobAction::ExportGron { path, content }.into())
+            }
+        }
+    }
+
+    fn handle_import_gron(
+        &mut self,
+        state: &WorkSpaceState,
+        confirm_action: ConfirmAction<(), Option<String>>,
+    ) -> Option<Action> {
+        if self.refuse_elided(state) {
+            return None;
+        }
+        match confirm_action {
+            ConfirmAction::Request(()) => {
+                self.dialogs.push(Box::new(
+                    TextConfirmDialog::new(Box::new(ConfirmAction::action_confirmer(
+                        WorkSpaceAction::ImportGron,
+                    )))
+                    .title(Line::from("Replace selected node with gron file")),
+                ));
+                None
+            }
+            ConfirmAction::Confirm(path) => {
+                self.dialogs.pop();
+                let path = path.filter(|path| !path.trim().is_empty())?;
+                Some(JobAction::ImportGron(path).into())
+            }
+        }
+    }
+
+    fn handle_jump_to_index(
+        &mut self,
+        state: &mut WorkSpaceState,
+        confirm_action: ConfirmAction<(), Option<String>>,
+    ) {
+        let index = state.list_state.selected().unwrap_or_default();
+        let selector = self.work_tree_root.selector(index);
+        let node_index = self
+            .file_root
+            .subtree(&selector)
+            .expect("broken selector")
+            .as_index();
+        let IndexKind::Array(len) = node_index.kind else {
+            self.dialogs.push(Box::new(ErrorConfirmDialog::new(
+                "Selected node is not an array".into(),
+            )));
+            return;
+        };
+
+        match confirm_action {
+            ConfirmAction::Request(()) => {
+                self.dialogs.push(Box::new(
+                    TextConfirmDialog::new(Box::new(ConfirmAction::action_confirmer(
+                        WorkSpaceAction::JumpToIndex,
+                    )))
+                    .title(Line::from(format!(
+                        "Jump to index (0-{})",
+                        len.saturating_sub(1)
+                    ))),
+                ));
+            }
+            ConfirmAction::Confirm(input) => {
+                self.dialogs.pop();
+                let Some(input) = input else {
+                    return;
+                };
+                let Some(position) = input.trim().parse::<usize>().ok().filter(|&i| i < len) else {
+                    self.dialogs.push(Box::new(ErrorConfirmDialog::new(
+                        format!("Invalid index: {input}").into(),
+                    )));
+                    return;
+                };
+
+                let mut selector = selector;
+                selector.push(&position.to_string());
+                self.select_path(state, &selector);
+            }
+        }
+    }
+
+    fn handle_go_to(
+        &mut self,
+        state: &mut WorkSpaceState,
+        confirm_action: ConfirmAction<(), Option<String>>,
+    ) {
+        match confirm_action {
+            ConfirmAction::Request(()) => {
+                let index = state.list_state.selected().unwrap_or_default();
+                let pointer = to_pointer(&self.work_tree_root.selector(index));
+                self.dialogs.push(Box::new(
+                    TextConfirmDialog::new(Box::new(ConfirmAction::action_confirmer(
+                        WorkSpaceAction::GoTo,
+                    )))
+                    .title(Line::from("Go to JSON Pointer or JSONPath"))
+                    .content(pointer),
+                ));
+            }
+            ConfirmAction::Confirm(query) => {
+                self.dialogs.pop();
+                let Some(query) = query else {
+                    return;
+                };
+                match self.file_root.resolve_pointer(query.trim()) {
+                    Ok(selector) => {
+                        self.select_path(state, &selector);
+                    }
+                    Err(PointerError::Indexing(IndexingError::MissingKey(_))) => {
+                        let selector = pointer::from_query(query.trim()).expect("resolved query");
+                        self.handle_create_path(state, ConfirmAction::Request(selector));
+                    }
+                    Err(error) => {
+                        self.dialogs.push(Box::new(
+                            ErrorConfirmDialog::new(error.to_string().into())
+                                .title(Line::from("Go to")),
+                        ));
+                    }
+                }
+            }
+        }
+    }
+
+    /// Offers to create a path go-to did not find, then sets it to `null` in one change,
+    /// creating the missing objects and arrays above it as `set --create-parents` does.
+    fn handle_create_path(
+        &mut self,
+        state: &mut WorkSpaceState,
+        confirm_action: ConfirmAction<Vec<String>, Option<Vec<String>>>,
+    ) {
+        match confirm_action {
+            ConfirmAction::Request(selector) => {
+                let pointer = to_pointer(&selector);
+                let mut confirm_dialog = BooleanConfirmDialog::new(
+                    Text::from(vec![
+                        Line::from(format!("{pointer} does not exist.")).centered(),
+                        Line::from("Create it as null?").centered(),
+                    ]),
+                    Box::new(move |ok: bool| {
+                        WorkSpaceAction::CreatePath(ConfirmAction::Confirm(
+                            ok.then(|| selector.clone()),
+                        ))
+                        .into()
+                    }),
+                );
+                confirm_dialog.title(Some(Line::from("Go to").left_aligned()));
+                self.dialogs.push(Box::new(confirm_dialog));
+            }
+            ConfirmAction::Confirm(selector) => {
+                self.dialogs.pop();
+                let Some(selector) = selector else {
+                    return;
+                };
+                let n_existing = (0..=selector.len())
+                    .rev()
+                    .find(|&len| self.file_root.subtree(&selector[..len]).is_ok())
+                    .expect("root exists");
+                let (parent_selector, missing) = selector.split_at(n_existing);
+                let mut parent = self
+                    .file_root
+                    .subtree(parent_selector)
+                    .expect("existing selector")
+                    .clone();
+                if let Err(error) = parent.set(missing, Node::null(), true) {
+                    self.dialogs.push(Box::new(
+                        ErrorConfirmDialog::new(error.to_string().into())
+                            .title(Line::from("Go to")),
+                    ));
+                    return;
+                }
+
+                self.select_path(state, parent_selector);
+                self.checkpoint = self.replace_selected(state, parent);
+                if let Some(checkpoint) = &self.checkpoint {
+                    checkpoint.record(&self.file_root, &mut self.patch);
+                }
+                self.is_edited = true;
+                self.select_path(state, &selector);
+                self.push_message(format!("Created {}", to_pointer(&selector)));
+            }
+        }
+    }
+
+    fn handle_search(
+        &mut self,
+        state: &mut WorkSpaceState,
+        confirm_action: ConfirmAction<(), Option<String>>,
+    ) {
+        match confirm_action {
+            ConfirmAction::Request(()) => {
+                let title = if self.config.search_values {
+                    "Search keys and values"
+                } else {
+                    "Search keys"
+                };
+                self.dialogs.push(Box::new(
+                    TextConfirmDialog::new(Box::new(ConfirmAction::action_confirmer(
+                        WorkSpaceAction::Search,
+                    )))
+                    .title(Line::from(title))
+                    .on_change(Box::new(|query| WorkSpaceAction::SearchInput(query).into())),
+                ));
+            }
+            ConfirmAction::Confirm(query) => {
+                self.dialogs.pop();
+                let Some(query) = query else {
+                    self.search = None;
+                    return;
+                };
+                self.search_for(state, query);
+                self.show_search_position();
+            }
+        }
+    }
+
+    /// Shows only the nodes whose key or value contains the query, and their ancestors. An empty
+    /// query shows the whole tree again.
+    fn handle_filter(
+        &mut self,
+        state: &mut WorkSpaceState,
+        confirm_action: ConfirmAction<(), Option<String>>,
+    ) {
+        let query = match confirm_action {
+            ConfirmAction::Request(()) => {
+                self.dialogs.push(Box::new(
+                    TextConfirmDialog::new(Box::new(ConfirmAction::action_confirmer(
+                        WorkSpaceAction::Filter,
+                    )))
+                    .title(Line::from("Filter keys and values"))
+                    .content(self.filter.clone().unwrap_or_default()),
+                ));
+                return;
+            }
+            ConfirmAction::Confirm(query) => {
+                self.dialogs.pop();
+                let Some(query) = query else {
+                    return;
+                };
+                query
+            }
+        };
+
+        let selector = state
+            .list_state
+            .selected()
+            .map(|index| self.work_tree_root.selector(index))
+            .unwrap_or_default();
+        if query.is_empty() {
+            self.filter = None;
+            self.work_tree_root =
+                WorkTreeNode::new(String::from("root"), Some(self.file_root.as_index().meta));
+            self.select_path(state, &selector);
+            return;
+        }
+
+        let matches = self.file_root.search(&query, true);
+        if matches.is_empty() {
+            self.push_message(format!("No match for {query}"));
+            return;
+        }
+        self.work_tree_root = WorkTreeNode::filtered(&self.file_root, &matches);
+        self.push_message(format!(
+            "{} matches for {query}, filter by an empty query to show all",
+            format::count(matches.len())
+        ));
+        self.filter = Some(query);
+        self.select_path(state, &matches[0]);
+    }
+
+    /// Whether the tree is filtered, which hides siblings that structural changes need to keep
+    /// array indices right.
+    fn refuse_while_filtered(&mut self) -> bool {
+        if self.filter.is_none() {
+            return false;
+        }
+        self.dialogs.push(Box::new(
+            ErrorConfirmDialog::new("Clear the filter first".into())
+                .title(Line::from("Filtered tree")),
+        ));
+        true
+    }
+
+    /// Refuses actions on a row standing for hidden elements, which would otherwise reach the
+    /// first of them.
+    fn refuse_elided(&mut self, state: &WorkSpaceState) -> bool {
+        let index = state.list_state.selected().unwrap_or_default();
+        if self.work_tree_root.elided(index).is_none() {
+            return false;
+        }
+        self.dialogs.push(Box::new(
+            ErrorConfirmDialog::new("Expand hidden elements first".into())
+                .title(Line::from("Invalid selection")),
+        ));
+        true
+    }
+
+    /// Selects the first match of `query`, expanding its ancestors.
+    fn search_for(&mut self, state: &mut WorkSpaceState, query: String) {
+        let matches = self.file_root.search(&query, self.config.search_values);
+        if let Some(selector) = matches.first() {
+            self.select_path(state, selector);
+        }
+        self.search = Some(Search {
+            query,
+            matches,
+            current: 0,
+        });
+    }
+
+    fn handle_search_next(&mut self, state: &mut WorkSpaceState, backward: bool) {
+        let Some(search) = &mut self.search else {
+            self.push_message(String::from("No search, press / to search"));
+            return;
+        };
+        let len = search.matches.len();
+        if len > 0 {
+            search.current = if backward {
+                (search.current + len - 1) % len
+            } else {
+                (search.current + 1) % len
+            };
+            let selector = search.matches[search.current].clone();
+            let query = search.query.clone();
+            // Edits since the search may have moved the match, so search again.
+            if !self.select_path(state, &selector) {
+                self.search_for(state, query);
+            }
+        }
+        self.show_search_position();
+    }
+
+    fn handle_preview_search(
+        &mut self,
+        state: &mut WorkSpaceState,
+        confirm_action: ConfirmAction<(), Option<String>>,
+    ) {
+        match confirm_action {
+            ConfirmAction::Request(()) => {
+                self.dialogs.push(Box::new(
+                    TextConfirmDialog::new(Box::new(ConfirmAction::action_confirmer(
+                        WorkSpaceAction::PreviewSearch,
+                    )))
+                    .title(Line::from("Search preview")),
+                ));
+            }
+            ConfirmAction::Confirm(query) => {
+                self.dialogs.pop();
+                let Some(query) = query else {
+                    return;
+                };
+                let Some((preview, preview_state)) = self.focused_preview(state) else {
+                    return;
+                };
+                if query.is_empty() {
+                    preview_state.clear_search();
+                } else if preview.search(preview_state, query.clone()) == 0 {
+                    self.push_message(format!("No match for {query}"));
+                }
+            }
+        }
+    }
+
+    fn handle_preview_search_next(&mut self, state: &mut WorkSpaceState, backward: bool) {
+        let n_matches = self
+            .focused_preview(state)
+            .and_then(|(preview, preview_state)| preview.search_next(preview_state, backward));
+        if n_matches.is_none() {
+            self.push_message(String::from("No search, press / to search the preview"));
+        }
+    }
+
+    /// The preview that has the focus, or the main one while the tree has it.
+    fn focused_preview<'a>(
+        &'a self,
+        state: &'a mut WorkSpaceState,
+    ) -> Option<(&'a Preview, &'a mut PreviewState)> {
+        match state.focus {
+            Focus::Pinned => self.pinned.as_ref().zip(Some(&mut state.pinned_state)),
+            // The table cannot be searched.
+            Focus::Tree | Focus::Preview if self.table.is_some() => None,
+            Focus::Tree | Focus::Preview => {
+                self.preview.as_ref().zip(Some(&mut state.preview_state))
+            }
+        }
+    }
+
+    fn show_search_position(&mut self) {
+        let Some(search) = &self.search else {
+            return;
+        };
+        let message = match search.matches.get(search.current) {
+            Some(selector) => format!(
+                "Match {} of {}: {}",
+                search.current + 1,
+                search.matches.len(),
+                to_pointer(selector)
+            ),
+            None => format!("No match for {}", search.query),
+        };
+        self.push_message(message);
+    }
+
+    fn index_for_mutation(&mut self, state: &WorkSpaceState) -> Option<usize> {
+        if self.refuse_while_filtered() {
+            return None;
+        }
+        let index = state.list_state.selected().unwrap_or_default();
+        if index == 0 {
+            self.dialogs.push(Box::new(
+                ErrorConfirmDialog::new("Index cannot be 0".into())
+                    .title(Line::from("Invalid selection")),
+            ));
+            return None;
+        }
+        if self.refuse_elided(state) {
+            return None;
+        }
+
+        Some(index)
+    }
+}
+
+impl WorkSpace {
+    fn handle_save_action(
+        &mut self,
+        confirm_action: ConfirmAction<()>,
+    ) -> std::io::Result<Option<Action>> {
+        match confirm_action {
+            ConfirmAction::Request(()) => {
+                self.dialogs.push(Box::new(BooleanConfirmDialog::new(
+                    Text::from(Line::from("Write file?").centered()),
+                    Box::new(ConfirmAction::action_confirmer(WorkSpaceAction::Save)),
+                )));
+                Ok(None)
+            }
+            ConfirmAction::Confirm(ok) => {
+                if ok {
+                    Ok(Some(JobAction::Save.into()))
+                } else {
+                    self.dialogs.pop();
+                    Ok(None)
+                }
+            }
+        }
+    }
+
+    fn handle_overwrite(&mut self, confirm_action: ConfirmAction<String>) -> Option<Action> {
+        match confirm_action {
+            ConfirmAction::Request(path) => {
+                self.dialogs.push(Box::new(BooleanConfirmDialog::new(
+                    Text::from(vec![
+                        Line::from(format!("{path} changed on disk since it was loaded."))
+                            .centered(),
+                        Line::from("Overwrite it?").centered(),
+                    ]),
+                    Box::new(ConfirmAction::action_confirmer(WorkSpaceAction::Overwrite)),
+                )));
+                None
+            }
+            ConfirmAction::Confirm(ok) => {
+                self.dialogs.pop();
+                if ok {
+                    Some(JobAction::Overwrite.into())
+                } else {
+                    self.push_message(String::from("Save aborted, the file on disk is unchanged"));
+                    None
+                }
+            }
+        }
+    }
+
+    fn handle_recover(
+        &mut self,
+        confirm_action: ConfirmAction<String, Option<String>>,
+    ) -> Option<Action> {
+        match confirm_action {
+            ConfirmAction::Request(path) => {
+                self.dialogs.push(Box::new(BooleanConfirmDialog::new(
+                    Text::from(vec![
+                        Line::from(format!("{path} has changes that were never saved.")).centered(),
+                        Line::from("Recover them?").centered(),
+                    ]),
+                    Box::new(move |ok| {
+                        WorkSpaceAction::Recover(ConfirmAction::Confirm(ok.then(|| path.clone())))
+                            .into()
+                    }),
+                )));
+                None
+            }
+            ConfirmAction::Confirm(path) => {
+                self.dialogs.pop();
+                path.map(|path| JobAction::Recover(path).into())
+            }
+        }
+    }
+
+    fn handle_input_changed(
+        &mut self,
+        confirm_action: ConfirmAction<String, Option<String>>,
+    ) -> Option<Action> {
+        match confirm_action {
+            ConfirmAction::Request(path) if self.is_edited => {
+                self.dialogs.push(Box::new(BooleanConfirmDialog::new(
+                    Text::from(vec![
+                        Line::from(format!("{path} changed on disk.")).centered(),
+                        Line::from("Reload it, discarding your changes?").centered(),
+                    ]),
+                    Box::new(move |ok| {
+                        WorkSpaceAction::InputChanged(ConfirmAction::Confirm(
+                            ok.then(|| path.clone()),
+                        ))
+                        .into()
+                    }),
+                )));
+                None
+            }
+            ConfirmAction::Request(path) => Some(JobAction::Reload(path).into()),
+            ConfirmAction::Confirm(path) => {
+                self.dialogs.pop();
+                match path {
+                    Some(path) => Some(JobAction::Reload(path).into()),
+                    None => {
+                        self.push_message(String::from(
+                            "Kept your changes, the file on disk differs",
+                        ));
+                        None
+                    }
+                }
+            }
+        }
+    }
+
+    /// Swaps in the reloaded document, keeping the selection where it still exists.
+    fn handle_reloaded(&mut self, state: &mut WorkSpaceState, path: String, node: Node) {
+        let selector = state
+            .list_state
+            .selected()
+            .map(|index| self.work_tree_root.selector(index))
+            .unwrap_or_default();
+        self.file_root = node;
+        self.filter = None;
+        self.work_tree_root =
+            WorkTreeNode::new(String::from("root"), Some(self.file_root.as_index().meta));
+        if !self.select_path(state, &selector) {
+            self.select_path::<String>(state, &[]);
+        }
+        self.is_edited = false;
+        self.checkpoint = None;
+        self.patch.clear();
+        self.conflicts.clear();
+        self.push_message(format!("Reloaded {path}"));
+    }
+
+    fn handle_save_as(
+        &mut self,
+        confirm_action: ConfirmAction<(), Option<String>>,
+    ) -> Option<Action> {
+        match confirm_action {
+            ConfirmAction::Request(()) => {
+                self.dialogs.push(Box::new(
+                    TextConfirmDialog::new(Box::new(ConfirmAction::action_confirmer(
+                        WorkSpaceAction::SaveAs,
+                    )))
+                    .title(Line::from("Save as")),
+                ));
+                None
+            }
+            ConfirmAction::Confirm(path) => {
+                self.dialogs.pop();
+                let path = path.filter(|path| !path.trim().is_empty())?;
+                Some(JobAction::SaveAs(path).into())
+            }
+        }
+    }
+
+    fn handle_save_patch(
+        &mut self,
+        confirm_action: ConfirmAction<(), Option<String>>,
+    ) -> Option<Action> {
+        match confirm_action {
+            ConfirmAction::Request(()) => {
+                if self.patch.is_empty() {
+                    self.push_message(String::from("No changes to save as a patch"));
+                    return None;
+                }
+                self.dialogs.push(Box::new(
+                    TextConfirmDialog::new(Box::new(ConfirmAction::action_confirmer(
+                        WorkSpaceAction::SavePatch,
+                    )))
+                    .title(Line::from(format!(
+                        "Save {} operations as JSON Patch",
+                        self.patch.len()
+                    ))),
+                ));
+                None
+            }
+            ConfirmAction::Confirm(path) => {
+                self.dialogs.pop();
+                let path = path.filter(|path| !path.trim().is_empty())?;
+                let content = patch::to_node(&self.patch)
+                    .to_string_pretty()
+                    .expect("broken internal representation");
+                Some(JobAction::SavePatch { path, content }.into())
+            }
+        }
+    }
+
+    fn handle_save_done(&mut self, message: String) {
+        self.is_edited = false;
+        self.push_message(message);
+    }
+
+    /// Shows a transient message in the status line and keeps it in the history.
+    pub fn push_message(&mut self, message: String) {
+        if self.messages.len() == MESSAGE_HISTORY_LEN {
+            self.messages.pop_front();
+        }
+        self.messages.push_back(message);
+        self.show_message = true;
+    }
+
+    fn handle_list_messages(&mut self) {
+        if self.messages.is_empty() {
+            self.dialogs.push(Box::new(
+                ErrorConfirmDialog::new("No messages".into()).title(Line::from("Messages")),
+            ));
+            return;
+        }
+
+        let items = self.messages.iter().cloned().map(Line::from).collect();
+        self.dialogs.push(Box::new(
+            ListConfirmDialog::new(items, Box::new(|_| WorkSpaceAction::ErrorConfirmed.into()))
+                .title(Line::from("Messages")),
+        ));
+    }
+}
+
+impl WorkSpace {
+    fn handle_edit_error_action(&mut self, confirm_action: ConfirmAction<String>) -> bool {
+        match confirm_action {
+            ConfirmAction::Request(message) => {
+                let mut confirm_dialog = BooleanConfirmDialog::new(
+                    Text::from(vec![
+                        Line::from(message),
+                        Line::from(""),
+                        Line::from("Continue to edit?").centered(),
+                    ]),
+                    Box::new(ConfirmAction::action_confirmer(WorkSpaceAction::EditError)),
+                );
+                confirm_dialog.title(Some(Line::from("JSON Error").left_aligned()));
+                self.dialogs.push(Box::new(confirm_dialog));
+                false
+            }
+            ConfirmAction::Confirm(ok) => {
+                self.dialogs.pop();
+                ok
+            }
+        }
+    }
+}
+
+/// Pane the keys go to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Focus {
+    Tree,
+    Preview,
+    Pinned,
+}
+
+/// Action of a key pressed while a preview has the focus, or `None` to handle it as in the tree.
+fn preview_key_action(event: KeyEvent) -> Option<Action> {
+    let action: Action = match (event.modifiers, event.code) {
+        (KeyModifiers::CONTROL, KeyCode::Char('u')) | (_, KeyCode::PageUp) => {
+            PreviewNavigationAction::Up(10).into()
+        }
+        (KeyModifiers::CONTROL, KeyCode::Char('d')) | (_, KeyCode::PageDown) => {
+            PreviewNavigationAction::Down(10).into()
+        }
+        (KeyModifiers::NONE | KeyModifiers::SHIFT, code) => match code {
+            KeyCode::Char('k') | KeyCode::Up => PreviewNavigationAction::Up(1).into(),
+            KeyCode::Char('j') | KeyCode::Down => PreviewNavigationAction::Down(1).into(),
+            KeyCode::Char('h') | KeyCode::Left => PreviewNavigationAction::Left.into(),
+            KeyCode::Char('l') | KeyCode::Right => PreviewNavigationAction::Right.into(),
+            KeyCode::Char('g') | KeyCode::Home => PreviewNavigationAction::Top.into(),
+            KeyCode::Char('G') | KeyCode::End => PreviewNavigationAction::Bottom.into(),
+            KeyCode::Char('/') => WorkSpaceAction::PreviewSearch(ConfirmAction::Request(())).into(),
+            KeyCode::Char('n') => WorkSpaceAction::PreviewSearchNext(false).into(),
+            KeyCode::Char('N') => WorkSpaceAction::PreviewSearchNext(true).into(),
+            KeyCode::Esc => NavigationAction::FocusTree.into(),
+            _ => return None,
+        },
+        _ => return None,
+    };
+    Some(action)
+}
+
+#[derive(Debug)]
+pub struct WorkSpaceState {
+    list_state: ListState,
+    /// Selection of the flat view, following the tree's.
+    flat_state: ListState,
+    preview_state: PreviewState,
+    pinned_state: PreviewState,
+    /// Pane the keys go to.
+    focus: Focus,
+    /// Where the tree and the previews were last drawn, for placing mouse events.
+    tree_area: Rect,
+    preview_area: Option<Rect>,
+    pinned_area: Option<Rect>,
+    /// Time and row of the last single click, to tell double clicks.
+    last_click: Option<(Instant, usize)>,
+    /// Whether the border between the tree and the preview is being dragged.
+    resizing: bool,
+    /// Panes as last rendered, keyed by what they show.
+    tree_cache: PaneCache<(Block<'static>, u64, usize), ListState>,
+    preview_cache: PaneCache<u64, PreviewState>,
+    pinned_cache: PaneCache<u64, PreviewState>,
+}
+
+impl Default for WorkSpaceState {
+    fn default() -> Self {
+        let mut list_state = ListState::default();
+        list_state.select(Some(0));
+        Self {
+            list_state,
+            flat_state: ListState::default(),
+            preview_state: PreviewState::default(),
+            pinned_state: PreviewState::default(),
+            focus: Focus::Tree,
+            tree_area: Rect::default(),
+            preview_area: None,
+            pinned_area: None,
+            last_click: None,
+            resizing: false,
+            tree_cache: PaneCache::default(),
+            preview_cache: PaneCache::default(),
+            pinned_cache: PaneCache::default(),
+        }
+    }
+}
+
+impl WorkSpaceState {
+    /// State of the preview that scrolls, the pinned one when it has the focus.
+    fn focused_preview_state(&mut self) -> &mut PreviewState {
+        if self.focus == Focus::Pinned {
+            &mut self.pinned_state
+        } else {
+            &mut self.preview_state
+        }
+    }
+}
+
+impl StatefulWidget for &WorkSpace {
+    type State = WorkSpaceState;
+
+    fn render(self, full_area: Rect, buf: &mut Buffer, state: &mut Self::State) {
+        let [area, status_area] =
+            Layout::vertical([Constraint::Fill(1), Constraint::Length(1)]).areas(full_area);
+        self.status_bar(state).render(status_area, buf);
+
+        if self.preview.is_some() || self.pinned.is_some() {
+            let constraints = [
+                Constraint::Percentage(100 - self.preview_pct),
+                Constraint::Fill(self.preview_pct),
+            ];
+            let layout = match self.preview_layout {
+                PreviewLayout::Beside => Layout::horizontal(constraints),
+                PreviewLayout::Below => Layout::vertical(constraints),
+            };
+            let [tree_area, side_area] = if self.is_preview_fullscreen {
+                [Rect::default(), area]
+            } else {
+                layout.areas(area)
+            };
+            let (preview_area, pinned_area) = match (&self.preview, &self.pinned) {
+                (Some(_), Some(_)) => {
+                    let [preview_area, pinned_area] =
+                        Layout::horizontal([Constraint::Fill(1); 2]).areas(side_area);
+                    (Some(preview_area), Some(pinned_area))
+                }
+                (Some(_), None) => (Some(side_area), None),
+                (None, _) => (None, Some(side_area)),
+            };
+
+            // Left unrendered, the tree keeps its scroll position for when it comes back.
+            if !self.is_preview_fullscreen {
+                self.render_tree(tree_area, buf, state);
+            }
+            state
+                .preview_state
+                .set_focused(state.focus == Focus::Preview);
+            state.pinned_state.set_focused(state.focus == Focus::Pinned);
+            if let Some((table, preview_area)) = self.table.as_ref().zip(preview_area) {
+                state.preview_cache.render(
+                    table.version(),
+                    preview_area,
+                    buf,
+                    &mut state.preview_state,
+                    |area, buf, state| table.render(area, buf, state),
+                );
+            } else if let Some((preview, preview_area)) = self.preview.as_ref().zip(preview_area) {
+                state.preview_cache.render(
+                    preview.version(),
+                    preview_area,
+                    buf,
+                    &mut state.preview_state,
+                    |area, buf, state| preview.render(area, buf, state),
+                );
+            }
+            if let Some((pinned, pinned_area)) = self.pinned.as_ref().zip(pinned_area) {
+                state.pinned_cache.render(
+                    pinned.version(),
+                    pinned_area,
+                    buf,
+                    &mut state.pinned_state,
+                    |area, buf, state| pinned.render(area, buf, state),
+                );
+            }
+            state.tree_area = tree_area;
+            state.preview_area = preview_area;
+            state.pinned_area = pinned_area;
+        } else {
+            self.render_tree(area, buf, state);
+            state.tree_area = area;
+            state.preview_area = None;
+            state.pinned_area = None;
+        }
+
+        for dialog in &self.dialogs {
+            dialog.render_ref(full_area, buf);
+        }
+
+        if let Some(loading) = &self.loading {
+            loading.render(full_area, buf);
+        }
+    }
+}
+
+impl WorkSpace {
+    fn status_bar(&self, state: &WorkSpaceState) -> StatusBar {
+        let index = state.list_state.selected().unwrap_or_default();
+        let selector = self.work_tree_root.selector(index);
+        let node = self.file_root.subtree(&selector).ok();
+        StatusBar {
+            path: to_path(&self.file_root, &selector),
+            type_name: match node {
+                Some(node) if node.looks_like_json() => "json string",
+                Some(node) => node.type_name(),
+                None => "",
+            },
+            n_bytes: node.map_or(0, |node| node.as_meta().n_bytes_with(self.config.indent)),
+            is_edited: self.is_edited,
+        }
+    }
+}
+
+impl WorkSpace {
+    fn render_tree(&self, area: Rect, buf: &mut Buffer, state: &mut WorkSpaceState) {
+        let mut title = String::from(if self.flat.is_some() { "Paths" } else { "Tree" });
+        if let Some(filter) = &self.filter {
+            title += &format!(", filtered by {filter}");
+        }
+        if let Some(compare) = &self.compare {
+            title += &format!(", compared with {}", compare.name);
+        }
+        let mut block = Block::bordered().title(title);
+        if let Some(message) = self.messages.back().filter(|_| self.show_message) {
+            block = block.title_bottom(Line::from(message.clone()).left_aligned());
+        }
+        if let Some(budget) = self.budget_warning() {
+            block = block.title_bottom(budget.right_aligned());
+        }
+
+        let (list, list_version, list_state, len) = match &self.flat {
+            Some(flat) => (
+                &flat.list,
+                flat.list_version,
+                &mut state.flat_state,
+                flat.selectors.len(),
+            ),
+            None => (
+                &self.list,
+                self.list_version,
+                &mut state.list_state,
+                self.work_tree_root.len(),
+            ),
+        };
+        let key = (block.clone(), list_version, len);
+        state
+            .tree_cache
+            .render(key, area, buf, list_state, |area, buf, list_state| {
+                let inner_area = block.inner(area);
+                block.render(area, buf);
+                StatefulWidget::render(list, inner_area, buf, list_state);
+
+                let scrollbar = scrollbar(ScrollbarOrientation::VerticalRight);
+                StatefulWidget::render(
+                    scrollbar,
+                    inner_area,
+                    buf,
+                    &mut ScrollbarState::new(len)
+                        .position(list_state.selected().unwrap_or_default()),
+                );
+            });
+    }
+}
+
+impl WorkSpace {
+    /// Warns when the document has grown past `max_document_size`.
+    fn budget_warning(&self) -> Option<Line<'static>> {
+        let budget = self.config.max_document_size?.as_u64() as usize;
+        let n_bytes = self.file_root.as_meta().n_bytes_with(self.config.indent);
+        if n_bytes <= budget {
+            return None;
+        }
+
+        Some(Line::from(vec![
+            Span::styled(
+                format!(
+                    "Over budget: {} / {} ",
+                    format::bytes(n_bytes),
+                    format::bytes(budget)
+                ),
+                Style::new().fg(RED.c500),
+            ),
+            Span::styled(
+                format!("(+{})", format::bytes(n_bytes - budget)),
+                Style::new()
+                    .fg(RED.c500)
+                    .add_modifier(Modifier::BOLD | Modifier::REVERSED),
+            ),
+        ]))
+    }
+}
+
+fn new_list(
+    work_tree_node: &WorkTreeNode,
+    file_root: &Node,
+    other_root: Option<&Node>,
+    config: &Config,
+) -> List<'static> {
+    // Counterparts in `other_root` of the current row and its ancestors.
+    let mut others: Vec<Option<&Node>> = Vec::new();
+    let items = work_tree_node
+        .as_tree_rows(Some(file_root), config.tree_guides)
+        .map(|row| {
+            let mut line = Line::default();
+            if let Some(other_root) = other_root {
+                others.truncate(row.depth);
+                let other = match others.last() {
+                    Some(parent) => parent.and_then(|parent| parent.child(row.name)),
+                    None => Some(other_root),
+                };
+                others.push(other);
+                line.push_span(match row.node {
+                    Some(node) => compare_mark(node, other),
+                    None => Span::from("  "),
+                });
+            }
+            line.push_span(row.text);
+            if let Some(node) = row.node {
+                if config.value_hints {
+                    line.push_span(value_hint(node, config.value_hint_width));
+                }
+                if config.type_badges {
+                    line.extend(type_badge(node, config.indent));
+                }
+            }
+            let item = ListItem::new(line);
+            match row.node {
+                Some(node) if config.heat_map => {
+                    item.style(heat_style(node.as_meta().n_bytes, row.max_sibling_bytes))
+                }
+                _ => item,
+            }
+        });
+
+    styled_list(items)
+}
+
+fn styled_list<'a>(items: impl IntoIterator<Item = ListItem<'a>>) -> List<'a> {
+    List::new(items)
+        .highlight_style(Style::new().bg(SLATE.c800).add_modifier(Modifier::BOLD))
+        .highlight_symbol("> ")
+        .highlight_spacing(HighlightSpacing::Always)
+        .scroll_padding(1)
+}
+
+/// Marks a row `+` when `other` lacks it, `-` when members of `other` are missing from it, and `~`
+/// when it differs otherwise.
+fn compare_mark(node: &Node, other: Option<&Node>) -> Span<'static> {
+    let Some(other) = other else {
+        return Span::styled("+ ", Style::new().fg(GREEN.c500));
+    };
+    if node.same_value(other) {
+        return Span::from("  ");
+    }
+    let lost_members = match (node.as_object(), other.as_object()) {
+        (Some(members), Some(other_members)) => {
+            other_members.keys().any(|key| !members.contains_key(key))
+        }
+        _ => node
+            .as_array()
+            .zip(other.as_array())
+            .is_some_and(|(items, other_items)| items.len() < other_items.len()),
+    };
+    if lost_members {
+        Span::styled("- ", Style::new().fg(RED.c500))
+    } else {
+        Span::styled("~ ", Style::new().fg(YELLOW.c300))
+    }
+}
+
+/// Describes what a delete removes, e.g. `Delete object 'users' with 1,204 children, 3.20 MiB?`.
+fn delete_summary<T: Deref<Target = str>>(selector: &[T], node: &Node, indent: Indent) -> String {
+    let key = selector.last().map_or("root", |key| &**key);
+    let size = format::bytes(node.as_meta().n_bytes_with(indent));
+    match node.as_meta().kind {
+        NodeKind::Object | NodeKind::Array => format!(
+            "Delete {} '{key}' with {} children, {size}?",
+            node.type_name(),
+            format::count(node.children().count())
+        ),
+        NodeKind::Terminal => format!("Delete {} '{key}', {size}?", node.type_name()),
+    }
+}
+
+/// Pointer to the node, or `root` for the document itself, whose pointer is empty.
+fn node_name<T: Deref<Target = str>>(selector: &[T]) -> String {
+    if selector.is_empty() {
+        String::from("root")
+    } else {
+        to_pointer(selector)
+    }
+}
+
+/// Substitutes `key` into a split file name pattern, keeping it within a single path segment.
+fn split_file_name(pattern: &str, key: &str) -> String {
+    let key = key
+        .chars()
+        .map(|c| match c {
+            '/' | '\\' | '\0' => '_',
+            c => c,
+        })
+        .collect::<String>();
+    let key = match key.as_str() {
+        "" | "." | ".." => format!("_{key}"),
+        _ => key,
+    };
+    pattern.replace(SPLIT_KEY_PLACEHOLDER, &key)
+}
+
+/// Tints a row by its size relative to the largest of its siblings.
+/// Summary shown after a key, such as `: "prod-cluster"`, `: [12]` or `: {…5}`, cut to `width`
+/// characters.
+fn value_hint(node: &Node, width: usize) -> Span<'static> {
+    let hint = if let Some(items) = node.as_array() {
+        format!("[{}]", items.len())
+    } else if let Some(members) = node.as_object() {
+        format!("{{…{}}}", members.len())
+    } else if let Some(value) = node.as_str() {
+        // Long strings are cut before serializing them.
+        Node::string(value.chars().take(width).collect())
+            .to_string_compact()
+            .expect("broken internal representation")
+    } else {
+        node.to_string_compact()
+            .expect("broken internal representation")
+    };
+    let hint = if hint.chars().count() > width {
+        let mut hint = hint
+            .chars()
+            .take(width.saturating_sub(1))
+            .collect::<String>();
+        hint.push('…');
+        hint
+    } else {
+        hint
+    };
+    Span::styled(format!(": {hint}"), Style::new().fg(SLATE.c400))
+}
+
+/// Badge naming the kind of a node, such as `{}` or `str`, followed by its number of children and
+/// its size.
+fn type_badge(node: &Node, indent: Indent) -> [Span<'static>; 3] {
+    let (badge, color) = match node.type_name() {
+        "object" => ("{}", BLUE.c400),
+        "array" => ("[]", VIOLET.c400),
+        "string" => ("str", GREEN.c400),
+        "number" => ("num", ORANGE.c400),
+        "boolean" => ("bool", CYAN.c400),
+        _ => ("null", SLATE.c500),
+    };
+    let n_bytes = format::bytes(node.as_meta().n_bytes_with(indent));
+    let n_children = node
+        .as_array()
+        .map(<[Node]>::len)
+        .or_else(|| node.as_object().map(|members| members.len()));
+    let detail = match n_children {
+        Some(n_children) => format!("{n_children} · {n_bytes}"),
+        None => n_bytes,
+    };
+    [
+        Span::from(" "),
+        Span::styled(badge, Style::new().fg(color).add_modifier(Modifier::BOLD)),
+        Span::styled(format!(" {detail}"), Style::new().fg(SLATE.c400)),
+    ]
+}
+
+fn heat_style(n_bytes: usize, max_sibling_bytes: usize) -> Style {
+    let ratio = n_bytes as f64 / max_sibling_bytes.max(1) as f64;
+    match ratio {
+        0.75.. => Style::new().fg(RED.c500),
+        0.5.. => Style::new().fg(ORANGE.c400),
+        0.25.. => Style::new().fg(YELLOW.c300),
+        _ => Style::new(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use byte_unit::Byte;
+    use crossterm::event::{KeyEvent, KeyEventKind, KeyEventState, KeyModifiers};
+    use indexmap::IndexMap;
+    use insta::assert_snapshot;
+
+    use crate::{
+        app::{component::test_render::stateful_render_to_string, tool::Tool},
+        container::node::NodeKind,
+        fixtures::SAMPLE_JSON,
+    };
+
+    use super::*;
+
+    #[test]
+    fn event_handler_ignore_key_release_test() {
+        let json = String::from("123");
+        let worktree = WorkSpace::new(Node::load(json.as_bytes()).unwrap(), Config::default());
+
+        assert_event_to_action(
+            &worktree,
+            Event::Key(KeyEvent {
+                code: KeyCode::Up,
+                modifiers: KeyModifiers::empty(),
+                kind: KeyEventKind::Release,
+                state: KeyEventState::NONE,
+            }),
+            vec![],
+        );
+    }
+
+    #[test]
+    fn event_handler_navigation_test() {
+        let json = String::from("123");
+        let worktree = WorkSpace::new(Node::load(json.as_bytes()).unwrap(), Config::default());
+
+        for (key, action) in [
+            ((KeyCode::Up, KeyModifiers::NONE), NavigationAction::Up(1)),
+            (
+                (KeyCode::Char('k'), KeyModifiers::NONE),
+                NavigationAction::Up(1),
+            ),
+            (
+                (KeyCode::Char('u'), KeyModifiers::CONTROL),
+                NavigationAction::Up(10),
+            ),
+            (
+                (KeyCode::Down, KeyModifiers::NONE),
+                NavigationAction::Down(1),
+            ),
+            (
+                (KeyCode::Char('j'), KeyModifiers::NONE),
+                NavigationAction::Down(1),
+            ),
+            (
+                (KeyCode::Char('d'), KeyModifiers::CONTROL),
+                NavigationAction::Down(10),
+            ),
+            (
+                (KeyCode::Enter, KeyModifiers::NONE),
+                NavigationAction::Expand,
+            ),
+            (
+                (KeyCode::Char('l'), KeyModifiers::NONE),
+                NavigationAction::Expand,
+            ),
+            (
+                (KeyCode::Char(' '), KeyModifiers::NONE),
+                NavigationAction::Expand,
+            ),
+            (
+                (KeyCode::Char('h'), KeyModifiers::NONE),
+                NavigationAction::Close,
+            ),
+            (
+                (KeyCode::Char('p'), KeyModifiers::NONE),
+                NavigationAction::TogglePreview,
+            ),
+            (
+                (KeyCode::Char('K'), KeyModifiers::NONE),
+                NavigationAction::PreviewNavigation(PreviewNavigationAction::Up(1)),
+            ),
+            (
+                (KeyCode::Char('J'), KeyModifiers::NONE),
+                NavigationAction::PreviewNavigation(PreviewNavigationAction::Down(1)),
+            ),
+            (
+                (KeyCode::Char('U'), KeyModifiers::CONTROL),
+                NavigationAction::PreviewNavigation(PreviewNavigationAction::Up(5)),
+            ),
+            (
+                (KeyCode::Char('D'), KeyModifiers::CONTROL),
+                NavigationAction::PreviewNavigation(PreviewNavigationAction::Down(5)),
+            ),
+            (
+                (KeyCode::Char('H'), KeyModifiers::NONE),
+                NavigationAction::PreviewNavigation(PreviewNavigationAction::Left),
+            ),
+            (
+                (KeyCode::Char('L'), KeyModifiers::NONE),
+                NavigationAction::PreviewNavigation(PreviewNavigationAction::Right),
+            ),
+            (
+                (KeyCode::Left, KeyModifiers::CONTROL),
+                NavigationAction::PreviewWindowResize(Op::Add(1)),
+            ),
+            (
+                (KeyCode::Right, KeyModifiers::CONTROL),
+                NavigationAction::PreviewWindowResize(Op::Sub(1)),
+            ),
+        ] {
+            assert_key_event_to_action(&worktree, key, vec![action.into()]);
+        }
+    }
+
+    #[test]
+    fn event_handler_fileops_test() {
+        let json = String::from("123");
+        let worktree = WorkSpace::new(Node::load(json.as_bytes()).unwrap(), Config::default());
+
+        for (key, action) in [
+            (
+                (KeyCode::Char('q'), KeyModifiers::NONE),
+                Action::Exit(ConfirmAction::Request(())),
+            ),
+            (
+                (KeyCode::Char('e'), KeyModifiers::NONE),
+                WorkSpaceAction::Edit.into(),
+            ),
+            (
+                (KeyCode::Char('w'), KeyModifiers::NONE),
+                WorkSpaceAction::Save(ConfirmAction::Request(())).into(),
+            ),
+        ] {
+            assert_key_event_to_action(&worktree, key, vec![action]);
+        }
+    }
+
+    #[test]
+    fn event_handler_ignore_on_confirm_dialog() {
+        let json = String::from("123");
+        let mut worktree = WorkSpace::new(Node::load(json.as_bytes()).unwrap(), Config::default());
+        let mut state = WorkSpaceState::default();
+        worktree.test_action(
+            &mut state,
+            WorkSpaceAction::Save(ConfirmAction::Request(())),
+        );
+
+        for key in [
+            (KeyCode::Char('q'), KeyModifiers::NONE),
+            (KeyCode::Char('e'), KeyModifiers::NONE),
+            (KeyCode::Char('w'), KeyModifiers::NONE),
+            (KeyCode::Char('k'), KeyModifiers::NONE),
+            (KeyCode::Up, KeyModifiers::NONE),
+        ] {
+            assert_key_event_to_action(&worktree, key, Vec::new());
+        }
+
+        worktree.test_action(
+            &mut state,
+            WorkSpaceAction::Save(ConfirmAction::Confirm(false)),
+        );
+        assert_key_event_to_action(
+            &worktree,
+            (KeyCode::Up, KeyModifiers::NONE),
+            vec![NavigationAction::Up(1).into()],
+        );
+    }
+
+    #[test]
+    fn handle_navigation_action() {
+        let json = String::from(r#"{"key": "string", "values": [1, 2, 3]}"#);
+        let mut worktree = WorkSpace::new(Node::load(json.as_bytes()).unwrap(), Config::default());
+        let mut state = WorkSpaceState::default();
+        worktree.test_action(&mut state, NavigationAction::Expand.into());
+        assert_snapshot!(stateful_render_to_string(&worktree, &mut state));
+
+        worktree.test_action(&mut state, NavigationAction::Down(1).into());
+        worktree.test_action(&mut state, NavigationAction::Expand.into());
+        assert_snapshot!(stateful_render_to_string(&worktree, &mut state));
+
+        for _ in 0..3 {
+            worktree.test_action(&mut state, NavigationAction::Up(1).into());
+        }
+        worktree.test_action(&mut state, NavigationAction::Close.into());
+        assert_snapshot!(stateful_render_to_string(&worktree, &mut state));
+    }
+
+    #[test]
+    fn write_selected_test() {
+        let json = String::from(r#"{"key": "string", "values": [1, 2, 3]}"#);
+        let mut worktree = WorkSpace::new(Node::load(json.as_bytes()).unwrap(), Config::default());
+        let mut state = WorkSpaceState::default();
+        worktree.test_action(&mut state, NavigationAction::Expand.into());
+
+        worktree.test_action(&mut state, NavigationAction::Down(1).into());
+        worktree.test_action(&mut state, NavigationAction::Expand.into());
+        worktree.test_action(&mut state, NavigationAction::Up(1).into());
+
+        let mut buffer = Vec::new();
+        worktree.write_selected(&state, &mut buffer).unwrap();
+        assert_eq!(String::from_utf8(buffer).unwrap(), "[\n  1,\n  2,\n  3\n]",)
+    }
+
+    #[test]
+    fn load_selected_test() {
+        let json = String::from(r#"{"key": "string", "values": [1, 2, 3]}"#);
+        let mut worktree = WorkSpace::new(Node::load(json.as_bytes()).unwrap(), Config::default());
+        let mut state = WorkSpaceState::default();
+        worktree.test_action(&mut state, NavigationAction::Expand.into());
+
+        worktree.test_action(&mut state, NavigationAction::Down(1).into());
+        worktree.test_action(&mut state, NavigationAction::Expand.into());
+        worktree.test_action(&mut state, NavigationAction::Up(1).into());
+
+        worktree.test_action(
+            &mut state,
+            WorkSpaceAction::Load {
+                node: Node::load("[{}, 5]".as_bytes()).unwrap(),
+                is_edit: true,
+                repaired: Repairs::default(),
+            },
+        );
+
+        assert_eq!(
+            worktree.file_root().to_string_pretty().unwrap(),
+            "{\n  \"key\": \"string\",\n  \"values\": [\n    {},\n    5\n  ]\n}"
+        );
+    }
+
+    #[test]
+    fn followed_test() {
+        let mut worktree = WorkSpace::new(Node::from_array(Vec::new()), Config::default());
+        let mut state = WorkSpaceState::default();
+        let followed = |records: &[&str]| WorkSpaceAction::Followed {
+            path: String::from("log.ndjson"),
+            reader: RecordReader::default(),
+            records: records
+                .iter()
+                .map(|record| Node::load(record.as_bytes()).unwrap())
+                .collect(),
+        };
+
+        assert_eq!(
+            worktree.test_action(&mut state, followed(&[r#"{"a": 1}"#, "2"])),
+            vec![
+                JobAction::Follow {
+                    path: String::from("log.ndjson"),
+                    reader: RecordReader::default(),
+                }
+                .into()
+            ]
+        );
+        worktree.test_action(&mut state, NavigationAction::Expand.into());
+        worktree.test_action(&mut state, NavigationAction::Expand.into());
+        worktree.test_action(&mut state, followed(&["3", "[4]"]));
+        assert_eq!(
+            worktree.file_root().to_string_compact().unwrap(),
+            r#"[{"a":1},2,3,[4]]"#
+        );
+        assert_snapshot!(stateful_render_to_string(&worktree, &mut state));
+
+        let mut worktree = WorkSpace::new(Node::null(), Config::default());
+        assert!(
+            worktree
+                .test_action(&mut state, followed(&["1"]))
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn render_read_only_test() {
+        let mut worktree = WorkSpace::new(Node::null(), Config::default());
+        let mut state = WorkSpaceState::default();
+
+        worktree.test_action(
+            &mut state,
+            WorkSpaceAction::ReadOnly(ConfirmAction::Request(String::from(
+                "Another jedit (pid 42) has unsaved changes to data.json.",
+            ))),
+        );
+        assert_snapshot!(stateful_render_to_string(&worktree, &mut state));
+        assert_eq!(
+            worktree.test_action(
+                &mut state,
+                WorkSpaceAction::ReadOnly(ConfirmAction::Confirm(true))
+            ),
+            vec![JobAction::ReadOnly(true).into()]
+        );
+        assert!(worktree.dialogs.is_empty());
+    }
+
+    #[test]
+    fn page_test() {
+        let json = r#"{"a": [1, 2]}"#;
+        let mut worktree = WorkSpace::new(Node::load(json.as_bytes()).unwrap(), Config::default());
+        let mut state = WorkSpaceState::default();
+        worktree.test_action(&mut state, NavigationAction::Expand.into());
+        assert_eq!(
+            worktree.test_action(&mut state, WorkSpaceAction::Page),
+            vec![
+                JobAction::Page {
+                    content: String::from("[\n  1,\n  2\n]"),
+                    command: None,
+                }
+                .into()
+            ]
+        );
+
+        let mut worktree = WorkSpace::new(
+            Node::load(json.as_bytes()).unwrap(),
+            Config::default().with_no_shell(),
+        );
+        let mut state = WorkSpaceState::default();
+        assert!(
+            worktree
+                .test_action(&mut state, WorkSpaceAction::Page)
+                .is_empty()
+        );
+        assert_eq!(
+            worktree.messages.back().map(String::as_str),
+            Some("The pager is disabled by no_shell")
+        );
+    }
+
+    #[test]
+    fn page_tool_test() {
+        let json = r#"{"certificates": {"root": "-----BEGIN CERTIFICATE-----"}, "n": 1}"#;
+        let mut worktree = WorkSpace::new(
+            Node::load(json.as_bytes()).unwrap(),
+            Config::default()
+                .without_inline_scalar_edits()
+                .with_tool(Tool {
+                    path: Some(String::from("/certificates/*")),
+                    value: None,
+                    command: String::from("openssl x509 -text"),
+                    edit: false,
+                }),
+        );
+        let mut state = WorkSpaceState::default();
+        worktree.test_action(&mut state, NavigationAction::Expand.into());
+        worktree.test_action(&mut state, NavigationAction::Expand.into());
+        assert_eq!(
+            worktree.test_action(&mut state, WorkSpaceAction::Page),
+            vec![
+                JobAction::Page {
+                    content: String::from("-----BEGIN CERTIFICATE-----"),
+                    command: Some(String::from("openssl x509 -text")),
+                }
+                .into()
+            ]
+        );
+        assert_eq!(
+            worktree.test_action(&mut state, WorkSpaceAction::Edit),
+            vec![JobAction::Edit(EditJobAction::Init(None)).into()]
+        );
+    }
+
+    #[test]
+    fn edit_value_test() {
+        let mut worktree = WorkSpace::new(
+            Node::load(r#"{"a": "x", "b": 3, "c": "line\nbreak", "d": [1]}"#.as_bytes()).unwrap(),
+            Config::default(),
+        );
+        let mut state = WorkSpaceState::default();
+
+        worktree.select_path(&mut state, &["a"]);
+        assert!(
+            worktree
+                .test_action(&mut state, WorkSpaceAction::Edit)
+                .is_empty()
+        );
+        assert_snapshot!(stateful_render_to_string(&worktree, &mut state));
+        worktree.test_action_chain(
+            &mut state,
+            WorkSpaceAction::InlineEdit(ConfirmAction::Confirm(Some(String::from("\"y\" 1")))),
+        );
+        assert_eq!(
+            worktree.selected_node(&state),
+            Some(&Node::string(String::from("\"y\" 1")))
+        );
+
+        worktree.select_path(&mut state, &["b"]);
+        worktree.test_action(&mut state, WorkSpaceAction::Edit);
+        worktree.test_action(
+            &mut state,
+            WorkSpaceAction::InlineEdit(ConfirmAction::Confirm(Some(String::from("four")))),
+        );
+        assert_eq!(worktree.dialogs.len(), 2);
+        worktree.test_action(&mut state, WorkSpaceAction::ErrorConfirmed);
+        worktree.test_action_chain(
+            &mut state,
+            WorkSpaceAction::InlineEdit(ConfirmAction::Confirm(Some(String::from("4")))),
+        );
+        assert!(worktree.dialogs.is_empty());
+        assert_eq!(
+            worktree.file_root().to_string_compact().unwrap(),
+            r#"{"a":"\"y\" 1","b":4,"c":"line\nbreak","d":[1]}"#
+        );
+
+        for selector in [["c"], ["d"]] {
+            worktree.select_path(&mut state, &selector);
+            assert_eq!(
+                worktree.test_action(&mut state, WorkSpaceAction::Edit),
+                vec![JobAction::Edit(EditJobAction::Init(None)).into()]
+            );
+        }
+    }
+
+    #[test]
+    fn set_type_test() {
+        let mut worktree = WorkSpace::new(
+            Node::load(r#"{"a": "42", "b": "x", "c": [1]}"#.as_bytes()).unwrap(),
+            Config::default(),
+        );
+        let mut state = WorkSpaceState::default();
+
+        worktree.select_path(&mut state, &["a"]);
+        assert_key_event_to_action(
+            &worktree,
+            (KeyCode::Char('t'), KeyModifiers::NONE),
+            vec![WorkSpaceAction::SetType(ConfirmAction::Request(())).into()],
+        );
+        worktree.test_action(
+            &mut state,
+            WorkSpaceAction::SetType(ConfirmAction::Request(())),
+        );
+        assert_snapshot!(stateful_render_to_string(&worktree, &mut state));
+        worktree.test_action_chain(
+            &mut state,
+            WorkSpaceAction::SetType(ConfirmAction::Confirm(Some(1))),
+        );
+        assert!(worktree.dialogs.is_empty());
+        assert_eq!(
+            worktree.file_root().to_string_compact().unwrap(),
+            r#"{"a":42,"b":"x","c":[1]}"#
+        );
+
+        worktree.select_path(&mut state, &["b"]);
+        worktree.test_action(
+            &mut state,
+            WorkSpaceAction::SetType(ConfirmAction::Request(())),
+        );
+        worktree.test_action(
+            &mut state,
+            WorkSpaceAction::SetType(ConfirmAction::Confirm(Some(2))),
+        );
+        assert_snapshot!(
+            "set_type_error_test",
+            stateful_render_to_string(&worktree, &mut state)
+        );
+        worktree.test_action(&mut state, WorkSpaceAction::ErrorConfirmed);
+
+        worktree.select_path(&mut state, &["c"]);
+        worktree.test_action(
+            &mut state,
+            WorkSpaceAction::SetType(ConfirmAction::Request(())),
+        );
+        assert_eq!(worktree.dialogs.len(), 1);
+        worktree.test_action(&mut state, WorkSpaceAction::ErrorConfirmed);
+        assert_eq!(
+            worktree.file_root().to_string_compact().unwrap(),
+            r#"{"a":42,"b":"x","c":[1]}"#
+        );
+    }
+
+    #[test]
+    fn embedded_json_test() {
+        let mut worktree = WorkSpace::new(
+            Node::load(r#"{"a": "{\"b\": [1, 2]}", "c": "x"}"#.as_bytes()).unwrap(),
+            Config::default(),
+        );
+        let mut state = WorkSpaceState::default();
+
+        worktree.select_path(&mut state, &["a"]);
+        assert_key_event_to_action(
+            &worktree,
+            (KeyCode::Char('{'), KeyModifiers::NONE),
+            vec![WorkSpaceAction::ExpandJson.into()],
+        );
+        assert_snapshot!(stateful_render_to_string(&worktree, &mut state));
+        worktree.test_action_chain(&mut state, WorkSpaceAction::ExpandJson);
+        assert_eq!(
+            worktree.file_root().to_string_compact().unwrap(),
+            r#"{"a":{"b":[1,2]},"c":"x"}"#
+        );
+
+        assert_key_event_to_action(
+            &worktree,
+            (KeyCode::Char('}'), KeyModifiers::NONE),
+            vec![WorkSpaceAction::Stringify.into()],
+        );
+        worktree.test_action_chain(&mut state, WorkSpaceAction::Stringify);
+        assert_eq!(
+            worktree.file_root().to_string_compact().unwrap(),
+            r#"{"a":"{\"b\":[1,2]}","c":"x"}"#
+        );
+
+        worktree.select_path(&mut state, &["c"]);
+        worktree.test_action(&mut state, WorkSpaceAction::ExpandJson);
+        assert_eq!(worktree.dialogs.len(), 1);
+        worktree.test_action(&mut state, WorkSpaceAction::ErrorConfirmed);
+        assert_eq!(
+            worktree.file_root().to_string_compact().unwrap(),
+            r#"{"a":"{\"b\":[1,2]}","c":"x"}"#
+        );
+    }
+
+    #[test]
+    fn sort_test() {
+        let mut worktree = WorkSpace::new(
+            Node::load(r#"{"z": {"b": 1, "a": 2}, "arr": [3, 1, 2], "m": [{"x": 1}]}"#.as_bytes())
+                .unwrap(),
+            Config::default(),
+        );
+        let mut state = WorkSpaceState::default();
+
+        assert_key_event_to_action(
+            &worktree,
+            (KeyCode::Char('s'), KeyModifiers::NONE),
+            vec![WorkSpaceAction::Sort(ConfirmAction::Request(())).into()],
+        );
+
+        worktree.select_path(&mut state, &["z", "b"]);
+        worktree.select_path(&mut state, &["arr", "0"]);
+        worktree.select_path(&mut state, &["z"]);
+        worktree.test_action(
+            &mut state,
+            WorkSpaceAction::Sort(ConfirmAction::Request(())),
+        );
+        assert_snapshot!(stateful_render_to_string(&worktree, &mut state));
+        worktree.test_action(
+            &mut state,
+            WorkSpaceAction::Sort(ConfirmAction::Confirm(Some(0))),
+        );
+        worktree.select_path(&mut state, &["arr"]);
+        worktree.test_action(
+            &mut state,
+            WorkSpaceAction::Sort(ConfirmAction::Request(())),
+        );
+        worktree.test_action(
+            &mut state,
+            WorkSpaceAction::Sort(ConfirmAction::Confirm(Some(1))),
+        );
+        assert!(worktree.dialogs.is_empty());
+        assert_eq!(
+            worktree.file_root().to_string_compact().unwrap(),
+            r#"{"z":{"a":2,"b":1},"arr":[3,2,1],"m":[{"x":1}]}"#
+        );
+        assert_snapshot!(
+            "sort_done_test",
+            stateful_render_to_string(&worktree, &mut state)
+        );
+
+        worktree.select_path(&mut state, &["m"]);
+        worktree.test_action(
+            &mut state,
+            WorkSpaceAction::Sort(ConfirmAction::Request(())),
+        );
+        worktree.test_action(
+            &mut state,
+            WorkSpaceAction::Sort(ConfirmAction::Confirm(Some(0))),
+        );
+        assert_eq!(worktree.dialogs.len(), 1);
+        worktree.test_action(&mut state, WorkSpaceAction::ErrorConfirmed);
+
+        worktree.select_path(&mut state, &["z", "a"]);
+        worktree.test_action(
+            &mut state,
+            WorkSpaceAction::Sort(ConfirmAction::Request(())),
+        );
+        assert_eq!(worktree.dialogs.len(), 1);
+    }
+
+    #[test]
+    fn filter_test() {
+        let mut worktree = WorkSpace::new(
+            Node::load(SAMPLE_JSON.as_bytes()).unwrap(),
+            Config::default(),
+        );
+        let mut state = WorkSpaceState::default();
+
+        assert_key_event_to_action(
+            &worktree,
+            (KeyCode::Char('f'), KeyModifiers::NONE),
+            vec![WorkSpaceAction::Filter(ConfirmAction::Request(())).into()],
+        );
+        worktree.test_action(
+            &mut state,
+            WorkSpaceAction::Filter(ConfirmAction::Request(())),
+        );
+        worktree.test_action(
+            &mut state,
+            WorkSpaceAction::Filter(ConfirmAction::Confirm(Some(String::from("taglib")))),
+        );
+        assert_snapshot!(stateful_render_to_string(&worktree, &mut state));
+
+        worktree.test_action(
+            &mut state,
+            WorkSpaceAction::Delete(ConfirmAction::Request(UNNAMED_REGISTER)),
+        );
+        assert_eq!(worktree.dialogs.len(), 1);
+        worktree.test_action(&mut state, WorkSpaceAction::ErrorConfirmed);
+
+        worktree.test_action(
+            &mut state,
+            WorkSpaceAction::Filter(ConfirmAction::Confirm(Some(String::from("nothing here")))),
+        );
+        assert_eq!(
+            worktree.messages.back().unwrap(),
+            "No match for nothing here"
+        );
+        assert_eq!(worktree.filter.as_deref(), Some("taglib"));
+
+        worktree.test_action(
+            &mut state,
+            WorkSpaceAction::Filter(ConfirmAction::Request(())),
+        );
+        worktree.test_action(
+            &mut state,
+            WorkSpaceAction::Filter(ConfirmAction::Confirm(Some(String::new()))),
+        );
+        assert_eq!(worktree.filter, None);
+        assert_snapshot!(
+            "filter_cleared_test",
+            stateful_render_to_string(&worktree, &mut state)
+        );
+    }
+
+    #[test]
+    fn inline_edit_test() {
+        let mut worktree = WorkSpace::new(
+            Node::load(r#"{"a": [1, 2], "b": 3}"#.as_bytes()).unwrap(),
+            Config::default().with_no_shell(),
+        );
+        let mut state = WorkSpaceState::default();
+        worktree.test_action(&mut state, NavigationAction::Expand.into());
+
+        assert!(
+            worktree
+                .test_action(&mut state, WorkSpaceAction::Edit)
+                .is_empty()
+        );
+        assert_snapshot!(
+            "render_inline_edit_test",
+            stateful_render_to_string(&worktree, &mut state)
+        );
+
+        let actions = worktree.test_action(
+            &mut state,
+            WorkSpaceAction::InlineEdit(ConfirmAction::Confirm(Some(String::from("[1, 2")))),
+        );
+        assert!(actions.is_empty());
+        assert_snapshot!(
+            "render_inline_edit_error_test",
+            stateful_render_to_string(&worktree, &mut state)
+        );
+        worktree.test_action(&mut state, WorkSpaceAction::ErrorConfirmed);
+
+        let actions = worktree.test_action(
+            &mut state,
+            WorkSpaceAction::InlineEdit(ConfirmAction::Confirm(Some(String::from("[1, 2, 3]")))),
+        );
+        assert!(worktree.dialogs.is_empty());
+        for action in actions {
+            let Action::Workspace(action) = action else {
+                panic!("unexpected action {action:?}");
+            };
+            worktree.test_action(&mut state, action);
+        }
+        assert_snapshot!(
+            "render_inline_edit_review_test",
+            stateful_render_to_string(&worktree, &mut state)
+        );
+        for action in worktree.test_action(
+            &mut state,
+            WorkSpaceAction::ApplyEdit(ConfirmAction::Confirm(true)),
+        ) {
+            let Action::Workspace(action) = action else {
+                panic!("unexpected action {action:?}");
+            };
+            worktree.test_action(&mut state, action);
+        }
+        assert_eq!(
+            worktree.file_root().to_string_compact().unwrap(),
+            r#"{"a":[1,2,3],"b":3}"#
+        );
+    }
+
+    #[test]
+    fn number_edits_test() {
+        let mut worktree = WorkSpace::new(
+            Node::load(r#"{"a": 1.5}"#.as_bytes()).unwrap(),
+            Config::default().with_number_edits(NumberEdits::KeepKind),
+        );
+        let mut state = WorkSpaceState::default();
+        worktree.test_action(&mut state, NavigationAction::Expand.into());
+
+        for action in worktree.test_action(
+            &mut state,
+            WorkSpaceAction::ApplyEdit(ConfirmAction::Request((
+                Node::load("2".as_bytes()).unwrap(),
+                Repairs::default(),
+            ))),
+        ) {
+            let Action::Workspace(action) = action else {
+                panic!("unexpected action {action:?}");
+            };
+            worktree.test_action(&mut state, action);
+        }
+        assert_eq!(
+            worktree.file_root().to_string_compact().unwrap(),
+            r#"{"a":2.0}"#
+        );
+    }
+
+    #[test]
+    fn apply_edit_test() {
+        let mut worktree = WorkSpace::new(
+            Node::load(r#"{"a": 1}"#.as_bytes()).unwrap(),
+            Config::default(),
+        );
+        let mut state = WorkSpaceState::default();
+        worktree.test_action(&mut state, NavigationAction::Expand.into());
+
+        // Terminals replacing terminals apply right away.
+        let actions = worktree.test_action(
+            &mut state,
+            WorkSpaceAction::ApplyEdit(ConfirmAction::Request((
+                Node::load("2".as_bytes()).unwrap(),
+                Repairs::default(),
+            ))),
+        );
+        assert!(matches!(
+            actions[..],
+            [Action::Workspace(WorkSpaceAction::Load { .. })]
+        ));
+
+        state.list_state.select(Some(0));
+        let actions = worktree.test_action(
+            &mut state,
+            WorkSpaceAction::ApplyEdit(ConfirmAction::Request((
+                Node::load(r#"{"b": 1}"#.as_bytes()).unwrap(),
+                Repairs::default(),
+            ))),
+        );
+        assert!(actions.is_empty());
+        assert_eq!(worktree.dialogs.len(), 1);
+        let actions = worktree.test_action(
+            &mut state,
+            WorkSpaceAction::ApplyEdit(ConfirmAction::Confirm(false)),
+        );
+        assert!(actions.is_empty());
+        assert!(worktree.dialogs.is_empty());
+        assert!(worktree.pending_edit.is_none());
+        assert_eq!(
+            worktree.file_root().to_string_compact().unwrap(),
+            r#"{"a":1}"#
+        );
+    }
+
+    #[test]
+    fn render_load_repaired_test() {
+        let mut worktree = WorkSpace::new(Node::null(), Config::default());
+        let mut state = WorkSpaceState::default();
+
+        worktree.test_action(
+            &mut state,
+            WorkSpaceAction::Load {
+                node: Node::load(r#"{"a": ["x�"]}"#.as_bytes()).unwrap(),
+                is_edit: false,
+                repaired: Repairs {
+                    utf8: vec![String::from("/a/0")],
+                    ..Default::default()
+                },
+            },
+        );
+        assert_eq!(worktree.dialogs.len(), 1);
+        assert_snapshot!(stateful_render_to_string(&worktree, &mut state));
+    }
+
+    #[test]
+    fn render_load_duplicate_keys_test() {
+        let config = Config::default().with_duplicate_keys(DuplicateKeys::Rename);
+        let (node, repaired) = Node::load_with_repairs(
+            r#"{"a": {"id": 1, "id": 2}, "b": "x", "b": "y", "b": "z"}"#.as_bytes(),
+            &config.parse_options(),
+        )
+        .unwrap();
+        let mut worktree = WorkSpace::new(Node::null(), config);
+        let mut state = WorkSpaceState::default();
+
+        worktree.test_action(
+            &mut state,
+            WorkSpaceAction::Load {
+                node,
+                is_edit: false,
+                repaired,
+            },
+        );
+        assert_eq!(worktree.dialogs.len(), 1);
+        assert_snapshot!(stateful_render_to_string(&worktree, &mut state));
+    }
+
+    #[test]
+    fn handle_edit_error_action_test() {
+        let json = String::from("123");
+        let mut worktree = WorkSpace::new(Node::load(json.as_bytes()).unwrap(), Config::default());
+        let mut state = WorkSpaceState::default();
+
+        let action = WorkSpaceAction::EditError(ConfirmAction::Request(String::from(
+            "Deserialization error: expected value at line 1 column 2",
+        )));
+        assert!(worktree.test_action(&mut state, action.clone()).is_empty());
+        assert_eq!(worktree.dialogs.len(), 1);
+        assert!(
+            worktree
+                .test_action(
+                    &mut state,
+                    WorkSpaceAction::EditError(ConfirmAction::Confirm(false))
+                )
+                .is_empty()
+        );
+        assert!(worktree.dialogs.is_empty());
+
+        assert!(worktree.test_action(&mut state, action.clone()).is_empty());
+        assert_eq!(worktree.dialogs.len(), 1);
+        assert_eq!(
+            worktree.test_action(
+                &mut state,
+                WorkSpaceAction::EditError(ConfirmAction::Confirm(true))
+            ),
+            vec![JobAction::Edit(EditJobAction::Open(None)).into()]
+        );
+        assert!(worktree.dialogs.is_empty());
+    }
+
+    #[test]
+    fn event_handler_dialog_test() {
+        let json = String::from("123");
+        let mut worktree = WorkSpace::new(Node::load(json.as_bytes()).unwrap(), Config::default());
+        let mut state = WorkSpaceState::default();
+
+        worktree.test_action(
+            &mut state,
+            WorkSpaceAction::EditError(ConfirmAction::Request(String::from(
+                "Deserialization error: expected value at line 1 column 2",
+            ))),
+        );
+        assert_key_event_to_action(
+            &worktree,
+            (KeyCode::Char('y'), KeyModifiers::NONE),
+            vec![WorkSpaceAction::EditError(ConfirmAction::Confirm(true)).into()],
+        );
+    }
+
+    #[test]
+    fn render_edit_error_test() {
+        let json = String::from("123");
+        let mut worktree = WorkSpace::new(Node::load(json.as_bytes()).unwrap(), Config::default());
+        let mut state = WorkSpaceState::default();
+
+        for response in [true, false] {
+            worktree.test_action(
+                &mut state,
+                WorkSpaceAction::EditError(ConfirmAction::Request(String::from(
+                    "Deserialization error: expected value at line 1 column 2",
+                ))),
+            );
+            if response {
+                assert_snapshot!(stateful_render_to_string(
+                    &worktree,
+                    &mut WorkSpaceState::default()
+                ));
+            }
+
+            worktree.handle_edit_error_action(ConfirmAction::Confirm(response));
+            assert_snapshot!(stateful_render_to_string(
+                &worktree,
+                &mut WorkSpaceState::default()
+            ));
+        }
+    }
+
+    #[test]
+    fn render_edit_error_long_message_test() {
+        let json = String::from("123");
+        let mut worktree = WorkSpace::new(Node::load(json.as_bytes()).unwrap(), Config::default());
+        let mut state = WorkSpaceState::default();
+
+        worktree.test_action(&mut state, WorkSpaceAction::EditError(ConfirmAction::Request(String::from(
+            concat!(
+                "Deserialization error: expected value at line 1 column 2. Lorem ipsum dolor sit amet,",
+                "consectetur adipiscing elit, sed do eiusmod tempor incididunt ut labore et dolore magna",
+                "aliqua.",
+            )
+        ))));
+
+        assert_snapshot!(stateful_render_to_string(
+            &worktree,
+            &mut WorkSpaceState::default()
+        ));
+    }
+
+    #[test]
+    fn exit_without_change_test() {
+        let json = String::from("123");
+        let mut worktree = WorkSpace::new(Node::load(json.as_bytes()).unwrap(), Config::default());
+        assert!(worktree.maybe_exit(ConfirmAction::Request(())));
+
+        let mut state = WorkSpaceState::default();
+        worktree.test_action(
+            &mut state,
+            WorkSpaceAction::Load {
+                node: Node::load(String::from("456").as_bytes()).unwrap(),
+                is_edit: true,
+                repaired: Repairs::default(),
+            },
+        );
+        assert!(!worktree.maybe_exit(ConfirmAction::Request(())));
+        assert!(!worktree.maybe_exit(ConfirmAction::Confirm(false)));
+
+        worktree.test_action(
+            &mut state,
+            WorkSpaceAction::Load {
+                node: Node::load(String::from("123").as_bytes()).unwrap(),
+                is_edit: true,
+                repaired: Repairs::default(),
+            },
+        );
+        assert!(!worktree.maybe_exit(ConfirmAction::Request(())));
+        assert!(worktree.maybe_exit(ConfirmAction::Confirm(true)));
+
+        worktree.test_action(
+            &mut state,
+            WorkSpaceAction::Load {
+                node: Node::load(String::from("123").as_bytes()).unwrap(),
+                is_edit: true,
+                repaired: Repairs::default(),
+            },
+        );
+        worktree.handle_save_done(String::from("Saved 3 B to out.json"));
+        assert!(worktree.maybe_exit(ConfirmAction::Request(())));
+    }
+
+    #[test]
+    fn restat_test() {
+        let mut worktree = WorkSpace::new(
+            Node::load(SAMPLE_JSON.as_bytes()).unwrap(),
+            Config::default(),
+        );
+        let mut state = WorkSpaceState::default();
+        worktree.test_action(&mut state, NavigationAction::Expand.into());
+        worktree.test_action(&mut state, NavigationAction::Expand.into());
+        let before = stateful_render_to_string(&worktree, &mut state);
+
+        let node = worktree.file_root().clone();
+        worktree.test_action(&mut state, WorkSpaceAction::Restated(node.restat()));
+        assert_eq!(stateful_render_to_string(&worktree, &mut state), before);
+        assert_eq!(
+            worktree.work_tree_root.meta(0),
+            Some(worktree.file_root().as_meta())
+        );
+    }
+
+    #[test]
+    fn render_diff_test() {
+        let mut worktree = WorkSpace::new(
+            Node::load(r#"{"a": {"b": 1, "c": [2, 3]}, "d": 4}"#.as_bytes()).unwrap(),
+            Config::default(),
+        );
+        let mut state = WorkSpaceState::default();
+
+        worktree.test_action(&mut state, WorkSpaceAction::Diff);
+        assert_eq!(
+            worktree.messages.back().map(String::as_str),
+            Some("No change to compare against")
+        );
+
+        worktree.test_action(&mut state, NavigationAction::Expand.into());
+        worktree.test_action(&mut state, NavigationAction::Expand.into());
+        worktree.test_action(
+            &mut state,
+            WorkSpaceAction::Rename(ConfirmAction::Confirm(Some(String::from("e")))),
+        );
+        worktree.test_action(&mut state, WorkSpaceAction::Diff);
+        assert_snapshot!(stateful_render_to_string(&worktree, &mut state));
+
+        worktree.test_action(&mut state, NavigationAction::Up(2).into());
+        worktree.test_action(&mut state, WorkSpaceAction::Diff);
+        assert_snapshot!(stateful_render_to_string(&worktree, &mut state));
+
+        worktree.test_action(&mut state, NavigationAction::Bottom.into());
+        worktree.test_action(&mut state, WorkSpaceAction::Diff);
+        assert_eq!(
+            worktree.messages.back().map(String::as_str),
+            Some("No change in selection")
+        );
+    }
+
+    #[test]
+    fn render_messages_test() {
+        let mut worktree = WorkSpace::new(
+            Node::load(SAMPLE_JSON.as_bytes()).unwrap(),
+            Config::default(),
+        );
+        let mut state = WorkSpaceState::default();
+
+        worktree.test_action(&mut state, WorkSpaceAction::ListMessages);
+        assert_snapshot!(stateful_render_to_string(&worktree, &mut state));
+        worktree.test_action(&mut state, WorkSpaceAction::ErrorConfirmed);
+
+        worktree.test_action(&mut state, NavigationAction::Expand.into());
+        worktree.test_action(&mut state, WorkSpaceAction::Yank('a'));
+        worktree.test_action(
+            &mut state,
+            WorkSpaceAction::SaveDone(String::from("Saved 3.2 KiB to out.json")),
+        );
+        assert_snapshot!(stateful_render_to_string(&worktree, &mut state));
+
+        worktree.test_action(&mut state, NavigationAction::Down(1).into());
+        worktree.test_action(&mut state, WorkSpaceAction::ListMessages);
+        assert_snapshot!(stateful_render_to_string(&worktree, &mut state));
+    }
+
+    #[test]
+    fn render_exit_confirm_test() {
+        let json = String::from("123");
+        let mut worktree = WorkSpace::new(Node::load(json.as_bytes()).unwrap(), Config::default());
+
+        let mut state = WorkSpaceState::default();
+        worktree.test_action(
+            &mut state,
+            WorkSpaceAction::Load {
+                node: Node::load(String::from("456").as_bytes()).unwrap(),
+                is_edit: true,
+                repaired: Repairs::default(),
+            },
+        );
+        assert!(!worktree.maybe_exit(ConfirmAction::Request(())));
+
+        assert_snapshot!(stateful_render_to_string(&worktree, &mut state,));
+    }
+
+    #[test]
+    fn save_as_test() {
+        let mut worktree = WorkSpace::new(Node::from_object(IndexMap::new()), Config::default());
+        let mut state = WorkSpaceState::default();
+
+        assert_key_event_to_action(
+            &worktree,
+            (KeyCode::Char('W'), KeyModifiers::NONE),
+            vec![WorkSpaceAction::SaveAs(ConfirmAction::Request(())).into()],
+        );
+
+        worktree.test_action(
+            &mut state,
+            WorkSpaceAction::SaveAs(ConfirmAction::Request(())),
+        );
+        assert_snapshot!(stateful_render_to_string(&worktree, &mut state));
+
+        assert_eq!(
+            worktree.test_action(
+                &mut state,
+                WorkSpaceAction::SaveAs(ConfirmAction::Confirm(Some(String::from("out.json"))))
+            ),
+            vec![JobAction::SaveAs(String::from("out.json")).into()]
+        );
+        assert!(worktree.dialogs.is_empty());
+
+        worktree.test_action(
+            &mut state,
+            WorkSpaceAction::SaveAs(ConfirmAction::Request(())),
+        );
+        assert_eq!(
+            worktree.test_action(
+                &mut state,
+                WorkSpaceAction::SaveAs(ConfirmAction::Confirm(Some(String::from(" "))))
+            ),
+            vec![]
+        );
+        assert!(worktree.dialogs.is_empty());
+    }
+
+    #[test]
+    fn add_into_empty_test() {
+        let mut worktree = WorkSpace::new(Node::from_object(IndexMap::new()), Config::default());
+        let mut state = WorkSpaceState::default();
+
+        worktree.test_action(&mut state, WorkSpaceAction::Add(ConfirmAction::Request(())));
+        worktree.test_action(
+            &mut state,
+            WorkSpaceAction::Add(ConfirmAction::Confirm(Some(String::from("a")))),
+        );
+        worktree.test_action(&mut state, WorkSpaceAction::Add(ConfirmAction::Request(())));
+        worktree.test_action(
+            &mut state,
+            WorkSpaceAction::Add(ConfirmAction::Confirm(Some(String::from("b")))),
+        );
+        assert!(worktree.dialogs.is_empty());
+        assert!(worktree.is_edited);
+        assert_eq!(
+            worktree.file_root,
+            Node::load(r#"{"a": null, "b": null}"#.as_bytes()).unwrap()
+        );
+        assert_snapshot!(stateful_render_to_string(&worktree, &mut state));
+    }
+
+    #[test]
+    fn render_save_dialog_test() {
+        let json = String::from("123");
+        let mut worktree = WorkSpace::new(Node::load(json.as_bytes()).unwrap(), Config::default());
+
+        let mut state = WorkSpaceState::default();
+        worktree.test_action(
+            &mut state,
+            WorkSpaceAction::Save(ConfirmAction::Request(())),
+        );
+
+        assert_snapshot!(stateful_render_to_string(&worktree, &mut state,));
+    }
+
+    #[test]
+    fn render_preview_test() {
+        let json = serde_json::to_string_pretty(&serde_json::json!({
+            "key": "value",
+            "array": [1, 2, ["cat", "dog"]]
+        }))
+        .unwrap();
+        let mut worktree = WorkSpace::new(Node::load(json.as_bytes()).unwrap(), Config::default());
+        let mut state = WorkSpaceState::default();
+
+        worktree.test_action(&mut state, NavigationAction::TogglePreview.into());
+        assert_snapshot!(stateful_render_to_string(&worktree, &mut state));
+
+        worktree.test_action(&mut state, NavigationAction::Expand.into());
+        assert_snapshot!(stateful_render_to_string(&worktree, &mut state));
+
+        worktree.test_action(&mut state, NavigationAction::Down(1).into());
+        assert_snapshot!(stateful_render_to_string(&worktree, &mut state));
+
+        worktree.test_action(&mut state, NavigationAction::TogglePreview.into());
+        assert_snapshot!(stateful_render_to_string(&worktree, &mut state));
+    }
+
+    #[test]
+    fn decoded_preview_test() {
+        let mut worktree = WorkSpace::new(
+            Node::load(r#"{"a": "aHR0cHM6Ly9leC5jb20vP3E9YSUyMGI=", "b": 1}"#.as_bytes()).unwrap(),
+            Config::default(),
+        );
+        let mut state = WorkSpaceState::default();
+        worktree.select_path(&mut state, &["a"]);
+
+        assert_key_event_to_action(
+            &worktree,
+            (KeyCode::Char('b'), KeyModifiers::NONE),
+            vec![NavigationAction::CycleDecoding.into()],
+        );
+        worktree.test_action(&mut state, NavigationAction::CycleDecoding.into());
+        assert_eq!(worktree.decoding, Some(Decoding::Base64));
+        assert_snapshot!(stateful_render_to_string(&worktree, &mut state));
+
+        // The string is not URL-encoded nor a JWT, so the next decoding shows its JSON again.
+        worktree.test_action(&mut state, NavigationAction::CycleDecoding.into());
+        assert_eq!(worktree.decoding, None);
+        assert_eq!(
+            worktree.messages.back().unwrap(),
+            "Previewing strings as JSON"
+        );
+
+        worktree.select_path(&mut state, &["b"]);
+        worktree.test_action(&mut state, NavigationAction::CycleDecoding.into());
+        assert_eq!(worktree.decoding, None);
+        assert_eq!(
+            worktree.messages.back().unwrap(),
+            "Only strings have decoded views"
+        );
+        assert_eq!(
+            worktree.file_root().to_string_compact().unwrap(),
+            r#"{"a":"aHR0cHM6Ly9leC5jb20vP3E9YSUyMGI=","b":1}"#
+        );
+    }
+
+    #[test]
+    fn table_view_test() {
+        let mut worktree = WorkSpace::new(
+            Node::load(
+                r#"{"users": [{"id": 1, "name": "ann"}, {"id": 2, "name": "bob", "admin": true}], "n": 2}"#
+                    .as_bytes(),
+            )
+            .unwrap(),
+            Config::default(),
+        );
+        let mut state = WorkSpaceState::default();
+        worktree.select_path(&mut state, &["users"]);
+
+        assert_key_event_to_action(
+            &worktree,
+            (KeyCode::Char('O'), KeyModifiers::NONE),
+            vec![NavigationAction::ToggleTableView.into()],
+        );
+        worktree.test_action(&mut state, NavigationAction::ToggleTableView.into());
+        assert!(worktree.table.is_some());
+        assert_snapshot!(stateful_render_to_string(&worktree, &mut state));
+
+        worktree.select_path(&mut state, &["n"]);
+        assert!(worktree.table.is_none());
+        assert!(worktree.preview.is_some());
+
+        worktree.select_path(&mut state, &["users"]);
+        assert!(worktree.table.is_some());
+        worktree.test_action(&mut state, NavigationAction::ToggleTableView.into());
+        assert!(worktree.table.is_none());
+        assert_eq!(
+            worktree.messages.back().unwrap(),
+            "Previewing arrays as JSON"
+        );
+    }
+
+    #[test]
+    fn jq_test() {
+        let json = serde_json::to_string_pretty(&serde_json::json!({
+            "key": "value",
+            "array": [1, 2, ["cat", "dog"]]
+        }))
+        .unwrap();
+        let mut worktree = WorkSpace::new(Node::load(json.as_bytes()).unwrap(), Config::default());
+        let mut state = WorkSpaceState::default();
+
+        assert_key_event_to_action(
+            &worktree,
+            (KeyCode::Char('Q'), KeyModifiers::NONE),
+            vec![WorkSpaceAction::Jq(ConfirmAction::Request(())).into()],
+        );
+        assert_key_event_to_action(
+            &worktree,
+            (KeyCode::Char('|'), KeyModifiers::NONE),
+            vec![WorkSpaceAction::Pin.into()],
+        );
+
+        worktree.test_action(&mut state, WorkSpaceAction::Jq(ConfirmAction::Request(())));
+        assert_eq!(worktree.dialogs.len(), 1);
+        assert_eq!(
+            worktree.test_action(
+                &mut state,
+                WorkSpaceAction::Jq(ConfirmAction::Confirm(Some(String::from(".array[]")))),
+            ),
+            vec![
+                JobAction::Jq {
+                    filter: String::from(".array[]"),
+                    content: String::from(r#"{"key":"value","array":[1,2,["cat","dog"]]}"#),
+                }
+                .into()
+            ]
+        );
+        assert!(worktree.dialogs.is_empty());
+        assert_eq!(worktree.jq_filter, ".array[]");
+
+        let values = ["1", "2", r#"["cat","dog"]"#]
+            .map(|value| Node::load(value.as_bytes()).unwrap())
+            .to_vec();
+        worktree.test_action(
+            &mut state,
+            WorkSpaceAction::JqOutput(ConfirmAction::Request((String::from(".array[]"), values))),
+        );
+        assert_snapshot!(stateful_render_to_string(&worktree, &mut state));
+
+        let [Action::Workspace(WorkSpaceAction::Load { node, is_edit, .. })] = &worktree
+            .test_action(
+                &mut state,
+                WorkSpaceAction::JqOutput(ConfirmAction::Confirm(true)),
+            )[..]
+        else {
+            panic!("confirming the jq output does not replace the selection");
+        };
+        assert!(is_edit);
+        assert_eq!(node.to_string_compact().unwrap(), r#"[1,2,["cat","dog"]]"#);
+        assert!(worktree.dialogs.is_empty());
+
+        worktree.test_action(
+            &mut state,
+            WorkSpaceAction::JqOutput(ConfirmAction::Request((String::from("empty"), vec![]))),
+        );
+        assert!(worktree.dialogs.is_empty());
+        assert_eq!(
+            worktree.messages.back().unwrap(),
+            "jq printed nothing for empty"
+        );
+
+        let mut worktree = WorkSpace::new(
+            Node::load(json.as_bytes()).unwrap(),
+            Config::default().with_no_shell(),
+        );
+        worktree.test_action(&mut state, WorkSpaceAction::Jq(ConfirmAction::Request(())));
+        assert!(worktree.dialogs.is_empty());
+        assert_eq!(
+            worktree.messages.back().unwrap(),
+            "jq is disabled by no_shell"
+        );
+    }
+
+    #[test]
+    fn pipe_test() {
+        let mut worktree =
+            WorkSpace::new(Node::load("[3, 1]".as_bytes()).unwrap(), Config::default());
+        let mut state = WorkSpaceState::default();
+
+        assert_key_event_to_action(
+            &worktree,
+            (KeyCode::Char('!'), KeyModifiers::NONE),
+            vec![WorkSpaceAction::Pipe(ConfirmAction::Request(())).into()],
+        );
+
+        worktree.test_action(
+            &mut state,
+            WorkSpaceAction::Pipe(ConfirmAction::Request(())),
+        );
+        assert_eq!(worktree.dialogs.len(), 1);
+        assert_eq!(
+            worktree.test_action(
+                &mut state,
+                WorkSpaceAction::Pipe(ConfirmAction::Confirm(Some(String::from("jq sort")))),
+            ),
+            vec![
+                JobAction::Pipe {
+                    command: String::from("jq sort"),
+                    content: String::from("[\n  3,\n  1\n]"),
+                }
+                .into()
+            ]
+        );
+        assert!(worktree.dialogs.is_empty());
+        assert_eq!(worktree.pipe_command, "jq sort");
+
+        worktree.test_action(
+            &mut state,
+            WorkSpaceAction::Pipe(ConfirmAction::Request(())),
+        );
+        assert_eq!(
+            worktree.test_action(
+                &mut state,
+                WorkSpaceAction::Pipe(ConfirmAction::Confirm(None))
+            ),
+            vec![]
+        );
+        assert!(worktree.dialogs.is_empty());
+
+        let mut worktree = WorkSpace::new(
+            Node::load("[3, 1]".as_bytes()).unwrap(),
+            Config::default().with_no_shell(),
+        );
+        worktree.test_action(
+            &mut state,
+            WorkSpaceAction::Pipe(ConfirmAction::Request(())),
+        );
+        assert!(worktree.dialogs.is_empty());
+        assert_eq!(
+            worktree.messages.back().unwrap(),
+            "Piping is disabled by no_shell"
+        );
+    }
+
+    #[test]
+    fn render_pinned_preview_test() {
+        let json = serde_json::to_string_pretty(&serde_json::json!({
+            "key": "value",
+            "array": [1, 2, ["cat", "dog"]]
+        }))
+        .unwrap();
+        let mut worktree = WorkSpace::new(Node::load(json.as_bytes()).unwrap(), Config::default());
+        let mut state = WorkSpaceState::default();
+
+        worktree.test_action(&mut state, NavigationAction::TogglePreview.into());
+        worktree.test_action(&mut state, NavigationAction::Expand.into());
+        worktree.test_action(&mut state, NavigationAction::Down(2).into());
+        worktree.test_action(&mut state, WorkSpaceAction::Pin);
+        worktree.test_action(&mut state, NavigationAction::Up(1).into());
+        assert_snapshot!(stateful_render_to_string(&worktree, &mut state));
+
+        worktree.test_action(&mut state, NavigationAction::SwitchFocus.into());
+        assert_eq!(state.focus, Focus::Preview);
+        worktree.test_action(&mut state, NavigationAction::SwitchFocus.into());
+        assert_eq!(state.focus, Focus::Pinned);
+        worktree.test_action(&mut state, NavigationAction::TogglePreview.into());
+        assert_snapshot!(
+            "render_pinned_preview_alone_test",
+            stateful_render_to_string(&worktree, &mut state)
+        );
+
+        worktree.test_action(&mut state, WorkSpaceAction::Pin);
+        assert_eq!(state.focus, Focus::Tree);
+        assert_eq!(worktree.messages.back().unwrap(), "Unpinned the preview");
+        worktree.test_action(&mut state, NavigationAction::SwitchFocus.into());
+        assert_eq!(state.focus, Focus::Tree);
+    }
+
+    #[test]
+    fn fullscreen_preview_test() {
+        let json = serde_json::to_string_pretty(&serde_json::json!({
+            "key": "value",
+            "array": [1, 2, ["cat", "dog"]]
+        }))
+        .unwrap();
+        let mut worktree = WorkSpace::new(Node::load(json.as_bytes()).unwrap(), Config::default());
+        let mut state = WorkSpaceState::default();
+
+        assert_key_event_to_action(
+            &worktree,
+            (KeyCode::Char('z'), KeyModifiers::NONE),
+            vec![NavigationAction::ToggleFullscreenPreview.into()],
+        );
+        worktree.test_action(&mut state, NavigationAction::Expand.into());
+        worktree.test_action(&mut state, NavigationAction::Down(2).into());
+        worktree.test_action(&mut state, NavigationAction::ToggleFullscreenPreview.into());
+        assert_snapshot!(stateful_render_to_string(&worktree, &mut state));
+
+        worktree.test_action(&mut state, NavigationAction::Expand.into());
+        worktree.test_action(&mut state, NavigationAction::Down(3).into());
+        worktree.test_action(&mut state, NavigationAction::ToggleFullscreenPreview.into());
+        assert_eq!(state.list_state.selected(), Some(5));
+        assert_snapshot!(
+            "fullscreen_preview_restored_test",
+            stateful_render_to_string(&worktree, &mut state)
+        );
+    }
+
+    #[test]
+    fn preview_layout_test() {
+        let json = r#"{"a": {"b": 1}, "c": [1, 2, 3]}"#;
+        let config = Config::default().with_preview_layout(PreviewLayout::Below);
+        let mut worktree = WorkSpace::new(Node::load(json.as_bytes()).unwrap(), config);
+        let mut state = WorkSpaceState::default();
+
+        worktree.test_action(&mut state, NavigationAction::Expand.into());
+        worktree.test_action(&mut state, NavigationAction::TogglePreview.into());
+        assert_snapshot!(stateful_render_to_string(&worktree, &mut state));
+
+        // Dragging the border between the tree and the preview resizes them vertically.
+        let mouse = |kind, row| {
+            WorkSpaceAction::Mouse(MouseEvent {
+                kind,
+                column: 5,
+                row,
+                modifiers: KeyModifiers::NONE,
+            })
+        };
+        let border = state.preview_area.unwrap().y;
+        worktree.test_action(
+            &mut state,
+            mouse(MouseEventKind::Down(MouseButton::Left), border),
+        );
+        worktree.test_action(
+            &mut state,
+            mouse(MouseEventKind::Drag(MouseButton::Left), 10),
+        );
+        worktree.test_action(&mut state, mouse(MouseEventKind::Up(MouseButton::Left), 10));
+        assert_eq!(worktree.preview_pct, 48);
+
+        assert_key_event_to_action(
+            &worktree,
+            (KeyCode::Char('o'), KeyModifiers::NONE),
+            vec![NavigationAction::TogglePreviewLayout.into()],
+        );
+        worktree.test_action(&mut state, NavigationAction::TogglePreviewLayout.into());
+        assert_snapshot!(
+            "preview_layout_beside_test",
+            stateful_render_to_string(&worktree, &mut state)
+        );
+    }
+
+    #[test]
+    fn flat_view_test() {
+        let json = r#"{"servlet": [{"init-param": {"cachePagesTrack": 200}}, []], "a b": "x"}"#;
+        let mut worktree = WorkSpace::new(Node::load(json.as_bytes()).unwrap(), Config::default());
+        let mut state = WorkSpaceState::default();
+
+        assert_key_event_to_action(
+            &worktree,
+            (KeyCode::Char('_'), KeyModifiers::NONE),
+            vec![NavigationAction::ToggleFlatView.into()],
+        );
+        worktree.test_action(&mut state, NavigationAction::TogglePreview.into());
+        worktree.test_action(&mut state, NavigationAction::ToggleFlatView.into());
+        assert_eq!(state.flat_state.selected(), Some(0));
+        worktree.test_action(&mut state, NavigationAction::Down(1).into());
+        assert_snapshot!(stateful_render_to_string(&worktree, &mut state));
+
+        // Actions apply to the node of the selected row.
+        worktree.test_action(&mut state, NavigationAction::Bottom.into());
+        worktree.test_action(
+            &mut state,
+            WorkSpaceAction::Delete(ConfirmAction::Request(UNNAMED_REGISTER)),
+        );
+        worktree.test_action(
+            &mut state,
+            WorkSpaceAction::Delete(ConfirmAction::Confirm(true)),
+        );
+        assert_eq!(
+            worktree.flat.as_ref().unwrap().selectors,
+            [
+                vec!["servlet", "0", "init-param", "cachePagesTrack"],
+                vec!["servlet", "1"]
+            ]
+        );
+        assert_eq!(state.flat_state.selected(), Some(1));
+
+        worktree.test_action(
+            &mut state,
+            WorkSpaceAction::GoTo(ConfirmAction::Confirm(Some(String::from("/servlet/0")))),
+        );
+        assert_eq!(state.flat_state.selected(), Some(0));
+
+        worktree.test_action(&mut state, NavigationAction::ToggleFlatView.into());
+        assert_snapshot!(
+            "flat_view_closed_test",
+            stateful_render_to_string(&worktree, &mut state)
+        );
+    }
+
+    #[test]
+    fn preview_focus_test() {
+        let json = serde_json::json!({
+            "list": (1..=30).collect::<Vec<_>>(),
+            "name": "needle",
+            "other": "Needle"
+        })
+        .to_string();
+        let mut worktree = WorkSpace::new(Node::load(json.as_bytes()).unwrap(), Config::default());
+        let mut state = WorkSpaceState::default();
+        let press = |worktree: &WorkSpace, state: &WorkSpaceState, code| {
+            let mut actions = Actions::new();
+            let event = Event::Key(KeyEvent::new(code, KeyModifiers::NONE));
+            worktree.handle_event(state, &mut actions, event);
+            actions.into_vec()
+        };
+
+        worktree.test_action(&mut state, NavigationAction::SwitchFocus.into());
+        assert_eq!(state.focus, Focus::Tree);
+        assert_eq!(
+            press(&worktree, &state, KeyCode::Char('j')),
+            vec![NavigationAction::Down(1).into()]
+        );
+        worktree.test_action(&mut state, NavigationAction::TogglePreview.into());
+        worktree.test_action(&mut state, NavigationAction::SwitchFocus.into());
+        assert_eq!(state.focus, Focus::Preview);
+        assert_eq!(
+            press(&worktree, &state, KeyCode::Char('j')),
+            vec![PreviewNavigationAction::Down(1).into()]
+        );
+        assert_eq!(
+            press(&worktree, &state, KeyCode::Char('G')),
+            vec![PreviewNavigationAction::Bottom.into()]
+        );
+        assert_eq!(
+            press(&worktree, &state, KeyCode::Char('/')),
+            vec![WorkSpaceAction::PreviewSearch(ConfirmAction::Request(())).into()]
+        );
+        assert_eq!(
+            press(&worktree, &state, KeyCode::Esc),
+            vec![NavigationAction::FocusTree.into()]
+        );
+        // Keys the preview has no use for still go to the tree.
+        assert_eq!(
+            press(&worktree, &state, KeyCode::Char('p')),
+            vec![NavigationAction::TogglePreview.into()]
+        );
+
+        worktree.test_action(
+            &mut state,
+            WorkSpaceAction::PreviewSearch(ConfirmAction::Request(())),
+        );
+        worktree.test_action(
+            &mut state,
+            WorkSpaceAction::PreviewSearch(ConfirmAction::Confirm(Some(String::from("needle")))),
+        );
+        assert!(worktree.dialogs.is_empty());
+        assert_eq!(state.preview_state.line(), 33);
+        worktree.test_action(&mut state, WorkSpaceAction::PreviewSearchNext(false));
+        assert_eq!(state.preview_state.line(), 34);
+        worktree.test_action(&mut state, WorkSpaceAction::PreviewSearchNext(false));
+        assert_eq!(state.preview_state.line(), 33);
+        worktree.test_action(&mut state, WorkSpaceAction::PreviewSearchNext(true));
+        assert_eq!(state.preview_state.line(), 34);
+        worktree.test_action(&mut state, PreviewNavigationAction::Top.into());
+        assert_eq!(state.preview_state.line(), 0);
+        worktree.test_action(
+            &mut state,
+            WorkSpaceAction::PreviewSearch(ConfirmAction::Confirm(Some(String::from("zz")))),
+        );
+        assert_eq!(worktree.messages.back().unwrap(), "No match for zz");
+
+        worktree.test_action(&mut state, NavigationAction::TogglePreview.into());
+        assert_eq!(state.focus, Focus::Tree);
+    }
+
+    #[test]
+    fn preview_out_of_bound_test() {
+        let json = serde_json::to_string_pretty(&serde_json::json!({
+            "key": "value",
+            "array": [1, 2, ["cat", "dog"]]
+        }))
+        .unwrap();
+        let mut worktree = WorkSpace::new(Node::load(json.as_bytes()).unwrap(), Config::default());
+        let mut state = WorkSpaceState::default();
+
+        for action in [
+            NavigationAction::TogglePreview,
+            NavigationAction::Up(1),
+            NavigationAction::Expand,
+            NavigationAction::Down(1),
+            NavigationAction::Down(1),
+            NavigationAction::Up(1),
+        ] {
+            worktree.test_action(&mut state, action.into());
+        }
+
+        assert_snapshot!(stateful_render_to_string(&worktree, &mut state));
+    }
+
+    #[test]
+    fn render_preview_scroll_test() {
+        let mut worktree = WorkSpace::new(
+            Node::load(SAMPLE_JSON.as_bytes()).unwrap(),
+            Config::default(),
+        );
+        let mut state = WorkSpaceState::default();
+
+        for action in [NavigationAction::TogglePreview, NavigationAction::Expand] {
+            worktree.test_action(&mut state, action.into());
+        }
+
+        for action in [
+            PreviewNavigationAction::Up(1),
+            PreviewNavigationAction::Down(1),
+            PreviewNavigationAction::Down(1),
+            PreviewNavigationAction::Up(1),
+            PreviewNavigationAction::Right,
+            PreviewNavigationAction::Right,
+            PreviewNavigationAction::Left,
+        ] {
+            worktree.test_action(&mut state, action.into());
+            assert_snapshot!(stateful_render_to_string(&worktree, &mut state));
+        }
+    }
+
+    #[test]
+    fn render_preview_overflow_scroll_test() {
+        let mut worktree = WorkSpace::new(
+            Node::load(SAMPLE_JSON.as_bytes()).unwrap(),
+            Config::default(),
+        );
+        let mut state = WorkSpaceState::default();
+
+        for action in [NavigationAction::TogglePreview, NavigationAction::Expand] {
+            worktree.test_action(&mut state, action.into());
+        }
+
+        for action in [
+            PreviewNavigationAction::Down(3),
+            PreviewNavigationAction::Down(100),
+            PreviewNavigationAction::Up(3),
+            PreviewNavigationAction::Up(100),
+        ] {
+            worktree.test_action(&mut state, action.into());
+            assert_snapshot!(stateful_render_to_string(&worktree, &mut state));
+        }
+    }
+
+    #[test]
+    fn render_preview_update_on_edit_test() {
+        let mut worktree = WorkSpace::new(
+            Node::load(SAMPLE_JSON.as_bytes()).unwrap(),
+            Config::default(),
+        );
+        let mut state = WorkSpaceState::default();
+
+        worktree.test_action(&mut state, NavigationAction::TogglePreview.into());
+        worktree.test_action(
+            &mut state,
+            WorkSpaceAction::Load {
+                node: Node::load("123".as_bytes()).unwrap(),
+                is_edit: true,
+                repaired: Repairs::default(),
+            },
+        );
+
+        assert_snapshot!(stateful_render_to_string(&worktree, &mut state));
+    }
+
+    #[test]
+    fn render_preview_overlap_test() {
+        let mut worktree = WorkSpace::new(
+            Node::load(SAMPLE_JSON.as_bytes()).unwrap(),
+            Config::default(),
+        );
+        let mut state = WorkSpaceState::default();
+
+        worktree.test_action(&mut state, NavigationAction::TogglePreview.into());
+        worktree.test_action(
+            &mut state,
+            WorkSpaceAction::Load {
+                node: Node::load(SAMPLE_JSON.as_bytes()).unwrap(),
+                is_edit: true,
+                repaired: Repairs::default(),
+            },
+        );
+        worktree.maybe_exit(ConfirmAction::Request(()));
+        assert_snapshot!(stateful_render_to_string(&worktree, &mut state));
+
+        worktree.maybe_exit(ConfirmAction::Confirm(false));
+        assert_snapshot!(stateful_render_to_string(&worktree, &mut state));
+    }
+
+    #[test]
+    fn meta_test() {
+        let mut worktree = WorkSpace::new(
+            Node::load(SAMPLE_JSON.as_bytes()).unwrap(),
+            Config::default(),
+        );
+
+        assert_eq!(
+            worktree.meta_on_index(0),
+            NodeMeta {
+                n_lines: 100,
+                n_bytes: 3718,
+                n_indents: 414,
+                kind: NodeKind::Object,
+            }
+        );
+    }
+
+    #[test]
+    fn render_loading_test() {
+        let mut worktree = WorkSpace::new(
+            Node::load(SAMPLE_JSON.as_bytes()).unwrap(),
+            Config::default(),
+        );
+        let mut state = WorkSpaceState::default();
+
+        worktree.test_action(&mut state, NavigationAction::TogglePreview.into());
+        worktree.set_loading(true);
+        assert_snapshot!(stateful_render_to_string(&worktree, &mut state));
+
+        worktree.set_loading(false);
+        assert_snapshot!(stateful_render_to_string(&worktree, &mut state));
+    }
+
+    #[test]
+    fn cancel_loading_test() {
+        let mut worktree = WorkSpace::new(Node::null(), Config::default());
+        worktree.set_loading(true);
+        assert_key_event_to_action(&worktree, (KeyCode::Char('j'), KeyModifiers::NONE), vec![]);
+        assert_key_event_to_action(
+            &worktree,
+            (KeyCode::Esc, KeyModifiers::NONE),
+            vec![Action::CancelJobs],
+        );
+        assert_key_event_to_action(
+            &worktree,
+            (KeyCode::Char('c'), KeyModifiers::CONTROL),
+            vec![Action::CancelJobs],
+        );
+    }
+
+    #[test]
+    fn render_large_preview_test() {
+        let json_bodies: Vec<_> = std::iter::repeat_n(SAMPLE_JSON, 1024).collect();
+        let json = String::from("[") + &json_bodies.join(",") + "]";
+        let mut worktree = WorkSpace::new(Node::load(json.as_bytes()).unwrap(), Config::default());
+        let mut state = WorkSpaceState::default();
+
+        worktree.test_action(&mut state, NavigationAction::TogglePreview.into());
+        assert_snapshot!(stateful_render_to_string(&worktree, &mut state));
+
+        worktree.test_action(&mut state, NavigationAction::Expand.into());
+        assert_snapshot!(stateful_render_to_string(&worktree, &mut state));
+
+        worktree.test_action(&mut state, NavigationAction::Up(1).into());
+        assert_snapshot!(stateful_render_to_string(&worktree, &mut state));
+    }
+
+    #[test]
+    fn render_preview_limited_by_config_test() {
+        let config = Config::default().with_max_preview_size(Byte::from_u64(3718));
+        let mut worktree = WorkSpace::new(Node::load(SAMPLE_JSON.as_bytes()).unwrap(), config);
+        assert_eq!(worktree.file_root.to_string_pretty().unwrap().len(), 3718);
+        let mut state = WorkSpaceState::default();
+
+        worktree.test_action(&mut state, NavigationAction::TogglePreview.into());
+        assert_snapshot!(stateful_render_to_string(&worktree, &mut state));
+
+        let config = Config::default().with_max_preview_size(Byte::from_u64(3717));
+        let mut worktree = WorkSpace::new(Node::load(SAMPLE_JSON.as_bytes()).unwrap(), config);
+        worktree.test_action(&mut state, NavigationAction::TogglePreview.into());
+        assert_snapshot!(stateful_render_to_string(&worktree, &mut state));
+    }
+
+    #[test]
+    fn render_navigation_far_test() {
+        let mut worktree = WorkSpace::new(
+            Node::load(SAMPLE_JSON.as_bytes()).unwrap(),
+            Config::default(),
+        );
+        let mut state = WorkSpaceState::default();
+
+        worktree.test_action(&mut state, NavigationAction::TogglePreview.into());
+        worktree.test_action(&mut state, NavigationAction::Expand.into());
+        worktree.test_action(&mut state, NavigationAction::Expand.into());
+        worktree.test_action(&mut state, NavigationAction::Expand.into());
+        worktree.test_action(&mut state, NavigationAction::Expand.into());
+        worktree.test_action(&mut state, NavigationAction::Down(2).into());
+        worktree.test_action(&mut state, NavigationAction::Expand.into());
+        worktree.test_action(&mut state, NavigationAction::Down(10).into());
+        assert_snapshot!(stateful_render_to_string(&worktree, &mut state));
+        worktree.test_action(&mut state, NavigationAction::Down(100).into());
+        assert_snapshot!(stateful_render_to_string(&worktree, &mut state));
+        worktree.test_action(&mut state, NavigationAction::Up(100).into());
+        assert_snapshot!(stateful_render_to_string(&worktree, &mut state));
+    }
+
+    #[test]
+    fn render_preview_resize_test() {
+        let mut worktree = WorkSpace::new(
+            Node::load(SAMPLE_JSON.as_bytes()).unwrap(),
+            Config::default(),
+        );
+        let mut state = WorkSpaceState::default();
+
+        worktree.test_action(&mut state, NavigationAction::TogglePreview.into());
+        worktree.test_action(&mut state, NavigationAction::Expand.into());
+        assert_snapshot!(stateful_render_to_string(&worktree, &mut state));
+        worktree.test_action(
+            &mut state,
+            NavigationAction::PreviewWindowResize(Op::Sub(1)).into(),
+        );
+        assert_snapshot!(stateful_render_to_string(&worktree, &mut state));
+        worktree.test_action(
+            &mut state,
+            NavigationAction::PreviewWindowResize(Op::Add(3)).into(),
+        );
+        assert_snapshot!(stateful_render_to_string(&worktree, &mut state));
+        worktree.test_action(
+            &mut state,
+            NavigationAction::PreviewWindowResize(Op::Sub(100)).into(),
+        );
+        assert_snapshot!(stateful_render_to_string(&worktree, &mut state));
+        worktree.test_action(
+            &mut state,
+            NavigationAction::PreviewWindowResize(Op::Add(100)).into(),
+        );
+        assert_snapshot!(stateful_render_to_string(&worktree, &mut state));
+    }
+
+    #[test]
+    fn render_top_bottom_test() {
+        let mut worktree = WorkSpace::new(
+            Node::load(SAMPLE_JSON.as_bytes()).unwrap(),
+            Config::default(),
+        );
+        let mut state = WorkSpaceState::default();
+
+        for _ in 0..4 {
+            worktree.test_action(&mut state, NavigationAction::Expand.into());
+        }
+        worktree.test_action(&mut state, NavigationAction::Down(2).into());
+        worktree.test_action(&mut state, NavigationAction::Expand.into());
+
+        worktree.test_action(&mut state, NavigationAction::Bottom.into());
+        assert_snapshot!(stateful_render_to_string(&worktree, &mut state));
+
+        worktree.test_action(&mut state, NavigationAction::Top.into());
+        assert_snapshot!(stateful_render_to_string(&worktree, &mut state));
+    }
+
+    #[test]
+    fn render_top_bottom_preview_test() {
+        let mut worktree = WorkSpace::new(
+            Node::load(SAMPLE_JSON.as_bytes()).unwrap(),
+            Config::default(),
+        );
+        let mut state = WorkSpaceState::default();
+
+        worktree.test_action(&mut state, NavigationAction::TogglePreview.into());
+        for _ in 0..4 {
+            worktree.test_action(&mut state, NavigationAction::Expand.into());
+        }
+        worktree.test_action(&mut state, NavigationAction::Down(2).into());
+        worktree.test_action(&mut state, NavigationAction::Expand.into());
+
+        worktree.test_action(&mut state, NavigationAction::Bottom.into());
+        assert_snapshot!(stateful_render_to_string(&worktree, &mut state));
 
-fn new_list(work_tree_node: &WorkTreeNode) -> List<'static> {
-    List::new(work_tree_node.as_tree_string())
-        .highlight_style(Style::new().bg(SLATE.c800).add_modifier(Modifier::BOLD))
-        .highlight_symbol("> ")
-        .highlight_spacing(HighlightSpacing::Always)
-        .scroll_padding(1)
-}
+        worktree.test_action(&mut state, NavigationAction::Top.into());
+        assert_snapshot!(stateful_render_to_string(&worktree, &mut state));
+    }
 
-#[cfg(test)]
-mod test {
-    use byte_unit::Byte;
-    use crossterm::event::{KeyEvent, KeyEventKind, KeyEventState, KeyModifiers};
-    use insta::assert_snapshot;
+    #[test]
+    fn render_bottom_select_last_index_test() {
+        let mut worktree = WorkSpace::new(
+            Node::load(SAMPLE_JSON.as_bytes()).unwrap(),
+            Config::default(),
+        );
+        let mut state = WorkSpaceState::default();
 
-    use crate::{
-        app::component::test_render::stateful_render_to_string, container::node::NodeKind,
-        fixtures::SAMPLE_JSON,
-    };
+        worktree.test_action(&mut state, NavigationAction::Bottom.into());
+        worktree.test_action(&mut state, NavigationAction::Expand.into());
+        assert_snapshot!(stateful_render_to_string(&worktree, &mut state));
 
-    use super::*;
+        worktree.test_action(&mut state, NavigationAction::Bottom.into());
+        worktree.test_action(&mut state, NavigationAction::Expand.into());
+        assert_snapshot!(stateful_render_to_string(&worktree, &mut state));
+    }
 
     #[test]
-    fn event_handler_ignore_key_release_test() {
-        let json = String::from("123");
-        let worktree = WorkSpace::new(Node::load(json.as_bytes()).unwrap(), Config::default());
+    fn render_rename_root_test() {
+        let mut worktree = WorkSpace::new(
+            Node::load(SAMPLE_JSON.as_bytes()).unwrap(),
+            Config::default(),
+        );
+        let mut state = WorkSpaceState::default();
 
-        assert_event_to_action(
-            &worktree,
-            Event::Key(KeyEvent {
-                code: KeyCode::Up,
-                modifiers: KeyModifiers::empty(),
-                kind: KeyEventKind::Release,
-                state: KeyEventState::NONE,
-            }),
-            vec![],
+        worktree.test_action(
+            &mut state,
+            WorkSpaceAction::Rename(ConfirmAction::Request(())),
+        );
+        assert_snapshot!(stateful_render_to_string(&worktree, &mut state));
+    }
+
+    #[test]
+    fn render_invalid_test() {
+        let mut worktree = WorkSpace::new(
+            Node::load(SAMPLE_JSON.as_bytes()).unwrap(),
+            Config::default(),
+        );
+        let mut state = WorkSpaceState::default();
+
+        worktree.test_action(&mut state, NavigationAction::Expand.into());
+        worktree.test_action(&mut state, NavigationAction::Expand.into());
+        worktree.test_action(&mut state, NavigationAction::Expand.into());
+        worktree.test_action(
+            &mut state,
+            WorkSpaceAction::Rename(ConfirmAction::Request(())),
+        );
+        assert_snapshot!(stateful_render_to_string(&worktree, &mut state));
+    }
+
+    #[test]
+    fn render_rename_test() {
+        let mut worktree = WorkSpace::new(
+            Node::load(SAMPLE_JSON.as_bytes()).unwrap(),
+            Config::default(),
+        );
+        let mut state = WorkSpaceState::default();
+
+        worktree.test_action(&mut state, NavigationAction::Expand.into());
+        worktree.test_action(&mut state, NavigationAction::Expand.into());
+        worktree.test_action(
+            &mut state,
+            WorkSpaceAction::Rename(ConfirmAction::Request(())),
+        );
+        assert_snapshot!(stateful_render_to_string(&worktree, &mut state));
+
+        worktree.test_action(
+            &mut state,
+            WorkSpaceAction::Rename(ConfirmAction::Confirm(None)),
+        );
+        assert_snapshot!(stateful_render_to_string(&worktree, &mut state));
+
+        worktree.test_action(
+            &mut state,
+            WorkSpaceAction::Rename(ConfirmAction::Request(())),
+        );
+        worktree.test_action(
+            &mut state,
+            WorkSpaceAction::Rename(ConfirmAction::Confirm(Some(String::from("new_key")))),
+        );
+        assert_snapshot!(stateful_render_to_string(&worktree, &mut state));
+
+        worktree.test_action(
+            &mut state,
+            WorkSpaceAction::Rename(ConfirmAction::Request(())),
+        );
+        assert_snapshot!(stateful_render_to_string(&worktree, &mut state));
+    }
+
+    #[test]
+    fn render_rename_duplicate_test() {
+        let mut worktree = WorkSpace::new(
+            Node::load(SAMPLE_JSON.as_bytes()).unwrap(),
+            Config::default(),
+        );
+        let mut state = WorkSpaceState::default();
+
+        worktree.test_action(&mut state, NavigationAction::Expand.into());
+        worktree.test_action(&mut state, NavigationAction::Expand.into());
+        worktree.test_action(
+            &mut state,
+            WorkSpaceAction::Rename(ConfirmAction::Request(())),
+        );
+        worktree.test_action(
+            &mut state,
+            WorkSpaceAction::Rename(ConfirmAction::Confirm(Some(String::from("taglib")))),
+        );
+        assert_snapshot!(stateful_render_to_string(&worktree, &mut state));
+    }
+
+    #[test]
+    fn render_rename_does_not_change_test() {
+        let mut worktree = WorkSpace::new(
+            Node::load(SAMPLE_JSON.as_bytes()).unwrap(),
+            Config::default(),
+        );
+        let mut state = WorkSpaceState::default();
+
+        worktree.test_action(&mut state, NavigationAction::Expand.into());
+        worktree.test_action(&mut state, NavigationAction::Expand.into());
+        worktree.test_action(
+            &mut state,
+            WorkSpaceAction::Rename(ConfirmAction::Request(())),
+        );
+        worktree.test_action(
+            &mut state,
+            WorkSpaceAction::Rename(ConfirmAction::Confirm(Some(String::from("servlet")))),
+        );
+        assert_snapshot!(stateful_render_to_string(&worktree, &mut state));
+    }
+
+    #[test]
+    fn render_simple_delete_test() {
+        let mut worktree = WorkSpace::new(
+            Node::load(SAMPLE_JSON.as_bytes()).unwrap(),
+            Config::default(),
+        );
+        let mut state = WorkSpaceState::default();
+
+        worktree.test_action(&mut state, NavigationAction::Expand.into());
+        worktree.test_action(&mut state, NavigationAction::Expand.into());
+        worktree.test_action(
+            &mut state,
+            WorkSpaceAction::Delete(ConfirmAction::Request(UNNAMED_REGISTER)),
+        );
+        worktree.test_action(
+            &mut state,
+            WorkSpaceAction::Delete(ConfirmAction::Confirm(true)),
+        );
+        assert_snapshot!(stateful_render_to_string(&worktree, &mut state));
+
+        worktree.test_action(&mut state, NavigationAction::Down(2).into());
+        assert_snapshot!(stateful_render_to_string(&worktree, &mut state));
+    }
+
+    #[test]
+    fn render_delete_confirm_test() {
+        let mut worktree = WorkSpace::new(
+            Node::load(SAMPLE_JSON.as_bytes()).unwrap(),
+            Config::default(),
+        );
+        let mut state = WorkSpaceState::default();
+
+        worktree.test_action(&mut state, NavigationAction::Expand.into());
+        worktree.test_action(&mut state, NavigationAction::Expand.into());
+        worktree.test_action(
+            &mut state,
+            WorkSpaceAction::Delete(ConfirmAction::Request(UNNAMED_REGISTER)),
+        );
+        assert_snapshot!(stateful_render_to_string(&worktree, &mut state));
+    }
+
+    #[test]
+    fn restore_test() {
+        let mut worktree = WorkSpace::new(
+            Node::load(SAMPLE_JSON.as_bytes()).unwrap(),
+            Config::default(),
+        );
+        let mut state = WorkSpaceState::default();
+        let original = worktree.file_root().to_string_pretty().unwrap();
+
+        worktree.test_action(&mut state, WorkSpaceAction::ListTrash);
+        assert_snapshot!(
+            "render_trash_empty_test",
+            stateful_render_to_string(&worktree, &mut state)
+        );
+        worktree.test_action(&mut state, WorkSpaceAction::Restore(None));
+
+        worktree.test_action(&mut state, NavigationAction::Expand.into());
+        worktree.test_action(&mut state, NavigationAction::Expand.into());
+        worktree.test_action(&mut state, NavigationAction::Down(1).into());
+        for _ in 0..2 {
+            worktree.test_action(
+                &mut state,
+                WorkSpaceAction::Delete(ConfirmAction::Request(UNNAMED_REGISTER)),
+            );
+            worktree.test_action(
+                &mut state,
+                WorkSpaceAction::Delete(ConfirmAction::Confirm(true)),
+            );
+        }
+        worktree.test_action(&mut state, WorkSpaceAction::ListTrash);
+        assert_snapshot!(
+            "render_trash_test",
+            stateful_render_to_string(&worktree, &mut state)
+        );
+
+        // Restore the most recent deletion first so both land back at their original positions.
+        worktree.test_action(&mut state, WorkSpaceAction::Restore(Some(1)));
+        worktree.test_action(&mut state, WorkSpaceAction::Restore(Some(0)));
+        assert!(worktree.trash.is_empty());
+        assert_eq!(worktree.file_root().to_string_pretty().unwrap(), original);
+        assert_snapshot!(
+            "render_restore_test",
+            stateful_render_to_string(&worktree, &mut state)
+        );
+    }
+
+    #[test]
+    fn merge_conflicts_test() {
+        let load = |input: &str| Node::load(input.as_bytes()).unwrap();
+        let (node, conflicts) = crate::container::merge::merge(
+            &load(r#"{"name": "a", "port": 80, "debug": false}"#),
+            &load(r#"{"name": "b", "port": 81}"#),
+            &load(r#"{"name": "c", "port": 80, "debug": true}"#),
+        );
+        let mut worktree = WorkSpace::new(Node::null(), Config::default());
+        let mut state = WorkSpaceState::default();
+        worktree.test_action(&mut state, WorkSpaceAction::Merged { node, conflicts });
+        assert!(worktree.is_edited());
+
+        worktree.test_action(&mut state, WorkSpaceAction::ListConflicts);
+        assert_snapshot!(
+            "render_merge_conflicts_test",
+            stateful_render_to_string(&worktree, &mut state)
+        );
+        worktree.test_action(&mut state, WorkSpaceAction::ConflictPicked(Some(1)));
+        assert_snapshot!(
+            "render_merge_conflict_picked_test",
+            stateful_render_to_string(&worktree, &mut state)
+        );
+        worktree.test_action(
+            &mut state,
+            WorkSpaceAction::ResolveConflict {
+                conflict: 1,
+                side: Some(1),
+            },
+        );
+        worktree.test_action(&mut state, WorkSpaceAction::ConflictPicked(Some(0)));
+        worktree.test_action(
+            &mut state,
+            WorkSpaceAction::ResolveConflict {
+                conflict: 0,
+                side: Some(1),
+            },
+        );
+        assert!(worktree.conflicts.is_empty());
+        assert_eq!(
+            worktree.file_root().to_string_compact().unwrap(),
+            r#"{"name":"c","port":81,"debug":true}"#
+        );
+        assert_snapshot!(
+            "render_merge_resolved_test",
+            stateful_render_to_string(&worktree, &mut state)
+        );
+    }
+
+    #[test]
+    fn compact_test() {
+        let mut worktree = WorkSpace::new(
+            Node::load(r#"{"a": 1, "b": 2, "c": 3}"#.as_bytes()).unwrap(),
+            Config::default().with_compact_after_deletes(2),
+        );
+        let mut state = WorkSpaceState::default();
+        worktree.test_action(&mut state, NavigationAction::Expand.into());
+
+        let mut delete = |worktree: &mut WorkSpace| {
+            worktree.test_action(
+                &mut state,
+                WorkSpaceAction::Delete(ConfirmAction::Request(UNNAMED_REGISTER)),
+            );
+            worktree.test_action(
+                &mut state,
+                WorkSpaceAction::Delete(ConfirmAction::Confirm(true)),
+            )
+        };
+        assert!(delete(&mut worktree).is_empty());
+        assert_eq!(delete(&mut worktree), vec![JobAction::Compact.into()]);
+
+        let mut node = worktree.file_root().clone();
+        let reclaimed = node.compact();
+        worktree.test_action(&mut state, WorkSpaceAction::Compacted { node, reclaimed });
+        assert_eq!(worktree.deletes_since_compact, 0);
+        assert_eq!(
+            worktree.file_root().to_string_compact().unwrap(),
+            r#"{"c":3}"#
+        );
+        assert!(
+            worktree
+                .messages
+                .back()
+                .is_some_and(|message| message.starts_with("Compacted, reclaimed "))
         );
     }
 
     #[test]
-    fn event_handler_navigation_test() {
-        let json = String::from("123");
-        let worktree = WorkSpace::new(Node::load(json.as_bytes()).unwrap(), Config::default());
+    fn render_delete_rename_test() {
+        let mut worktree = WorkSpace::new(
+            Node::load(SAMPLE_JSON.as_bytes()).unwrap(),
+            Config::default(),
+        );
+        let mut state = WorkSpaceState::default();
 
-        for (key, action) in [
-            ((KeyCode::Up, KeyModifiers::NONE), NavigationAction::Up(1)),
-            (
-                (KeyCode::Char('k'), KeyModifiers::NONE),
-                NavigationAction::Up(1),
-            ),
-            (
-                (KeyCode::Char('u'), KeyModifiers::CONTROL),
-                NavigationAction::Up(10),
-            ),
-            (
-                (KeyCode::Down, KeyModifiers::NONE),
-                NavigationAction::Down(1),
-            ),
-            (
-                (KeyCode::Char('j'), KeyModifiers::NONE),
-                NavigationAction::Down(1),
-            ),
-            (
-                (KeyCode::Char('d'), KeyModifiers::CONTROL),
-                NavigationAction::Down(10),
-            ),
-            (
-                (KeyCode::Enter, KeyModifiers::NONE),
-                NavigationAction::Expand,
-            ),
-            (
-                (KeyCode::Char('l'), KeyModifiers::NONE),
-                NavigationAction::Expand,
-            ),
-            (
-                (KeyCode::Char(' '), KeyModifiers::NONE),
-                NavigationAction::Expand,
-            ),
-            (
-                (KeyCode::Char('h'), KeyModifiers::NONE),
-                NavigationAction::Close,
-            ),
-            (
-                (KeyCode::Char('p'), KeyModifiers::NONE),
-                NavigationAction::TogglePreview,
-            ),
-            (
-                (KeyCode::Char('K'), KeyModifiers::NONE),
-                NavigationAction::PreviewNavigation(PreviewNavigationAction::Up(1)),
-            ),
-            (
-                (KeyCode::Char('J'), KeyModifiers::NONE),
-                NavigationAction::PreviewNavigation(PreviewNavigationAction::Down(1)),
-            ),
-            (
-                (KeyCode::Char('U'), KeyModifiers::CONTROL),
-                NavigationAction::PreviewNavigation(PreviewNavigationAction::Up(5)),
-            ),
-            (
-                (KeyCode::Char('D'), KeyModifiers::CONTROL),
-                NavigationAction::PreviewNavigation(PreviewNavigationAction::Down(5)),
-            ),
-            (
-                (KeyCode::Char('H'), KeyModifiers::NONE),
-                NavigationAction::PreviewNavigation(PreviewNavigationAction::Left),
-            ),
-            (
-                (KeyCode::Char('L'), KeyModifiers::NONE),
-                NavigationAction::PreviewNavigation(PreviewNavigationAction::Right),
-            ),
-            (
-                (KeyCode::Left, KeyModifiers::CONTROL),
-                NavigationAction::PreviewWindowResize(Op::Add(1)),
-            ),
-            (
-                (KeyCode::Right, KeyModifiers::CONTROL),
-                NavigationAction::PreviewWindowResize(Op::Sub(1)),
-            ),
-        ] {
-            assert_key_event_to_action(&worktree, key, vec![action.into()]);
-        }
+        worktree.test_action(&mut state, NavigationAction::Expand.into());
+        worktree.test_action(&mut state, NavigationAction::Expand.into());
+        worktree.test_action(
+            &mut state,
+            WorkSpaceAction::Delete(ConfirmAction::Request(UNNAMED_REGISTER)),
+        );
+        worktree.test_action(
+            &mut state,
+            WorkSpaceAction::Delete(ConfirmAction::Confirm(true)),
+        );
+        worktree.test_action(
+            &mut state,
+            WorkSpaceAction::Rename(ConfirmAction::Request(())),
+        );
+        worktree.test_action(
+            &mut state,
+            WorkSpaceAction::Rename(ConfirmAction::Confirm(Some(String::from("new_key")))),
+        );
+
+        assert_snapshot!(stateful_render_to_string(&worktree, &mut state));
     }
 
     #[test]
-    fn event_handler_fileops_test() {
-        let json = String::from("123");
-        let worktree = WorkSpace::new(Node::load(json.as_bytes()).unwrap(), Config::default());
+    fn render_delete_preview_test() {
+        let mut worktree = WorkSpace::new(
+            Node::load(SAMPLE_JSON.as_bytes()).unwrap(),
+            Config::default(),
+        );
+        let mut state = WorkSpaceState::default();
 
-        for (key, action) in [
-            (
-                (KeyCode::Char('q'), KeyModifiers::NONE),
-                Action::Exit(ConfirmAction::Request(())),
-            ),
-            (
-                (KeyCode::Char('e'), KeyModifiers::NONE),
-                WorkSpaceAction::Edit.into(),
-            ),
-            (
-                (KeyCode::Char('w'), KeyModifiers::NONE),
-                WorkSpaceAction::Save(ConfirmAction::Request(())).into(),
-            ),
-        ] {
-            assert_key_event_to_action(&worktree, key, vec![action]);
-        }
+        worktree.test_action(&mut state, NavigationAction::TogglePreview.into());
+        worktree.test_action(&mut state, NavigationAction::Expand.into());
+        worktree.test_action(&mut state, NavigationAction::Expand.into());
+        worktree.test_action(
+            &mut state,
+            WorkSpaceAction::Delete(ConfirmAction::Request(UNNAMED_REGISTER)),
+        );
+        worktree.test_action(
+            &mut state,
+            WorkSpaceAction::Delete(ConfirmAction::Confirm(true)),
+        );
+
+        assert_snapshot!(stateful_render_to_string(&worktree, &mut state));
     }
 
     #[test]
-    fn event_handler_ignore_on_confirm_dialog() {
-        let json = String::from("123");
-        let mut worktree = WorkSpace::new(Node::load(json.as_bytes()).unwrap(), Config::default());
+    fn render_delete_and_go_to_bottom_test() {
+        let mut worktree = WorkSpace::new(
+            Node::load(SAMPLE_JSON.as_bytes()).unwrap(),
+            Config::default(),
+        );
         let mut state = WorkSpaceState::default();
+
+        worktree.test_action(&mut state, NavigationAction::Expand.into());
+        worktree.test_action(&mut state, NavigationAction::Expand.into());
         worktree.test_action(
             &mut state,
-            WorkSpaceAction::Save(ConfirmAction::Request(())),
+            WorkSpaceAction::Delete(ConfirmAction::Request(UNNAMED_REGISTER)),
+        );
+        worktree.test_action(
+            &mut state,
+            WorkSpaceAction::Delete(ConfirmAction::Confirm(true)),
         );
 
-        for key in [
-            (KeyCode::Char('q'), KeyModifiers::NONE),
-            (KeyCode::Char('e'), KeyModifiers::NONE),
-            (KeyCode::Char('w'), KeyModifiers::NONE),
-            (KeyCode::Char('k'), KeyModifiers::NONE),
-            (KeyCode::Up, KeyModifiers::NONE),
-        ] {
-            assert_key_event_to_action(&worktree, key, Vec::new());
-        }
+        worktree.test_action(&mut state, NavigationAction::Bottom.into());
+        worktree.test_action(
+            &mut state,
+            WorkSpaceAction::Delete(ConfirmAction::Request(UNNAMED_REGISTER)),
+        );
+        worktree.test_action(
+            &mut state,
+            WorkSpaceAction::Delete(ConfirmAction::Confirm(true)),
+        );
+        assert_snapshot!(stateful_render_to_string(&worktree, &mut state));
+    }
+
+    #[test]
+    fn render_delete_large_preview_test() {
+        let mut worktree = WorkSpace::new(
+            Node::load(SAMPLE_JSON.as_bytes()).unwrap(),
+            Config::default().with_max_preview_size(Byte::from_u64(3700)),
+        );
+        let mut state = WorkSpaceState::default();
 
+        worktree.test_action(&mut state, NavigationAction::TogglePreview.into());
+        worktree.test_action(&mut state, NavigationAction::Expand.into());
+        worktree.test_action(&mut state, NavigationAction::Expand.into());
         worktree.test_action(
             &mut state,
-            WorkSpaceAction::Save(ConfirmAction::Confirm(false)),
+            WorkSpaceAction::Delete(ConfirmAction::Request(UNNAMED_REGISTER)),
         );
-        assert_key_event_to_action(
-            &worktree,
-            (KeyCode::Up, KeyModifiers::NONE),
-            vec![NavigationAction::Up(1).into()],
+        worktree.test_action(
+            &mut state,
+            WorkSpaceAction::Delete(ConfirmAction::Confirm(true)),
         );
+
+        worktree.test_action(&mut state, NavigationAction::Top.into());
+        assert_snapshot!(stateful_render_to_string(&worktree, &mut state));
     }
 
     #[test]
-    fn handle_navigation_action() {
-        let json = String::from(r#"{"key": "string", "values": [1, 2, 3]}"#);
-        let mut worktree = WorkSpace::new(Node::load(json.as_bytes()).unwrap(), Config::default());
+    fn render_append_after_into_array_test() {
+        let mut worktree = WorkSpace::new(
+            Node::load(SAMPLE_JSON.as_bytes()).unwrap(),
+            Config::default().with_max_preview_size(Byte::from_u64(3700)),
+        );
         let mut state = WorkSpaceState::default();
+
+        worktree.test_action(&mut state, NavigationAction::TogglePreview.into());
+        worktree.test_action(&mut state, NavigationAction::Expand.into());
         worktree.test_action(&mut state, NavigationAction::Expand.into());
+        worktree.test_action(&mut state, NavigationAction::Expand.into());
+        worktree.test_action(&mut state, WorkSpaceAction::Add(ConfirmAction::Request(())));
         assert_snapshot!(stateful_render_to_string(&worktree, &mut state));
 
         worktree.test_action(&mut state, NavigationAction::Down(1).into());
-        worktree.test_action(&mut state, NavigationAction::Expand.into());
         assert_snapshot!(stateful_render_to_string(&worktree, &mut state));
 
-        for _ in 0..3 {
-            worktree.test_action(&mut state, NavigationAction::Up(1).into());
-        }
-        worktree.test_action(&mut state, NavigationAction::Close.into());
+        worktree.test_action(&mut state, NavigationAction::Down(4).into());
         assert_snapshot!(stateful_render_to_string(&worktree, &mut state));
     }
 
     #[test]
-    fn write_selected_test() {
-        let json = String::from(r#"{"key": "string", "values": [1, 2, 3]}"#);
-        let mut worktree = WorkSpace::new(Node::load(json.as_bytes()).unwrap(), Config::default());
+    fn render_append_after_into_object_test() {
+        let mut worktree = WorkSpace::new(
+            Node::load(SAMPLE_JSON.as_bytes()).unwrap(),
+            Config::default().with_max_preview_size(Byte::from_u64(3700)),
+        );
         let mut state = WorkSpaceState::default();
+
+        worktree.test_action(&mut state, NavigationAction::TogglePreview.into());
         worktree.test_action(&mut state, NavigationAction::Expand.into());
+        worktree.test_action(&mut state, NavigationAction::Expand.into());
+        worktree.test_action(&mut state, WorkSpaceAction::Add(ConfirmAction::Request(())));
+        assert_snapshot!(stateful_render_to_string(&worktree, &mut state));
+
+        worktree.test_action(
+            &mut state,
+            WorkSpaceAction::Add(ConfirmAction::Confirm(None)),
+        );
+        assert_snapshot!(stateful_render_to_string(&worktree, &mut state));
+
+        worktree.test_action(
+            &mut state,
+            WorkSpaceAction::Add(ConfirmAction::Confirm(Some(String::from("new_key")))),
+        );
+        assert_snapshot!(stateful_render_to_string(&worktree, &mut state));
 
         worktree.test_action(&mut state, NavigationAction::Down(1).into());
-        worktree.test_action(&mut state, NavigationAction::Expand.into());
-        worktree.test_action(&mut state, NavigationAction::Up(1).into());
+        assert_snapshot!(stateful_render_to_string(&worktree, &mut state));
 
-        let mut buffer = Vec::new();
-        worktree.write_selected(&state, &mut buffer).unwrap();
-        assert_eq!(String::from_utf8(buffer).unwrap(), "[\n  1,\n  2,\n  3\n]",)
+        worktree.test_action(&mut state, NavigationAction::Down(3).into());
+        assert_snapshot!(stateful_render_to_string(&worktree, &mut state));
     }
-
-    #[test]
-    fn load_selected_test() {
-        let json = String::from(r#"{"key": "string", "values": [1, 2, 3]}"#);
-        let mut worktree = WorkSpace::new(Node::load(json.as_bytes()).unwrap(), Config::default());
+
+    #[test]
+    fn render_append_after_into_object_key_exists_test() {
+        let mut worktree = WorkSpace::new(
+            Node::load(SAMPLE_JSON.as_bytes()).unwrap(),
+            Config::default().with_max_preview_size(Byte::from_u64(3700)),
+        );
         let mut state = WorkSpaceState::default();
-        worktree.test_action(&mut state, NavigationAction::Expand.into());
 
-        worktree.test_action(&mut state, NavigationAction::Down(1).into());
+        worktree.test_action(&mut state, NavigationAction::TogglePreview.into());
         worktree.test_action(&mut state, NavigationAction::Expand.into());
-        worktree.test_action(&mut state, NavigationAction::Up(1).into());
+        worktree.test_action(&mut state, NavigationAction::Expand.into());
+        worktree.test_action(&mut state, WorkSpaceAction::Add(ConfirmAction::Request(())));
 
         worktree.test_action(
             &mut state,
-            WorkSpaceAction::Load {
-                node: Node::load("[{}, 5]".as_bytes()).unwrap(),
-                is_edit: true,
-            },
+            WorkSpaceAction::Add(ConfirmAction::Confirm(Some(String::from("taglib")))),
         );
+        assert_snapshot!(stateful_render_to_string(&worktree, &mut state));
 
-        assert_eq!(
-            worktree.file_root().to_string_pretty().unwrap(),
-            "{\n  \"key\": \"string\",\n  \"values\": [\n    {},\n    5\n  ]\n}"
+        worktree.test_action(&mut state, WorkSpaceAction::ErrorConfirmed);
+        assert_snapshot!(stateful_render_to_string(&worktree, &mut state));
+
+        worktree.test_action(
+            &mut state,
+            WorkSpaceAction::Add(ConfirmAction::Confirm(Some(String::from("taglib2")))),
         );
+        assert_snapshot!(stateful_render_to_string(&worktree, &mut state));
     }
 
     #[test]
-    fn handle_edit_error_action_test() {
-        let json = String::from("123");
-        let mut worktree = WorkSpace::new(Node::load(json.as_bytes()).unwrap(), Config::default());
-        let mut state = WorkSpaceState::default();
-
-        let action = WorkSpaceAction::EditError(ConfirmAction::Request(String::from(
-            "Deserialization error: expected value at line 1 column 2",
-        )));
-        assert!(worktree.test_action(&mut state, action.clone()).is_empty());
-        assert_eq!(worktree.dialogs.len(), 1);
-        assert!(
-            worktree
-                .test_action(
-                    &mut state,
-                    WorkSpaceAction::EditError(ConfirmAction::Confirm(false))
-                )
-                .is_empty()
+    fn render_test_expand_expanded_test() {
+        let mut worktree = WorkSpace::new(
+            Node::load(SAMPLE_JSON.as_bytes()).unwrap(),
+            Config::default().with_max_preview_size(Byte::from_u64(3700)),
         );
-        assert!(worktree.dialogs.is_empty());
+        let mut state = WorkSpaceState::default();
 
-        assert!(worktree.test_action(&mut state, action.clone()).is_empty());
-        assert_eq!(worktree.dialogs.len(), 1);
-        assert_eq!(
-            worktree.test_action(
-                &mut state,
-                WorkSpaceAction::EditError(ConfirmAction::Confirm(true))
-            ),
-            vec![JobAction::Edit(EditJobAction::Open).into()]
-        );
-        assert!(worktree.dialogs.is_empty());
+        worktree.test_action(&mut state, NavigationAction::TogglePreview.into());
+        worktree.test_action(&mut state, NavigationAction::Expand.into());
+        worktree.test_action(&mut state, NavigationAction::Expand.into());
+        worktree.test_action(&mut state, NavigationAction::Top.into());
+        worktree.test_action(&mut state, NavigationAction::Expand.into());
+        assert_snapshot!(stateful_render_to_string(&worktree, &mut state));
     }
 
     #[test]
-    fn event_handler_dialog_test() {
-        let json = String::from("123");
-        let mut worktree = WorkSpace::new(Node::load(json.as_bytes()).unwrap(), Config::default());
+    fn render_sampled_array_test() {
+        let json = serde_json::to_string(&(0..100).collect::<Vec<_>>()).unwrap();
+        let mut worktree = WorkSpace::new(
+            Node::load(json.as_bytes()).unwrap(),
+            Config::default().with_array_sample_size(3),
+        );
         let mut state = WorkSpaceState::default();
 
+        worktree.test_action(&mut state, NavigationAction::TogglePreview.into());
+        worktree.test_action(&mut state, NavigationAction::Expand.into());
+        worktree.test_action(&mut state, NavigationAction::Down(3).into());
+        assert_snapshot!(stateful_render_to_string(&worktree, &mut state));
+
         worktree.test_action(
             &mut state,
-            WorkSpaceAction::EditError(ConfirmAction::Request(String::from(
-                "Deserialization error: expected value at line 1 column 2",
-            ))),
-        );
-        assert_key_event_to_action(
-            &worktree,
-            (KeyCode::Char('y'), KeyModifiers::NONE),
-            vec![WorkSpaceAction::EditError(ConfirmAction::Confirm(true)).into()],
+            WorkSpaceAction::Delete(ConfirmAction::Request(UNNAMED_REGISTER)),
         );
+        assert_eq!(worktree.dialogs.len(), 1);
+        worktree.test_action(&mut state, WorkSpaceAction::ErrorConfirmed);
+
+        worktree.test_action(&mut state, NavigationAction::Expand.into());
+        assert_eq!(worktree.work_tree_root.len(), 101);
+        assert_snapshot!(stateful_render_to_string(&worktree, &mut state));
     }
 
     #[test]
-    fn render_edit_error_test() {
-        let json = String::from("123");
-        let mut worktree = WorkSpace::new(Node::load(json.as_bytes()).unwrap(), Config::default());
+    fn elided_row_test() {
+        let json = serde_json::to_string(&(0..100).collect::<Vec<_>>()).unwrap();
+        let original = Node::load(json.as_bytes()).unwrap();
+        let mut worktree = WorkSpace::new(
+            original.clone(),
+            Config::default().with_array_sample_size(3),
+        );
         let mut state = WorkSpaceState::default();
-
-        for response in [true, false] {
-            worktree.test_action(
-                &mut state,
-                WorkSpaceAction::EditError(ConfirmAction::Request(String::from(
-                    "Deserialization error: expected value at line 1 column 2",
-                ))),
-            );
-            if response {
-                assert_snapshot!(stateful_render_to_string(
-                    &worktree,
-                    &mut WorkSpaceState::default()
-                ));
-            }
-
-            worktree.handle_edit_error_action(ConfirmAction::Confirm(response));
-            assert_snapshot!(stateful_render_to_string(
-                &worktree,
-                &mut WorkSpaceState::default()
-            ));
-        }
+        worktree.test_action(&mut state, NavigationAction::Expand.into());
+        worktree.test_action(&mut state, NavigationAction::Down(3).into());
+        let index = state.list_state.selected().unwrap();
+        assert_eq!(worktree.work_tree_root.elided(index), Some(94));
+
+        // Actions on the placeholder row must not reach the hidden element it starts at.
+        let mut assert_refused = |action: WorkSpaceAction| {
+            assert_eq!(worktree.test_action_chain(&mut state, action), []);
+            assert_eq!(worktree.dialogs.len(), 1);
+            worktree.test_action(&mut state, WorkSpaceAction::ErrorConfirmed);
+            assert_eq!(worktree.file_root, original);
+            assert!(!worktree.is_edited);
+        };
+        assert_refused(WorkSpaceAction::Stringify);
+        assert_refused(WorkSpaceAction::ExpandJson);
+        assert_refused(WorkSpaceAction::SetType(ConfirmAction::Request(())));
+        assert_refused(WorkSpaceAction::ImportFile(ConfirmAction::Request(())));
+        assert_refused(WorkSpaceAction::ImportFolder(ConfirmAction::Request(())));
+        assert_refused(WorkSpaceAction::ImportGron(ConfirmAction::Request(())));
+        assert_refused(WorkSpaceAction::ExportGron(ConfirmAction::Request(())));
+        assert_refused(WorkSpaceAction::Split(ConfirmAction::Request(())));
+
+        worktree.test_action(&mut state, WorkSpaceAction::Info);
+        assert_snapshot!(stateful_render_to_string(&worktree, &mut state));
     }
 
     #[test]
-    fn render_edit_error_long_message_test() {
-        let json = String::from("123");
-        let mut worktree = WorkSpace::new(Node::load(json.as_bytes()).unwrap(), Config::default());
+    fn render_jump_to_index_test() {
+        let json = serde_json::to_string(&serde_json::json!({
+            "key": "value",
+            "array": (0..100).collect::<Vec<_>>(),
+        }))
+        .unwrap();
+        let mut worktree = WorkSpace::new(
+            Node::load(json.as_bytes()).unwrap(),
+            Config::default().with_array_sample_size(3),
+        );
         let mut state = WorkSpaceState::default();
 
-        worktree.test_action(&mut state, WorkSpaceAction::EditError(ConfirmAction::Request(String::from(
-            concat!(
-                "Deserialization error: expected value at line 1 column 2. Lorem ipsum dolor sit amet,",
-                "consectetur adipiscing elit, sed do eiusmod tempor incididunt ut labore et dolore magna",
-                "aliqua.",
-            )
-        ))));
-
-        assert_snapshot!(stateful_render_to_string(
+        assert_key_event_to_action(
             &worktree,
-            &mut WorkSpaceState::default()
-        ));
-    }
-
-    #[test]
-    fn exit_without_change_test() {
-        let json = String::from("123");
-        let mut worktree = WorkSpace::new(Node::load(json.as_bytes()).unwrap(), Config::default());
-        assert!(worktree.maybe_exit(ConfirmAction::Request(())));
+            (KeyCode::Char('#'), KeyModifiers::NONE),
+            vec![WorkSpaceAction::JumpToIndex(ConfirmAction::Request(())).into()],
+        );
 
-        let mut state = WorkSpaceState::default();
         worktree.test_action(
             &mut state,
-            WorkSpaceAction::Load {
-                node: Node::load(String::from("456").as_bytes()).unwrap(),
-                is_edit: true,
-            },
+            WorkSpaceAction::JumpToIndex(ConfirmAction::Request(())),
         );
-        assert!(!worktree.maybe_exit(ConfirmAction::Request(())));
-        assert!(!worktree.maybe_exit(ConfirmAction::Confirm(false)));
+        assert_snapshot!(stateful_render_to_string(&worktree, &mut state));
+        worktree.test_action(&mut state, WorkSpaceAction::ErrorConfirmed);
 
+        worktree.test_action(&mut state, NavigationAction::Expand.into());
+        worktree.test_action(&mut state, NavigationAction::Down(1).into());
         worktree.test_action(
             &mut state,
-            WorkSpaceAction::Load {
-                node: Node::load(String::from("123").as_bytes()).unwrap(),
-                is_edit: true,
-            },
+            WorkSpaceAction::JumpToIndex(ConfirmAction::Request(())),
         );
-        assert!(!worktree.maybe_exit(ConfirmAction::Request(())));
-        assert!(worktree.maybe_exit(ConfirmAction::Confirm(true)));
+        worktree.test_action(
+            &mut state,
+            WorkSpaceAction::JumpToIndex(ConfirmAction::Confirm(Some(String::from("50")))),
+        );
+        assert!(worktree.dialogs.is_empty());
+        assert_eq!(
+            worktree
+                .work_tree_root
+                .selector(state.list_state.selected().unwrap()),
+            ["array", "50"]
+        );
+        assert_snapshot!(stateful_render_to_string(&worktree, &mut state));
 
+        worktree.test_action(&mut state, NavigationAction::Up(51).into());
         worktree.test_action(
             &mut state,
-            WorkSpaceAction::Load {
-                node: Node::load(String::from("123").as_bytes()).unwrap(),
-                is_edit: true,
-            },
+            WorkSpaceAction::JumpToIndex(ConfirmAction::Request(())),
         );
-        worktree.handle_save_done();
-        assert!(worktree.maybe_exit(ConfirmAction::Request(())));
+        worktree.test_action(
+            &mut state,
+            WorkSpaceAction::JumpToIndex(ConfirmAction::Confirm(Some(String::from("100")))),
+        );
+        assert_snapshot!(stateful_render_to_string(&worktree, &mut state));
     }
 
     #[test]
-    fn render_exit_confirm_test() {
-        let json = String::from("123");
+    fn search_test() {
+        let json = r#"{"a": {"id": 1, "b": {"id": 2}}, "c": [{"id": 3}], "d": "id"}"#;
         let mut worktree = WorkSpace::new(Node::load(json.as_bytes()).unwrap(), Config::default());
-
         let mut state = WorkSpaceState::default();
+        let selected = |worktree: &WorkSpace, state: &WorkSpaceState| {
+            to_pointer(
+                &worktree
+                    .work_tree_root
+                    .selector(state.list_state.selected().unwrap()),
+            )
+        };
+
+        assert_key_event_to_action(
+            &worktree,
+            (KeyCode::Char('/'), KeyModifiers::NONE),
+            vec![WorkSpaceAction::Search(ConfirmAction::Request(())).into()],
+        );
         worktree.test_action(
             &mut state,
-            WorkSpaceAction::Load {
-                node: Node::load(String::from("456").as_bytes()).unwrap(),
-                is_edit: true,
-            },
+            WorkSpaceAction::Search(ConfirmAction::Request(())),
+        );
+        assert_eq!(worktree.dialogs.len(), 1);
+        let mut actions = Actions::new();
+        worktree.dialogs.last().unwrap().handle_event(
+            &mut actions,
+            Event::Key(KeyEvent::new(KeyCode::Char('i'), KeyModifiers::NONE)),
+        );
+        assert_eq!(
+            actions.into_vec(),
+            vec![WorkSpaceAction::SearchInput(String::from("i")).into()]
         );
-        assert!(!worktree.maybe_exit(ConfirmAction::Request(())));
 
-        assert_snapshot!(stateful_render_to_string(&worktree, &mut state,));
-    }
+        worktree.test_action(&mut state, WorkSpaceAction::SearchInput(String::from("i")));
+        assert_eq!(selected(&worktree, &state), "/a/id");
+        worktree.test_action(
+            &mut state,
+            WorkSpaceAction::Search(ConfirmAction::Confirm(Some(String::from("id")))),
+        );
+        assert!(worktree.dialogs.is_empty());
+        assert_eq!(worktree.messages.back().unwrap(), "Match 1 of 3: /a/id");
 
-    #[test]
-    fn render_save_dialog_test() {
-        let json = String::from("123");
-        let mut worktree = WorkSpace::new(Node::load(json.as_bytes()).unwrap(), Config::default());
+        worktree.test_action(&mut state, WorkSpaceAction::SearchNext(false));
+        assert_eq!(selected(&worktree, &state), "/a/b/id");
+        worktree.test_action(&mut state, WorkSpaceAction::SearchNext(false));
+        assert_eq!(selected(&worktree, &state), "/c/0/id");
+        assert_snapshot!(stateful_render_to_string(&worktree, &mut state));
+        worktree.test_action(&mut state, WorkSpaceAction::SearchNext(false));
+        assert_eq!(selected(&worktree, &state), "/a/id");
+        worktree.test_action(&mut state, WorkSpaceAction::SearchNext(true));
+        assert_eq!(selected(&worktree, &state), "/c/0/id");
 
-        let mut state = WorkSpaceState::default();
         worktree.test_action(
             &mut state,
-            WorkSpaceAction::Save(ConfirmAction::Request(())),
+            WorkSpaceAction::Search(ConfirmAction::Confirm(Some(String::from("zz")))),
         );
-
-        assert_snapshot!(stateful_render_to_string(&worktree, &mut state,));
+        assert_eq!(worktree.messages.back().unwrap(), "No match for zz");
     }
 
     #[test]
-    fn render_preview_test() {
-        let json = serde_json::to_string_pretty(&serde_json::json!({
-            "key": "value",
-            "array": [1, 2, ["cat", "dog"]]
-        }))
-        .unwrap();
+    fn mouse_test() {
+        let json = r#"{"a": {"b": 1}, "c": [1, 2, 3, 4, 5, 6, 7, 8, 9, 10]}"#;
         let mut worktree = WorkSpace::new(Node::load(json.as_bytes()).unwrap(), Config::default());
         let mut state = WorkSpaceState::default();
-
-        worktree.test_action(&mut state, NavigationAction::TogglePreview.into());
-        assert_snapshot!(stateful_render_to_string(&worktree, &mut state));
+        let mouse = |kind, column, row| {
+            WorkSpaceAction::Mouse(MouseEvent {
+                kind,
+                column,
+                row,
+                modifiers: KeyModifiers::NONE,
+            })
+        };
+        let click = MouseEventKind::Down(MouseButton::Left);
 
         worktree.test_action(&mut state, NavigationAction::Expand.into());
-        assert_snapshot!(stateful_render_to_string(&worktree, &mut state));
-
-        worktree.test_action(&mut state, NavigationAction::Down(1).into());
-        assert_snapshot!(stateful_render_to_string(&worktree, &mut state));
-
         worktree.test_action(&mut state, NavigationAction::TogglePreview.into());
+        stateful_render_to_string(&worktree, &mut state);
+
+        // Rows start below the tree's top border.
+        worktree.test_action(&mut state, mouse(click, 5, 3));
+        assert_eq!(state.list_state.selected(), Some(2));
+        worktree.test_action(&mut state, mouse(click, 5, 3));
+        assert_eq!(state.list_state.selected(), Some(3));
+        assert_eq!(worktree.work_tree_root.len(), 13);
+        worktree.test_action(&mut state, mouse(click, 5, 3));
+        worktree.test_action(&mut state, mouse(click, 5, 3));
+        assert_eq!(worktree.work_tree_root.len(), 3);
+
+        worktree.test_action(&mut state, mouse(MouseEventKind::ScrollUp, 5, 5));
+        assert_eq!(state.list_state.selected(), Some(0));
+        worktree.test_action(&mut state, mouse(MouseEventKind::ScrollDown, 5, 5));
+        assert_eq!(state.list_state.selected(), Some(2));
+
+        let preview_area = state.preview_area.unwrap();
+        worktree.test_action(&mut state, mouse(click, preview_area.x + 5, 5));
+        assert_eq!(state.focus, Focus::Preview);
+        worktree.test_action(&mut state, mouse(click, 5, 10));
+        assert_eq!(state.focus, Focus::Tree);
+        assert_eq!(state.list_state.selected(), Some(2));
+
+        let border = state.preview_area.unwrap().x;
+        worktree.test_action(&mut state, mouse(click, border, 5));
+        worktree.test_action(
+            &mut state,
+            mouse(MouseEventKind::Drag(MouseButton::Left), 40, 5),
+        );
+        worktree.test_action(
+            &mut state,
+            mouse(MouseEventKind::Up(MouseButton::Left), 40, 5),
+        );
+        assert_eq!(worktree.preview_pct, 50);
+        worktree.test_action(
+            &mut state,
+            mouse(MouseEventKind::Drag(MouseButton::Left), 60, 5),
+        );
+        assert_eq!(worktree.preview_pct, 50);
         assert_snapshot!(stateful_render_to_string(&worktree, &mut state));
     }
 
     #[test]
-    fn preview_out_of_bound_test() {
-        let json = serde_json::to_string_pretty(&serde_json::json!({
-            "key": "value",
-            "array": [1, 2, ["cat", "dog"]]
-        }))
-        .unwrap();
+    fn go_to_test() {
+        let json = r#"{"a": {"b": [1, {"c/d": 2}]}, "e": 3}"#;
         let mut worktree = WorkSpace::new(Node::load(json.as_bytes()).unwrap(), Config::default());
         let mut state = WorkSpaceState::default();
-
-        for action in [
-            NavigationAction::TogglePreview,
-            NavigationAction::Up(1),
-            NavigationAction::Expand,
-            NavigationAction::Down(1),
-            NavigationAction::Down(1),
-            NavigationAction::Up(1),
-        ] {
-            worktree.test_action(&mut state, action.into());
+
+        assert_key_event_to_action(
+            &worktree,
+            (KeyCode::Char(':'), KeyModifiers::NONE),
+            vec![WorkSpaceAction::GoTo(ConfirmAction::Request(())).into()],
+        );
+        for query in ["/a/b/1/c~1d", "$.a.b[1]['c/d']"] {
+            worktree.test_action(&mut state, NavigationAction::Top.into());
+            worktree.test_action(
+                &mut state,
+                WorkSpaceAction::GoTo(ConfirmAction::Request(())),
+            );
+            worktree.test_action(
+                &mut state,
+                WorkSpaceAction::GoTo(ConfirmAction::Confirm(Some(String::from(query)))),
+            );
+            assert!(worktree.dialogs.is_empty());
+            assert_eq!(
+                worktree
+                    .work_tree_root
+                    .selector(state.list_state.selected().unwrap()),
+                ["a", "b", "1", "c/d"]
+            );
         }
+        assert_snapshot!(stateful_render_to_string(&worktree, &mut state));
+
+        worktree.test_action(
+            &mut state,
+            WorkSpaceAction::GoTo(ConfirmAction::Request(())),
+        );
+        worktree.test_action(
+            &mut state,
+            WorkSpaceAction::GoTo(ConfirmAction::Confirm(Some(String::from("/e/x")))),
+        );
+        assert_snapshot!(stateful_render_to_string(&worktree, &mut state));
+        worktree.dialogs.clear();
 
+        worktree.test_action(
+            &mut state,
+            WorkSpaceAction::GoTo(ConfirmAction::Confirm(Some(String::from("/a/x/1")))),
+        );
         assert_snapshot!(stateful_render_to_string(&worktree, &mut state));
+        let selector = vec![String::from("a"), String::from("x"), String::from("1")];
+        worktree.test_action(
+            &mut state,
+            WorkSpaceAction::CreatePath(ConfirmAction::Confirm(Some(selector))),
+        );
+        assert!(worktree.dialogs.is_empty());
+        assert_eq!(
+            worktree
+                .work_tree_root
+                .selector(state.list_state.selected().unwrap()),
+            ["a", "x", "1"]
+        );
+        assert_eq!(
+            worktree.file_root.subtree(&["a", "x"]).unwrap(),
+            &Node::load("[null, null]".as_bytes()).unwrap()
+        );
+        assert!(worktree.is_edited);
     }
 
     #[test]
-    fn render_preview_scroll_test() {
-        let mut worktree = WorkSpace::new(
-            Node::load(SAMPLE_JSON.as_bytes()).unwrap(),
-            Config::default(),
-        );
+    fn search_values_test() {
+        let json = r#"{"a": {"id": 1}, "d": "id"}"#;
+        let config = Config {
+            search_values: true,
+            ..Config::default()
+        };
+        let mut worktree = WorkSpace::new(Node::load(json.as_bytes()).unwrap(), config);
         let mut state = WorkSpaceState::default();
 
-        for action in [NavigationAction::TogglePreview, NavigationAction::Expand] {
-            worktree.test_action(&mut state, action.into());
-        }
-
-        for action in [
-            PreviewNavigationAction::Up(1),
-            PreviewNavigationAction::Down(1),
-            PreviewNavigationAction::Down(1),
-            PreviewNavigationAction::Up(1),
-            PreviewNavigationAction::Right,
-            PreviewNavigationAction::Right,
-            PreviewNavigationAction::Left,
-        ] {
-            worktree.test_action(&mut state, action.into());
-            assert_snapshot!(stateful_render_to_string(&worktree, &mut state));
-        }
+        worktree.test_action(
+            &mut state,
+            WorkSpaceAction::Search(ConfirmAction::Confirm(Some(String::from("ID")))),
+        );
+        assert_eq!(worktree.messages.back().unwrap(), "Match 1 of 2: /a/id");
+        worktree.test_action(&mut state, WorkSpaceAction::SearchNext(true));
+        assert_eq!(worktree.messages.back().unwrap(), "Match 2 of 2: /d");
     }
 
     #[test]
-    fn render_preview_overflow_scroll_test() {
+    fn render_info_test() {
         let mut worktree = WorkSpace::new(
             Node::load(SAMPLE_JSON.as_bytes()).unwrap(),
             Config::default(),
         );
         let mut state = WorkSpaceState::default();
 
-        for action in [NavigationAction::TogglePreview, NavigationAction::Expand] {
-            worktree.test_action(&mut state, action.into());
-        }
+        assert_key_event_to_action(
+            &worktree,
+            (KeyCode::Char('i'), KeyModifiers::NONE),
+            vec![WorkSpaceAction::Info.into()],
+        );
+
+        worktree.test_action(&mut state, WorkSpaceAction::Info);
+        assert_snapshot!(stateful_render_to_string(&worktree, &mut state));
+        worktree.test_action(&mut state, WorkSpaceAction::ErrorConfirmed);
 
         for action in [
-            PreviewNavigationAction::Down(3),
-            PreviewNavigationAction::Down(100),
-            PreviewNavigationAction::Up(3),
-            PreviewNavigationAction::Up(100),
+            NavigationAction::Expand,
+            NavigationAction::Expand,
+            NavigationAction::Expand,
+            NavigationAction::Expand,
         ] {
             worktree.test_action(&mut state, action.into());
-            assert_snapshot!(stateful_render_to_string(&worktree, &mut state));
         }
+        worktree.test_action(&mut state, WorkSpaceAction::Info);
+        assert_snapshot!(stateful_render_to_string(&worktree, &mut state));
     }
 
     #[test]
-    fn render_preview_update_on_edit_test() {
+    fn render_largest_subtrees_test() {
         let mut worktree = WorkSpace::new(
             Node::load(SAMPLE_JSON.as_bytes()).unwrap(),
             Config::default(),
         );
         let mut state = WorkSpaceState::default();
 
-        worktree.test_action(&mut state, NavigationAction::TogglePreview.into());
+        assert_key_event_to_action(
+            &worktree,
+            (KeyCode::Char('B'), KeyModifiers::NONE),
+            vec![WorkSpaceAction::LargestSubtrees.into()],
+        );
+
+        worktree.test_action(&mut state, WorkSpaceAction::LargestSubtrees);
+        assert_snapshot!(stateful_render_to_string(&worktree, &mut state));
+
+        let (selector, _) = worktree.file_root.largest_subtrees(2).pop().unwrap();
+        let selector = KeyPath::from(selector);
         worktree.test_action(
             &mut state,
-            WorkSpaceAction::Load {
-                node: Node::load("123".as_bytes()).unwrap(),
-                is_edit: true,
-            },
+            WorkSpaceAction::SelectPath(Some(selector.clone())),
+        );
+        assert!(worktree.dialogs.is_empty());
+        assert_eq!(
+            worktree
+                .work_tree_root
+                .selector(state.list_state.selected().unwrap()),
+            selector
         );
-
         assert_snapshot!(stateful_render_to_string(&worktree, &mut state));
     }
 
     #[test]
-    fn render_preview_overlap_test() {
+    fn validate_test() {
         let mut worktree = WorkSpace::new(
-            Node::load(SAMPLE_JSON.as_bytes()).unwrap(),
+            Node::load(r#"{"name": "api", "servers": [{"port": 80}, {"port": "443"}]}"#.as_bytes())
+                .unwrap(),
             Config::default(),
         );
         let mut state = WorkSpaceState::default();
 
-        worktree.test_action(&mut state, NavigationAction::TogglePreview.into());
+        assert_key_event_to_action(
+            &worktree,
+            (KeyCode::Char('V'), KeyModifiers::NONE),
+            vec![WorkSpaceAction::Validate.into()],
+        );
+
+        worktree.test_action(&mut state, WorkSpaceAction::Validate);
+        assert_eq!(worktree.dialogs.len(), 1);
+        worktree.dialogs.pop();
+
+        worktree.set_schema(Schema::new(serde_json::json!({
+            "required": ["name", "version"],
+            "properties": {
+                "servers": {"items": {"properties": {"port": {"type": "integer"}}}}
+            }
+        })));
+        worktree.test_action(&mut state, WorkSpaceAction::Validate);
+        assert_snapshot!(stateful_render_to_string(&worktree, &mut state));
+
         worktree.test_action(
             &mut state,
-            WorkSpaceAction::Load {
-                node: Node::load(SAMPLE_JSON.as_bytes()).unwrap(),
-                is_edit: true,
-            },
+            WorkSpaceAction::SelectPath(Some(KeyPath::from(["servers", "1", "port"]))),
+        );
+        assert!(worktree.dialogs.is_empty());
+        assert_eq!(
+            worktree
+                .work_tree_root
+                .selector(state.list_state.selected().unwrap()),
+            ["servers", "1", "port"]
         );
-        worktree.maybe_exit(ConfirmAction::Request(()));
-        assert_snapshot!(stateful_render_to_string(&worktree, &mut state));
 
-        worktree.maybe_exit(ConfirmAction::Confirm(false));
-        assert_snapshot!(stateful_render_to_string(&worktree, &mut state));
+        worktree.select_path(&mut state, &["servers", "0"]);
+        worktree.test_action(&mut state, WorkSpaceAction::Validate);
+        assert!(worktree.dialogs.is_empty());
+        assert_eq!(worktree.messages.back().unwrap(), "/servers/0 is valid");
     }
 
     #[test]
-    fn meta_test() {
-        let mut worktree = WorkSpace::new(
+    fn render_budget_warning_test() {
+        let worktree = WorkSpace::new(
             Node::load(SAMPLE_JSON.as_bytes()).unwrap(),
-            Config::default(),
+            Config::default().with_max_document_size(Byte::from_u64(1024)),
         );
+        let mut state = WorkSpaceState::default();
+        assert_snapshot!(stateful_render_to_string(&worktree, &mut state));
 
-        assert_eq!(
-            worktree.meta_on_index(0),
-            NodeMeta {
-                n_lines: 100,
-                n_bytes: 3718,
-                kind: NodeKind::Object,
-            }
+        let worktree = WorkSpace::new(
+            Node::load(SAMPLE_JSON.as_bytes()).unwrap(),
+            Config::default().with_max_document_size(Byte::from_u64(1024 * 1024)),
         );
+        assert!(worktree.budget_warning().is_none());
     }
 
     #[test]
-    fn render_loading_test() {
+    fn split_test() {
         let mut worktree = WorkSpace::new(
             Node::load(SAMPLE_JSON.as_bytes()).unwrap(),
             Config::default(),
         );
         let mut state = WorkSpaceState::default();
 
-        worktree.test_action(&mut state, NavigationAction::TogglePreview.into());
-        worktree.set_loading(true);
+        assert_key_event_to_action(
+            &worktree,
+            (KeyCode::Char('S'), KeyModifiers::NONE),
+            vec![WorkSpaceAction::Split(ConfirmAction::Request(())).into()],
+        );
+
+        worktree.test_action(&mut state, NavigationAction::Expand.into());
+        worktree.test_action(&mut state, NavigationAction::Down(1).into());
+        worktree.test_action(
+            &mut state,
+            WorkSpaceAction::Split(ConfirmAction::Request(())),
+        );
         assert_snapshot!(stateful_render_to_string(&worktree, &mut state));
 
-        worktree.set_loading(false);
+        let mut actions = Actions::new();
+        worktree
+            .handle_action(
+                &mut state,
+                &mut actions,
+                WorkSpaceAction::Split(ConfirmAction::Confirm(Some(String::from(
+                    "out/{key}.json",
+                )))),
+            )
+            .unwrap();
+        assert!(worktree.dialogs.is_empty());
+        let actions = actions.into_vec();
+        let [Action::ExecuteJob(JobAction::WriteFiles(files))] = actions.as_slice() else {
+            panic!("unexpected actions: {actions:?}");
+        };
+        assert_eq!(
+            files
+                .iter()
+                .map(|(path, _)| path.as_str())
+                .collect::<Vec<_>>(),
+            vec![
+                "out/servlet.json",
+                "out/servlet-mapping.json",
+                "out/taglib.json"
+            ]
+        );
+        assert_eq!(
+            files[2].1,
+            worktree
+                .file_root
+                .subtree(&["web-app", "taglib"])
+                .unwrap()
+                .to_string_pretty()
+                .unwrap()
+        );
+
+        worktree.test_action(&mut state, WorkSpaceAction::SplitDone(3));
         assert_snapshot!(stateful_render_to_string(&worktree, &mut state));
     }
 
     #[test]
-    fn render_large_preview_test() {
-        let json_bodies: Vec<_> = std::iter::repeat_n(SAMPLE_JSON, 1024).collect();
-        let json = String::from("[") + &json_bodies.join(",") + "]";
-        let mut worktree = WorkSpace::new(Node::load(json.as_bytes()).unwrap(), Config::default());
+    fn compare_test() {
+        let mut worktree = WorkSpace::new(
+            Node::load(r#"{"a": 1, "b": {"c": 2}, "d": [1, 2], "e": "x"}"#.as_bytes()).unwrap(),
+            Config::default(),
+        );
         let mut state = WorkSpaceState::default();
 
-        worktree.test_action(&mut state, NavigationAction::TogglePreview.into());
-        assert_snapshot!(stateful_render_to_string(&worktree, &mut state));
+        assert_key_event_to_action(
+            &worktree,
+            (KeyCode::Char('='), KeyModifiers::NONE),
+            vec![WorkSpaceAction::Compare(ConfirmAction::Request(())).into()],
+        );
+
+        worktree.test_action(
+            &mut state,
+            WorkSpaceAction::Compare(ConfirmAction::Request(())),
+        );
+        let mut actions = Actions::new();
+        worktree
+            .handle_action(
+                &mut state,
+                &mut actions,
+                WorkSpaceAction::Compare(ConfirmAction::Confirm(Some(String::from("prod.json")))),
+            )
+            .unwrap();
+        assert!(worktree.dialogs.is_empty());
+        assert_eq!(
+            actions.into_vec(),
+            vec![JobAction::Compare(String::from("prod.json")).into()]
+        );
 
         worktree.test_action(&mut state, NavigationAction::Expand.into());
+        worktree.test_action(&mut state, NavigationAction::TogglePreview.into());
+        worktree.test_action(
+            &mut state,
+            WorkSpaceAction::Compared {
+                name: String::from("prod.json"),
+                node: Node::load(r#"{"a": 1, "b": {"c": 3}, "d": [1, 2, 3]}"#.as_bytes()).unwrap(),
+            },
+        );
+        assert_eq!(
+            worktree.messages.back().unwrap(),
+            "Compared with prod.json: 1 added, 1 removed, 1 changed"
+        );
+        worktree.test_action(&mut state, NavigationAction::Down(1).into());
         assert_snapshot!(stateful_render_to_string(&worktree, &mut state));
 
-        worktree.test_action(&mut state, NavigationAction::Up(1).into());
-        assert_snapshot!(stateful_render_to_string(&worktree, &mut state));
+        worktree.test_action(
+            &mut state,
+            WorkSpaceAction::Compare(ConfirmAction::Request(())),
+        );
+        worktree.test_action(
+            &mut state,
+            WorkSpaceAction::Compare(ConfirmAction::Confirm(Some(String::new()))),
+        );
+        assert!(worktree.compare.is_none());
+        assert_eq!(
+            worktree.messages.back().unwrap(),
+            "Stopped comparing with prod.json"
+        );
     }
 
     #[test]
-    fn render_preview_limited_by_config_test() {
-        let config = Config::default().with_max_preview_size(Byte::from_u64(3718));
-        let mut worktree = WorkSpace::new(Node::load(SAMPLE_JSON.as_bytes()).unwrap(), config);
-        assert_eq!(worktree.file_root.to_string_pretty().unwrap().len(), 3718);
+    fn import_folder_test() {
+        let mut worktree = WorkSpace::new(
+            Node::load(SAMPLE_JSON.as_bytes()).unwrap(),
+            Config::default(),
+        );
         let mut state = WorkSpaceState::default();
 
-        worktree.test_action(&mut state, NavigationAction::TogglePreview.into());
-        assert_snapshot!(stateful_render_to_string(&worktree, &mut state));
+        assert_key_event_to_action(
+            &worktree,
+            (KeyCode::Char('F'), KeyModifiers::NONE),
+            vec![WorkSpaceAction::ImportFolder(ConfirmAction::Request(())).into()],
+        );
 
-        let config = Config::default().with_max_preview_size(Byte::from_u64(3717));
-        let mut worktree = WorkSpace::new(Node::load(SAMPLE_JSON.as_bytes()).unwrap(), config);
-        worktree.test_action(&mut state, NavigationAction::TogglePreview.into());
+        worktree.test_action(
+            &mut state,
+            WorkSpaceAction::ImportFolder(ConfirmAction::Request(())),
+        );
         assert_snapshot!(stateful_render_to_string(&worktree, &mut state));
+
+        let mut actions = Actions::new();
+        worktree
+            .handle_action(
+                &mut state,
+                &mut actions,
+                WorkSpaceAction::ImportFolder(ConfirmAction::Confirm(Some(String::from(
+                    "./parts",
+                )))),
+            )
+            .unwrap();
+        assert!(worktree.dialogs.is_empty());
+        assert_eq!(
+            actions.into_vec(),
+            vec![JobAction::ImportFolder(String::from("./parts")).into()]
+        );
     }
 
     #[test]
-    fn render_navigation_far_test() {
+    fn gron_test() {
         let mut worktree = WorkSpace::new(
             Node::load(SAMPLE_JSON.as_bytes()).unwrap(),
             Config::default(),
         );
         let mut state = WorkSpaceState::default();
 
-        worktree.test_action(&mut state, NavigationAction::TogglePreview.into());
-        worktree.test_action(&mut state, NavigationAction::Expand.into());
-        worktree.test_action(&mut state, NavigationAction::Expand.into());
-        worktree.test_action(&mut state, NavigationAction::Expand.into());
-        worktree.test_action(&mut state, NavigationAction::Expand.into());
-        worktree.test_action(&mut state, NavigationAction::Down(2).into());
-        worktree.test_action(&mut state, NavigationAction::Expand.into());
-        worktree.test_action(&mut state, NavigationAction::Down(10).into());
-        assert_snapshot!(stateful_render_to_string(&worktree, &mut state));
-        worktree.test_action(&mut state, NavigationAction::Down(100).into());
-        assert_snapshot!(stateful_render_to_string(&worktree, &mut state));
-        worktree.test_action(&mut state, NavigationAction::Up(100).into());
-        assert_snapshot!(stateful_render_to_string(&worktree, &mut state));
+        assert_key_event_to_action(
+            &worktree,
+            (KeyCode::Char('X'), KeyModifiers::NONE),
+            vec![WorkSpaceAction::ExportGron(ConfirmAction::Request(())).into()],
+        );
+        assert_key_event_to_action(
+            &worktree,
+            (KeyCode::Char('U'), KeyModifiers::NONE),
+            vec![WorkSpaceAction::ImportGron(ConfirmAction::Request(())).into()],
+        );
+
+        let expected = gron::to_gron(&worktree.file_root);
+
+        worktree.test_action(
+            &mut state,
+            WorkSpaceAction::ExportGron(ConfirmAction::Request(())),
+        );
+        assert_eq!(worktree.dialogs.len(), 1);
+        let [Action::ExecuteJob(JobAction::ExportGron { path, content })] = &worktree.test_action(
+            &mut state,
+            WorkSpaceAction::ExportGron(ConfirmAction::Confirm(Some(String::from("out.gron")))),
+        )[..] else {
+            panic!("export with a path does not write the file");
+        };
+        assert!(worktree.dialogs.is_empty());
+        assert_eq!(path, "out.gron");
+        assert_eq!(content, &expected);
+
+        worktree.test_action(
+            &mut state,
+            WorkSpaceAction::ExportGron(ConfirmAction::Request(())),
+        );
+        assert_eq!(
+            worktree.test_action(
+                &mut state,
+                WorkSpaceAction::ExportGron(ConfirmAction::Confirm(Some(String::new()))),
+            ),
+            vec![
+                JobAction::Copy {
+                    content: expected,
+                    run_tools: true,
+                }
+                .into()
+            ]
+        );
+
+        worktree.test_action(
+            &mut state,
+            WorkSpaceAction::ImportGron(ConfirmAction::Request(())),
+        );
+        assert_eq!(
+            worktree.test_action(
+                &mut state,
+                WorkSpaceAction::ImportGron(ConfirmAction::Confirm(Some(String::from("in.gron")))),
+            ),
+            vec![JobAction::ImportGron(String::from("in.gron")).into()]
+        );
+        assert!(worktree.dialogs.is_empty());
     }
 
     #[test]
-    fn render_preview_resize_test() {
+    fn import_file_test() {
         let mut worktree = WorkSpace::new(
             Node::load(SAMPLE_JSON.as_bytes()).unwrap(),
             Config::default(),
         );
         let mut state = WorkSpaceState::default();
 
-        worktree.test_action(&mut state, NavigationAction::TogglePreview.into());
-        worktree.test_action(&mut state, NavigationAction::Expand.into());
-        assert_snapshot!(stateful_render_to_string(&worktree, &mut state));
-        worktree.test_action(
-            &mut state,
-            NavigationAction::PreviewWindowResize(Op::Sub(1)).into(),
+        assert_key_event_to_action(
+            &worktree,
+            (KeyCode::Char('I'), KeyModifiers::NONE),
+            vec![WorkSpaceAction::ImportFile(ConfirmAction::Request(())).into()],
         );
-        assert_snapshot!(stateful_render_to_string(&worktree, &mut state));
+
         worktree.test_action(
             &mut state,
-            NavigationAction::PreviewWindowResize(Op::Add(3)).into(),
+            WorkSpaceAction::ImportFile(ConfirmAction::Request(())),
         );
-        assert_snapshot!(stateful_render_to_string(&worktree, &mut state));
-        worktree.test_action(
-            &mut state,
-            NavigationAction::PreviewWindowResize(Op::Sub(100)).into(),
+        assert_eq!(worktree.dialogs.len(), 1);
+        assert_eq!(
+            worktree.test_action(
+                &mut state,
+                WorkSpaceAction::ImportFile(ConfirmAction::Confirm(Some(String::from(
+                    "./parts/a.json"
+                )))),
+            ),
+            vec![JobAction::ImportFile(String::from("./parts/a.json")).into()]
         );
-        assert_snapshot!(stateful_render_to_string(&worktree, &mut state));
+        assert!(worktree.dialogs.is_empty());
+
         worktree.test_action(
             &mut state,
-            NavigationAction::PreviewWindowResize(Op::Add(100)).into(),
+            WorkSpaceAction::ImportFile(ConfirmAction::Request(())),
         );
-        assert_snapshot!(stateful_render_to_string(&worktree, &mut state));
+        assert_eq!(
+            worktree.test_action(
+                &mut state,
+                WorkSpaceAction::ImportFile(ConfirmAction::Confirm(None)),
+            ),
+            vec![]
+        );
+        assert!(worktree.dialogs.is_empty());
     }
 
     #[test]
-    fn render_top_bottom_test() {
+    fn tab_key_test() {
         let mut worktree = WorkSpace::new(
             Node::load(SAMPLE_JSON.as_bytes()).unwrap(),
             Config::default(),
         );
-        let mut state = WorkSpaceState::default();
-
-        for _ in 0..4 {
-            worktree.test_action(&mut state, NavigationAction::Expand.into());
-        }
-        worktree.test_action(&mut state, NavigationAction::Down(2).into());
-        worktree.test_action(&mut state, NavigationAction::Expand.into());
-
-        worktree.test_action(&mut state, NavigationAction::Bottom.into());
-        assert_snapshot!(stateful_render_to_string(&worktree, &mut state));
+        let g = (KeyCode::Char('g'), KeyModifiers::NONE);
+        assert_key_event_to_action(&worktree, g, vec![NavigationAction::Top.into()]);
 
-        worktree.test_action(&mut state, NavigationAction::Top.into());
-        assert_snapshot!(stateful_render_to_string(&worktree, &mut state));
+        worktree.set_has_tabs(true);
+        assert_key_event_to_action(&worktree, g, vec![]);
+        assert_key_event_to_action(
+            &worktree,
+            (KeyCode::Char('t'), KeyModifiers::NONE),
+            vec![Action::SwitchTab(false)],
+        );
+        assert_key_event_to_action(&worktree, g, vec![]);
+        assert_key_event_to_action(
+            &worktree,
+            (KeyCode::Char('T'), KeyModifiers::NONE),
+            vec![Action::SwitchTab(true)],
+        );
+        assert_key_event_to_action(&worktree, g, vec![]);
+        assert_key_event_to_action(&worktree, g, vec![NavigationAction::Top.into()]);
+        assert_key_event_to_action(&worktree, g, vec![]);
+        assert_key_event_to_action(
+            &worktree,
+            (KeyCode::Char('j'), KeyModifiers::NONE),
+            vec![
+                NavigationAction::Top.into(),
+                NavigationAction::Down(1).into(),
+            ],
+        );
     }
 
     #[test]
-    fn render_top_bottom_preview_test() {
-        let mut worktree = WorkSpace::new(
+    fn register_key_test() {
+        let worktree = WorkSpace::new(
             Node::load(SAMPLE_JSON.as_bytes()).unwrap(),
             Config::default(),
         );
-        let mut state = WorkSpaceState::default();
 
-        worktree.test_action(&mut state, NavigationAction::TogglePreview.into());
-        for _ in 0..4 {
-            worktree.test_action(&mut state, NavigationAction::Expand.into());
+        assert_key_event_to_action(
+            &worktree,
+            (KeyCode::Char('y'), KeyModifiers::NONE),
+            vec![WorkSpaceAction::Yank(UNNAMED_REGISTER).into()],
+        );
+        for c in ['"', 'a'] {
+            assert_key_event_to_action(&worktree, (KeyCode::Char(c), KeyModifiers::NONE), vec![]);
         }
-        worktree.test_action(&mut state, NavigationAction::Down(2).into());
-        worktree.test_action(&mut state, NavigationAction::Expand.into());
-
-        worktree.test_action(&mut state, NavigationAction::Bottom.into());
-        assert_snapshot!(stateful_render_to_string(&worktree, &mut state));
-
-        worktree.test_action(&mut state, NavigationAction::Top.into());
-        assert_snapshot!(stateful_render_to_string(&worktree, &mut state));
+        assert_key_event_to_action(
+            &worktree,
+            (KeyCode::Char('P'), KeyModifiers::NONE),
+            vec![
+                WorkSpaceAction::Paste {
+                    register: 'a',
+                    key: ConfirmAction::Request(()),
+                }
+                .into(),
+            ],
+        );
+        assert_key_event_to_action(
+            &worktree,
+            (KeyCode::Char('P'), KeyModifiers::NONE),
+            vec![
+                WorkSpaceAction::Paste {
+                    register: UNNAMED_REGISTER,
+                    key: ConfirmAction::Request(()),
+                }
+                .into(),
+            ],
+        );
+        for c in ['"', '1'] {
+            assert_key_event_to_action(&worktree, (KeyCode::Char(c), KeyModifiers::NONE), vec![]);
+        }
+        assert_key_event_to_action(
+            &worktree,
+            (KeyCode::Char('y'), KeyModifiers::NONE),
+            vec![WorkSpaceAction::Yank(UNNAMED_REGISTER).into()],
+        );
+        assert_key_event_to_action(
+            &worktree,
+            (KeyCode::Char('R'), KeyModifiers::NONE),
+            vec![WorkSpaceAction::ListRegisters.into()],
+        );
     }
 
     #[test]
-    fn render_bottom_select_last_index_test() {
+    fn expand_all_test() {
         let mut worktree = WorkSpace::new(
             Node::load(SAMPLE_JSON.as_bytes()).unwrap(),
             Config::default(),
         );
         let mut state = WorkSpaceState::default();
+        assert_key_event_to_action(
+            &worktree,
+            (KeyCode::Char('E'), KeyModifiers::NONE),
+            vec![WorkSpaceAction::ExpandAll(ConfirmAction::Request(())).into()],
+        );
+        assert_key_event_to_action(
+            &worktree,
+            (KeyCode::Char('Z'), KeyModifiers::NONE),
+            vec![NavigationAction::CollapseAll.into()],
+        );
 
-        worktree.test_action(&mut state, NavigationAction::Bottom.into());
         worktree.test_action(&mut state, NavigationAction::Expand.into());
+        worktree.test_action(
+            &mut state,
+            WorkSpaceAction::ExpandAll(ConfirmAction::Request(())),
+        );
+        assert!(worktree.dialogs.is_empty());
         assert_snapshot!(stateful_render_to_string(&worktree, &mut state));
 
-        worktree.test_action(&mut state, NavigationAction::Bottom.into());
-        worktree.test_action(&mut state, NavigationAction::Expand.into());
+        worktree.test_action(&mut state, NavigationAction::CollapseAll.into());
         assert_snapshot!(stateful_render_to_string(&worktree, &mut state));
     }
 
     #[test]
-    fn render_rename_root_test() {
-        let mut worktree = WorkSpace::new(
-            Node::load(SAMPLE_JSON.as_bytes()).unwrap(),
-            Config::default(),
-        );
+    fn expand_all_guard_test() {
+        let json = format!("[{}]", vec!["[1]"; EXPAND_ALL_LIMIT].join(","));
+        let mut worktree = WorkSpace::new(Node::load(json.as_bytes()).unwrap(), Config::default());
         let mut state = WorkSpaceState::default();
+        worktree.test_action(&mut state, NavigationAction::Top.into());
 
         worktree.test_action(
             &mut state,
-            WorkSpaceAction::Rename(ConfirmAction::Request(())),
+            WorkSpaceAction::ExpandAll(ConfirmAction::Request(())),
         );
         assert_snapshot!(stateful_render_to_string(&worktree, &mut state));
+        assert_eq!(
+            worktree.test_action(
+                &mut state,
+                WorkSpaceAction::ExpandAll(ConfirmAction::Confirm(true))
+            ),
+            vec![
+                JobAction::ExpandAll {
+                    selector: KeyPath::new(),
+                    keep: None
+                }
+                .into()
+            ]
+        );
+        assert!(worktree.dialogs.is_empty());
+
+        let node = worktree.file_root().clone();
+        let tree = WorkTreeNode::expanded(String::new(), &node, None);
+        worktree.test_action(
+            &mut state,
+            WorkSpaceAction::ExpandedAll {
+                node,
+                selector: KeyPath::new(),
+                tree,
+            },
+        );
+        assert_eq!(worktree.work_tree_root.len(), 2 * EXPAND_ALL_LIMIT + 1);
     }
 
     #[test]
-    fn render_invalid_test() {
+    fn bookmark_key_test() {
+        let worktree = WorkSpace::new(
+            Node::load(SAMPLE_JSON.as_bytes()).unwrap(),
+            Config::default(),
+        );
+
+        assert_key_event_to_action(&worktree, (KeyCode::Char('m'), KeyModifiers::NONE), vec![]);
+        assert_key_event_to_action(
+            &worktree,
+            (KeyCode::Char('a'), KeyModifiers::NONE),
+            vec![WorkSpaceAction::Bookmark('a').into()],
+        );
+        assert_key_event_to_action(&worktree, (KeyCode::Char('\''), KeyModifiers::NONE), vec![]);
+        assert_key_event_to_action(
+            &worktree,
+            (KeyCode::Char('a'), KeyModifiers::NONE),
+            vec![WorkSpaceAction::JumpToBookmark('a').into()],
+        );
+        assert_key_event_to_action(&worktree, (KeyCode::Char('m'), KeyModifiers::NONE), vec![]);
+        assert_key_event_to_action(&worktree, (KeyCode::Char('1'), KeyModifiers::NONE), vec![]);
+        assert_key_event_to_action(
+            &worktree,
+            (KeyCode::Char('`'), KeyModifiers::NONE),
+            vec![WorkSpaceAction::ListBookmarks.into()],
+        );
+    }
+
+    #[test]
+    fn bookmark_test() {
         let mut worktree = WorkSpace::new(
             Node::load(SAMPLE_JSON.as_bytes()).unwrap(),
             Config::default(),
         );
         let mut state = WorkSpaceState::default();
 
+        worktree.test_action(&mut state, WorkSpaceAction::ListBookmarks);
+        assert_snapshot!(stateful_render_to_string(&worktree, &mut state));
+        worktree.test_action(&mut state, WorkSpaceAction::ErrorConfirmed);
+
         worktree.test_action(&mut state, NavigationAction::Expand.into());
+        worktree.test_action(&mut state, NavigationAction::Down(1).into());
         worktree.test_action(&mut state, NavigationAction::Expand.into());
-        worktree.test_action(&mut state, NavigationAction::Expand.into());
+        worktree.test_action(&mut state, NavigationAction::Down(1).into());
+        worktree.test_action(&mut state, WorkSpaceAction::Bookmark('s'));
+        assert_eq!(
+            worktree.messages.back().unwrap(),
+            "Bookmarked /web-app/servlet-mapping as s"
+        );
+        worktree.test_action(&mut state, NavigationAction::Top.into());
+        worktree.test_action(&mut state, WorkSpaceAction::Bookmark('r'));
+
+        // Collapsing shifts every row, the bookmark follows its node.
+        worktree.test_action(&mut state, NavigationAction::Close.into());
+        worktree.test_action(&mut state, WorkSpaceAction::JumpToBookmark('s'));
+        assert_eq!(
+            worktree.selected_node(&state),
+            worktree
+                .file_root()
+                .subtree(&["web-app", "servlet-mapping"])
+                .ok()
+        );
+
+        worktree.test_action(&mut state, WorkSpaceAction::JumpToBookmark('x'));
+        assert_eq!(worktree.messages.back().unwrap(), "No bookmark x");
+
+        worktree.test_action(&mut state, WorkSpaceAction::ListBookmarks);
+        assert_snapshot!(stateful_render_to_string(&worktree, &mut state));
+        worktree.test_action(&mut state, WorkSpaceAction::SelectPath(None));
+
         worktree.test_action(
             &mut state,
-            WorkSpaceAction::Rename(ConfirmAction::Request(())),
+            WorkSpaceAction::Delete(ConfirmAction::Request(UNNAMED_REGISTER)),
+        );
+        worktree.test_action(
+            &mut state,
+            WorkSpaceAction::Delete(ConfirmAction::Confirm(true)),
+        );
+        worktree.test_action(&mut state, WorkSpaceAction::JumpToBookmark('s'));
+        assert_eq!(
+            worktree.messages.back().unwrap(),
+            "Bookmark s at /web-app/servlet-mapping no longer exists"
         );
-        assert_snapshot!(stateful_render_to_string(&worktree, &mut state));
     }
 
     #[test]
-    fn render_rename_test() {
+    fn yank_paste_test() {
         let mut worktree = WorkSpace::new(
             Node::load(SAMPLE_JSON.as_bytes()).unwrap(),
             Config::default(),
         );
         let mut state = WorkSpaceState::default();
 
+        worktree.test_action(&mut state, WorkSpaceAction::ListRegisters);
+        assert_snapshot!(stateful_render_to_string(&worktree, &mut state));
+        worktree.test_action(&mut state, WorkSpaceAction::ErrorConfirmed);
+
         worktree.test_action(&mut state, NavigationAction::Expand.into());
         worktree.test_action(&mut state, NavigationAction::Expand.into());
-        worktree.test_action(
-            &mut state,
-            WorkSpaceAction::Rename(ConfirmAction::Request(())),
+        assert_eq!(
+            worktree.test_action(&mut state, WorkSpaceAction::Yank('a')),
+            vec![]
+        );
+        worktree.test_action(&mut state, NavigationAction::Down(1).into());
+        let [Action::ExecuteJob(JobAction::Copy { content, run_tools })] =
+            &worktree.test_action(&mut state, WorkSpaceAction::Yank(UNNAMED_REGISTER))[..]
+        else {
+            panic!("yank into the unnamed register does not copy");
+        };
+        assert!(run_tools);
+        assert_eq!(
+            content,
+            &worktree
+                .selected_node(&state)
+                .unwrap()
+                .to_string_pretty()
+                .unwrap()
         );
+
+        worktree.test_action(&mut state, WorkSpaceAction::ListRegisters);
         assert_snapshot!(stateful_render_to_string(&worktree, &mut state));
+        assert_eq!(
+            worktree.test_action(&mut state, WorkSpaceAction::RegisterPicked(Some('a'))),
+            vec![
+                WorkSpaceAction::Paste {
+                    register: 'a',
+                    key: ConfirmAction::Request(()),
+                }
+                .into()
+            ]
+        );
 
         worktree.test_action(
             &mut state,
-            WorkSpaceAction::Rename(ConfirmAction::Confirm(None)),
+            WorkSpaceAction::Paste {
+                register: 'a',
+                key: ConfirmAction::Request(()),
+            },
+        );
+        worktree.test_action(
+            &mut state,
+            WorkSpaceAction::Paste {
+                register: 'a',
+                key: ConfirmAction::Confirm(Some(String::from("servlet-copy"))),
+            },
+        );
+        assert!(worktree.dialogs.is_empty());
+        assert!(worktree.is_edited);
+        assert_eq!(
+            worktree
+                .file_root
+                .subtree(&["web-app", "servlet-copy"])
+                .unwrap(),
+            worktree.file_root.subtree(&["web-app", "servlet"]).unwrap()
         );
         assert_snapshot!(stateful_render_to_string(&worktree, &mut state));
 
         worktree.test_action(
             &mut state,
-            WorkSpaceAction::Rename(ConfirmAction::Request(())),
+            WorkSpaceAction::Paste {
+                register: 'b',
+                key: ConfirmAction::Request(()),
+            },
         );
+        assert_snapshot!(stateful_render_to_string(&worktree, &mut state));
+    }
+
+    #[test]
+    fn overwrite_test() {
+        let mut worktree = WorkSpace::new(
+            Node::load(SAMPLE_JSON.as_bytes()).unwrap(),
+            Config::default(),
+        );
+        let mut state = WorkSpaceState::default();
+
         worktree.test_action(
             &mut state,
-            WorkSpaceAction::Rename(ConfirmAction::Confirm(Some(String::from("new_key")))),
+            WorkSpaceAction::Overwrite(ConfirmAction::Request(String::from("config.json"))),
         );
         assert_snapshot!(stateful_render_to_string(&worktree, &mut state));
+        assert_eq!(
+            worktree.test_action(
+                &mut state,
+                WorkSpaceAction::Overwrite(ConfirmAction::Confirm(true))
+            ),
+            vec![JobAction::Overwrite.into()]
+        );
+        assert!(worktree.dialogs.is_empty());
 
         worktree.test_action(
             &mut state,
-            WorkSpaceAction::Rename(ConfirmAction::Request(())),
+            WorkSpaceAction::Overwrite(ConfirmAction::Request(String::from("config.json"))),
+        );
+        assert_eq!(
+            worktree.test_action(
+                &mut state,
+                WorkSpaceAction::Overwrite(ConfirmAction::Confirm(false))
+            ),
+            vec![]
+        );
+        assert!(worktree.dialogs.is_empty());
+        assert_eq!(
+            worktree.messages.back().unwrap(),
+            "Save aborted, the file on disk is unchanged"
         );
-        assert_snapshot!(stateful_render_to_string(&worktree, &mut state));
     }
 
     #[test]
-    fn render_rename_duplicate_test() {
+    fn recover_test() {
         let mut worktree = WorkSpace::new(
             Node::load(SAMPLE_JSON.as_bytes()).unwrap(),
             Config::default(),
         );
         let mut state = WorkSpaceState::default();
+        let recover =
+            || WorkSpaceAction::Recover(ConfirmAction::Request("config.json.jedit~".into()));
 
-        worktree.test_action(&mut state, NavigationAction::Expand.into());
-        worktree.test_action(&mut state, NavigationAction::Expand.into());
-        worktree.test_action(
-            &mut state,
-            WorkSpaceAction::Rename(ConfirmAction::Request(())),
+        worktree.test_action(&mut state, recover());
+        assert_snapshot!(stateful_render_to_string(&worktree, &mut state));
+        assert_eq!(
+            worktree.test_action(
+                &mut state,
+                WorkSpaceAction::Recover(ConfirmAction::Confirm(Some("config.json.jedit~".into())))
+            ),
+            vec![JobAction::Recover(String::from("config.json.jedit~")).into()]
+        );
+        assert!(worktree.dialogs.is_empty());
+
+        worktree.test_action(&mut state, recover());
+        assert_eq!(
+            worktree.test_action(
+                &mut state,
+                WorkSpaceAction::Recover(ConfirmAction::Confirm(None))
+            ),
+            vec![]
         );
+        assert!(worktree.dialogs.is_empty());
+
+        worktree.test_action(&mut state, WorkSpaceAction::Autosaved(None));
+        assert!(!worktree.show_message);
         worktree.test_action(
             &mut state,
-            WorkSpaceAction::Rename(ConfirmAction::Confirm(Some(String::from("taglib")))),
+            WorkSpaceAction::Autosaved(Some(String::from("disk full"))),
+        );
+        assert_eq!(
+            worktree.messages.back().unwrap(),
+            "Stopped autosaving: disk full"
         );
-        assert_snapshot!(stateful_render_to_string(&worktree, &mut state));
     }
 
     #[test]
-    fn render_rename_does_not_change_test() {
+    fn input_changed_test() {
         let mut worktree = WorkSpace::new(
             Node::load(SAMPLE_JSON.as_bytes()).unwrap(),
             Config::default(),
         );
         let mut state = WorkSpaceState::default();
+        let changed =
+            || WorkSpaceAction::InputChanged(ConfirmAction::Request("config.json".into()));
+
+        assert_eq!(
+            worktree.test_action(&mut state, changed()),
+            vec![JobAction::Reload(String::from("config.json")).into()]
+        );
+
+        worktree.is_edited = true;
+        worktree.test_action(&mut state, changed());
+        assert_snapshot!(stateful_render_to_string(&worktree, &mut state));
+        assert_eq!(
+            worktree.test_action(
+                &mut state,
+                WorkSpaceAction::InputChanged(ConfirmAction::Confirm(None))
+            ),
+            vec![]
+        );
+        assert!(worktree.dialogs.is_empty());
+        assert!(worktree.is_edited());
+
+        worktree.test_action(&mut state, changed());
+        assert_eq!(
+            worktree.test_action(
+                &mut state,
+                WorkSpaceAction::InputChanged(ConfirmAction::Confirm(Some("config.json".into())))
+            ),
+            vec![JobAction::Reload(String::from("config.json")).into()]
+        );
 
         worktree.test_action(&mut state, NavigationAction::Expand.into());
-        worktree.test_action(&mut state, NavigationAction::Expand.into());
+        worktree.test_action(&mut state, NavigationAction::Down(1).into());
         worktree.test_action(
             &mut state,
-            WorkSpaceAction::Rename(ConfirmAction::Request(())),
+            WorkSpaceAction::Reloaded {
+                path: String::from("config.json"),
+                node: Node::load(r#"{"a": 1, "web-app": {"b": 2}}"#.as_bytes()).unwrap(),
+            },
         );
-        worktree.test_action(
-            &mut state,
-            WorkSpaceAction::Rename(ConfirmAction::Confirm(Some(String::from("servlet")))),
+        assert!(!worktree.is_edited());
+        assert_eq!(worktree.messages.back().unwrap(), "Reloaded config.json");
+        assert_snapshot!(
+            "input_changed_reloaded_test",
+            stateful_render_to_string(&worktree, &mut state)
         );
-        assert_snapshot!(stateful_render_to_string(&worktree, &mut state));
     }
 
     #[test]
-    fn render_simple_delete_test() {
+    fn save_patch_test() {
         let mut worktree = WorkSpace::new(
-            Node::load(SAMPLE_JSON.as_bytes()).unwrap(),
+            Node::load(r#"{"a": [1, 2], "b": [3]}"#.as_bytes()).unwrap(),
             Config::default(),
         );
         let mut state = WorkSpaceState::default();
 
-        worktree.test_action(&mut state, NavigationAction::Expand.into());
-        worktree.test_action(&mut state, NavigationAction::Expand.into());
-        worktree.test_action(
-            &mut state,
-            WorkSpaceAction::Delete(ConfirmAction::Request(())),
+        assert_key_event_to_action(
+            &worktree,
+            (KeyCode::Char('w'), KeyModifiers::CONTROL),
+            vec![WorkSpaceAction::SavePatch(ConfirmAction::Request(())).into()],
         );
         worktree.test_action(
             &mut state,
-            WorkSpaceAction::Delete(ConfirmAction::Confirm(true)),
+            WorkSpaceAction::SavePatch(ConfirmAction::Request(())),
         );
-        assert_snapshot!(stateful_render_to_string(&worktree, &mut state));
-
-        worktree.test_action(&mut state, NavigationAction::Down(2).into());
-        assert_snapshot!(stateful_render_to_string(&worktree, &mut state));
-    }
-
-    #[test]
-    fn render_delete_rename_test() {
-        let mut worktree = WorkSpace::new(
-            Node::load(SAMPLE_JSON.as_bytes()).unwrap(),
-            Config::default(),
+        assert!(worktree.dialogs.is_empty());
+        assert_eq!(
+            worktree.messages.back().unwrap(),
+            "No changes to save as a patch"
         );
-        let mut state = WorkSpaceState::default();
 
-        worktree.test_action(&mut state, NavigationAction::Expand.into());
-        worktree.test_action(&mut state, NavigationAction::Expand.into());
+        worktree.select_path(&mut state, &["a", "0"]);
         worktree.test_action(
             &mut state,
-            WorkSpaceAction::Delete(ConfirmAction::Request(())),
+            WorkSpaceAction::Delete(ConfirmAction::Request('"')),
         );
         worktree.test_action(
             &mut state,
             WorkSpaceAction::Delete(ConfirmAction::Confirm(true)),
         );
+        worktree.select_path(&mut state, &["b", "0"]);
+        worktree.test_action(&mut state, WorkSpaceAction::Duplicate);
+        worktree.select_path(&mut state, &["b"]);
         worktree.test_action(
             &mut state,
             WorkSpaceAction::Rename(ConfirmAction::Request(())),
         );
         worktree.test_action(
             &mut state,
-            WorkSpaceAction::Rename(ConfirmAction::Confirm(Some(String::from("new_key")))),
+            WorkSpaceAction::Rename(ConfirmAction::Confirm(Some(String::from("c")))),
         );
 
+        worktree.test_action(
+            &mut state,
+            WorkSpaceAction::SavePatch(ConfirmAction::Request(())),
+        );
         assert_snapshot!(stateful_render_to_string(&worktree, &mut state));
+
+        let actions = worktree.test_action(
+            &mut state,
+            WorkSpaceAction::SavePatch(ConfirmAction::Confirm(Some(String::from("changes.json")))),
+        );
+        assert!(worktree.dialogs.is_empty());
+        let [Action::ExecuteJob(JobAction::SavePatch { path, content })] = &actions[..] else {
+            panic!("expected a patch to save, got {actions:?}");
+        };
+        assert_eq!(path, "changes.json");
+        assert_eq!(
+            Node::load(content.as_bytes())
+                .unwrap()
+                .to_string_compact()
+                .unwrap(),
+            concat!(
+                r#"[{"op":"remove","path":"/a/0"},"#,
+                r#"{"op":"add","path":"/b/1","value":3},"#,
+                r#"{"op":"remove","path":"/b"},"#,
+                r#"{"op":"add","path":"/c","value":[3,3]}]"#
+            )
+        );
     }
 
     #[test]
-    fn render_delete_preview_test() {
+    fn cut_paste_test() {
         let mut worktree = WorkSpace::new(
-            Node::load(SAMPLE_JSON.as_bytes()).unwrap(),
+            Node::load(r#"{"a": [1, 2], "b": [3]}"#.as_bytes()).unwrap(),
             Config::default(),
         );
         let mut state = WorkSpaceState::default();
 
-        worktree.test_action(&mut state, NavigationAction::TogglePreview.into());
-        worktree.test_action(&mut state, NavigationAction::Expand.into());
-        worktree.test_action(&mut state, NavigationAction::Expand.into());
+        for c in ['"', 'x'] {
+            assert_key_event_to_action(&worktree, (KeyCode::Char(c), KeyModifiers::NONE), vec![]);
+        }
+        assert_key_event_to_action(
+            &worktree,
+            (KeyCode::Char('d'), KeyModifiers::NONE),
+            vec![WorkSpaceAction::Delete(ConfirmAction::Request('x')).into()],
+        );
+
+        worktree.select_path(&mut state, &["a", "0"]);
         worktree.test_action(
             &mut state,
-            WorkSpaceAction::Delete(ConfirmAction::Request(())),
+            WorkSpaceAction::Delete(ConfirmAction::Request('x')),
+        );
+        // Cutting never reaches the system clipboard.
+        assert_eq!(
+            worktree.test_action(
+                &mut state,
+                WorkSpaceAction::Delete(ConfirmAction::Confirm(true)),
+            ),
+            vec![]
+        );
+        assert_eq!(
+            worktree.messages.back().unwrap(),
+            "Cut /a/0 into \"x, press P to paste it"
         );
+
+        worktree.select_path(&mut state, &["b", "0"]);
         worktree.test_action(
             &mut state,
-            WorkSpaceAction::Delete(ConfirmAction::Confirm(true)),
+            WorkSpaceAction::Paste {
+                register: 'x',
+                key: ConfirmAction::Request(()),
+            },
+        );
+        assert_eq!(
+            worktree.file_root().to_string_pretty().unwrap(),
+            Node::load(r#"{"a": [2], "b": [3, 1]}"#.as_bytes())
+                .unwrap()
+                .to_string_pretty()
+                .unwrap()
         );
-
-        assert_snapshot!(stateful_render_to_string(&worktree, &mut state));
     }
 
     #[test]
-    fn render_delete_and_go_to_bottom_test() {
+    fn duplicate_test() {
         let mut worktree = WorkSpace::new(
-            Node::load(SAMPLE_JSON.as_bytes()).unwrap(),
+            Node::load(r#"{"a": {"x": 1}, "b": [1, 2]}"#.as_bytes()).unwrap(),
             Config::default(),
         );
         let mut state = WorkSpaceState::default();
 
-        worktree.test_action(&mut state, NavigationAction::Expand.into());
-        worktree.test_action(&mut state, NavigationAction::Expand.into());
-        worktree.test_action(
-            &mut state,
-            WorkSpaceAction::Delete(ConfirmAction::Request(())),
-        );
-        worktree.test_action(
-            &mut state,
-            WorkSpaceAction::Delete(ConfirmAction::Confirm(true)),
+        assert_key_event_to_action(
+            &worktree,
+            (KeyCode::Char('D'), KeyModifiers::SHIFT),
+            vec![WorkSpaceAction::Duplicate.into()],
         );
 
-        worktree.test_action(&mut state, NavigationAction::Bottom.into());
-        worktree.test_action(
-            &mut state,
-            WorkSpaceAction::Delete(ConfirmAction::Request(())),
+        worktree.select_path(&mut state, &["a", "x"]);
+        worktree.select_path(&mut state, &["a"]);
+        worktree.test_action(&mut state, WorkSpaceAction::Duplicate);
+        assert_eq!(
+            worktree.selected_node(&state),
+            worktree.file_root.subtree(&["a_copy"]).ok()
         );
-        worktree.test_action(
-            &mut state,
-            WorkSpaceAction::Delete(ConfirmAction::Confirm(true)),
+        worktree.select_path(&mut state, &["a"]);
+        worktree.test_action(&mut state, WorkSpaceAction::Duplicate);
+        worktree.select_path(&mut state, &["b", "0"]);
+        worktree.test_action(&mut state, WorkSpaceAction::Duplicate);
+        assert!(worktree.is_edited);
+        assert_eq!(
+            worktree.file_root().to_string_compact().unwrap(),
+            r#"{"a":{"x":1},"a_copy2":{"x":1},"a_copy":{"x":1},"b":[1,1,2]}"#
         );
         assert_snapshot!(stateful_render_to_string(&worktree, &mut state));
     }
 
     #[test]
-    fn render_delete_large_preview_test() {
+    fn move_within_test() {
         let mut worktree = WorkSpace::new(
-            Node::load(SAMPLE_JSON.as_bytes()).unwrap(),
-            Config::default().with_max_preview_size(Byte::from_u64(3700)),
+            Node::load(r#"{"a": {"x": 1}, "b": [1, 2, 3], "c": 4}"#.as_bytes()).unwrap(),
+            Config::default(),
         );
         let mut state = WorkSpaceState::default();
 
-        worktree.test_action(&mut state, NavigationAction::TogglePreview.into());
-        worktree.test_action(&mut state, NavigationAction::Expand.into());
-        worktree.test_action(&mut state, NavigationAction::Expand.into());
-        worktree.test_action(
-            &mut state,
-            WorkSpaceAction::Delete(ConfirmAction::Request(())),
+        assert_key_event_to_action(
+            &worktree,
+            (KeyCode::Char('j'), KeyModifiers::ALT),
+            vec![WorkSpaceAction::MoveWithin(false).into()],
         );
-        worktree.test_action(
-            &mut state,
-            WorkSpaceAction::Delete(ConfirmAction::Confirm(true)),
+        assert_key_event_to_action(
+            &worktree,
+            (KeyCode::Char('k'), KeyModifiers::ALT),
+            vec![WorkSpaceAction::MoveWithin(true).into()],
         );
 
-        worktree.test_action(&mut state, NavigationAction::Top.into());
+        worktree.select_path(&mut state, &["a", "x"]);
+        worktree.select_path(&mut state, &["a"]);
+        worktree.test_action(&mut state, WorkSpaceAction::MoveWithin(false));
+        assert_eq!(
+            worktree.selected_node(&state),
+            worktree.file_root.subtree(&["a"]).ok()
+        );
+        worktree.test_action(&mut state, WorkSpaceAction::MoveWithin(true));
+        worktree.test_action(&mut state, WorkSpaceAction::MoveWithin(true));
+        worktree.select_path(&mut state, &["c"]);
+        worktree.test_action(&mut state, WorkSpaceAction::MoveWithin(true));
+        worktree.select_path(&mut state, &["b", "2"]);
+        worktree.test_action(&mut state, WorkSpaceAction::MoveWithin(true));
+        assert!(worktree.dialogs.is_empty());
+        assert!(worktree.is_edited);
+        assert_eq!(
+            worktree.file_root().to_string_compact().unwrap(),
+            r#"{"a":{"x":1},"c":4,"b":[1,3,2]}"#
+        );
         assert_snapshot!(stateful_render_to_string(&worktree, &mut state));
     }
 
     #[test]
-    fn render_append_after_into_array_test() {
+    fn clipboard_read_test() {
         let mut worktree = WorkSpace::new(
-            Node::load(SAMPLE_JSON.as_bytes()).unwrap(),
-            Config::default().with_max_preview_size(Byte::from_u64(3700)),
+            Node::load(r#"{"a": [1, 2]}"#.as_bytes()).unwrap(),
+            Config::default(),
         );
         let mut state = WorkSpaceState::default();
 
-        worktree.test_action(&mut state, NavigationAction::TogglePreview.into());
-        worktree.test_action(&mut state, NavigationAction::Expand.into());
+        for c in ['"', '+'] {
+            assert_key_event_to_action(&worktree, (KeyCode::Char(c), KeyModifiers::NONE), vec![]);
+        }
+        assert_key_event_to_action(
+            &worktree,
+            (KeyCode::Char('P'), KeyModifiers::NONE),
+            vec![
+                JobAction::ReadClipboard {
+                    replace: false,
+                    run_tools: true,
+                }
+                .into(),
+            ],
+        );
+
         worktree.test_action(&mut state, NavigationAction::Expand.into());
         worktree.test_action(&mut state, NavigationAction::Expand.into());
-        worktree.test_action(&mut state, WorkSpaceAction::Add(ConfirmAction::Request(())));
-        assert_snapshot!(stateful_render_to_string(&worktree, &mut state));
+        for action in worktree.test_action(
+            &mut state,
+            WorkSpaceAction::ClipboardRead {
+                content: String::from(r#"{"b": 3}"#),
+                replace: false,
+            },
+        ) {
+            let Action::Workspace(action) = action else {
+                panic!("unexpected action {action:?}");
+            };
+            worktree.test_action(&mut state, action);
+        }
+        assert_eq!(
+            worktree.file_root().to_string_compact().unwrap(),
+            r#"{"a":[1,{"b":3},2]}"#
+        );
 
-        worktree.test_action(&mut state, NavigationAction::Down(1).into());
-        assert_snapshot!(stateful_render_to_string(&worktree, &mut state));
+        assert_eq!(
+            worktree.test_action(
+                &mut state,
+                WorkSpaceAction::ClipboardRead {
+                    content: String::from("4"),
+                    replace: true,
+                },
+            ),
+            vec![
+                WorkSpaceAction::ApplyEdit(ConfirmAction::Request((
+                    Node::load("4".as_bytes()).unwrap(),
+                    Repairs::default()
+                )))
+                .into()
+            ]
+        );
 
-        worktree.test_action(&mut state, NavigationAction::Down(4).into());
+        worktree.test_action(
+            &mut state,
+            WorkSpaceAction::ClipboardRead {
+                content: String::from("{oops"),
+                replace: false,
+            },
+        );
         assert_snapshot!(stateful_render_to_string(&worktree, &mut state));
     }
 
     #[test]
-    fn render_append_after_into_object_test() {
+    fn insert_snippet_test() {
         let mut worktree = WorkSpace::new(
             Node::load(SAMPLE_JSON.as_bytes()).unwrap(),
-            Config::default().with_max_preview_size(Byte::from_u64(3700)),
+            Config::default()
+                .with_snippet("cors", r#"{"allowOrigins": ["*"]}"#)
+                .with_snippet("broken", "{"),
         );
         let mut state = WorkSpaceState::default();
 
-        worktree.test_action(&mut state, NavigationAction::TogglePreview.into());
+        assert_key_event_to_action(
+            &worktree,
+            (KeyCode::Char('A'), KeyModifiers::NONE),
+            vec![WorkSpaceAction::ListSnippets.into()],
+        );
+
         worktree.test_action(&mut state, NavigationAction::Expand.into());
         worktree.test_action(&mut state, NavigationAction::Expand.into());
-        worktree.test_action(&mut state, WorkSpaceAction::Add(ConfirmAction::Request(())));
+        worktree.test_action(&mut state, WorkSpaceAction::ListSnippets);
         assert_snapshot!(stateful_render_to_string(&worktree, &mut state));
 
+        assert_eq!(
+            worktree.test_action(
+                &mut state,
+                WorkSpaceAction::SnippetPicked(Some(String::from("cors")))
+            ),
+            vec![
+                WorkSpaceAction::InsertSnippet {
+                    name: String::from("cors"),
+                    key: ConfirmAction::Request(()),
+                }
+                .into()
+            ]
+        );
         worktree.test_action(
             &mut state,
-            WorkSpaceAction::Add(ConfirmAction::Confirm(None)),
+            WorkSpaceAction::InsertSnippet {
+                name: String::from("cors"),
+                key: ConfirmAction::Request(()),
+            },
         );
         assert_snapshot!(stateful_render_to_string(&worktree, &mut state));
+        worktree.test_action(
+            &mut state,
+            WorkSpaceAction::InsertSnippet {
+                name: String::from("cors"),
+                key: ConfirmAction::Confirm(Some(String::from("cors"))),
+            },
+        );
+        assert!(worktree.dialogs.is_empty());
+        assert_eq!(
+            worktree.file_root.subtree(&["web-app", "cors"]).unwrap(),
+            &Node::load(r#"{"allowOrigins": ["*"]}"#.as_bytes()).unwrap()
+        );
 
         worktree.test_action(
             &mut state,
-            WorkSpaceAction::Add(ConfirmAction::Confirm(Some(String::from("new_key")))),
+            WorkSpaceAction::InsertSnippet {
+                name: String::from("broken"),
+                key: ConfirmAction::Request(()),
+            },
         );
         assert_snapshot!(stateful_render_to_string(&worktree, &mut state));
+    }
 
-        worktree.test_action(&mut state, NavigationAction::Down(1).into());
-        assert_snapshot!(stateful_render_to_string(&worktree, &mut state));
+    #[test]
+    fn split_file_name_test() {
+        assert_eq!(split_file_name("./{key}.json", "a"), "./a.json");
+        assert_eq!(split_file_name("{key}/{key}.json", "a/b"), "a_b/a_b.json");
+        assert_eq!(split_file_name("./{key}.json", ".."), "./_...json");
+        assert_eq!(split_file_name("./{key}.json", ""), "./_.json");
+    }
 
-        worktree.test_action(&mut state, NavigationAction::Down(3).into());
-        assert_snapshot!(stateful_render_to_string(&worktree, &mut state));
+    #[test]
+    fn heat_style_test() {
+        assert_eq!(heat_style(100, 100), Style::new().fg(RED.c500));
+        assert_eq!(heat_style(60, 100), Style::new().fg(ORANGE.c400));
+        assert_eq!(heat_style(30, 100), Style::new().fg(YELLOW.c300));
+        assert_eq!(heat_style(10, 100), Style::new());
+        assert_eq!(heat_style(0, 0), Style::new());
     }
 
     #[test]
-    fn render_append_after_into_object_key_exists_test() {
+    fn value_hint_test() {
+        let hint = |json: &str, width| {
+            value_hint(&Node::load(json.as_bytes()).unwrap(), width)
+                .content
+                .into_owned()
+        };
+        assert_eq!(hint(r#""prod-cluster""#, 30), r#": "prod-cluster""#);
+        assert_eq!(hint(r#""prod-cluster""#, 8), r#": "prod-c…"#);
+        assert_eq!(hint("[1, 2, 3]", 30), ": [3]");
+        assert_eq!(hint(r#"{"a": 1, "b": 2}"#, 30), ": {…2}");
+        assert_eq!(hint("12.5", 30), ": 12.5");
+        assert_eq!(hint("null", 3), ": nu…");
+    }
+
+    #[test]
+    fn type_badge_test() {
+        let badge = |json: &str| {
+            type_badge(&Node::load(json.as_bytes()).unwrap(), Indent::Auto)
+                .iter()
+                .map(|span| span.content.as_ref())
+                .collect::<String>()
+        };
+        assert_eq!(badge(r#"{"a": 1, "b": 2}"#), " {} 2 · 22 B");
+        assert_eq!(badge("[1, 2, 3]"), " [] 3 · 17 B");
+        assert_eq!(badge(r#""prod""#), " str 6 B");
+        assert_eq!(badge("12.5"), " num 4 B");
+        assert_eq!(badge("true"), " bool 4 B");
+        assert_eq!(badge("null"), " null 4 B");
+    }
+
+    #[test]
+    fn render_type_badges_test() {
         let mut worktree = WorkSpace::new(
             Node::load(SAMPLE_JSON.as_bytes()).unwrap(),
-            Config::default().with_max_preview_size(Byte::from_u64(3700)),
+            Config::default().with_type_badges(),
         );
         let mut state = WorkSpaceState::default();
-
-        worktree.test_action(&mut state, NavigationAction::TogglePreview.into());
-        worktree.test_action(&mut state, NavigationAction::Expand.into());
-        worktree.test_action(&mut state, NavigationAction::Expand.into());
-        worktree.test_action(&mut state, WorkSpaceAction::Add(ConfirmAction::Request(())));
-
-        worktree.test_action(
-            &mut state,
-            WorkSpaceAction::Add(ConfirmAction::Confirm(Some(String::from("taglib")))),
-        );
-        assert_snapshot!(stateful_render_to_string(&worktree, &mut state));
-
-        worktree.test_action(&mut state, WorkSpaceAction::ErrorConfirmed);
-        assert_snapshot!(stateful_render_to_string(&worktree, &mut state));
-
-        worktree.test_action(
-            &mut state,
-            WorkSpaceAction::Add(ConfirmAction::Confirm(Some(String::from("taglib2")))),
-        );
+        for _ in 0..4 {
+            worktree.test_action(&mut state, NavigationAction::Expand.into());
+        }
         assert_snapshot!(stateful_render_to_string(&worktree, &mut state));
     }
 
     #[test]
-    fn render_test_expand_expanded_test() {
+    fn render_value_hints_test() {
         let mut worktree = WorkSpace::new(
             Node::load(SAMPLE_JSON.as_bytes()).unwrap(),
-            Config::default().with_max_preview_size(Byte::from_u64(3700)),
+            Config::default().with_value_hints(20),
         );
         let mut state = WorkSpaceState::default();
-
-        worktree.test_action(&mut state, NavigationAction::TogglePreview.into());
-        worktree.test_action(&mut state, NavigationAction::Expand.into());
-        worktree.test_action(&mut state, NavigationAction::Expand.into());
-        worktree.test_action(&mut state, NavigationAction::Top.into());
-        worktree.test_action(&mut state, NavigationAction::Expand.into());
+        for action in [
+            NavigationAction::Expand,
+            NavigationAction::Expand,
+            NavigationAction::Expand,
+            NavigationAction::Expand,
+        ] {
+            worktree.test_action(&mut state, action.into());
+        }
         assert_snapshot!(stateful_render_to_string(&worktree, &mut state));
     }
 
@@ -1809,7 +8593,7 @@ mod test {
 
     fn assert_event_to_action(worktree: &WorkSpace, event: Event, expected_actions: Vec<Action>) {
         let mut actions = Actions::new();
-        worktree.handle_event(&mut actions, event);
+        worktree.handle_event(&WorkSpaceState::default(), &mut actions, event);
         assert_eq!(actions.into_vec(), expected_actions)
     }
 
@@ -1824,6 +8608,25 @@ mod test {
             actions.into_vec()
         }
 
+        /// Handles `action` and the workspace actions following from it, returning the others.
+        fn test_action_chain(
+            &mut self,
+            state: &mut WorkSpaceState,
+            action: WorkSpaceAction,
+        ) -> Vec<Action> {
+            let mut pending = VecDeque::from([action]);
+            let mut others = Vec::new();
+            while let Some(action) = pending.pop_front() {
+                for action in self.test_action(state, action) {
+                    match action {
+                        Action::Workspace(action) => pending.push_back(action),
+                        action => others.push(action),
+                    }
+                }
+            }
+            others
+        }
+
         pub fn write_selected(
             &self,
             worktree_state: &WorkSpaceState,
@@ -1,31 +1,81 @@
-use std::{cell::RefCell, iter::Peekable, slice::Iter};
+use std::{borrow::Cow, cell::RefCell, iter::Peekable, slice::Iter};
 
-use crate::container::node::{Index, IndexKind, NodeKind, NodeMeta};
+use crate::{
+    app::config::TreeGuides,
+    container::{
+        node::{Index, IndexKind, Node, NodeKind, NodeMeta},
+        pointer::{Key, KeyPath, intern},
+    },
+};
 
 #[derive(Debug)]
+#[cfg_attr(test, derive(PartialEq, Clone))]
 pub struct WorkTreeNode {
-    name: String,
+    name: Key,
     len: usize,
     meta: Option<NodeMeta>,
     child: Option<Vec<WorkTreeNode>>,
+    /// Number of array elements hidden behind this placeholder row, starting at `name`
+    elided: Option<usize>,
 }
 
 impl WorkTreeNode {
     pub fn new(name: String, meta: Option<NodeMeta>) -> Self {
         Self {
-            name,
+            name: intern(&name),
             len: 1,
             meta,
             child: None,
+            elided: None,
         }
     }
 
     pub fn new_empty(name: String) -> Self {
         Self {
-            name,
+            name: intern(&name),
             len: 1,
             meta: None,
             child: None,
+            elided: None,
+        }
+    }
+
+    /// Tree of `root` showing only the nodes at `paths`, given in document order, and their
+    /// ancestors, expanded to reveal them.
+    pub fn filtered(root: &Node, paths: &[Vec<String>]) -> Self {
+        let mut tree = Self::new(String::from("root"), Some(root.as_meta()));
+        for path in paths {
+            tree.insert_path(root, path);
+        }
+        tree
+    }
+
+    /// Adds the rows leading to `path` below this node, returning how many were added.
+    fn insert_path(&mut self, node: &Node, path: &[String]) -> usize {
+        let Some((key, rest)) = path.split_first() else {
+            return 0;
+        };
+        let child_node = node.child(key).expect("broken path");
+        let child = self.child.get_or_insert_with(Vec::new);
+        // In document order, a path either continues below the last child or starts a new one.
+        let mut added = 0;
+        if child.last().is_none_or(|last| *last.name != **key) {
+            child.push(Self::new(key.clone(), Some(child_node.as_meta())));
+            added += 1;
+        }
+        let last = child.last_mut().expect("child was just ensured");
+        added += last.insert_path(child_node, rest);
+        self.len += added;
+        added
+    }
+
+    fn new_elided(start: usize, n: usize) -> Self {
+        Self {
+            name: intern(&start.to_string()),
+            len: 1,
+            meta: None,
+            child: None,
+            elided: Some(n),
         }
     }
 
@@ -33,19 +83,36 @@ impl WorkTreeNode {
         self.len
     }
 
+    #[cfg(test)]
     pub fn as_tree_string(&self) -> impl Iterator<Item = String> {
-        std::iter::once(self.formatted_name(Vec::new()))
-            .chain(WorkTreeStringIter::new(self.child.as_deref()))
+        self.as_tree_rows(None, TreeGuides::default())
+            .map(|row| row.text)
+    }
+
+    /// Rows of the tree, each paired with its node in `root` when given.
+    pub fn as_tree_rows<'a>(
+        &'a self,
+        root: Option<&'a Node>,
+        guides: TreeGuides,
+    ) -> impl Iterator<Item = TreeRow<'a>> {
+        std::iter::once(TreeRow {
+            text: self.formatted_name(Vec::new(), guides),
+            name: &self.name,
+            depth: 0,
+            node: root,
+            max_sibling_bytes: root.map(|root| root.as_meta().n_bytes).unwrap_or_default(),
+        })
+        .chain(WorkTreeRowIter::new(self.child.as_deref(), root, guides))
     }
 
-    pub fn selector(&self, index: usize) -> Vec<&str> {
-        let mut res = Vec::new();
+    pub fn selector(&self, index: usize) -> KeyPath {
+        let mut res = KeyPath::new();
 
         self.traverse_node(
             index,
             &mut |node| {
                 if !std::ptr::eq(self, node) {
-                    res.push(node.name.as_str());
+                    res.push_key(node.name.clone());
                 }
             },
             &mut |_| {},
@@ -59,6 +126,104 @@ impl WorkTreeNode {
         self.traverse_node(index, &mut |_| {}, &mut |_| {}, |node| node.child.is_some())
     }
 
+    /// Row of the child named `key` under the node at `index`. Hidden array elements resolve to
+    /// the placeholder row hiding them.
+    pub fn child_row(&self, index: usize, key: &str) -> Option<usize> {
+        self.traverse_node(index, &mut |_| {}, &mut |_| {}, |node| {
+            let position = key.parse::<usize>().ok();
+            let mut row = index + 1;
+            for child in node.child.as_deref()? {
+                let is_match = match (child.elided, position) {
+                    (Some(n), Some(position)) => child
+                        .name
+                        .parse::<usize>()
+                        .is_ok_and(|start| (start..start + n).contains(&position)),
+                    _ => *child.name == *key,
+                };
+                if is_match {
+                    return Some(row);
+                }
+                row += child.len;
+            }
+            None
+        })
+    }
+
+    pub fn elided(&self, index: usize) -> Option<usize> {
+        self.traverse_node(index, &mut |_| {}, &mut |_| {}, |node| node.elided)
+    }
+
+    /// Number of rows the node at `index` takes, counting its expanded descendants.
+    pub fn rows(&self, index: usize) -> usize {
+        self.traverse_node(index, &mut |_| {}, &mut |_| {}, |node| node.len)
+    }
+
+    /// Hides every child of the node at `index` except the first and last `keep` ones behind a
+    /// single placeholder row.
+    pub(crate) fn elide(&mut self, index: usize, keep: usize) {
+        let removed_len = RefCell::new(0);
+        self.traverse_node_mut(
+            index,
+            &mut |_| {},
+            &mut |node: &mut WorkTreeNode, child_index| {
+                if child_index.is_some() {
+                    node.len -= *removed_len.borrow();
+                }
+            },
+            |node: &mut WorkTreeNode| {
+                let Some(child) = &mut node.child else {
+                    return;
+                };
+                let n = child.len();
+                if n <= 2 * keep + 1 {
+                    return;
+                }
+
+                let removed = child
+                    .splice(keep..n - keep, [Self::new_elided(keep, n - 2 * keep)])
+                    .map(|node| node.len)
+                    .sum::<usize>()
+                    - 1;
+                node.len -= removed;
+                *removed_len.borrow_mut() = removed;
+            },
+        );
+    }
+
+    /// Replaces the placeholder row at `index` with the elements it was hiding.
+    pub(crate) fn materialize(&mut self, index: usize) -> bool {
+        let Some(hidden) = self.elided(index) else {
+            return false;
+        };
+
+        let is_parent = RefCell::new(true);
+        self.traverse_node_mut(
+            index,
+            &mut |_| {},
+            &mut |node: &mut WorkTreeNode, child_index| {
+                let Some(child_index) = child_index else {
+                    return;
+                };
+                if *is_parent.borrow() {
+                    let child = node.child.as_mut().expect("broken elided node");
+                    let start = child[child_index]
+                        .name
+                        .parse::<usize>()
+                        .expect("broken elided node");
+                    child.splice(
+                        child_index..=child_index,
+                        (start..start + hidden).map(|i| Self::new_empty(i.to_string())),
+                    );
+                    *is_parent.borrow_mut() = false;
+                }
+                node.len += hidden - 1;
+            },
+            |_| {},
+        );
+
+        true
+    }
+
     pub fn reindex(&mut self, index: usize, node_index: Index, force: bool) {
         let (len, child) = match node_index.kind {
             IndexKind::Terminal => (1, Vec::new()),
@@ -95,6 +260,116 @@ impl WorkTreeNode {
         );
     }
 
+    /// Rows of `node` with every descendant expanded, hiding all but the first and last `keep`
+    /// elements of longer arrays like [`WorkTreeNode::elide`].
+    pub fn expanded(name: String, node: &Node, keep: Option<usize>) -> Self {
+        let node_index = node.as_index();
+        let child = |key: String| {
+            let child_node = node.child(&key).expect("broken index");
+            Self::expanded(key, child_node, keep)
+        };
+        let child = match node_index.kind {
+            IndexKind::Terminal => Vec::new(),
+            IndexKind::Object(items) => items.into_iter().map(child).collect(),
+            IndexKind::Array(n) => match keep.filter(|&keep| n > 2 * keep + 1) {
+                Some(keep) => (0..keep)
+                    .map(|i| child(i.to_string()))
+                    .chain([Self::new_elided(keep, n - 2 * keep)])
+                    .chain((n - keep..n).map(|i| child(i.to_string())))
+                    .collect(),
+                None => (0..n).map(|i| child(i.to_string())).collect(),
+            },
+        };
+        Self {
+            name: intern(&name),
+            len: 1 + child.iter().map(|child| child.len).sum::<usize>(),
+            meta: Some(node_index.meta),
+            child: Some(child),
+            elided: None,
+        }
+    }
+
+    /// Number of rows [`WorkTreeNode::expanded`] would build for `node`, counting no further than
+    /// `limit`.
+    pub fn expanded_len(node: &Node, keep: Option<usize>, limit: usize) -> usize {
+        let mut len = 1;
+        let mut stack = vec![node];
+        while let Some(node) = stack.pop() {
+            if len > limit {
+                break;
+            }
+            let keys = match node.as_index().kind {
+                IndexKind::Terminal => continue,
+                IndexKind::Object(items) => items,
+                IndexKind::Array(n) => match keep.filter(|&keep| n > 2 * keep + 1) {
+                    Some(keep) => {
+                        len += 1;
+                        (0..keep)
+                            .chain(n - keep..n)
+                            .map(|i| i.to_string())
+                            .collect()
+                    }
+                    None => (0..n).map(|i| i.to_string()).collect(),
+                },
+            };
+            len += keys.len();
+            stack.extend(keys.iter().filter_map(|key| node.child(key)));
+        }
+        len
+    }
+
+    /// Puts `subtree`, built by [`WorkTreeNode::expanded`], in place of the node at `index`,
+    /// keeping its name.
+    pub(crate) fn replace_subtree(&mut self, index: usize, subtree: WorkTreeNode) {
+        let old_len = RefCell::new(0);
+        let new_len = subtree.len;
+        self.traverse_node_mut(
+            index,
+            &mut |_| {},
+            &mut |node: &mut WorkTreeNode, child_index| {
+                if child_index.is_some() {
+                    node.len -= *old_len.borrow();
+                    node.len += new_len;
+                }
+            },
+            |node: &mut WorkTreeNode| {
+                *old_len.borrow_mut() = node.len;
+                node.len = new_len;
+                node.meta = subtree.meta;
+                node.child = subtree.child;
+            },
+        );
+    }
+
+    /// Adds `n` rows after the last child of the root array.
+    pub(crate) fn extend_root(&mut self, n: usize, meta: NodeMeta) {
+        self.meta = Some(meta);
+        let Some(child) = &mut self.child else {
+            return;
+        };
+        let start = child
+            .last()
+            .map(|last| {
+                let start = last.name.parse::<usize>().expect("broken array row");
+                start + last.elided.unwrap_or(1)
+            })
+            .unwrap_or_default();
+        child.extend((start..start + n).map(|i| Self::new_empty(i.to_string())));
+        self.len += n;
+    }
+
+    /// Refreshes the cached metas of every loaded row from `node`.
+    pub fn refresh_metas(&mut self, node: &Node) {
+        if self.meta.is_some() {
+            self.meta = Some(node.as_meta());
+        }
+        for child in self.child.iter_mut().flatten() {
+            if let Ok(child_node) = node.subtree(&[&*child.name]) {
+                child.refresh_metas(child_node);
+            }
+        }
+    }
+
     pub(crate) fn rename(&mut self, index: usize, new_key: String) {
         let new_key_len = new_key.len();
         let old_key_len = RefCell::new(0);
@@ -109,7 +384,7 @@ impl WorkTreeNode {
             },
             |node: &mut WorkTreeNode| {
                 *old_key_len.borrow_mut() = node.name.len();
-                node.name = new_key;
+                node.name = intern(&new_key);
             },
         );
     }
@@ -130,9 +405,7 @@ impl WorkTreeNode {
                     };
 
                     if matches!(meta.kind, NodeKind::Array) {
-                        for (index, child) in child.iter_mut().enumerate() {
-                            child.name = index.to_string();
-                        }
+                        renumber(child);
                     }
                     *should_delete.borrow_mut() = false;
                 }
@@ -170,9 +443,7 @@ impl WorkTreeNode {
                     };
 
                     if matches!(meta.kind, NodeKind::Array) {
-                        for (index, child) in child.iter_mut().enumerate() {
-                            child.name = index.to_string();
-                        }
+                        renumber(child);
                     }
                     *should_append.borrow_mut() = false;
                 }
@@ -186,6 +457,86 @@ impl WorkTreeNode {
         );
     }
 
+    /// Exchanges the node at `index` with its next sibling, or the previous one when `up`, and
+    /// returns the index it moved to. Hidden elements are never swapped with.
+    pub(crate) fn swap(&mut self, index: usize, up: bool) -> Option<usize> {
+        let mut is_parent = true;
+        let mut moved_to = None;
+        self.traverse_node_mut(
+            index,
+            &mut |_| {},
+            &mut |node: &mut WorkTreeNode, child_index| {
+                let Some(child_index) = child_index else {
+                    return;
+                };
+                if !std::mem::replace(&mut is_parent, false) {
+                    return;
+                }
+                let Some(child) = &mut node.child else {
+                    return;
+                };
+                let other = if up {
+                    child_index.checked_sub(1)
+                } else {
+                    Some(child_index + 1).filter(|&other| other < child.len())
+                };
+                let Some(other) = other.filter(|&other| child[other].elided.is_none()) else {
+                    return;
+                };
+
+                moved_to = Some(if up {
+                    index - child[other].len
+                } else {
+                    index + child[other].len
+                });
+                child.swap(child_index, other);
+                if node
+                    .meta
+                    .is_some_and(|meta| matches!(meta.kind, NodeKind::Array))
+                {
+                    renumber(child);
+                }
+            },
+            |_| {},
+        );
+        moved_to
+    }
+
+    /// Reorders the children of the node at `index` after a sort, given their previous positions
+    /// in their new order. Returns `false` when some are hidden, as the rows must then be rebuilt.
+    pub(crate) fn permute(&mut self, index: usize, order: &[usize]) -> bool {
+        let mut permuted = true;
+        self.traverse_node_mut(
+            index,
+            &mut |_| {},
+            &mut |_, _| {},
+            |node: &mut WorkTreeNode| {
+                let Some(child) = &mut node.child else {
+                    return;
+                };
+                if child.len() != order.len() || child.iter().any(|child| child.elided.is_some()) {
+                    permuted = false;
+                    return;
+                }
+                let mut old = std::mem::take(child)
+                    .into_iter()
+                    .map(Some)
+                    .collect::<Vec<_>>();
+                *child = order
+                    .iter()
+                    .map(|&index| old[index].take().expect("order is a permutation"))
+                    .collect();
+                if node
+                    .meta
+                    .is_some_and(|meta| matches!(meta.kind, NodeKind::Array))
+                {
+                    renumber(child);
+                }
+            },
+        );
+        permuted
+    }
+
     pub fn close(&mut self, index: usize) {
         let old_len = RefCell::new(1);
         self.traverse_node_mut(
@@ -281,69 +632,133 @@ impl WorkTreeNode {
         unreachable!()
     }
 
-    fn formatted_name(&self, is_last: Vec<bool>) -> String {
-        prefix(is_last).chain(self.name.chars()).collect()
+    fn formatted_name(&self, is_last: Vec<bool>, guides: TreeGuides) -> String {
+        prefix(is_last, guides)
+            .chain(self.label().chars())
+            .collect()
+    }
+
+    fn label(&self) -> Cow<'_, str> {
+        match self.elided {
+            Some(n) => Cow::Owned(format!("… {n} more")),
+            None => Cow::Borrowed(&*self.name),
+        }
     }
 }
 
-pub struct WorkTreeStringIter<'a> {
-    stack: Vec<Peekable<Iter<'a, WorkTreeNode>>>,
+fn renumber(child: &mut [WorkTreeNode]) {
+    let mut index = 0;
+    for child in child {
+        child.name = intern(&index.to_string());
+        index += child.elided.unwrap_or(1);
+    }
+}
+
+pub struct TreeRow<'a> {
+    pub text: String,
+    /// Key of the row within its parent, and how many ancestors it has.
+    pub name: &'a str,
+    pub depth: usize,
+    pub node: Option<&'a Node>,
+    /// Largest byte size among the row and its siblings
+    pub max_sibling_bytes: usize,
+}
+
+pub struct WorkTreeRowIter<'a> {
+    stack: Vec<RowLevel<'a>>,
+    guides: TreeGuides,
+}
+
+struct RowLevel<'a> {
+    children: Peekable<Iter<'a, WorkTreeNode>>,
+    parent: Option<&'a Node>,
+    max_child_bytes: usize,
+}
+
+impl<'a> RowLevel<'a> {
+    fn new(children: &'a [WorkTreeNode], parent: Option<&'a Node>) -> Self {
+        Self {
+            children: children.iter().peekable(),
+            parent,
+            max_child_bytes: parent
+                .into_iter()
+                .flat_map(Node::children)
+                .map(|child| child.as_meta().n_bytes)
+                .max()
+                .unwrap_or_default(),
+        }
+    }
 }
 
-impl<'a> WorkTreeStringIter<'a> {
-    fn new(init: Option<&'a [WorkTreeNode]>) -> Self {
+impl<'a> WorkTreeRowIter<'a> {
+    fn new(init: Option<&'a [WorkTreeNode]>, parent: Option<&'a Node>, guides: TreeGuides) -> Self {
         Self {
             stack: if let Some(init) = init {
-                vec![init.iter().peekable()]
+                vec![RowLevel::new(init, parent)]
             } else {
                 Vec::new()
             },
+            guides,
         }
     }
 }
 
-impl Iterator for WorkTreeStringIter<'_> {
-    type Item = String;
+impl<'a> Iterator for WorkTreeRowIter<'a> {
+    type Item = TreeRow<'a>;
 
     fn next(&mut self) -> Option<Self::Item> {
         let mut next = None;
         while next.is_none() {
-            let next_iter = self.stack.last_mut()?;
-            next = next_iter.next();
+            let level = self.stack.last_mut()?;
+            next = level
+                .children
+                .next()
+                .map(|next| (next, level.parent, level.max_child_bytes));
             if next.is_none() {
                 self.stack.pop();
             }
         }
 
-        let next = next?;
+        let (next, parent, max_sibling_bytes) = next?;
+        let node = parent
+            .filter(|_| next.elided.is_none())
+            .and_then(|parent| parent.child(&next.name));
         let is_last: Vec<_> = self
             .stack
             .iter_mut()
-            .map(|parent| parent.peek().is_none())
+            .map(|level| level.children.peek().is_none())
             .collect();
         if let Some(child) = &next.child {
-            self.stack.push(child.iter().peekable());
+            self.stack.push(RowLevel::new(child, node));
         }
-        Some(next.formatted_name(is_last))
+        let depth = is_last.len();
+        Some(TreeRow {
+            text: next.formatted_name(is_last, self.guides),
+            name: &next.name,
+            depth,
+            node,
+            max_sibling_bytes,
+        })
     }
 }
 
-fn prefix(mut is_last: Vec<bool>) -> impl Iterator<Item = char> {
+fn prefix(mut is_last: Vec<bool>, guides: TreeGuides) -> impl Iterator<Item = char> {
+    let [line, branch, last_branch, dash] = guides.glyphs();
     let last = is_last.pop();
 
     is_last
         .into_iter()
-        .flat_map(|is_last| {
+        .flat_map(move |is_last| {
             if is_last {
                 [' ', ' ', ' ']
             } else {
-                [' ', '│', ' ']
+                [' ', line, ' ']
             }
         })
         .chain(match last {
-            None => [' '].as_slice().iter().copied(),
-            Some(true) => [' ', '└', '─', ' '].as_slice().iter().copied(),
-            Some(false) => [' ', '├', '─', ' '].as_slice().iter().copied(),
+            None => vec![' '],
+            Some(true) => vec![' ', last_branch, dash, ' '],
+            Some(false) => vec![' ', branch, dash, ' '],
         })
         .skip(1)
 }
@@ -409,6 +824,32 @@ mod test {
                 String::from("└─ d"),
             ]
         );
+
+        let text = |guides| {
+            node.as_tree_rows(None, guides)
+                .map(|row| row.text)
+                .skip(3)
+                .take(4)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            text(TreeGuides::Ascii),
+            vec![
+                String::from("|  `- ab"),
+                String::from("|- b"),
+                String::from("|  |- 0"),
+                String::from("|  |- 1"),
+            ]
+        );
+        assert_eq!(
+            text(TreeGuides::Rounded),
+            vec![
+                String::from("│  ╰─ ab"),
+                String::from("├─ b"),
+                String::from("│  ├─ 0"),
+                String::from("│  ├─ 1"),
+            ]
+        );
     }
 
     #[test]
@@ -445,12 +886,158 @@ mod test {
         );
 
         assert_eq!(node.len(), 10);
-        assert_eq!(node.selector(0), Vec::<&str>::new());
-        assert_eq!(node.selector(1), vec!["a"]);
-        assert_eq!(node.selector(2), vec!["a", "aa"]);
-        assert_eq!(node.selector(3), vec!["a", "ab"]);
-        assert_eq!(node.selector(4), vec!["b"]);
-        assert_eq!(node.selector(5), vec!["b", "0"]);
-        assert_eq!(node.selector(8), vec!["c"]);
+        assert!(node.selector(0).is_empty());
+        assert_eq!(node.selector(1), ["a"]);
+        assert_eq!(node.selector(2), ["a", "aa"]);
+        assert_eq!(node.selector(3), ["a", "ab"]);
+        assert_eq!(node.selector(4), ["b"]);
+        assert_eq!(node.selector(5), ["b", "0"]);
+        assert_eq!(node.selector(8), ["c"]);
+    }
+
+    #[test]
+    fn work_tree_rows_test() {
+        let root = Node::load(r#"{"a": [1, 2], "bb": "x", "c": null}"#.as_bytes()).unwrap();
+        let mut node = WorkTreeNode::new_empty(String::from("root"));
+        node.reindex(0, root.as_index(), true);
+        node.reindex(1, root.subtree(&["a"]).unwrap().as_index(), true);
+
+        let rows = node
+            .as_tree_rows(Some(&root), TreeGuides::default())
+            .map(|row| {
+                (
+                    row.text,
+                    row.node.map(|node| node.as_meta().n_bytes),
+                    row.max_sibling_bytes,
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            rows,
+            vec![
+                (String::from("root"), Some(55), 55),
+                (String::from("├─ a"), Some(12), 12),
+                (String::from("│  ├─ 0"), Some(1), 1),
+                (String::from("│  └─ 1"), Some(1), 1),
+                (String::from("├─ bb"), Some(3), 12),
+                (String::from("└─ c"), Some(4), 12),
+            ]
+        );
+        assert_eq!(
+            node.as_tree_rows(None, TreeGuides::default())
+                .map(|row| (row.name, row.depth))
+                .collect::<Vec<_>>(),
+            [
+                ("root", 0),
+                ("a", 1),
+                ("0", 2),
+                ("1", 2),
+                ("bb", 1),
+                ("c", 1)
+            ]
+        );
+    }
+
+    #[test]
+    fn work_tree_filtered_test() {
+        let root = Node::load(r#"{"a": {"b": 1, "c": [2, 3]}, "d": 4, "e": {"f": 5}}"#.as_bytes())
+            .unwrap();
+        let paths = [
+            vec![String::from("a")],
+            vec![String::from("a"), String::from("c"), String::from("1")],
+            vec![String::from("e")],
+        ];
+        let node = WorkTreeNode::filtered(&root, &paths);
+
+        assert_eq!(node.len(), 5);
+        assert_eq!(
+            node.as_tree_string().collect::<Vec<_>>(),
+            vec![
+                String::from("root"),
+                String::from("├─ a"),
+                String::from("│  └─ c"),
+                String::from("│     └─ 1"),
+                String::from("└─ e"),
+            ]
+        );
+        assert_eq!(node.selector(3), ["a", "c", "1"]);
+    }
+
+    #[test]
+    fn work_tree_elide_test() {
+        let mut node = WorkTreeNode::new_empty(String::from("root"));
+        node.reindex(
+            0,
+            Index {
+                meta: NodeMeta::null(),
+                kind: IndexKind::Array(10),
+            },
+            true,
+        );
+        node.elide(0, 2);
+
+        assert_eq!(node.len(), 6);
+        assert_eq!(
+            node.as_tree_string().collect::<Vec<_>>(),
+            vec![
+                String::from("root"),
+                String::from("├─ 0"),
+                String::from("├─ 1"),
+                String::from("├─ … 6 more"),
+                String::from("├─ 8"),
+                String::from("└─ 9"),
+            ]
+        );
+        assert_eq!(node.elided(3), Some(6));
+        assert_eq!(node.selector(4), ["8"]);
+
+        assert_eq!(node.child_row(0, "1"), Some(2));
+        assert_eq!(node.child_row(0, "5"), Some(3));
+        assert_eq!(node.child_row(0, "9"), Some(5));
+        assert_eq!(node.child_row(0, "10"), None);
+
+        assert_eq!(node.swap(1, false), Some(2));
+        assert_eq!(node.swap(2, true), Some(1));
+        assert_eq!(node.swap(1, true), None);
+        assert_eq!(node.swap(2, false), None);
+
+        assert!(node.materialize(3));
+        assert_eq!(node.len(), 11);
+        assert_eq!(node.elided(3), None);
+        assert_eq!(node.selector(3), ["2"]);
+        assert_eq!(node.selector(10), ["9"]);
+    }
+
+    #[test]
+    fn work_tree_expanded_test() {
+        let root = Node::load(r#"{"a": {"b": [1, 2, 3, 4, 5, 6]}, "c": 7}"#.as_bytes()).unwrap();
+        let mut node = WorkTreeNode::new_empty(String::from("root"));
+        node.reindex(0, root.as_index(), true);
+
+        assert_eq!(WorkTreeNode::expanded_len(&root, Some(1), usize::MAX), 7);
+        assert_eq!(WorkTreeNode::expanded_len(&root, None, usize::MAX), 10);
+        assert!(WorkTreeNode::expanded_len(&root, None, 2) < 10);
+
+        let a = root.subtree(&["a"]).unwrap();
+        node.replace_subtree(1, WorkTreeNode::expanded(String::new(), a, Some(1)));
+        assert_eq!(node.len(), 7);
+        assert_eq!(
+            node.as_tree_string().collect::<Vec<_>>(),
+            vec![
+                String::from("root"),
+                String::from("├─ a"),
+                String::from("│  └─ b"),
+                String::from("│     ├─ 0"),
+                String::from("│     ├─ … 4 more"),
+                String::from("│     └─ 5"),
+                String::from("└─ c"),
+            ]
+        );
+        assert_eq!(node.selector(5), ["a", "b", "5"]);
+
+        node.replace_subtree(0, WorkTreeNode::expanded(String::new(), &root, None));
+        assert_eq!(node.len(), 10);
+        assert_eq!(node.selector(0).len(), 0);
+        assert_eq!(node.selector(9), ["c"]);
     }
 }
@@ -1,12 +1,140 @@
-use std::{fs::File, io::Read};
+use std::{
+    collections::BTreeMap,
+    fs::File,
+    io::Read,
+    path::{Path, PathBuf},
+    time::Duration,
+};
 
 use byte_unit::{Byte, Unit};
 use serde::Deserialize;
 
+use super::{
+    paths,
+    remote::{self, Remote},
+    tool::Tool,
+};
+use crate::container::{
+    encoding::{Indent, LineEnding},
+    parser::{DuplicateKeys, NonFinite, ParseOptions},
+};
+
 #[derive(Debug)]
 #[cfg_attr(test, derive(PartialEq))]
 pub struct Config {
     pub max_preview_size: Byte,
+    pub array_sample_size: Option<usize>,
+    pub heat_map: bool,
+    /// Shows a short summary of each value after its key in the tree.
+    pub value_hints: bool,
+    /// Longest value summary, in characters.
+    pub value_hint_width: usize,
+    /// Shows the kind, number of children and size of each node after its key in the tree.
+    pub type_badges: bool,
+    pub tree_guides: TreeGuides,
+    pub preview_layout: PreviewLayout,
+    pub max_document_size: Option<Byte>,
+    pub persist_registers: bool,
+    /// Named JSON fragments offered when inserting a snippet.
+    pub snippets: BTreeMap<String, String>,
+    pub non_finite_numbers: NonFinite,
+    pub lossy_utf8: bool,
+    pub duplicate_keys: DuplicateKeys,
+    /// Documents at least this large only have their top levels parsed on load, the rest as it
+    /// is expanded.
+    pub lazy_load_size: Byte,
+    pub preserve_encoding: bool,
+    pub line_ending: LineEnding,
+    pub indent: Indent,
+    /// Saves without any whitespace, whatever the indent.
+    pub minify: bool,
+    /// Time between redraws while the loading popup animates or keys are replayed; otherwise the
+    /// editor sleeps until something happens.
+    pub frame_time: Duration,
+    pub animations: bool,
+    /// Capture the mouse for selecting, scrolling and resizing.
+    pub mouse: bool,
+    /// Never run external programs such as `$EDITOR`.
+    pub no_shell: bool,
+    /// External programs for matching nodes, tried in order.
+    pub tools: Vec<Tool>,
+    /// Helpers for opening and saving URLs, keyed by scheme.
+    pub remotes: BTreeMap<String, Remote>,
+    /// Compacts the document in the background after this many deletions.
+    pub compact_after_deletes: Option<usize>,
+    pub number_edits: NumberEdits,
+    /// Searches match terminal values as well as keys.
+    pub search_values: bool,
+    /// Yanks into the unnamed register also go to the system clipboard.
+    pub clipboard: bool,
+    /// Edits strings, numbers, booleans and null in a text dialog rather than `$EDITOR`.
+    pub inline_scalar_edits: bool,
+    /// Writes unsaved changes next to the document this often, to recover them after a crash.
+    pub autosave: Option<Duration>,
+    /// Copies the output file to `file.json.bak` before saving over it.
+    pub backup: bool,
+    /// Where the scratch files handed to `$EDITOR` go, rather than the temporary directory.
+    pub editor_buffer_dir: Option<PathBuf>,
+}
+
+/// How an edited number is stored when it is written as the other numeric kind.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+#[cfg_attr(test, derive(serde::Serialize))]
+#[serde(rename_all = "snake_case")]
+pub enum NumberEdits {
+    /// As written, so `2` is an integer and `2.0` a float.
+    #[default]
+    Literal,
+    /// As the kind it replaces, so `2` over a float is stored as `2.0` and `2.0` over an integer
+    /// as `2`.
+    KeepKind,
+}
+
+/// Characters drawing the guides that link tree rows to their parents.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+#[cfg_attr(test, derive(serde::Serialize))]
+#[serde(rename_all = "snake_case")]
+pub enum TreeGuides {
+    /// `│`, `├─` and `└─`.
+    #[default]
+    Unicode,
+    /// `│`, `├─` and `╰─`.
+    Rounded,
+    /// `|`, `|-` and `` `- ``, for fonts without box-drawing characters.
+    Ascii,
+}
+
+/// Where the previews go relative to the tree.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+#[cfg_attr(test, derive(serde::Serialize))]
+#[serde(rename_all = "snake_case")]
+pub enum PreviewLayout {
+    /// Right of the tree.
+    #[default]
+    Beside,
+    /// Under the tree, for narrow terminals.
+    Below,
+}
+
+impl PreviewLayout {
+    pub fn toggle(self) -> Self {
+        match self {
+            PreviewLayout::Beside => PreviewLayout::Below,
+            PreviewLayout::Below => PreviewLayout::Beside,
+        }
+    }
+}
+
+impl TreeGuides {
+    /// The vertical line, the branch to a child, the branch to the last child, and the line
+    /// leading to the child's name.
+    pub fn glyphs(self) -> [char; 4] {
+        match self {
+            TreeGuides::Unicode => ['│', '├', '└', '─'],
+            TreeGuides::Rounded => ['│', '├', '╰', '─'],
+            TreeGuides::Ascii => ['|', '|', '`', '-'],
+        }
+    }
 }
 
 impl Default for Config {
@@ -14,16 +142,58 @@ impl Default for Config {
         Self {
             max_preview_size: Byte::from_u64_with_unit(1, Unit::MiB)
                 .expect("failed to build default max_preview_size"),
+            array_sample_size: None,
+            heat_map: false,
+            value_hints: false,
+            value_hint_width: 30,
+            type_badges: false,
+            tree_guides: TreeGuides::default(),
+            preview_layout: PreviewLayout::default(),
+            max_document_size: None,
+            persist_registers: false,
+            snippets: BTreeMap::new(),
+            non_finite_numbers: NonFinite::default(),
+            lossy_utf8: false,
+            duplicate_keys: DuplicateKeys::default(),
+            lazy_load_size: Byte::from_u64_with_unit(128, Unit::MiB)
+                .expect("failed to build default lazy_load_size"),
+            preserve_encoding: true,
+            line_ending: LineEnding::default(),
+            indent: Indent::default(),
+            minify: false,
+            frame_time: Duration::from_millis(16),
+            animations: true,
+            mouse: true,
+            no_shell: false,
+            tools: Vec::new(),
+            remotes: remote::defaults(),
+            compact_after_deletes: None,
+            number_edits: NumberEdits::default(),
+            search_values: false,
+            clipboard: true,
+            inline_scalar_edits: true,
+            autosave: None,
+            backup: false,
+            editor_buffer_dir: None,
         }
     }
 }
 
 impl Config {
     pub fn load() -> Self {
-        Self::default().patch_from_files(&["/etc/jedit", "~/.jedit", ".jedit"])
+        Self::default().patch_from_files(&paths::config_files())
+    }
+
+    pub fn parse_options(&self) -> ParseOptions {
+        ParseOptions {
+            non_finite: self.non_finite_numbers,
+            lossy_utf8: self.lossy_utf8,
+            duplicate_keys: self.duplicate_keys,
+            lazy_size: Some(self.lazy_load_size.as_u64() as usize),
+        }
     }
 
-    fn patch_from_files(self, files: &[&str]) -> Self {
+    fn patch_from_files<P: AsRef<Path>>(self, files: &[P]) -> Self {
         files
             .iter()
             .map(File::open)
@@ -41,6 +211,102 @@ impl Config {
         if let Some(max_preview_size) = patch.max_preview_size {
             self.max_preview_size = max_preview_size
         }
+        if let Some(array_sample_size) = patch.array_sample_size {
+            self.array_sample_size = Some(array_sample_size)
+        }
+        if let Some(heat_map) = patch.heat_map {
+            self.heat_map = heat_map
+        }
+        if let Some(value_hints) = patch.value_hints {
+            self.value_hints = value_hints
+        }
+        if let Some(value_hint_width) = patch.value_hint_width {
+            self.value_hint_width = value_hint_width
+        }
+        if let Some(type_badges) = patch.type_badges {
+            self.type_badges = type_badges
+        }
+        if let Some(tree_guides) = patch.tree_guides {
+            self.tree_guides = tree_guides
+        }
+        if let Some(preview_layout) = patch.preview_layout {
+            self.preview_layout = preview_layout
+        }
+        if let Some(max_document_size) = patch.max_document_size {
+            self.max_document_size = Some(max_document_size)
+        }
+        if let Some(persist_registers) = patch.persist_registers {
+            self.persist_registers = persist_registers
+        }
+        if let Some(snippets) = patch.snippets {
+            self.snippets.extend(snippets)
+        }
+        if let Some(non_finite_numbers) = patch.non_finite_numbers {
+            self.non_finite_numbers = non_finite_numbers
+        }
+        if let Some(lossy_utf8) = patch.lossy_utf8 {
+            self.lossy_utf8 = lossy_utf8
+        }
+        if let Some(duplicate_keys) = patch.duplicate_keys {
+            self.duplicate_keys = duplicate_keys
+        }
+        if let Some(lazy_load_size) = patch.lazy_load_size {
+            self.lazy_load_size = lazy_load_size
+        }
+        if let Some(preserve_encoding) = patch.preserve_encoding {
+            self.preserve_encoding = preserve_encoding
+        }
+        if let Some(line_ending) = patch.line_ending {
+            self.line_ending = line_ending
+        }
+        if let Some(indent) = patch.indent {
+            self.indent = indent
+        }
+        if let Some(minify) = patch.minify {
+            self.minify = minify
+        }
+        if let Some(frame_time_ms) = patch.frame_time_ms {
+            self.frame_time = Duration::from_millis(frame_time_ms)
+        }
+        if let Some(animations) = patch.animations {
+            self.animations = animations
+        }
+        if let Some(mouse) = patch.mouse {
+            self.mouse = mouse
+        }
+        if let Some(no_shell) = patch.no_shell {
+            self.no_shell = no_shell
+        }
+        if let Some(tools) = patch.tools {
+            self.tools.extend(tools)
+        }
+        if let Some(remotes) = patch.remotes {
+            self.remotes.extend(remotes)
+        }
+        if let Some(compact_after_deletes) = patch.compact_after_deletes {
+            self.compact_after_deletes = Some(compact_after_deletes)
+        }
+        if let Some(number_edits) = patch.number_edits {
+            self.number_edits = number_edits
+        }
+        if let Some(search_values) = patch.search_values {
+            self.search_values = search_values
+        }
+        if let Some(clipboard) = patch.clipboard {
+            self.clipboard = clipboard
+        }
+        if let Some(inline_scalar_edits) = patch.inline_scalar_edits {
+            self.inline_scalar_edits = inline_scalar_edits
+        }
+        if let Some(autosave_secs) = patch.autosave_secs {
+            self.autosave = Some(Duration::from_secs(autosave_secs))
+        }
+        if let Some(backup) = patch.backup {
+            self.backup = backup
+        }
+        if let Some(editor_buffer_dir) = patch.editor_buffer_dir {
+            self.editor_buffer_dir = Some(editor_buffer_dir)
+        }
 
         self
     }
@@ -52,12 +318,111 @@ impl Config {
         self.max_preview_size = max_preview_size;
         self
     }
+
+    pub fn with_array_sample_size(mut self, array_sample_size: usize) -> Self {
+        self.array_sample_size = Some(array_sample_size);
+        self
+    }
+
+    pub fn with_value_hints(mut self, value_hint_width: usize) -> Self {
+        self.value_hints = true;
+        self.value_hint_width = value_hint_width;
+        self
+    }
+
+    pub fn with_type_badges(mut self) -> Self {
+        self.type_badges = true;
+        self
+    }
+
+    pub fn with_tree_guides(mut self, tree_guides: TreeGuides) -> Self {
+        self.tree_guides = tree_guides;
+        self
+    }
+
+    pub fn with_preview_layout(mut self, preview_layout: PreviewLayout) -> Self {
+        self.preview_layout = preview_layout;
+        self
+    }
+
+    pub fn with_snippet(mut self, name: &str, content: &str) -> Self {
+        self.snippets
+            .insert(String::from(name), String::from(content));
+        self
+    }
+
+    pub fn with_no_shell(mut self) -> Self {
+        self.no_shell = true;
+        self
+    }
+
+    pub fn without_inline_scalar_edits(mut self) -> Self {
+        self.inline_scalar_edits = false;
+        self
+    }
+
+    pub fn with_tool(mut self, tool: Tool) -> Self {
+        self.tools.push(tool);
+        self
+    }
+
+    pub fn with_compact_after_deletes(mut self, compact_after_deletes: usize) -> Self {
+        self.compact_after_deletes = Some(compact_after_deletes);
+        self
+    }
+
+    pub fn with_number_edits(mut self, number_edits: NumberEdits) -> Self {
+        self.number_edits = number_edits;
+        self
+    }
+
+    pub fn with_duplicate_keys(mut self, duplicate_keys: DuplicateKeys) -> Self {
+        self.duplicate_keys = duplicate_keys;
+        self
+    }
+
+    pub fn with_max_document_size(mut self, max_document_size: Byte) -> Self {
+        self.max_document_size = Some(max_document_size);
+        self
+    }
 }
 
 #[derive(Debug, Default, Deserialize)]
 #[cfg_attr(test, derive(serde::Serialize))]
 struct ConfigPatch {
     pub max_preview_size: Option<Byte>,
+    pub array_sample_size: Option<usize>,
+    pub heat_map: Option<bool>,
+    pub value_hints: Option<bool>,
+    pub value_hint_width: Option<usize>,
+    pub type_badges: Option<bool>,
+    pub tree_guides: Option<TreeGuides>,
+    pub preview_layout: Option<PreviewLayout>,
+    pub max_document_size: Option<Byte>,
+    pub persist_registers: Option<bool>,
+    pub snippets: Option<BTreeMap<String, String>>,
+    pub non_finite_numbers: Option<NonFinite>,
+    pub lossy_utf8: Option<bool>,
+    pub duplicate_keys: Option<DuplicateKeys>,
+    pub lazy_load_size: Option<Byte>,
+    pub preserve_encoding: Option<bool>,
+    pub line_ending: Option<LineEnding>,
+    pub indent: Option<Indent>,
+    pub minify: Option<bool>,
+    pub frame_time_ms: Option<u64>,
+    pub animations: Option<bool>,
+    pub mouse: Option<bool>,
+    pub no_shell: Option<bool>,
+    pub tools: Option<Vec<Tool>>,
+    pub remotes: Option<BTreeMap<String, Remote>>,
+    pub compact_after_deletes: Option<usize>,
+    pub number_edits: Option<NumberEdits>,
+    pub search_values: Option<bool>,
+    pub clipboard: Option<bool>,
+    pub inline_scalar_edits: Option<bool>,
+    pub autosave_secs: Option<u64>,
+    pub backup: Option<bool>,
+    pub editor_buffer_dir: Option<PathBuf>,
 }
 
 #[cfg(test)]
@@ -71,6 +436,7 @@ mod test {
         let config = Config::default();
         let patch = ConfigPatch {
             max_preview_size: None,
+            ..Default::default()
         };
 
         let config = config.patch(patch);
@@ -78,6 +444,7 @@ mod test {
 
         let patch = ConfigPatch {
             max_preview_size: Some(Byte::from_u64(123)),
+            ..Default::default()
         };
         let config = config.patch(patch);
         assert_eq!(
@@ -96,6 +463,7 @@ mod test {
             "/tmp/jedit-config-none",
             &toml::to_string_pretty(&ConfigPatch {
                 max_preview_size: None,
+                ..Default::default()
             })
             .unwrap(),
         );
@@ -106,6 +474,7 @@ mod test {
             "/tmp/jedit-config-some",
             &toml::to_string_pretty(&ConfigPatch {
                 max_preview_size: Some(Byte::from_u64(123)),
+                ..Default::default()
             })
             .unwrap(),
         );
@@ -119,6 +488,7 @@ mod test {
             "/tmp/jedit-config-some-2",
             &toml::to_string_pretty(&ConfigPatch {
                 max_preview_size: Some(Byte::from_u64(1234)),
+                ..Default::default()
             })
             .unwrap(),
         );
@@ -130,6 +500,74 @@ mod test {
         );
     }
 
+    #[test]
+    fn config_patch_snippets_test() {
+        setup_file(
+            "/tmp/jedit-config-snippets-1",
+            "[snippets]\na = \"1\"\nb = \"2\"\n",
+        );
+        setup_file(
+            "/tmp/jedit-config-snippets-2",
+            "[snippets]\nb = '{\"b\": 3}'\n",
+        );
+        let config = Config::default().patch_from_files(&[
+            "/tmp/jedit-config-snippets-1",
+            "/tmp/jedit-config-snippets-2",
+        ]);
+        assert_eq!(
+            config,
+            Config::default()
+                .with_snippet("a", "1")
+                .with_snippet("b", r#"{"b": 3}"#)
+        );
+    }
+
+    #[test]
+    fn config_patch_indent_test() {
+        let indent =
+            |content: &str| toml::from_str::<ConfigPatch>(content).map(|patch| patch.indent);
+        assert_eq!(indent("indent = 4").unwrap(), Some(Indent::Spaces(4)));
+        assert_eq!(indent("indent = \"tab\"").unwrap(), Some(Indent::Tab));
+        assert_eq!(indent("indent = \"auto\"").unwrap(), Some(Indent::Auto));
+        assert!(indent("indent = \"tabs\"").is_err());
+
+        let patch = ConfigPatch {
+            indent: Some(Indent::Tab),
+            ..Default::default()
+        };
+        let content = toml::to_string_pretty(&patch).unwrap();
+        assert_eq!(indent(&content).unwrap(), Some(Indent::Tab));
+    }
+
+    #[test]
+    fn config_patch_tools_test() {
+        setup_file(
+            "/tmp/jedit-config-tools",
+            "[[tools]]\npath = \"/certs/*\"\ncommand = \"openssl x509 -text\"\n",
+        );
+        let config = Config::default().patch_from_files(&["/tmp/jedit-config-tools"]);
+        assert_eq!(
+            config,
+            Config::default().with_tool(Tool {
+                path: Some(String::from("/certs/*")),
+                value: None,
+                command: String::from("openssl x509 -text"),
+                edit: false,
+            })
+        );
+    }
+
+    #[test]
+    fn config_patch_remotes_test() {
+        setup_file(
+            "/tmp/jedit-config-remotes",
+            "[remotes.gs]\nfetch = \"gsutil cat {url}\"\nput = \"gsutil cp - {url}\"\n",
+        );
+        let config = Config::default().patch_from_files(&["/tmp/jedit-config-remotes"]);
+        assert_eq!(config.remotes.keys().collect::<Vec<_>>(), ["gs", "s3"]);
+        assert_eq!(config.remotes["gs"].fetch, "gsutil cat {url}");
+    }
+
     fn setup_file(file_path: &str, content: &str) {
         let mut file = File::create(file_path).unwrap();
         file.write_all(content.as_bytes()).unwrap();
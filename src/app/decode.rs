@@ -0,0 +1,197 @@
+use indexmap::IndexMap;
+
+use crate::container::node::Node;
+
+/// Way the preview can show a string value decoded instead of as JSON.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Decoding {
+    Base64,
+    Percent,
+    Jwt,
+}
+
+impl Decoding {
+    pub const ALL: [Self; 3] = [Self::Base64, Self::Percent, Self::Jwt];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Base64 => "base64",
+            Self::Percent => "URL-encoding",
+            Self::Jwt => "JWT",
+        }
+    }
+
+    /// `value` decoded, or `None` when it is not encoded this way. Binary base64 data is shown as
+    /// a hex dump, and JWTs as their pretty-printed header and payload.
+    pub fn decode(self, value: &str) -> Option<String> {
+        match self {
+            Self::Base64 => base64(value).map(|bytes| text_or_hex(&bytes)),
+            Self::Percent => percent(value),
+            Self::Jwt => jwt(value),
+        }
+    }
+}
+
+/// Bytes encoded by `value` in the standard or the URL-safe base64 alphabet, with or without
+/// padding. Whitespace is ignored.
+fn base64(value: &str) -> Option<Vec<u8>> {
+    let digits = value
+        .bytes()
+        .filter(|byte| !byte.is_ascii_whitespace())
+        .collect::<Vec<_>>();
+    let n_padding = digits
+        .iter()
+        .rev()
+        .take_while(|&&byte| byte == b'=')
+        .count();
+    let digits = &digits[..digits.len() - n_padding];
+    if digits.is_empty() || n_padding > 2 || digits.len() % 4 == 1 {
+        return None;
+    }
+
+    let mut bytes = Vec::with_capacity(digits.len() * 3 / 4);
+    let mut buffer = 0u32;
+    let mut n_bits = 0;
+    for &digit in digits {
+        let sextet = match digit {
+            b'A'..=b'Z' => digit - b'A',
+            b'a'..=b'z' => digit - b'a' + 26,
+            b'0'..=b'9' => digit - b'0' + 52,
+            b'+' | b'-' => 62,
+            b'/' | b'_' => 63,
+            _ => return None,
+        };
+        buffer = (buffer << 6) | u32::from(sextet);
+        n_bits += 6;
+        if n_bits >= 8 {
+            n_bits -= 8;
+            bytes.push((buffer >> n_bits) as u8);
+            buffer &= (1 << n_bits) - 1;
+        }
+    }
+    Some(bytes)
+}
+
+/// `bytes` as text when they are printable UTF-8, or as a `hexdump -C` style dump otherwise.
+fn text_or_hex(bytes: &[u8]) -> String {
+    if let Ok(text) = std::str::from_utf8(bytes)
+        && !text
+            .chars()
+            .any(|c| c.is_control() && !matches!(c, '\n' | '\r' | '\t'))
+    {
+        return String::from(text);
+    }
+
+    let mut dump = String::new();
+    for (index, chunk) in bytes.chunks(16).enumerate() {
+        let hex = chunk
+            .iter()
+            .map(|byte| format!("{byte:02x}"))
+            .collect::<Vec<_>>()
+            .join(" ");
+        let ascii = chunk
+            .iter()
+            .map(|&byte| match byte {
+                0x20..=0x7e => char::from(byte),
+                _ => '.',
+            })
+            .collect::<String>();
+        dump.push_str(&format!("{:08x}  {hex:<47}  |{ascii}|\n", index * 16));
+    }
+    dump
+}
+
+/// `value` with its `%XX` escapes decoded, unless it has none.
+fn percent(value: &str) -> Option<String> {
+    let mut bytes = Vec::with_capacity(value.len());
+    let mut is_escaped = false;
+    let mut rest = value.as_bytes();
+    while let Some((&byte, after)) = rest.split_first() {
+        let escaped = after
+            .get(..2)
+            .filter(|_| byte == b'%')
+            .and_then(|hex| std::str::from_utf8(hex).ok())
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match escaped {
+            Some(escaped) => {
+                bytes.push(escaped);
+                is_escaped = true;
+                rest = &after[2..];
+            }
+            None => {
+                bytes.push(byte);
+                rest = after;
+            }
+        }
+    }
+    is_escaped.then(|| String::from_utf8_lossy(&bytes).into_owned())
+}
+
+/// Header and payload of the JSON Web Token `value` as a pretty-printed object, leaving out its
+/// signature.
+fn jwt(value: &str) -> Option<String> {
+    let parts = value.trim().split('.').collect::<Vec<_>>();
+    let [header, payload, _signature] = parts[..] else {
+        return None;
+    };
+    let decode = |part: &str| {
+        let node = Node::load(&base64(part)?[..]).ok()?;
+        node.as_object().is_some().then_some(node)
+    };
+    let token = IndexMap::from([
+        (String::from("header"), decode(header)?),
+        (String::from("payload"), decode(payload)?),
+    ]);
+    Node::from_object(token).to_string_pretty().ok()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn base64_test() {
+        let decode = |value| Decoding::Base64.decode(value);
+        assert_eq!(decode("aGVsbG8gd29ybGQ=").unwrap(), "hello world");
+        assert_eq!(decode("aGVsbG8g\nd29ybGQ").unwrap(), "hello world");
+        assert_eq!(decode("-_8").unwrap(), decode("+/8=").unwrap());
+        assert_eq!(
+            decode("AAEC/w==").unwrap(),
+            format!("00000000  00 01 02 ff{}  |....|\n", " ".repeat(36))
+        );
+        assert_eq!(decode("abcde"), None);
+        assert_eq!(decode("a*bc"), None);
+        assert_eq!(decode("==="), None);
+    }
+
+    #[test]
+    fn percent_test() {
+        let decode = |value| Decoding::Percent.decode(value);
+        assert_eq!(decode("a%20b%2Fc%C3%A9+100%").unwrap(), "a b/cé+100%");
+        assert_eq!(decode("plain"), None);
+    }
+
+    #[test]
+    fn jwt_test() {
+        let token = concat!(
+            "eyJhbGciOiJIUzI1NiIsInR5cCI6IkpXVCJ9.",
+            "eyJzdWIiOiIxMjM0NTY3ODkwIiwibmFtZSI6IkpvaG4gRG9lIiwiaWF0IjoxNTE2MjM5MDIyfQ.",
+            "SflKxwRJSMeKKF2QT4fwpMeJf36POk6yJV_adQssw5c",
+        );
+        assert_eq!(
+            Decoding::Jwt.decode(token).unwrap(),
+            Node::load(
+                r#"{
+                    "header": {"alg": "HS256", "typ": "JWT"},
+                    "payload": {"sub": "1234567890", "name": "John Doe", "iat": 1516239022}
+                }"#
+                .as_bytes()
+            )
+            .unwrap()
+            .to_string_pretty()
+            .unwrap()
+        );
+        assert_eq!(Decoding::Jwt.decode("a.b.c"), None);
+        assert_eq!(Decoding::Jwt.decode("eyJhbGciOiJIUzI1NiJ9"), None);
+    }
+}
@@ -0,0 +1,262 @@
+use std::ops::Deref;
+
+use crate::container::node::Node;
+
+/// Largest `old × new` line table diffed exactly; bigger inputs show as a full replacement.
+const MAX_TABLE_SIZE: usize = 1 << 24;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DiffLine<'a> {
+    Same(&'a str),
+    Removed(&'a str),
+    Added(&'a str),
+}
+
+/// Line diff of `old` against `new` from their longest common subsequence.
+pub fn line_diff<'a>(old: &'a str, new: &'a str) -> Vec<DiffLine<'a>> {
+    let old = old.lines().collect::<Vec<_>>();
+    let new = new.lines().collect::<Vec<_>>();
+
+    // Skip the common prefix and suffix, which is most of the document for small edits.
+    let prefix = old
+        .iter()
+        .zip(&new)
+        .take_while(|(old, new)| old == new)
+        .count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(old, new)| old == new)
+        .count();
+    let old_mid = &old[prefix..old.len() - suffix];
+    let new_mid = &new[prefix..new.len() - suffix];
+
+    let mut lines = old[..prefix]
+        .iter()
+        .map(|line| DiffLine::Same(line))
+        .collect::<Vec<_>>();
+    if old_mid.len().saturating_mul(new_mid.len()) > MAX_TABLE_SIZE {
+        lines.extend(old_mid.iter().map(|line| DiffLine::Removed(line)));
+        lines.extend(new_mid.iter().map(|line| DiffLine::Added(line)));
+    } else {
+        lines.extend(lcs_diff(old_mid, new_mid));
+    }
+    lines.extend(
+        old[old.len() - suffix..]
+            .iter()
+            .map(|line| DiffLine::Same(line)),
+    );
+    lines
+}
+
+fn lcs_diff<'a>(old: &[&'a str], new: &[&'a str]) -> Vec<DiffLine<'a>> {
+    let width = new.len() + 1;
+    let mut table = vec![0usize; (old.len() + 1) * width];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            table[i * width + j] = if old[i] == new[j] {
+                table[(i + 1) * width + j + 1] + 1
+            } else {
+                table[(i + 1) * width + j].max(table[i * width + j + 1])
+            };
+        }
+    }
+
+    let mut lines = Vec::with_capacity(old.len() + new.len());
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() && j < new.len() {
+        if old[i] == new[j] {
+            lines.push(DiffLine::Same(old[i]));
+            i += 1;
+            j += 1;
+        } else if table[(i + 1) * width + j] >= table[i * width + j + 1] {
+            lines.push(DiffLine::Removed(old[i]));
+            i += 1;
+        } else {
+            lines.push(DiffLine::Added(new[j]));
+            j += 1;
+        }
+    }
+    lines.extend(old[i..].iter().map(|line| DiffLine::Removed(line)));
+    lines.extend(new[j..].iter().map(|line| DiffLine::Added(line)));
+    lines
+}
+
+/// Lays the line diff of `old` against `new` out in two columns headed by their names. Rows start
+/// with `-` or `+` where a line exists on one side only, and `~` where both sides changed.
+pub fn side_by_side(old_name: &str, old: &str, new_name: &str, new: &str) -> String {
+    let mut rows = vec![(' ', old_name, new_name)];
+    let mut removed = Vec::new();
+    let mut added = Vec::new();
+    let flush = |rows: &mut Vec<_>, removed: &mut Vec<_>, added: &mut Vec<_>| {
+        for i in 0..removed.len().max(added.len()) {
+            let row = match (removed.get(i), added.get(i)) {
+                (Some(old), Some(new)) => ('~', *old, *new),
+                (Some(old), None) => ('-', *old, ""),
+                (None, new) => ('+', "", new.copied().unwrap_or_default()),
+            };
+            rows.push(row);
+        }
+        removed.clear();
+        added.clear();
+    };
+    for line in line_diff(old, new) {
+        match line {
+            DiffLine::Same(line) => {
+                flush(&mut rows, &mut removed, &mut added);
+                rows.push((' ', line, line));
+            }
+            DiffLine::Removed(line) => removed.push(line),
+            DiffLine::Added(line) => added.push(line),
+        }
+    }
+    flush(&mut rows, &mut removed, &mut added);
+
+    let width = rows
+        .iter()
+        .map(|(_, old, _)| old.chars().count())
+        .max()
+        .unwrap_or_default();
+    rows.into_iter()
+        .map(|(sign, old, new)| {
+            let row = format!("{sign} {old:<width$} │ {new}");
+            format!("{}\n", row.trim_end())
+        })
+        .collect()
+}
+
+/// Paths that differ between two versions of a subtree.
+#[derive(Debug, Default)]
+#[cfg_attr(test, derive(PartialEq))]
+pub struct StructuralDiff {
+    pub added: Vec<Vec<String>>,
+    pub removed: Vec<Vec<String>>,
+    pub changed: Vec<Vec<String>>,
+}
+
+impl StructuralDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+/// Compares `old` and `new` member by member, where `old` lives at `selector`. Added and removed
+/// paths are the outermost ones; anything else that differs counts as changed, with arrays
+/// compared index by index.
+pub fn structural_diff<T: Deref<Target = str>>(
+    selector: &[T],
+    old: &Node,
+    new: &Node,
+) -> StructuralDiff {
+    let mut diff = StructuralDiff::default();
+    let mut selector = selector.iter().map(|key| key.to_string()).collect();
+    diff_at(&mut selector, old, new, &mut diff);
+    diff
+}
+
+fn diff_at(selector: &mut Vec<String>, old: &Node, new: &Node, diff: &mut StructuralDiff) {
+    let mut member = |selector: &mut Vec<String>, key: String, old: Option<&Node>, new| {
+        selector.push(key);
+        match (old, new) {
+            (Some(old), Some(new)) => diff_at(selector, old, new, diff),
+            (Some(_), None) => diff.removed.push(selector.clone()),
+            (None, Some(_)) => diff.added.push(selector.clone()),
+            (None, None) => {}
+        }
+        selector.pop();
+    };
+
+    if let (Some(old), Some(new)) = (old.as_object(), new.as_object()) {
+        for (key, old_member) in old {
+            member(selector, key.clone(), Some(old_member), new.get(key));
+        }
+        for (key, new_member) in new.iter().filter(|(key, _)| !old.contains_key(*key)) {
+            member(selector, key.clone(), None, Some(new_member));
+        }
+    } else if let (Some(old), Some(new)) = (old.as_array(), new.as_array()) {
+        for position in 0..old.len().max(new.len()) {
+            member(
+                selector,
+                position.to_string(),
+                old.get(position),
+                new.get(position),
+            );
+        }
+    } else if !old.same_value(new) {
+        diff.changed.push(selector.clone());
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn line_diff_test() {
+        assert_eq!(
+            line_diff("a\nb\nc\nd", "a\nc\nx\nd"),
+            vec![
+                DiffLine::Same("a"),
+                DiffLine::Removed("b"),
+                DiffLine::Same("c"),
+                DiffLine::Added("x"),
+                DiffLine::Same("d"),
+            ]
+        );
+        assert_eq!(line_diff("", "a"), vec![DiffLine::Added("a")]);
+        assert_eq!(line_diff("a", ""), vec![DiffLine::Removed("a")]);
+        assert_eq!(line_diff("a\na", "a\na"), vec![DiffLine::Same("a"); 2]);
+    }
+
+    #[test]
+    fn side_by_side_test() {
+        assert_eq!(
+            side_by_side("old", "a\nbb\nc\nd", "new", "a\nx\nc\ny\nz"),
+            [
+                "  old │ new",
+                "  a   │ a",
+                "~ bb  │ x",
+                "  c   │ c",
+                "~ d   │ y",
+                "+     │ z",
+                "",
+            ]
+            .join("\n")
+        );
+        assert_eq!(side_by_side("a", "1", "b", ""), "  a │ b\n- 1 │\n");
+    }
+
+    #[test]
+    fn structural_diff_test() {
+        let load = |input: &str| Node::load(input.as_bytes()).unwrap();
+        let selectors = |paths: &[&[&str]]| {
+            paths
+                .iter()
+                .map(|path| path.iter().map(|key| key.to_string()).collect::<Vec<_>>())
+                .collect::<Vec<_>>()
+        };
+
+        let diff = structural_diff(
+            &[String::from("root")],
+            &load(r#"{"a": {"b": 1, "c": 2}, "d": [1, 2], "e": "x"}"#),
+            &load(r#"{"a": {"b": 1, "c": 3}, "d": [1], "e": [], "f": {"g": 1}}"#),
+        );
+        assert_eq!(
+            diff,
+            StructuralDiff {
+                added: selectors(&[&["root", "f"]]),
+                removed: selectors(&[&["root", "d", "1"]]),
+                changed: selectors(&[&["root", "a", "c"], &["root", "e"]]),
+            }
+        );
+        assert!(
+            structural_diff::<&str>(
+                &[],
+                &load(r#"{"a": 1, "b": 2}"#),
+                &load(r#"{"b": 2, "a": 1}"#)
+            )
+            .is_empty()
+        );
+    }
+}
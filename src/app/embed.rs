@@ -0,0 +1,246 @@
+//! A jedit JSON pane for embedding in other ratatui applications.
+//!
+//! The host owns the terminal and the event loop. It feeds events to the pane, drains the
+//! [`Actions`] queue once per frame, and handles the [`Request`]s the pane cannot fulfil itself:
+//!
+//! ```ignore
+//! let mut pane = JsonPane::new(r#"{"a": 1}"#)?;
+//! let mut actions = Actions::new();
+//! loop {
+//!     terminal.draw(|frame| frame.render_widget(&mut pane, frame.area()))?;
+//!     if event::poll(Duration::from_millis(16))? {
+//!         pane.handle_event(&mut actions, event::read()?);
+//!     }
+//!     for request in pane.update(&mut actions) {
+//!         match request {
+//!             Request::Save | Request::SaveAs(_) => {
+//!                 fs::write("out.json", pane.document())?;
+//!                 pane.saved("Saved out.json");
+//!             }
+//!             Request::Copy(_) => {}
+//!             Request::Close => return Ok(()),
+//!         }
+//!     }
+//! }
+//! ```
+
+use crossterm::event::Event;
+use ratatui::{buffer::Buffer, layout::Rect, widgets::StatefulWidget, widgets::Widget};
+
+pub use super::action::Actions;
+use super::{
+    action::{Action, JobAction, WorkSpaceAction},
+    compact_job, compare_job,
+    component::workspace::{WorkSpace, WorkSpaceState},
+    config::Config,
+    expand_all_job, export_gron_job, import_file_job, import_folder_job, import_gron_job,
+    job::Job,
+    jq_job, restat_job, save_patch_job, write_files_job,
+};
+use crate::container::{node::Node, parser::ParseOptions};
+
+/// What the pane needs from its host.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Request {
+    /// Write [`JsonPane::document`] wherever the host keeps it, then call [`JsonPane::saved`].
+    Save,
+    /// Like [`Request::Save`], to the path the user typed.
+    SaveAs(String),
+    /// Put this JSON on the clipboard, as the pane cannot reach the terminal.
+    Copy(String),
+    /// The user quit the pane, after confirming any unsaved changes.
+    Close,
+}
+
+/// Editor for one JSON document, rendered as a widget. External programs such as `$EDITOR` and
+/// `$PAGER` are never run, as the terminal belongs to the host; nodes are edited inline instead.
+pub struct JsonPane {
+    workspace: WorkSpace,
+    state: WorkSpaceState,
+    parse_options: ParseOptions,
+    jobs: Vec<Job>,
+}
+
+impl JsonPane {
+    /// Opens `json` with the default configuration, ignoring any configuration files.
+    pub fn new(json: &str) -> std::io::Result<Self> {
+        let config = Config {
+            no_shell: true,
+            ..Config::default()
+        };
+        let node = Node::load(json.as_bytes())
+            .map_err(|error| std::io::Error::other(error.to_string()))?;
+        Ok(Self {
+            parse_options: config.parse_options(),
+            workspace: WorkSpace::new(node, config),
+            state: WorkSpaceState::default(),
+            jobs: Vec::new(),
+        })
+    }
+
+    /// Queues the actions `event` leads to, for the next [`JsonPane::update`].
+    pub fn handle_event(&self, actions: &mut Actions, event: Event) {
+        self.workspace.handle_event(&self.state, actions, event);
+    }
+
+    /// Handles the queued actions and those of finished background jobs. Call it once per frame,
+    /// even without events, so that jobs such as compaction land.
+    pub fn update(&mut self, actions: &mut Actions) -> Vec<Request> {
+        let mut jobs = Vec::new();
+        std::mem::swap(&mut jobs, &mut self.jobs);
+        for job in jobs {
+            if !job.is_done() {
+                self.jobs.push(job);
+                continue;
+            }
+            match job.action() {
+                Ok(action) => actions.push(action),
+                // The host keeps running whatever happens to the pane.
+                Err(error) => actions.push(WorkSpaceAction::Error(error.to_string()).into()),
+            }
+        }
+
+        let mut requests = Vec::new();
+        while let Some(action) = actions.next() {
+            match action {
+                Action::Exit(confirm_action) => {
+                    if self.workspace.maybe_exit(confirm_action) {
+                        requests.push(Request::Close);
+                    }
+                }
+                // There is a single document.
+                Action::SwitchTab(_) => {}
+                Action::CancelJobs => self.jobs.iter().for_each(Job::cancel),
+                Action::Workspace(workspace_action) => {
+                    if let Err(error) =
+                        self.workspace
+                            .handle_action(&mut self.state, actions, workspace_action)
+                    {
+                        self.workspace.push_message(error.to_string());
+                    }
+                }
+                Action::ExecuteJob(job) => match job {
+                    JobAction::Save | JobAction::Overwrite => requests.push(Request::Save),
+                    JobAction::SaveAs(path) => requests.push(Request::SaveAs(path)),
+                    JobAction::Copy { content, .. } => requests.push(Request::Copy(content)),
+                    JobAction::Restat => self
+                        .jobs
+                        .push(restat_job(self.workspace.file_root().clone())),
+                    JobAction::Compact => self
+                        .jobs
+                        .push(compact_job(self.workspace.file_root().clone())),
+                    JobAction::ExpandAll { selector, keep } => self.jobs.push(expand_all_job(
+                        self.workspace.file_root().clone(),
+                        selector,
+                        keep,
+                    )),
+                    JobAction::WriteFiles(files) => self.jobs.push(write_files_job(files)),
+                    JobAction::ImportFolder(dir) => {
+                        self.jobs.push(import_folder_job(dir, self.parse_options))
+                    }
+                    JobAction::ImportFile(path) => {
+                        self.jobs.push(import_file_job(path, self.parse_options))
+                    }
+                    JobAction::Compare(path) => {
+                        self.jobs.push(compare_job(path, self.parse_options))
+                    }
+                    JobAction::SavePatch { path, content } => {
+                        self.jobs.push(save_patch_job(path, content))
+                    }
+                    JobAction::ExportGron { path, content } => {
+                        self.jobs.push(export_gron_job(path, content))
+                    }
+                    JobAction::ImportGron(path) => self.jobs.push(import_gron_job(path)),
+                    JobAction::Jq { filter, content } => self.jobs.push(jq_job(filter, content)),
+                    // Editors, pagers and piped commands are disabled, there is no lock,
+                    // journal, followed or watched file, and the host owns the clipboard.
+                    JobAction::Edit(_)
+                    | JobAction::ReadClipboard { .. }
+                    | JobAction::Page { .. }
+                    | JobAction::Pipe { .. }
+                    | JobAction::ReadOnly(_)
+                    | JobAction::Follow { .. }
+                    | JobAction::Reload(_)
+                    | JobAction::Recover(_) => {}
+                },
+            }
+        }
+
+        self.workspace
+            .set_loading(self.jobs.iter().any(|job| !job.is_background()));
+        self.workspace
+            .set_progress(self.jobs.iter().find_map(Job::progress));
+        requests
+    }
+
+    /// Marks the document as saved after a [`Request::Save`], showing `message` in the status line.
+    pub fn saved(&mut self, message: &str) {
+        let mut actions = Actions::new();
+        actions.push(WorkSpaceAction::SaveDone(String::from(message)).into());
+        self.update(&mut actions);
+    }
+
+    /// The document as it would be saved.
+    pub fn document(&self) -> String {
+        self.workspace
+            .file_root()
+            .to_string_pretty()
+            .expect("broken internal representation")
+    }
+
+    pub fn is_edited(&self) -> bool {
+        self.workspace.is_edited()
+    }
+
+    /// Whether background jobs are still running, for hosts that only redraw on events.
+    pub fn is_busy(&self) -> bool {
+        !self.jobs.is_empty()
+    }
+}
+
+impl Widget for &mut JsonPane {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        (&self.workspace).render(area, buf, &mut self.state);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+    use super::*;
+
+    fn press(pane: &mut JsonPane, code: KeyCode) -> Vec<Request> {
+        let mut actions = Actions::new();
+        pane.handle_event(
+            &mut actions,
+            Event::Key(KeyEvent::new(code, KeyModifiers::NONE)),
+        );
+        pane.update(&mut actions)
+    }
+
+    #[test]
+    fn json_pane_test() {
+        let mut pane = JsonPane::new(r#"{"a": 1, "b": 2}"#).unwrap();
+        press(&mut pane, KeyCode::Enter);
+        press(&mut pane, KeyCode::Char('d'));
+        assert_eq!(press(&mut pane, KeyCode::Char('y')), []);
+        assert_eq!(pane.document(), "{\n  \"b\": 2\n}");
+
+        press(&mut pane, KeyCode::Char('w'));
+        assert_eq!(press(&mut pane, KeyCode::Char('y')), [Request::Save]);
+        assert!(pane.is_edited());
+        pane.saved("Saved");
+        assert!(!pane.is_edited());
+
+        press(&mut pane, KeyCode::Char('C'));
+        while pane.is_busy() {
+            pane.update(&mut Actions::new());
+        }
+        assert_eq!(pane.document(), "{\n  \"b\": 2\n}");
+
+        let mut buffer = Buffer::empty(Rect::new(0, 0, 40, 10));
+        (&mut pane).render(buffer.area, &mut buffer);
+        assert_eq!(press(&mut pane, KeyCode::Char('q')), [Request::Close]);
+    }
+}
@@ -0,0 +1,41 @@
+use byte_unit::{Byte, UnitType};
+
+pub fn bytes(n_bytes: usize) -> String {
+    format!(
+        "{:.2}",
+        Byte::from_u64(n_bytes as u64).get_appropriate_unit(UnitType::Binary)
+    )
+}
+
+/// Formats a count with thousands separators, e.g. `1,204`.
+pub fn count(n: usize) -> String {
+    let digits = n.to_string();
+    let mut res = String::with_capacity(digits.len() + digits.len() / 3);
+    for (i, c) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            res.push(',');
+        }
+        res.push(c);
+    }
+    res
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn bytes_test() {
+        assert_eq!(bytes(12), "12 B");
+        assert_eq!(bytes(3718), "3.63 KiB");
+        assert_eq!(bytes(5 * 1024 * 1024), "5.00 MiB");
+    }
+
+    #[test]
+    fn count_test() {
+        assert_eq!(count(0), "0");
+        assert_eq!(count(999), "999");
+        assert_eq!(count(1204), "1,204");
+        assert_eq!(count(1234567), "1,234,567");
+    }
+}
@@ -0,0 +1,107 @@
+use std::{
+    sync::mpsc::{self, Receiver, RecvTimeoutError, Sender},
+    time::Duration,
+};
+
+use crossterm::event::{self, Event};
+
+/// Longest a pause waits for the reader to let go of the terminal.
+const PAUSE_LATENCY: Duration = Duration::from_millis(100);
+
+/// Why the editor woke up.
+pub enum Wakeup {
+    Input(std::io::Result<Event>),
+    /// A job finished or sent an update.
+    Job,
+}
+
+enum Command {
+    Pause,
+    Resume,
+}
+
+/// Terminal events read on their own thread, so that the editor sleeps until an event arrives or
+/// a job wakes it.
+pub struct Input {
+    sender: Sender<Wakeup>,
+    receiver: Receiver<Wakeup>,
+    commands: Sender<Command>,
+    paused: Receiver<()>,
+}
+
+impl Input {
+    pub fn spawn() -> Self {
+        let (sender, receiver) = mpsc::channel();
+        let (commands, command_receiver) = mpsc::channel();
+        let (paused_sender, paused) = mpsc::channel();
+        let events = sender.clone();
+        std::thread::spawn(move || read_events(events, command_receiver, paused_sender));
+        Self {
+            sender,
+            receiver,
+            commands,
+            paused,
+        }
+    }
+
+    /// Sender waking [`Input::next`] without an event.
+    pub fn waker(&self) -> Sender<Wakeup> {
+        self.sender.clone()
+    }
+
+    /// The next event, or `None` when woken up or once `timeout` passes; without a timeout it
+    /// waits for good.
+    pub fn next(&self, timeout: Option<Duration>) -> std::io::Result<Option<Event>> {
+        let wakeup = match timeout {
+            Some(timeout) => match self.receiver.recv_timeout(timeout) {
+                Ok(wakeup) => wakeup,
+                Err(RecvTimeoutError::Timeout | RecvTimeoutError::Disconnected) => return Ok(None),
+            },
+            None => match self.receiver.recv() {
+                Ok(wakeup) => wakeup,
+                Err(_) => return Ok(None),
+            },
+        };
+        match wakeup {
+            Wakeup::Input(event) => event.map(Some),
+            Wakeup::Job => Ok(None),
+        }
+    }
+
+    /// Stops reading, so that an external program gets the keys typed while it runs.
+    pub fn pause(&self) {
+        if self.commands.send(Command::Pause).is_ok() {
+            let _ = self.paused.recv();
+        }
+    }
+
+    pub fn resume(&self) {
+        let _ = self.commands.send(Command::Resume);
+    }
+}
+
+fn read_events(events: Sender<Wakeup>, commands: Receiver<Command>, paused: Sender<()>) {
+    loop {
+        match commands.try_recv() {
+            Ok(Command::Pause) => {
+                let _ = paused.send(());
+                // Anything but a resume means the editor is gone.
+                if !matches!(commands.recv(), Ok(Command::Resume)) {
+                    return;
+                }
+            }
+            Ok(Command::Resume) => {}
+            Err(mpsc::TryRecvError::Empty) => {}
+            Err(mpsc::TryRecvError::Disconnected) => return,
+        }
+        let event = match event::poll(PAUSE_LATENCY) {
+            Ok(false) => continue,
+            Ok(true) => event::read(),
+            Err(error) => Err(error),
+        };
+        let is_error = event.is_err();
+        if events.send(Wakeup::Input(event)).is_err() || is_error {
+            return;
+        }
+    }
+}
@@ -1,9 +1,112 @@
-use std::thread::JoinHandle;
+use std::{
+    any::Any,
+    cell::Cell,
+    io::Read,
+    panic::{self, AssertUnwindSafe},
+    sync::{
+        Arc, LazyLock, Mutex, OnceLock,
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        mpsc::{self, Receiver, Sender},
+    },
+    thread,
+};
 
-use super::action::Action;
+use super::{action::Action, input::Wakeup};
+use crate::error::JobError;
+
+/// Most bytes a [`ProgressReader`] reads at once, so that the progress bar moves on huge files.
+const READ_CHUNK_SIZE: usize = 1 << 20;
+
+/// Fewest threads running jobs, so that a long job does not hold up a short one on single core
+/// machines.
+const MIN_WORKERS: usize = 2;
+
+static NEXT_JOB_ID: AtomicUsize = AtomicUsize::new(1);
+static WAKER: OnceLock<Sender<Wakeup>> = OnceLock::new();
+/// Queue of the worker threads running the jobs that end on their own, started on the first one.
+static POOL: LazyLock<Sender<Task>> = LazyLock::new(start_workers);
+
+type Task = Box<dyn FnOnce() + Send>;
+
+thread_local! {
+    static IN_JOB: Cell<bool> = const { Cell::new(false) };
+}
 
 #[derive(Debug)]
-pub struct Job(JoinHandle<Result<Action, std::io::Error>>);
+pub struct Job {
+    id: usize,
+    name: &'static str,
+    result: Receiver<Result<Action, JobError>>,
+    is_done: Arc<AtomicBool>,
+    progress: Option<Arc<Progress>>,
+    /// Actions sent while the job runs, for jobs spawned with [`Job::stream`].
+    updates: Option<Receiver<Action>>,
+    /// Whether the job runs without blocking the UI behind the loading popup.
+    is_background: bool,
+}
+
+/// Units of work done out of the total, shared between a job thread and the UI, which can also
+/// ask the job to stop through it.
+#[derive(Debug, Default)]
+pub struct Progress {
+    done: AtomicUsize,
+    total: AtomicUsize,
+    is_cancelled: AtomicBool,
+}
+
+impl Progress {
+    pub fn set_total(&self, total: usize) {
+        self.total.store(total, Ordering::Relaxed);
+    }
+
+    pub fn advance(&self, n: usize) {
+        self.done.fetch_add(n, Ordering::Relaxed);
+    }
+
+    pub fn cancel(&self) {
+        self.is_cancelled.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.is_cancelled.load(Ordering::Relaxed)
+    }
+
+    /// Fails once the job is cancelled, for jobs to call between steps.
+    pub fn check(&self) -> std::io::Result<()> {
+        if self.is_cancelled() {
+            return Err(std::io::Error::other("cancelled"));
+        }
+        Ok(())
+    }
+
+    /// `(done, total)`, or `None` until the total is known.
+    pub fn get(&self) -> Option<(usize, usize)> {
+        let total = self.total.load(Ordering::Relaxed);
+        (total > 0).then(|| (self.done.load(Ordering::Relaxed), total))
+    }
+}
+
+/// Reader advancing a [`Progress`] by the bytes read through it.
+pub struct ProgressReader<R> {
+    reader: R,
+    progress: Arc<Progress>,
+}
+
+impl<R> ProgressReader<R> {
+    pub fn new(reader: R, progress: Arc<Progress>) -> Self {
+        Self { reader, progress }
+    }
+}
+
+impl<R: Read> Read for ProgressReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        self.progress.check()?;
+        let len = buf.len().min(READ_CHUNK_SIZE);
+        let n = self.reader.read(&mut buf[..len])?;
+        self.progress.advance(n);
+        Ok(n)
+    }
+}
 
 #[cfg(test)]
 impl PartialEq for Job {
@@ -14,18 +117,278 @@ impl PartialEq for Job {
 
 impl Job {
     pub fn new<F: FnOnce() -> Result<Action, std::io::Error> + Sync + Send + 'static>(
+        name: &'static str,
+        f: F,
+    ) -> Self {
+        Self::spawn(name, None, false, f)
+    }
+
+    /// Spawns a job that keeps running while the user works, such as following a file.
+    pub fn background<F: FnOnce() -> Result<Action, std::io::Error> + Sync + Send + 'static>(
+        name: &'static str,
+        f: F,
+    ) -> Self {
+        Self::spawn(name, None, true, f)
+    }
+
+    /// Spawns a background job that sends actions through `updates` as things happen, such as
+    /// watching a file, besides the one it returns.
+    pub fn stream<F: FnOnce(Updates) -> Result<Action, std::io::Error> + Sync + Send + 'static>(
+        name: &'static str,
+        f: F,
+    ) -> Self {
+        let (sender, receiver) = mpsc::channel();
+        let mut job = Self::spawn(name, None, true, move || f(Updates(sender)));
+        job.updates = Some(receiver);
+        job
+    }
+
+    /// Spawns a job reporting its progress through `progress`.
+    pub fn with_progress<F: FnOnce() -> Result<Action, std::io::Error> + Sync + Send + 'static>(
+        name: &'static str,
+        progress: Arc<Progress>,
         f: F,
     ) -> Self {
-        Self(std::thread::spawn(f))
+        Self::spawn(name, Some(progress), false, f)
+    }
+
+    /// Queues `f` on the worker pool, or gives it a thread of its own when it runs in the
+    /// background, since jobs such as following a file would otherwise hold a worker for good.
+    fn spawn<F: FnOnce() -> Result<Action, std::io::Error> + Sync + Send + 'static>(
+        name: &'static str,
+        progress: Option<Arc<Progress>>,
+        is_background: bool,
+        f: F,
+    ) -> Self {
+        let id = NEXT_JOB_ID.fetch_add(1, Ordering::Relaxed);
+        let (sender, result) = mpsc::channel();
+        let is_done = Arc::new(AtomicBool::new(false));
+        let task = {
+            let is_done = is_done.clone();
+            move || {
+                IN_JOB.set(true);
+                let res = match panic::catch_unwind(AssertUnwindSafe(f)) {
+                    Ok(res) => res.map_err(JobError::from),
+                    Err(payload) => Err(JobError::Panicked {
+                        name,
+                        id,
+                        message: panic_message(payload.as_ref()),
+                    }),
+                };
+                let _ = sender.send(res);
+                is_done.store(true, Ordering::Release);
+                wake();
+            }
+        };
+        if is_background {
+            thread::spawn(task);
+        } else {
+            POOL.send(Box::new(task)).expect("job workers never stop");
+        }
+        Self {
+            id,
+            name,
+            result,
+            is_done,
+            progress,
+            updates: None,
+            is_background,
+        }
+    }
+
+    pub fn is_background(&self) -> bool {
+        self.is_background
+    }
+
+    /// Asks the job to stop, when it reports its progress.
+    pub fn cancel(&self) {
+        if let Some(progress) = &self.progress {
+            progress.cancel();
+        }
+    }
+
+    pub fn progress(&self) -> Option<(usize, usize)> {
+        self.progress.as_ref().and_then(|progress| progress.get())
+    }
+
+    /// Actions sent by a streaming job since the last call.
+    pub fn updates(&self) -> Vec<Action> {
+        self.updates
+            .as_ref()
+            .map(|updates| updates.try_iter().collect())
+            .unwrap_or_default()
     }
 
     pub fn is_done(&self) -> bool {
-        self.0.is_finished()
+        self.is_done.load(Ordering::Acquire)
+    }
+
+    /// Result of the job, waiting for it to finish.
+    pub fn action(self) -> Result<Action, JobError> {
+        let Self { id, name, .. } = self;
+        self.result.recv().unwrap_or_else(|_| {
+            Err(JobError::Panicked {
+                name,
+                id,
+                message: String::from("its thread stopped"),
+            })
+        })
+    }
+}
+
+/// Actions a streaming job sends while it runs.
+pub struct Updates(Sender<Action>);
+
+impl Updates {
+    /// Sends `action`, returning whether the editor is still there to handle it.
+    pub fn send(&self, action: Action) -> bool {
+        let is_sent = self.0.send(action).is_ok();
+        wake();
+        is_sent
+    }
+}
+
+/// Starts a worker per core, at least [`MIN_WORKERS`], taking tasks off the returned queue in
+/// turn.
+fn start_workers() -> Sender<Task> {
+    let (sender, receiver) = mpsc::channel::<Task>();
+    let receiver = Arc::new(Mutex::new(receiver));
+    let n_workers = thread::available_parallelism()
+        .map_or(MIN_WORKERS, usize::from)
+        .max(MIN_WORKERS);
+    for index in 0..n_workers {
+        let receiver = receiver.clone();
+        thread::Builder::new()
+            .name(format!("job-{index}"))
+            .spawn(move || {
+                loop {
+                    // The lock is released before running the task so others can be taken.
+                    let task = receiver.lock().unwrap_or_else(|e| e.into_inner()).recv();
+                    match task {
+                        Ok(task) => task(),
+                        Err(_) => break,
+                    }
+                }
+            })
+            .expect("failed to start a job worker");
+    }
+    sender
+}
+
+/// Wakes the editor through `waker` whenever a job finishes or sends an update, so that it can
+/// sleep in between.
+pub fn wake_with(waker: Sender<Wakeup>) {
+    let _ = WAKER.set(waker);
+}
+
+fn wake() {
+    if let Some(waker) = WAKER.get() {
+        let _ = waker.send(Wakeup::Job);
+    }
+}
+
+/// Keeps panics inside jobs away from the hooks installed so far, such as the one restoring the
+/// terminal, since the editor keeps running and reports them in a dialog instead.
+pub fn isolate_job_panics() {
+    let hook = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        if !IN_JOB.get() {
+            hook(info);
+        }
+    }));
+}
+
+fn panic_message(payload: &(dyn Any + Send)) -> String {
+    payload
+        .downcast_ref::<&str>()
+        .map(|message| message.to_string())
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| String::from("unknown cause"))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::app::action::WorkSpaceAction;
+
+    #[test]
+    fn panicked_job_test() {
+        let job = Job::new("Restat", || panic!("broken {}", "selector"));
+        let id = job.id;
+        while !job.is_done() {
+            std::thread::yield_now();
+        }
+        let error = job.action().unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            format!("Restat job #{id} panicked: broken selector")
+        );
+    }
+
+    #[test]
+    fn pool_test() {
+        let n_workers = thread::available_parallelism()
+            .map_or(MIN_WORKERS, usize::from)
+            .max(MIN_WORKERS);
+        let running = Arc::new(AtomicUsize::new(0));
+        let most_running = Arc::new(AtomicUsize::new(0));
+        let jobs = (0..n_workers * 3)
+            .map(|n| {
+                let running = running.clone();
+                let most_running = most_running.clone();
+                Job::new("Split", move || {
+                    let now = running.fetch_add(1, Ordering::SeqCst) + 1;
+                    most_running.fetch_max(now, Ordering::SeqCst);
+                    thread::sleep(std::time::Duration::from_millis(5));
+                    running.fetch_sub(1, Ordering::SeqCst);
+                    Ok(WorkSpaceAction::SplitDone(n).into())
+                })
+            })
+            .collect::<Vec<_>>();
+        for (n, job) in jobs.into_iter().enumerate() {
+            assert_eq!(job.action().unwrap(), WorkSpaceAction::SplitDone(n).into());
+        }
+        assert!(most_running.load(Ordering::SeqCst) <= n_workers);
+    }
+
+    #[test]
+    fn progress_reader_test() {
+        let progress = Arc::new(Progress::default());
+        let input = vec![b' '; READ_CHUNK_SIZE + 10];
+        progress.set_total(input.len());
+        let mut reader = ProgressReader::new(input.as_slice(), progress.clone());
+
+        let mut buf = vec![0; input.len()];
+        assert_eq!(reader.read(&mut buf).unwrap(), READ_CHUNK_SIZE);
+        assert_eq!(progress.get(), Some((READ_CHUNK_SIZE, input.len())));
+        let mut rest = Vec::new();
+        reader.read_to_end(&mut rest).unwrap();
+        assert_eq!(progress.get(), Some((input.len(), input.len())));
+
+        progress.cancel();
+        assert_eq!(reader.read(&mut buf).unwrap_err().to_string(), "cancelled");
     }
 
-    pub fn action(self) -> Result<Action, std::io::Error> {
-        self.0.join().map_err(|err| {
-            std::io::Error::new(std::io::ErrorKind::UnexpectedEof, format!("{err:?}"))
-        })?
+    #[test]
+    fn stream_job_test() {
+        let job = Job::stream("Watch", |updates| {
+            for n in 0..2 {
+                let _ = updates.send(WorkSpaceAction::SplitDone(n).into());
+            }
+            Ok(WorkSpaceAction::SplitDone(2).into())
+        });
+        assert!(job.is_background());
+        while !job.is_done() {
+            std::thread::yield_now();
+        }
+        assert_eq!(
+            job.updates(),
+            [
+                WorkSpaceAction::SplitDone(0).into(),
+                WorkSpaceAction::SplitDone(1).into()
+            ]
+        );
+        assert_eq!(job.updates(), []);
+        assert_eq!(job.action().unwrap(), WorkSpaceAction::SplitDone(2).into());
     }
 }
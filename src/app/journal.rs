@@ -0,0 +1,118 @@
+use std::{
+    fs,
+    time::{Duration, Instant},
+};
+
+const JOURNAL_SUFFIX: &str = ".jedit~";
+
+/// Copy of the unsaved changes kept next to the document, to recover them after a crash.
+#[derive(Debug)]
+pub struct Journal {
+    path: String,
+    interval: Duration,
+    last_write: Instant,
+    /// Whether a write is still in flight.
+    is_writing: bool,
+}
+
+impl Journal {
+    /// Journal of the document at `path`, written every `interval`.
+    pub fn new(path: &str, interval: Duration) -> Self {
+        Self {
+            path: format!("{path}{JOURNAL_SUFFIX}"),
+            interval,
+            last_write: Instant::now(),
+            is_writing: false,
+        }
+    }
+
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// Whether the journal was written after the document at `path`, so that it holds changes an
+    /// earlier session never saved.
+    pub fn is_newer_than(&self, path: &str) -> bool {
+        let modified = |path: &str| fs::metadata(path).and_then(|metadata| metadata.modified());
+        let Ok(journal) = modified(&self.path) else {
+            return false;
+        };
+        modified(path)
+            .ok()
+            .is_none_or(|document| journal > document)
+    }
+
+    /// Whether to write the journal at `now`, marking the write as started if so.
+    pub fn start_write(&mut self, now: Instant) -> bool {
+        if self.is_writing || now.duration_since(self.last_write) < self.interval {
+            return false;
+        }
+        self.is_writing = true;
+        self.last_write = now;
+        true
+    }
+
+    /// How long after `now` the next write is due, or `None` while one is in flight.
+    pub fn due_in(&self, now: Instant) -> Option<Duration> {
+        (!self.is_writing).then(|| (self.last_write + self.interval).saturating_duration_since(now))
+    }
+
+    pub fn finish_write(&mut self) {
+        self.is_writing = false;
+    }
+
+    /// Deletes the journal once its changes are saved or discarded.
+    pub fn remove(&self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn journal_test() {
+        let document = std::env::temp_dir().join("jedit-journal-test.json");
+        let document = document.to_str().unwrap();
+        let journal = Journal::new(document, Duration::from_secs(1));
+        assert_eq!(journal.path(), format!("{document}.jedit~"));
+        journal.remove();
+        fs::write(document, "{}").unwrap();
+        assert!(!journal.is_newer_than(document));
+
+        std::thread::sleep(Duration::from_millis(10));
+        fs::write(journal.path(), "{\"a\": 1}").unwrap();
+        assert!(journal.is_newer_than(document));
+        fs::remove_file(document).unwrap();
+        assert!(journal.is_newer_than(document));
+        journal.remove();
+        assert!(!journal.is_newer_than(document));
+    }
+
+    #[test]
+    fn journal_interval_test() {
+        let mut journal = Journal::new("config.json", Duration::from_secs(5));
+        let now = journal.last_write;
+        assert_eq!(
+            journal.due_in(now + Duration::from_secs(2)),
+            Some(Duration::from_secs(3))
+        );
+        assert!(!journal.start_write(now));
+        assert!(journal.start_write(now + Duration::from_secs(5)));
+        assert!(!journal.start_write(now + Duration::from_secs(20)));
+        assert_eq!(journal.due_in(now + Duration::from_secs(20)), None);
+        journal.finish_write();
+        assert!(journal.start_write(now + Duration::from_secs(20)));
+        assert!(!journal.start_write(now + Duration::from_secs(21)));
+        journal.finish_write();
+        assert_eq!(
+            journal.due_in(now + Duration::from_secs(21)),
+            Some(Duration::from_secs(4))
+        );
+        assert_eq!(
+            journal.due_in(now + Duration::from_secs(30)),
+            Some(Duration::ZERO)
+        );
+    }
+}
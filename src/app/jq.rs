@@ -0,0 +1,85 @@
+use std::{
+    io::Write,
+    process::{Command, Stdio},
+    thread,
+};
+
+use crate::{container::node::Node, error::LoadError};
+
+/// Runs `jq` with `filter` on `input`, returning what it prints as compact JSON, one value per
+/// line.
+pub fn run(filter: &str, input: &str) -> std::io::Result<String> {
+    let mut command = Command::new("jq");
+    command.arg("--compact-output").arg(filter);
+    pipe(&mut command, input).map_err(|error| std::io::Error::other(format!("jq: {error}")))
+}
+
+/// Values printed by [`run`].
+pub fn values(output: &str) -> Result<Vec<Node>, LoadError> {
+    output
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(index, line)| {
+            Node::load(line.as_bytes())
+                .map_err(|error| LoadError::InLine(index + 1, Box::new(error)))
+        })
+        .collect()
+}
+
+/// Feeds `input` to `command` and returns its stdout, or its stderr as the error when it fails.
+fn pipe(command: &mut Command, input: &str) -> std::io::Result<String> {
+    let mut child = command
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+    let mut stdin = child.stdin.take().expect("piped stdin");
+    let input = input.to_owned();
+    // Written from another thread, so that the command never blocks on a full stdout.
+    let writer = thread::spawn(move || stdin.write_all(input.as_bytes()));
+    let output = child.wait_with_output()?;
+    // Commands may exit before reading all of their input.
+    let _ = writer.join();
+    if !output.status.success() {
+        return Err(std::io::Error::other(
+            String::from_utf8_lossy(&output.stderr).trim().to_owned(),
+        ));
+    }
+    String::from_utf8(output.stdout).map_err(std::io::Error::other)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    #[cfg(not(windows))]
+    fn pipe_test() {
+        let input = "[1, 2]\n".repeat(100_000);
+        assert_eq!(pipe(&mut Command::new("cat"), &input).unwrap(), input);
+
+        let mut failing = Command::new("sh");
+        failing.args(["-c", "echo 'bad filter' >&2; exit 3"]);
+        assert_eq!(
+            pipe(&mut failing, "{}").unwrap_err().to_string(),
+            "bad filter"
+        );
+    }
+
+    #[test]
+    fn values_test() {
+        let values = values("{\"a\":1}\n\n2\n").unwrap();
+        assert_eq!(
+            values,
+            vec![
+                Node::load("{\"a\": 1}".as_bytes()).unwrap(),
+                Node::load("2".as_bytes()).unwrap()
+            ]
+        );
+        assert!(matches!(
+            super::values("1\n{"),
+            Err(LoadError::InLine(2, _))
+        ));
+    }
+}
@@ -0,0 +1,171 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+const LOCK_DIR_NAME: &str = "jedit-locks";
+/// Age past which a lock counts as left behind, where whether its holder runs cannot be told.
+#[cfg(not(unix))]
+const STALE_LOCK_AGE: std::time::Duration = std::time::Duration::from_secs(12 * 60 * 60);
+
+/// Marks a document as open in this jedit instance, so that other instances can warn about it.
+#[derive(Debug)]
+pub struct DocumentLock {
+    path: PathBuf,
+    /// Whether the lock file last said the document was edited, once acquired.
+    held: Option<bool>,
+}
+
+/// Another instance holding a document.
+#[derive(Debug, PartialEq)]
+pub struct Holder {
+    pub pid: u32,
+    pub is_edited: bool,
+}
+
+impl DocumentLock {
+    /// Lock of the document at `path`, keyed by its canonical path.
+    pub fn new(path: &str) -> Option<Self> {
+        let path = fs::canonicalize(path).ok()?;
+        Some(Self::in_dir(
+            &std::env::temp_dir().join(LOCK_DIR_NAME),
+            &path,
+        ))
+    }
+
+    /// Lock file named by a hash of `document`, so that every version of jedit finds the same one.
+    fn in_dir(dir: &Path, document: &Path) -> Self {
+        Self {
+            path: dir.join(format!(
+                "{:016x}.lock",
+                fnv1a(document.as_os_str().as_encoded_bytes())
+            )),
+            held: None,
+        }
+    }
+
+    /// The other running instance holding the document, if any.
+    pub fn holder(&self) -> Option<Holder> {
+        let content = fs::read_to_string(&self.path).ok()?;
+        let (pid, is_edited) = content.trim().split_once(' ')?;
+        let holder = Holder {
+            pid: pid.parse().ok()?,
+            is_edited: is_edited == "edited",
+        };
+        (holder.pid != std::process::id() && self.is_live(holder.pid)).then_some(holder)
+    }
+
+    /// Whether the process `pid` that wrote the lock still runs.
+    #[cfg(unix)]
+    fn is_live(&self, pid: u32) -> bool {
+        let Ok(pid) = libc::pid_t::try_from(pid) else {
+            return false;
+        };
+        // SAFETY: signal 0 only checks that the process exists and can be signalled.
+        let res = unsafe { libc::kill(pid, 0) };
+        // A process of another user cannot be signalled, but it still runs.
+        res == 0 || std::io::Error::last_os_error().raw_os_error() == Some(libc::EPERM)
+    }
+
+    /// Whether the lock is recent enough for its holder to still run, as other systems cannot
+    /// tell cheaply whether a process does.
+    #[cfg(not(unix))]
+    fn is_live(&self, _pid: u32) -> bool {
+        fs::metadata(&self.path)
+            .and_then(|metadata| metadata.modified())
+            .is_ok_and(|modified| {
+                modified
+                    .elapsed()
+                    .is_ok_and(|elapsed| elapsed < STALE_LOCK_AGE)
+            })
+    }
+
+    /// Records this instance as the holder.
+    pub fn acquire(&mut self, is_edited: bool) -> std::io::Result<()> {
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let state = if is_edited { "edited" } else { "clean" };
+        fs::write(&self.path, format!("{} {state}", std::process::id()))?;
+        self.held = Some(is_edited);
+        Ok(())
+    }
+
+    /// Keeps the edited state of an acquired lock up to date.
+    pub fn update(&mut self, is_edited: bool) -> std::io::Result<()> {
+        match self.held {
+            Some(held) if held != is_edited => self.acquire(is_edited),
+            _ => Ok(()),
+        }
+    }
+
+    /// Removes the lock if this instance still holds it.
+    pub fn release(&self) {
+        if self.held.is_none() {
+            return;
+        }
+        let is_ours = fs::read_to_string(&self.path).is_ok_and(|content| {
+            content.split(' ').next() == Some(std::process::id().to_string().as_str())
+        });
+        if is_ours {
+            let _ = fs::remove_file(&self.path);
+        }
+    }
+}
+
+/// 64-bit FNV-1a hash of `bytes`, which unlike the standard hasher stays the same across builds.
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf29ce484222325, |hash, &byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x100000001b3)
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn lock_test() {
+        let dir = std::env::temp_dir().join("jedit-lock-test");
+        let mut lock = DocumentLock::in_dir(&dir, Path::new("/data/config.json"));
+        assert_eq!(
+            lock.path,
+            dir.join(format!("{:016x}.lock", fnv1a(b"/data/config.json")))
+        );
+        assert_eq!(fnv1a(b"a"), 0xaf63dc4c8601ec8c);
+        let _ = fs::remove_file(&lock.path);
+        assert_eq!(lock.holder(), None);
+
+        lock.update(true).unwrap();
+        assert!(!lock.path.exists());
+        lock.acquire(false).unwrap();
+        lock.update(true).unwrap();
+        assert_eq!(
+            fs::read_to_string(&lock.path).unwrap(),
+            format!("{} edited", std::process::id())
+        );
+        // This instance never counts as another holder.
+        assert_eq!(lock.holder(), None);
+        lock.release();
+        assert!(!lock.path.exists());
+
+        #[cfg(unix)]
+        {
+            fs::write(&lock.path, "1 edited").unwrap();
+            assert_eq!(
+                lock.holder(),
+                Some(Holder {
+                    pid: 1,
+                    is_edited: true
+                })
+            );
+            // Someone else's lock stays.
+            lock.release();
+            assert!(lock.path.exists());
+
+            fs::write(&lock.path, format!("{} clean", i32::MAX)).unwrap();
+            assert_eq!(lock.holder(), None);
+            fs::remove_file(&lock.path).unwrap();
+        }
+    }
+}
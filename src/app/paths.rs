@@ -0,0 +1,190 @@
+use std::{
+    env,
+    fs::File,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use tempfile::TempPath;
+
+/// Config files in load order, later files overriding earlier ones.
+pub fn config_files() -> Vec<PathBuf> {
+    let mut files = Vec::new();
+    if cfg!(windows) {
+        if let Some(program_data) = env::var_os("PROGRAMDATA") {
+            files.push(
+                PathBuf::from(program_data)
+                    .join("jedit")
+                    .join("config.toml"),
+            );
+        }
+        if let Some(app_data) = env::var_os("APPDATA") {
+            files.push(PathBuf::from(app_data).join("jedit").join("config.toml"));
+        }
+    } else {
+        files.push(PathBuf::from("/etc/jedit"));
+        if let Some(home) = home_dir() {
+            files.push(home.join(".jedit"));
+        }
+    }
+    files.push(PathBuf::from(".jedit"));
+    files
+}
+
+pub fn home_dir() -> Option<PathBuf> {
+    let var = if cfg!(windows) { "USERPROFILE" } else { "HOME" };
+    env::var_os(var).map(PathBuf::from)
+}
+
+/// Creates a scratch file for the external editor in `dir`, or else the temporary directory. Only
+/// the user can read it, and it is removed once the path is dropped.
+pub fn editor_buffer(dir: Option<&Path>) -> std::io::Result<(File, TempPath)> {
+    let dir = dir.map_or_else(env::temp_dir, Path::to_path_buf);
+    let buffer = tempfile::Builder::new()
+        .prefix("jedit-")
+        .suffix(".json")
+        .tempfile_in(dir)?;
+    Ok(buffer.into_parts())
+}
+
+/// Builds the command opening `path` in `$EDITOR`, which may carry arguments such as
+/// `code --wait`.
+pub fn editor_command(editor: Option<&str>, path: &Path) -> Command {
+    let default_editor = if cfg!(windows) { "notepad" } else { "vi" };
+    let mut command = user_command(editor, default_editor);
+    command.arg(path);
+    command
+}
+
+/// Builds the command reading from stdin in `$PAGER`.
+pub fn pager_command(pager: Option<&str>) -> Command {
+    let default_pager = if cfg!(windows) { "more" } else { "less" };
+    user_command(pager, default_pager)
+}
+
+/// Builds the command of a configured tool.
+pub fn tool_command(command: &str) -> Command {
+    user_command(Some(command), "")
+}
+
+/// Builds the command of a configured tool with each of its arguments passed through
+/// `substitute`, so that the values put in are never split.
+pub fn tool_command_with(command: &str, substitute: impl Fn(&str) -> String) -> Command {
+    split_command(command.split_whitespace().map(substitute), "")
+}
+
+/// Builds the command running `command` in the shell, so that it may use pipes and quotes.
+pub fn shell_command(command: &str) -> Command {
+    let (shell, flag) = if cfg!(windows) {
+        ("cmd", "/C")
+    } else {
+        ("sh", "-c")
+    };
+    let mut shell = Command::new(shell);
+    shell.arg(flag).arg(command);
+    shell
+}
+
+/// Splits a user-configured program into its arguments. Windows programs often are `.cmd` shims,
+/// so they go through `cmd /C`.
+fn user_command(program: Option<&str>, default_program: &str) -> Command {
+    let parts = program.map(str::split_whitespace).into_iter().flatten();
+    split_command(parts.map(String::from), default_program)
+}
+
+fn split_command(parts: impl Iterator<Item = String>, default_program: &str) -> Command {
+    let mut parts = parts.peekable();
+    let mut command = if cfg!(windows) {
+        let mut command = Command::new("cmd");
+        command.arg("/C");
+        command
+    } else {
+        Command::new(parts.next().as_deref().unwrap_or(default_program))
+    };
+    if cfg!(windows) && parts.peek().is_none() {
+        command.arg(default_program);
+    }
+    command.args(parts);
+    command
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn args(command: &Command) -> Vec<String> {
+        std::iter::once(command.get_program())
+            .chain(command.get_args())
+            .map(|arg| arg.to_string_lossy().into_owned())
+            .collect()
+    }
+
+    #[test]
+    fn editor_buffer_test() {
+        let dir = env::temp_dir();
+        let (_, a) = editor_buffer(Some(&dir)).unwrap();
+        let (_, b) = editor_buffer(Some(&dir)).unwrap();
+        assert_ne!(a.to_path_buf(), b.to_path_buf());
+        assert!(a.starts_with(&dir) && a.extension().is_some_and(|ext| ext == "json"));
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = std::fs::metadata(&a).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+
+        let path = a.to_path_buf();
+        drop(a);
+        assert!(!path.exists());
+    }
+
+    #[test]
+    #[cfg(not(windows))]
+    fn editor_command_test() {
+        let path = Path::new("/tmp/buffer.json");
+        assert_eq!(
+            args(&editor_command(None, path)),
+            ["vi", "/tmp/buffer.json"]
+        );
+        assert_eq!(
+            args(&editor_command(Some("code --wait"), path)),
+            ["code", "--wait", "/tmp/buffer.json"]
+        );
+        assert_eq!(
+            args(&editor_command(Some("  "), path)),
+            ["vi", "/tmp/buffer.json"]
+        );
+    }
+
+    #[test]
+    #[cfg(not(windows))]
+    fn pager_command_test() {
+        assert_eq!(args(&pager_command(None)), ["less"]);
+        assert_eq!(args(&pager_command(Some("less -R"))), ["less", "-R"]);
+    }
+
+    #[test]
+    #[cfg(not(windows))]
+    fn tool_command_with_test() {
+        let command = tool_command_with("gsutil cat {url}", |arg| arg.replace("{url}", "a b"));
+        assert_eq!(args(&command), ["gsutil", "cat", "a b"]);
+    }
+
+    #[test]
+    #[cfg(not(windows))]
+    fn shell_command_test() {
+        assert_eq!(
+            args(&shell_command("sort | uniq -c")),
+            ["sh", "-c", "sort | uniq -c"]
+        );
+        let output = shell_command("printf '%s' \"$0\" | tr a-z A-Z")
+            .output()
+            .unwrap();
+        assert_eq!(output.stdout, b"SH");
+    }
+
+    #[test]
+    fn config_files_test() {
+        assert_eq!(config_files().last(), Some(&PathBuf::from(".jedit")));
+    }
+}
@@ -0,0 +1,140 @@
+use std::{collections::BTreeMap, fs, path::PathBuf};
+
+use super::paths;
+use crate::container::{
+    node::{IndexKind, Node},
+    parser::ParseOptions,
+};
+
+/// Register used by yank and paste when no register is named.
+pub const UNNAMED_REGISTER: char = '"';
+/// Register backed by the system clipboard.
+pub const CLIPBOARD_REGISTER: char = '+';
+
+/// Yanked JSON fragments, keyed by register name.
+///
+/// Named registers (`a`-`z`) are written back to `path` on every change when persistence is
+/// enabled, the unnamed register only lives for the session.
+#[derive(Debug, Default)]
+pub struct Registers {
+    registers: BTreeMap<char, Node>,
+    path: Option<PathBuf>,
+}
+
+impl Registers {
+    pub fn is_named(name: char) -> bool {
+        name.is_ascii_lowercase()
+    }
+
+    /// Loads the persisted registers from `path`, starting empty if it cannot be read.
+    pub fn load(path: PathBuf) -> Self {
+        let registers = Node::load_file(&path, &ParseOptions::default())
+            .ok()
+            .map(|node| {
+                let keys = match node.as_index().kind {
+                    IndexKind::Object(keys) => keys,
+                    _ => Vec::new(),
+                };
+                keys.into_iter()
+                    .filter_map(|key| {
+                        let mut chars = key.chars();
+                        let name = chars.next().filter(|&c| Self::is_named(c))?;
+                        if chars.next().is_some() {
+                            return None;
+                        }
+                        Some((name, node.child(&key)?.clone()))
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        Self {
+            registers,
+            path: Some(path),
+        }
+    }
+
+    pub fn get(&self, name: char) -> Option<&Node> {
+        self.registers.get(&name)
+    }
+
+    pub fn set(&mut self, name: char, node: Node) -> std::io::Result<()> {
+        self.registers.insert(name, node);
+        if Self::is_named(name) {
+            self.persist()?;
+        }
+        Ok(())
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (char, &Node)> {
+        self.registers.iter().map(|(name, node)| (*name, node))
+    }
+
+    fn persist(&self) -> std::io::Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+
+        let named = self
+            .registers
+            .iter()
+            .filter(|(name, _)| Self::is_named(**name))
+            .map(|(name, node)| (name.to_string(), node))
+            .collect::<BTreeMap<_, _>>();
+        let content = sonic_rs::to_string_pretty(&named)
+            .map_err(|error| std::io::Error::new(std::io::ErrorKind::InvalidData, error))?;
+        fs::write(path, content)
+    }
+}
+
+/// Default location of the persisted registers.
+pub fn default_path() -> Option<PathBuf> {
+    paths::home_dir().map(|home| home.join(".jedit-registers.json"))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn registers_test() {
+        let mut registers = Registers::default();
+        registers
+            .set(UNNAMED_REGISTER, Node::load("1".as_bytes()).unwrap())
+            .unwrap();
+        registers
+            .set('a', Node::load("[2]".as_bytes()).unwrap())
+            .unwrap();
+
+        assert_eq!(
+            registers.iter().map(|(name, _)| name).collect::<Vec<_>>(),
+            vec![UNNAMED_REGISTER, 'a']
+        );
+        assert_eq!(
+            registers.get('a'),
+            Some(&Node::load("[2]".as_bytes()).unwrap())
+        );
+        assert_eq!(registers.get('b'), None);
+    }
+
+    #[test]
+    fn registers_persistence_test() {
+        let path = PathBuf::from("/tmp/jedit-registers-test.json");
+        let _ = fs::remove_file(&path);
+
+        let mut registers = Registers::load(path.clone());
+        assert_eq!(registers.iter().count(), 0);
+        registers
+            .set(UNNAMED_REGISTER, Node::load("1".as_bytes()).unwrap())
+            .unwrap();
+        registers
+            .set('a', Node::load(r#"{"x": 2}"#.as_bytes()).unwrap())
+            .unwrap();
+
+        let registers = Registers::load(path);
+        assert_eq!(
+            registers.iter().collect::<Vec<_>>(),
+            vec![('a', &Node::load(r#"{"x": 2}"#.as_bytes()).unwrap())]
+        );
+    }
+}
@@ -0,0 +1,146 @@
+use std::{
+    collections::BTreeMap,
+    io::Write,
+    process::{Child, ChildStdin, Command, Stdio},
+};
+
+use serde::Deserialize;
+
+use super::paths;
+
+const URL_PLACEHOLDER: &str = "{url}";
+
+/// Helper commands reading and writing documents behind URLs of one scheme. `{url}` in an
+/// argument of a command is replaced by the URL, which stays within that argument.
+#[derive(Debug, Clone, Deserialize)]
+#[cfg_attr(test, derive(PartialEq, serde::Serialize))]
+pub struct Remote {
+    /// Prints the document to stdout.
+    pub fetch: String,
+    /// Reads the document from stdin.
+    pub put: String,
+}
+
+/// Remotes available without configuration.
+pub fn defaults() -> BTreeMap<String, Remote> {
+    BTreeMap::from([(
+        String::from("s3"),
+        Remote {
+            fetch: String::from("aws s3 cp {url} -"),
+            put: String::from("aws s3 cp - {url}"),
+        },
+    )])
+}
+
+/// Remote handling the scheme of `url`, if it is a URL at all.
+pub fn find<'a>(remotes: &'a BTreeMap<String, Remote>, url: &str) -> Option<&'a Remote> {
+    let (scheme, _) = url.split_once("://")?;
+    remotes.get(scheme)
+}
+
+impl Remote {
+    pub fn fetch(&self, url: &str) -> std::io::Result<Vec<u8>> {
+        let output = command(&self.fetch, url).stderr(Stdio::piped()).output()?;
+        if !output.status.success() {
+            return Err(helper_error("fetch", url, &output.stderr));
+        }
+        Ok(output.stdout)
+    }
+
+    /// Starts the put helper; the document is written to the returned upload.
+    pub fn put(&self, url: &str) -> std::io::Result<Upload> {
+        let mut child = command(&self.put, url)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .spawn()?;
+        let stdin = child.stdin.take().expect("piped stdin");
+        Ok(Upload {
+            url: String::from(url),
+            child,
+            stdin,
+        })
+    }
+}
+
+/// Document being written to a put helper.
+pub struct Upload {
+    url: String,
+    child: Child,
+    stdin: ChildStdin,
+}
+
+impl Upload {
+    /// Closes the document and waits for the helper to store it.
+    pub fn finish(self) -> std::io::Result<()> {
+        let Self { url, child, stdin } = self;
+        drop(stdin);
+        let output = child.wait_with_output()?;
+        if !output.status.success() {
+            return Err(helper_error("put", &url, &output.stderr));
+        }
+        Ok(())
+    }
+}
+
+impl Write for Upload {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.stdin.write(buf)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.stdin.flush()
+    }
+}
+
+fn command(template: &str, url: &str) -> Command {
+    paths::tool_command_with(template, |arg| arg.replace(URL_PLACEHOLDER, url))
+}
+
+fn helper_error(helper: &str, url: &str, stderr: &[u8]) -> std::io::Error {
+    std::io::Error::other(format!(
+        "Failed to {helper} {url}: {}",
+        String::from_utf8_lossy(stderr).trim()
+    ))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn find_test() {
+        let remotes = defaults();
+        assert_eq!(find(&remotes, "s3://bucket/config.json"), remotes.get("s3"));
+        assert_eq!(find(&remotes, "gs://bucket/config.json"), None);
+        assert_eq!(find(&remotes, "config.json"), None);
+    }
+
+    #[test]
+    #[cfg(not(windows))]
+    fn fetch_put_test() {
+        let remote = Remote {
+            fetch: String::from("cat {url}"),
+            put: String::from("tee {url}"),
+        };
+        let dir = tempfile::tempdir().unwrap();
+        // Spaces in the URL must not split it into several arguments.
+        let url = dir.path().join("remote test.json");
+        let url = url.to_str().unwrap();
+
+        let mut upload = remote.put(url).unwrap();
+        upload.write_all(b"{\"a\": 1}").unwrap();
+        upload.finish().unwrap();
+        assert_eq!(remote.fetch(url).unwrap(), b"{\"a\": 1}");
+
+        let missing = dir.path().join("missing.json");
+        let missing = missing.to_str().unwrap();
+        let error = remote.fetch(missing).unwrap_err();
+        assert!(
+            error
+                .to_string()
+                .starts_with(&format!("Failed to fetch {missing}: cat:")),
+            "{error}"
+        );
+    }
+}
@@ -0,0 +1,146 @@
+use std::{
+    collections::VecDeque,
+    fs,
+    time::{Duration, Instant},
+};
+
+use crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers};
+
+/// Recorded key presses fed to the editor one at a time, as if typed.
+#[derive(Debug)]
+pub struct Replay {
+    events: VecDeque<Event>,
+    interval: Duration,
+    next_at: Instant,
+}
+
+impl Replay {
+    /// Loads a JSON array of keys such as `"j"`, `"Enter"` or `"Ctrl+d"`.
+    pub fn load(path: &str, interval: Duration) -> std::io::Result<Self> {
+        let keys: Vec<String> = serde_json::from_slice(&fs::read(path)?)
+            .map_err(|error| std::io::Error::other(format!("Invalid replay {path}: {error}")))?;
+        let events = keys
+            .iter()
+            .map(|key| {
+                parse_key(key).map(Event::Key).ok_or_else(|| {
+                    std::io::Error::other(format!("Invalid replay {path}: unknown key {key:?}"))
+                })
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Self {
+            events,
+            interval,
+            next_at: Instant::now() + interval,
+        })
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.events.is_empty()
+    }
+
+    /// The next key, once `interval` has passed since the previous one.
+    pub fn next(&mut self, now: Instant) -> Option<Event> {
+        if now < self.next_at {
+            return None;
+        }
+        let event = self.events.pop_front()?;
+        self.next_at = now + self.interval;
+        Some(event)
+    }
+}
+
+/// Parses a key name, optionally prefixed by `Ctrl+`, `Alt+` or `Shift+`.
+fn parse_key(key: &str) -> Option<KeyEvent> {
+    let mut modifiers = KeyModifiers::NONE;
+    let mut name = key;
+    while let Some((modifier, rest)) = name.split_once('+').filter(|(_, rest)| !rest.is_empty()) {
+        modifiers |= match modifier.to_ascii_lowercase().as_str() {
+            "ctrl" => KeyModifiers::CONTROL,
+            "alt" => KeyModifiers::ALT,
+            "shift" => KeyModifiers::SHIFT,
+            _ => return None,
+        };
+        name = rest;
+    }
+
+    let mut chars = name.chars();
+    let code = match (chars.next(), chars.next()) {
+        (Some(c), None) => KeyCode::Char(c),
+        _ => match name.to_ascii_lowercase().as_str() {
+            "enter" => KeyCode::Enter,
+            "esc" => KeyCode::Esc,
+            "space" => KeyCode::Char(' '),
+            "tab" => KeyCode::Tab,
+            "backspace" => KeyCode::Backspace,
+            "delete" => KeyCode::Delete,
+            "up" => KeyCode::Up,
+            "down" => KeyCode::Down,
+            "left" => KeyCode::Left,
+            "right" => KeyCode::Right,
+            "home" => KeyCode::Home,
+            "end" => KeyCode::End,
+            "pageup" => KeyCode::PageUp,
+            "pagedown" => KeyCode::PageDown,
+            function => KeyCode::F(function.strip_prefix('f')?.parse().ok()?),
+        },
+    };
+    Some(KeyEvent::new(code, modifiers))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parse_key_test() {
+        assert_eq!(
+            parse_key("G"),
+            Some(KeyEvent::new(KeyCode::Char('G'), KeyModifiers::NONE))
+        );
+        assert_eq!(
+            parse_key("Ctrl+d"),
+            Some(KeyEvent::new(KeyCode::Char('d'), KeyModifiers::CONTROL))
+        );
+        assert_eq!(
+            parse_key("ctrl+Right"),
+            Some(KeyEvent::new(KeyCode::Right, KeyModifiers::CONTROL))
+        );
+        assert_eq!(
+            parse_key("+"),
+            Some(KeyEvent::new(KeyCode::Char('+'), KeyModifiers::NONE))
+        );
+        assert_eq!(
+            parse_key("F5"),
+            Some(KeyEvent::new(KeyCode::F(5), KeyModifiers::NONE))
+        );
+        assert_eq!(parse_key("Hyper+a"), None);
+        assert_eq!(parse_key("Return"), None);
+    }
+
+    #[test]
+    fn next_test() {
+        let path = "/tmp/jedit-replay-test.json";
+        fs::write(path, r#"["j", "Enter"]"#).unwrap();
+        let mut replay = Replay::load(path, Duration::from_millis(100)).unwrap();
+        let start = replay.next_at;
+
+        assert_eq!(replay.next(start - Duration::from_millis(1)), None);
+        assert_eq!(
+            replay.next(start),
+            Some(Event::Key(KeyEvent::new(
+                KeyCode::Char('j'),
+                KeyModifiers::NONE
+            )))
+        );
+        assert_eq!(replay.next(start + Duration::from_millis(99)), None);
+        assert!(replay.next(start + Duration::from_millis(100)).is_some());
+        assert_eq!(replay.next(start + Duration::from_secs(1)), None);
+
+        fs::write(path, r#"["j", "Return"]"#).unwrap();
+        let error = Replay::load(path, Duration::ZERO).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            format!("Invalid replay {path}: unknown key \"Return\"")
+        );
+    }
+}
@@ -0,0 +1,126 @@
+//! Drives the editor without a terminal, for integration tests of forks and plugins.
+//!
+//! ```ignore
+//! let mut harness = Harness::new(r#"{"a": 1}"#);
+//! harness.press(KeyCode::Enter).press(KeyCode::Char('d')).press(KeyCode::Char('y'));
+//! assert_eq!(harness.document(), "{}");
+//! insta::assert_snapshot!(harness.render());
+//! ```
+
+use crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers};
+
+use super::{
+    action::{Action, Actions},
+    component::{
+        test_render::stateful_render_to_string,
+        workspace::{WorkSpace, WorkSpaceState},
+    },
+    config::Config,
+};
+use crate::container::node::Node;
+
+/// Editor fed with terminal events, whose frames render to strings. Jobs such as opening
+/// `$EDITOR` or saving are recorded instead of run.
+pub struct Harness {
+    workspace: WorkSpace,
+    state: WorkSpaceState,
+    jobs: Vec<String>,
+    exited: bool,
+}
+
+impl Harness {
+    /// Opens `json` with the default configuration, ignoring any configuration files.
+    ///
+    /// # Panics
+    ///
+    /// When `json` is not valid JSON.
+    pub fn new(json: &str) -> Self {
+        let node = Node::load(json.as_bytes()).expect("invalid JSON");
+        Self {
+            workspace: WorkSpace::new(node, Config::default()),
+            state: WorkSpaceState::default(),
+            jobs: Vec::new(),
+            exited: false,
+        }
+    }
+
+    pub fn press(&mut self, code: KeyCode) -> &mut Self {
+        self.key(code, KeyModifiers::NONE)
+    }
+
+    pub fn key(&mut self, code: KeyCode, modifiers: KeyModifiers) -> &mut Self {
+        self.event(Event::Key(KeyEvent::new(code, modifiers)))
+    }
+
+    /// Handles `event` and every action it leads to.
+    pub fn event(&mut self, event: Event) -> &mut Self {
+        let mut actions = Actions::new();
+        self.workspace
+            .handle_event(&self.state, &mut actions, event);
+        while let Some(action) = actions.next() {
+            match action {
+                Action::Exit(confirm_action) => {
+                    self.exited |= self.workspace.maybe_exit(confirm_action);
+                }
+                // There is a single document, and jobs are only recorded.
+                Action::SwitchTab(_) | Action::CancelJobs => {}
+                Action::Workspace(workspace_action) => self
+                    .workspace
+                    .handle_action(&mut self.state, &mut actions, workspace_action)
+                    .expect("failed to handle action"),
+                Action::ExecuteJob(job) => self.jobs.push(format!("{job:?}")),
+            }
+        }
+        self
+    }
+
+    /// The current frame on an 80×20 terminal, one quoted line per row.
+    pub fn render(&mut self) -> String {
+        stateful_render_to_string(&self.workspace, &mut self.state)
+    }
+
+    /// The document as it would be saved.
+    pub fn document(&self) -> String {
+        self.workspace
+            .file_root()
+            .to_string_pretty()
+            .expect("broken internal representation")
+    }
+
+    pub fn is_edited(&self) -> bool {
+        self.workspace.is_edited()
+    }
+
+    /// Debug representations of the jobs requested so far, oldest first.
+    pub fn jobs(&self) -> &[String] {
+        &self.jobs
+    }
+
+    /// Whether the editor asked to exit and was allowed to.
+    pub fn exited(&self) -> bool {
+        self.exited
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn harness_test() {
+        let mut harness = Harness::new(r#"{"a": 1, "b": 2}"#);
+        harness
+            .press(KeyCode::Enter)
+            .press(KeyCode::Char('d'))
+            .press(KeyCode::Char('y'));
+        assert_eq!(harness.document(), "{\n  \"b\": 2\n}");
+        assert!(harness.is_edited());
+        assert!(harness.render().contains("└─ b"));
+
+        harness.press(KeyCode::Char('w')).press(KeyCode::Char('y'));
+        assert_eq!(harness.jobs(), ["Save"]);
+
+        harness.press(KeyCode::Char('q'));
+        assert!(!harness.exited());
+    }
+}
@@ -0,0 +1,165 @@
+use serde::Deserialize;
+
+use crate::container::node::{Node, NodeKind};
+
+/// External program configured for nodes matching a path or value pattern.
+#[derive(Debug, Clone, Deserialize)]
+#[cfg_attr(test, derive(PartialEq, serde::Serialize))]
+pub struct Tool {
+    /// JSON Pointer pattern, where `*` matches within one segment and `**` across segments.
+    pub path: Option<String>,
+    /// Pattern matched against terminal values, where `*` matches anything.
+    pub value: Option<String>,
+    pub command: String,
+    /// Opens the node like `$EDITOR` instead of viewing it like `$PAGER`.
+    #[serde(default)]
+    pub edit: bool,
+}
+
+impl Tool {
+    fn matches(&self, pointer: &str, node: &Node) -> bool {
+        let path_matches = self
+            .path
+            .as_deref()
+            .is_none_or(|pattern| glob_match(pattern, pointer, true));
+        let value_matches = self.value.as_deref().is_none_or(|pattern| {
+            terminal_text(node).is_some_and(|text| glob_match(pattern, &text, false))
+        });
+        path_matches && value_matches
+    }
+}
+
+/// First tool of the requested kind matching the node at `pointer`.
+pub fn find<'a>(tools: &'a [Tool], edit: bool, pointer: &str, node: &Node) -> Option<&'a Tool> {
+    tools
+        .iter()
+        .find(|tool| tool.edit == edit && tool.matches(pointer, node))
+}
+
+/// Content handed to a viewer: strings raw, so that PEM blocks and the like reach the program
+/// as-is, and everything else as pretty JSON.
+pub fn view_content(node: &Node) -> String {
+    match node.as_str() {
+        Some(content) => String::from(content),
+        None => node
+            .to_string_pretty()
+            .expect("broken internal representation"),
+    }
+}
+
+fn terminal_text(node: &Node) -> Option<String> {
+    match node.as_meta().kind {
+        NodeKind::Terminal => Some(node.as_str().map_or_else(
+            || {
+                node.to_string_compact()
+                    .expect("broken internal representation")
+            },
+            String::from,
+        )),
+        NodeKind::Object | NodeKind::Array => None,
+    }
+}
+
+/// Matches `text` against a pattern of literals and `*` wildcards. With `in_path`, a single `*`
+/// stops at `/` while `**` does not.
+fn glob_match(pattern: &str, text: &str, in_path: bool) -> bool {
+    let Some((literal, rest)) = pattern.split_once('*') else {
+        return pattern == text;
+    };
+    let Some(text) = text.strip_prefix(literal) else {
+        return false;
+    };
+    let (rest, crosses_segments) = match rest.strip_prefix('*') {
+        Some(rest) => (rest, true),
+        None => (rest, !in_path),
+    };
+
+    let mut end = 0;
+    loop {
+        if glob_match(rest, &text[end..], in_path) {
+            return true;
+        }
+        let Some(c) = text[end..].chars().next() else {
+            return false;
+        };
+        if c == '/' && !crosses_segments {
+            return false;
+        }
+        end += c.len_utf8();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn glob_match_test() {
+        assert!(glob_match("/certificates/*", "/certificates/root", true));
+        assert!(!glob_match(
+            "/certificates/*",
+            "/certificates/root/pem",
+            true
+        ));
+        assert!(glob_match(
+            "/certificates/**",
+            "/certificates/root/pem",
+            true
+        ));
+        assert!(glob_match("/*/pem", "/root/pem", true));
+        assert!(!glob_match("/*/pem", "/root/key", true));
+        assert!(glob_match(
+            "-----BEGIN *",
+            "-----BEGIN CERTIFICATE-----\nMII",
+            false
+        ));
+        assert!(glob_match("*", "", false));
+        assert!(!glob_match("a", "ab", false));
+    }
+
+    #[test]
+    fn find_test() {
+        let tools = [
+            Tool {
+                path: Some(String::from("/certificates/*")),
+                value: None,
+                command: String::from("openssl x509 -text"),
+                edit: false,
+            },
+            Tool {
+                path: None,
+                value: Some(String::from("-----BEGIN *")),
+                command: String::from("openssl asn1parse"),
+                edit: false,
+            },
+            Tool {
+                path: Some(String::from("/**")),
+                value: None,
+                command: String::from("code --wait"),
+                edit: true,
+            },
+        ];
+        let pem = Node::load(r#""-----BEGIN CERTIFICATE-----""#.as_bytes()).unwrap();
+        let number = Node::load("1".as_bytes()).unwrap();
+
+        let command = |edit, pointer, node| find(&tools, edit, pointer, node).map(|t| &t.command);
+        assert_eq!(
+            command(false, "/certificates/root", &number),
+            Some(&tools[0].command)
+        );
+        assert_eq!(command(false, "/keys/root", &pem), Some(&tools[1].command));
+        assert_eq!(command(false, "/keys/root", &number), None);
+        assert_eq!(
+            command(true, "/keys/root", &number),
+            Some(&tools[2].command)
+        );
+    }
+
+    #[test]
+    fn view_content_test() {
+        let node = Node::load(r#""a\nb""#.as_bytes()).unwrap();
+        assert_eq!(view_content(&node), "a\nb");
+        let node = Node::load("[1]".as_bytes()).unwrap();
+        assert_eq!(view_content(&node), "[\n  1\n]");
+    }
+}
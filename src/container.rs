@@ -1,3 +1,12 @@
+pub(super) mod encoding;
+pub(super) mod gron;
+pub(super) mod join;
+pub(super) mod merge;
+pub(super) mod ndjson;
 pub(super) mod node;
+pub(super) mod parser;
+pub(super) mod patch;
+pub(super) mod pointer;
+pub(super) mod validation;
 
 const INDENT: usize = 2;
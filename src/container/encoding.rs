@@ -0,0 +1,293 @@
+use std::{borrow::Cow, str::FromStr};
+
+use serde::Deserialize;
+
+use crate::error::LoadError;
+
+const UTF8_BOM: &[u8] = &[0xef, 0xbb, 0xbf];
+const UTF16_LE_BOM: &[u8] = &[0xff, 0xfe];
+const UTF16_BE_BOM: &[u8] = &[0xfe, 0xff];
+
+/// Byte encoding of a JSON file on disk.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum Encoding {
+    #[default]
+    Utf8,
+    Utf8Bom,
+    Utf16Le,
+    Utf16Be,
+}
+
+impl Encoding {
+    /// Detects the encoding from a BOM, or from the NUL pattern of the first character since a
+    /// JSON document always starts with ASCII.
+    pub fn detect(bytes: &[u8]) -> Self {
+        if bytes.starts_with(UTF8_BOM) {
+            Self::Utf8Bom
+        } else if bytes.starts_with(UTF16_LE_BOM) {
+            Self::Utf16Le
+        } else if bytes.starts_with(UTF16_BE_BOM) {
+            Self::Utf16Be
+        } else {
+            match bytes {
+                [0, c, ..] if *c != 0 => Self::Utf16Be,
+                [c, 0, ..] if *c != 0 => Self::Utf16Le,
+                _ => Self::Utf8,
+            }
+        }
+    }
+
+    /// Strips the BOM and transcodes to UTF-8.
+    pub fn decode(self, bytes: &[u8], lossy: bool) -> Result<Cow<'_, [u8]>, LoadError> {
+        let from_bytes: fn([u8; 2]) -> u16 = match self {
+            Self::Utf8 => return Ok(Cow::Borrowed(bytes)),
+            Self::Utf8Bom => return Ok(Cow::Borrowed(&bytes[UTF8_BOM.len()..])),
+            Self::Utf16Le => u16::from_le_bytes,
+            Self::Utf16Be => u16::from_be_bytes,
+        };
+
+        let bom = if self == Self::Utf16Le {
+            UTF16_LE_BOM
+        } else {
+            UTF16_BE_BOM
+        };
+        let bytes = bytes.strip_prefix(bom).unwrap_or(bytes);
+        let chunks = bytes.chunks_exact(2);
+        if !chunks.remainder().is_empty() && !lossy {
+            return Err(LoadError::InvalidUtf16);
+        }
+        let units = chunks
+            .map(|chunk| from_bytes([chunk[0], chunk[1]]))
+            .collect::<Vec<_>>();
+        let content = if lossy {
+            String::from_utf16_lossy(&units)
+        } else {
+            String::from_utf16(&units).map_err(|_| LoadError::InvalidUtf16)?
+        };
+        Ok(Cow::Owned(content.into_bytes()))
+    }
+
+    /// Encodes UTF-8 content back into this encoding, BOM included.
+    pub fn encode(self, content: &str) -> Vec<u8> {
+        let to_bytes: fn(u16) -> [u8; 2] = match self {
+            Self::Utf8 => return content.as_bytes().to_vec(),
+            Self::Utf8Bom => return [UTF8_BOM, content.as_bytes()].concat(),
+            Self::Utf16Le => u16::to_le_bytes,
+            Self::Utf16Be => u16::to_be_bytes,
+        };
+
+        let mut bytes = Vec::with_capacity(2 + content.len() * 2);
+        bytes.extend(to_bytes(0xfeff));
+        content
+            .encode_utf16()
+            .for_each(|unit| bytes.extend(to_bytes(unit)));
+        bytes
+    }
+}
+
+/// Line ending written on save.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+#[cfg_attr(test, derive(serde::Serialize))]
+#[serde(rename_all = "lowercase")]
+pub enum LineEnding {
+    /// Follow the first line ending of the input.
+    #[default]
+    Auto,
+    Lf,
+    Crlf,
+}
+
+impl LineEnding {
+    /// Resolves [`LineEnding::Auto`] from the start of the decoded input, defaulting to LF.
+    pub fn resolve(self, head: &[u8]) -> Self {
+        if self != Self::Auto {
+            return self;
+        }
+
+        match head.iter().position(|&c| c == b'\n') {
+            Some(i) if i > 0 && head[i - 1] == b'\r' => Self::Crlf,
+            _ => Self::Lf,
+        }
+    }
+
+    /// Rewrites the LF line endings of serialized JSON, which never has raw newlines inside
+    /// strings.
+    pub fn apply(self, content: &str) -> Cow<'_, str> {
+        match self {
+            Self::Crlf => Cow::Owned(content.replace('\n', "\r\n")),
+            Self::Auto | Self::Lf => Cow::Borrowed(content),
+        }
+    }
+}
+
+/// Indentation of each nesting level, written on save and shown in the preview.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+#[cfg_attr(test, derive(serde::Serialize), serde(into = "IndentSetting"))]
+#[serde(try_from = "IndentSetting")]
+pub enum Indent {
+    /// Follow the first indented line of the input.
+    #[default]
+    Auto,
+    Spaces(usize),
+    Tab,
+}
+
+/// How [`Indent`] is written in the configuration: a number of spaces, `"tab"` or `"auto"`.
+#[derive(Deserialize)]
+#[cfg_attr(test, derive(serde::Serialize))]
+#[serde(untagged)]
+enum IndentSetting {
+    Spaces(usize),
+    Name(String),
+}
+
+impl TryFrom<IndentSetting> for Indent {
+    type Error = String;
+
+    fn try_from(setting: IndentSetting) -> Result<Self, Self::Error> {
+        match setting {
+            IndentSetting::Spaces(n) => Ok(Self::Spaces(n)),
+            IndentSetting::Name(name) => match name.as_str() {
+                "auto" => Ok(Self::Auto),
+                "tab" => Ok(Self::Tab),
+                _ => Err(format!(
+                    "unknown indent {name:?}, expected a number, \"tab\" or \"auto\""
+                )),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+impl From<Indent> for IndentSetting {
+    fn from(indent: Indent) -> Self {
+        match indent {
+            Indent::Auto => Self::Name(String::from("auto")),
+            Indent::Spaces(n) => Self::Spaces(n),
+            Indent::Tab => Self::Name(String::from("tab")),
+        }
+    }
+}
+
+/// Reads the indentation as written on the command line: a number of spaces, `tab` or `auto`.
+impl FromStr for Indent {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.parse() {
+            Ok(n) => Ok(Self::Spaces(n)),
+            Err(_) => IndentSetting::Name(String::from(s)).try_into(),
+        }
+    }
+}
+
+impl Indent {
+    /// Resolves [`Indent::Auto`] from the first indented line at the start of the decoded input,
+    /// defaulting to two spaces.
+    pub fn resolve(self, head: &[u8]) -> Self {
+        if self != Self::Auto {
+            return self;
+        }
+
+        let indented = head
+            .split(|&c| c == b'\n')
+            .skip(1)
+            .find(|line| line.first().is_some_and(|&c| c == b' ' || c == b'\t'));
+        match indented {
+            Some([b'\t', ..]) => Self::Tab,
+            Some(line) => Self::Spaces(line.iter().take_while(|&&c| c == b' ').count()),
+            None => Self::Spaces(2),
+        }
+    }
+
+    /// Characters making up one level, with [`Indent::Auto`] taken as two spaces.
+    pub fn unit(self) -> String {
+        match self {
+            Self::Auto => String::from("  "),
+            Self::Spaces(n) => " ".repeat(n),
+            Self::Tab => String::from("\t"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn detect_test() {
+        assert_eq!(Encoding::detect(b"{}"), Encoding::Utf8);
+        assert_eq!(Encoding::detect(b""), Encoding::Utf8);
+        assert_eq!(Encoding::detect(b"\xef\xbb\xbf{}"), Encoding::Utf8Bom);
+        assert_eq!(Encoding::detect(b"\xff\xfe{\0}\0"), Encoding::Utf16Le);
+        assert_eq!(Encoding::detect(b"\xfe\xff\0{\0}"), Encoding::Utf16Be);
+        assert_eq!(Encoding::detect(b"{\0}\0"), Encoding::Utf16Le);
+        assert_eq!(Encoding::detect(b"\0{\0}"), Encoding::Utf16Be);
+    }
+
+    #[test]
+    fn round_trip_test() {
+        let content = r#"{"a": "é😀"}"#;
+        for encoding in [
+            Encoding::Utf8,
+            Encoding::Utf8Bom,
+            Encoding::Utf16Le,
+            Encoding::Utf16Be,
+        ] {
+            let bytes = encoding.encode(content);
+            assert_eq!(Encoding::detect(&bytes), encoding);
+            assert_eq!(
+                encoding.decode(&bytes, false).unwrap().as_ref(),
+                content.as_bytes()
+            );
+        }
+    }
+
+    #[test]
+    fn decode_invalid_utf16_test() {
+        let bytes = b"\xff\xfe\x00\xd8{\0";
+        assert!(matches!(
+            Encoding::Utf16Le.decode(bytes, false),
+            Err(LoadError::InvalidUtf16)
+        ));
+        assert_eq!(
+            Encoding::Utf16Le.decode(bytes, true).unwrap().as_ref(),
+            "\u{fffd}{".as_bytes()
+        );
+    }
+
+    #[test]
+    fn line_ending_test() {
+        assert_eq!(LineEnding::Auto.resolve(b"{\r\n}"), LineEnding::Crlf);
+        assert_eq!(LineEnding::Auto.resolve(b"{\n}\r\n"), LineEnding::Lf);
+        assert_eq!(LineEnding::Auto.resolve(b"{}"), LineEnding::Lf);
+        assert_eq!(LineEnding::Lf.resolve(b"{\r\n}"), LineEnding::Lf);
+        assert_eq!(LineEnding::Crlf.resolve(b"{}"), LineEnding::Crlf);
+
+        assert_eq!(
+            LineEnding::Crlf.apply("{\n  \"a\": 1\n}"),
+            "{\r\n  \"a\": 1\r\n}"
+        );
+        assert_eq!(LineEnding::Lf.apply("{\n}"), "{\n}");
+    }
+
+    #[test]
+    fn indent_test() {
+        assert_eq!(
+            Indent::Auto.resolve(b"{\n    \"a\": 1\n}"),
+            Indent::Spaces(4)
+        );
+        assert_eq!(Indent::Auto.resolve(b"{\r\n\t\"a\": 1\r\n}"), Indent::Tab);
+        assert_eq!(Indent::Auto.resolve(b"{\n}\n"), Indent::Spaces(2));
+        assert_eq!(Indent::Auto.resolve(b"{\"a\": 1}"), Indent::Spaces(2));
+        assert_eq!(Indent::Tab.resolve(b"{\n  \"a\": 1\n}"), Indent::Tab);
+
+        assert_eq!(Indent::Spaces(4).unit(), "    ");
+        assert_eq!(Indent::Tab.unit(), "\t");
+        assert_eq!(Indent::Auto.unit(), "  ");
+
+        assert_eq!("4".parse(), Ok(Indent::Spaces(4)));
+        assert_eq!("tab".parse(), Ok(Indent::Tab));
+        assert!("-1".parse::<Indent>().is_err());
+    }
+}
@@ -0,0 +1,311 @@
+use std::ops::Deref;
+
+use indexmap::IndexMap;
+
+use super::node::Node;
+use crate::error::LoadError;
+
+/// Name gron gives the root of a document.
+const ROOT: &str = "json";
+
+/// Lists every node of `root`, containers included, as one assignment per line in document
+/// order, the way `gron` prints documents:
+///
+/// ```text
+/// json = {};
+/// json.servlet = [];
+/// json.servlet[0] = {};
+/// json.servlet[0]["init-param"] = {};
+/// json.servlet[0]["init-param"].cachePagesTrack = 200;
+/// ```
+pub fn to_gron(root: &Node) -> String {
+    let mut gron = String::new();
+    write_assignments(root, &mut String::from(ROOT), &mut gron);
+    gron
+}
+
+fn write_assignments(node: &Node, path: &mut String, gron: &mut String) {
+    gron.push_str(path);
+    gron.push_str(" = ");
+    gron.push_str(&value(node));
+    gron.push_str(";\n");
+
+    let len = path.len();
+    if let Some(items) = node.as_array() {
+        for (index, item) in items.iter().enumerate() {
+            push_index(path, index);
+            write_assignments(item, path, gron);
+            path.truncate(len);
+        }
+    } else if let Some(members) = node.as_object() {
+        for (key, member) in members {
+            push_key(path, key);
+            write_assignments(member, path, gron);
+            path.truncate(len);
+        }
+    }
+}
+
+/// Right-hand side of the assignment of `node`, leaving out the children of containers.
+pub fn value(node: &Node) -> String {
+    if node.as_array().is_some() {
+        String::from("[]")
+    } else if node.as_object().is_some() {
+        String::from("{}")
+    } else {
+        node.to_string_compact()
+            .expect("broken internal representation")
+    }
+}
+
+/// Formats a selector into `root` as the left-hand side of a gron assignment, such as
+/// `json.servlet[0]["init-param"]`.
+pub fn path<T: Deref<Target = str>>(root: &Node, selector: &[T]) -> String {
+    let mut path = String::from(ROOT);
+    let mut node = Some(root);
+    for key in selector {
+        match node.filter(|node| node.as_array().is_some()) {
+            Some(_) => path.push_str(&format!("[{}]", &**key)),
+            None => push_key(&mut path, key),
+        }
+        node = node.and_then(|node| node.child(key));
+    }
+    path
+}
+
+fn push_index(path: &mut String, index: usize) {
+    path.push_str(&format!("[{index}]"));
+}
+
+fn push_key(path: &mut String, key: &str) {
+    let is_identifier = key.chars().enumerate().all(|(i, c)| {
+        c == '_' || c == '$' || c.is_ascii_alphabetic() || (i > 0 && c.is_ascii_digit())
+    }) && !key.is_empty();
+    if is_identifier {
+        path.push('.');
+        path.push_str(key);
+    } else {
+        path.push('[');
+        path.push_str(
+            &Node::string(String::from(key))
+                .to_string_compact()
+                .expect("broken internal representation"),
+        );
+        path.push(']');
+    }
+}
+
+/// Builds the document listed by gron assignments, as [`to_gron`] or `gron` print them. The
+/// trailing `;` is optional, blank lines are skipped, and assignments may come in any order:
+/// containers are created as their members are assigned, and missing array elements are `null`.
+pub fn from_gron(gron: &str) -> Result<Node, LoadError> {
+    let mut root = Draft::Value(Node::null());
+    for (line_index, line) in gron.lines().enumerate() {
+        let line_number = line_index + 1;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let syntax_error = |message: &str, rest: &str| LoadError::Syntax {
+            message: String::from(message),
+            line: line_number,
+            column: line.len() - rest.len() + 1,
+        };
+
+        let (keys, rest) = parse_path(line).map_err(|rest| syntax_error("invalid path", rest))?;
+        let value = rest
+            .trim_start()
+            .strip_prefix('=')
+            .ok_or_else(|| syntax_error("expected `=`", rest))?
+            .trim();
+        let node = value
+            .strip_suffix(';')
+            .and_then(|value| Node::load(value.as_bytes()).ok())
+            .map_or_else(|| Node::load(value.as_bytes()), Ok)
+            .map_err(|error| LoadError::InLine(line_number, Box::new(error)))?;
+        root.assign(&keys, node);
+    }
+    Ok(root.into_node())
+}
+
+enum Key {
+    Name(String),
+    Index(usize),
+}
+
+/// Keys of the path starting `line`, with what follows it, or the unparsed rest on error.
+fn parse_path(line: &str) -> Result<(Vec<Key>, &str), &str> {
+    let mut rest = line.trim_start().strip_prefix(ROOT).ok_or(line)?;
+    let mut keys = Vec::new();
+    loop {
+        if let Some(after) = rest.strip_prefix('.') {
+            let end = after
+                .find(|c: char| c == '.' || c == '[' || c == '=' || c.is_whitespace())
+                .unwrap_or(after.len());
+            if end == 0 {
+                return Err(rest);
+            }
+            keys.push(Key::Name(String::from(&after[..end])));
+            rest = &after[end..];
+        } else if let Some(after) = rest.strip_prefix("[\"") {
+            let end = closing_quote(after).ok_or(rest)?;
+            let key = sonic_rs::from_str::<String>(&rest[1..end + 3]).map_err(|_| rest)?;
+            keys.push(Key::Name(key));
+            rest = after[end + 1..].strip_prefix(']').ok_or(rest)?;
+        } else if let Some(after) = rest.strip_prefix('[') {
+            let end = after.find(']').ok_or(rest)?;
+            let index = after[..end].parse().map_err(|_| rest)?;
+            keys.push(Key::Index(index));
+            rest = &after[end + 1..];
+        } else {
+            return Ok((keys, rest));
+        }
+    }
+}
+
+/// Byte position of the quote ending the JSON string whose content starts `text`.
+fn closing_quote(text: &str) -> Option<usize> {
+    let mut is_escaped = false;
+    for (position, c) in text.char_indices() {
+        match c {
+            '"' if !is_escaped => return Some(position),
+            '\\' => is_escaped = !is_escaped,
+            _ => is_escaped = false,
+        }
+    }
+    None
+}
+
+/// Document being assembled from assignments.
+enum Draft {
+    Value(Node),
+    Array(Vec<Draft>),
+    Object(IndexMap<String, Draft>),
+}
+
+impl Draft {
+    fn assign(&mut self, keys: &[Key], node: Node) {
+        let Some((key, rest)) = keys.split_first() else {
+            // `json.a = {};` only declares the container its members are assigned into.
+            let is_declared = match self {
+                Draft::Array(_) => node.as_array().is_some_and(<[Node]>::is_empty),
+                Draft::Object(_) => node.as_object().is_some_and(IndexMap::is_empty),
+                Draft::Value(_) => false,
+            };
+            if !is_declared {
+                *self = Draft::Value(node);
+            }
+            return;
+        };
+        let child = match key {
+            Key::Name(name) => {
+                if !matches!(self, Draft::Object(_)) {
+                    *self = Draft::Object(IndexMap::new());
+                }
+                let Draft::Object(members) = self else {
+                    unreachable!("draft was just made an object");
+                };
+                members
+                    .entry(name.clone())
+                    .or_insert_with(|| Draft::Value(Node::null()))
+            }
+            Key::Index(index) => {
+                if !matches!(self, Draft::Array(_)) {
+                    *self = Draft::Array(Vec::new());
+                }
+                let Draft::Array(items) = self else {
+                    unreachable!("draft was just made an array");
+                };
+                if items.len() <= *index {
+                    items.resize_with(index + 1, || Draft::Value(Node::null()));
+                }
+                &mut items[*index]
+            }
+        };
+        child.assign(rest, node);
+    }
+
+    fn into_node(self) -> Node {
+        match self {
+            Draft::Value(node) => node,
+            Draft::Array(items) => {
+                Node::from_array(items.into_iter().map(Self::into_node).collect())
+            }
+            Draft::Object(members) => Node::from_object(
+                members
+                    .into_iter()
+                    .map(|(key, member)| (key, member.into_node()))
+                    .collect(),
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn to_gron_test() {
+        let node =
+            Node::load(r#"{"servlet": [{"init-param": {"it's": 1}}, []], "_a1": "x"}"#.as_bytes())
+                .unwrap();
+        assert_eq!(
+            to_gron(&node),
+            concat!(
+                "json = {};\n",
+                "json.servlet = [];\n",
+                "json.servlet[0] = {};\n",
+                "json.servlet[0][\"init-param\"] = {};\n",
+                "json.servlet[0][\"init-param\"][\"it's\"] = 1;\n",
+                "json.servlet[1] = [];\n",
+                "json._a1 = \"x\";\n",
+            )
+        );
+        assert_eq!(
+            path(&node, &["servlet", "0", "init-param"]),
+            "json.servlet[0][\"init-param\"]"
+        );
+        assert_eq!(from_gron(&to_gron(&node)).unwrap(), node);
+    }
+
+    #[test]
+    fn from_gron_test() {
+        let node = from_gron(concat!(
+            "json.b[2].c = \"a = b;\"\n",
+            "\n",
+            "json[\"x \\\"y\\\"\"] = true;\n",
+            "json.b = [];\n",
+            "json.b[0] = 1;\n",
+        ))
+        .unwrap();
+        assert_eq!(
+            node.to_string_compact().unwrap(),
+            r#"{"b":[1,null,{"c":"a = b;"}],"x \"y\"":true}"#
+        );
+        assert_eq!(
+            from_gron("json = 3").unwrap().to_string_compact().unwrap(),
+            "3"
+        );
+
+        assert!(matches!(
+            from_gron("json.a = 1;\nroot.b = 2;"),
+            Err(LoadError::Syntax {
+                line: 2,
+                column: 1,
+                ..
+            })
+        ));
+        assert!(matches!(
+            from_gron("json.a 1"),
+            Err(LoadError::Syntax {
+                line: 1,
+                column: 7,
+                ..
+            })
+        ));
+        assert!(matches!(
+            from_gron("json.a = nope"),
+            Err(LoadError::InLine(1, _))
+        ));
+    }
+}
@@ -0,0 +1,130 @@
+use std::{fs, io, path::Path};
+
+use indexmap::{IndexMap, map::Entry};
+
+use super::{node::Node, parser::ParseOptions};
+use crate::error::LoadError;
+
+#[derive(Debug, Clone, Copy)]
+#[cfg_attr(test, derive(PartialEq))]
+pub enum JoinKind {
+    Array,
+    /// Keyed by file name, without the extension.
+    Object,
+}
+
+/// Loads every file in `paths` and combines them into a single document.
+pub fn join<P: AsRef<Path>>(
+    paths: &[P],
+    kind: JoinKind,
+    options: &ParseOptions,
+) -> Result<Node, LoadError> {
+    let nodes = paths
+        .iter()
+        .map(|path| {
+            let path = path.as_ref();
+            Node::load_file(path, options)
+                .map(|node| (path, node))
+                .map_err(|error| LoadError::InFile(path.display().to_string(), Box::new(error)))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    match kind {
+        JoinKind::Array => Ok(Node::from_array(
+            nodes.into_iter().map(|(_, node)| node).collect(),
+        )),
+        JoinKind::Object => {
+            let mut index_map = IndexMap::new();
+            for (path, node) in nodes {
+                let key = path
+                    .file_stem()
+                    .unwrap_or(path.as_os_str())
+                    .to_string_lossy()
+                    .into_owned();
+                match index_map.entry(key) {
+                    Entry::Occupied(entry) => {
+                        return Err(LoadError::DuplicateKey(entry.key().clone()));
+                    }
+                    Entry::Vacant(entry) => {
+                        entry.insert(node);
+                    }
+                }
+            }
+            Ok(Node::from_object(index_map))
+        }
+    }
+}
+
+/// Lists the `.json` files directly inside `dir`, sorted by name.
+pub fn json_files_in(dir: impl AsRef<Path>) -> io::Result<Vec<std::path::PathBuf>> {
+    let mut paths = fs::read_dir(dir)?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .filter(|path| {
+            path.as_ref().map_or(true, |path| {
+                path.is_file() && path.extension().is_some_and(|ext| ext == "json")
+            })
+        })
+        .collect::<io::Result<Vec<_>>>()?;
+    paths.sort();
+    Ok(paths)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn setup_dir(dir: &str, files: &[(&str, &str)]) {
+        let _ = fs::remove_dir_all(dir);
+        fs::create_dir_all(dir).unwrap();
+        for (name, content) in files {
+            fs::write(Path::new(dir).join(name), content).unwrap();
+        }
+    }
+
+    #[test]
+    fn join_test() {
+        let dir = "/tmp/jedit-join-test";
+        setup_dir(
+            dir,
+            &[
+                ("b.json", r#"{"b": 2}"#),
+                ("a.json", "[1]"),
+                ("notes.txt", "not json"),
+            ],
+        );
+
+        let paths = json_files_in(dir).unwrap();
+        assert_eq!(
+            paths,
+            vec![Path::new(dir).join("a.json"), Path::new(dir).join("b.json")]
+        );
+
+        let node = join(&paths, JoinKind::Array, &ParseOptions::default()).unwrap();
+        assert_eq!(node, Node::load(r#"[[1], {"b": 2}]"#.as_bytes()).unwrap());
+
+        let node = join(&paths, JoinKind::Object, &ParseOptions::default()).unwrap();
+        assert_eq!(
+            node,
+            Node::load(r#"{"a": [1], "b": {"b": 2}}"#.as_bytes()).unwrap()
+        );
+    }
+
+    #[test]
+    fn join_error_test() {
+        let dir = "/tmp/jedit-join-error-test";
+        setup_dir(dir, &[("a.json", "[1]"), ("bad.json", "{")]);
+
+        let paths = [Path::new(dir).join("a.json"), Path::new(dir).join("a.json")];
+        assert!(matches!(
+            join(&paths, JoinKind::Object, &ParseOptions::default()),
+            Err(LoadError::DuplicateKey(key)) if key == "a"
+        ));
+        assert!(join(&paths, JoinKind::Array, &ParseOptions::default()).is_ok());
+
+        let paths = [Path::new(dir).join("bad.json")];
+        assert!(matches!(
+            join(&paths, JoinKind::Array, &ParseOptions::default()),
+            Err(LoadError::InFile(path, _)) if path.ends_with("bad.json")
+        ));
+    }
+}
@@ -0,0 +1,146 @@
+use indexmap::IndexMap;
+
+use super::node::Node;
+
+/// Path changed differently on both sides of a merge. A side is `None` where it deleted the path.
+#[derive(Debug, Clone)]
+#[cfg_attr(test, derive(PartialEq))]
+pub struct Conflict {
+    pub selector: Vec<String>,
+    pub ours: Option<Node>,
+    pub theirs: Option<Node>,
+}
+
+/// Three-way merges `ours` and `theirs` against their common `base`. Objects are merged key by
+/// key, while arrays and terminals are replaced as a whole. Conflicting paths keep our side and
+/// are returned alongside the merged document.
+pub fn merge(base: &Node, ours: &Node, theirs: &Node) -> (Node, Vec<Conflict>) {
+    let mut conflicts = Vec::new();
+    let merged = merge_at(
+        &mut Vec::new(),
+        Some(base),
+        Some(ours),
+        Some(theirs),
+        &mut conflicts,
+    )
+    .unwrap_or_else(Node::null);
+    (merged, conflicts)
+}
+
+fn merge_at(
+    selector: &mut Vec<String>,
+    base: Option<&Node>,
+    ours: Option<&Node>,
+    theirs: Option<&Node>,
+    conflicts: &mut Vec<Conflict>,
+) -> Option<Node> {
+    let same = |a: Option<&Node>, b: Option<&Node>| match (a, b) {
+        (Some(a), Some(b)) => a.same_value(b),
+        (None, None) => true,
+        _ => false,
+    };
+    if same(ours, theirs) || same(theirs, base) {
+        return ours.cloned();
+    }
+    if same(ours, base) {
+        return theirs.cloned();
+    }
+
+    let objects = (
+        ours.and_then(Node::as_object),
+        theirs.and_then(Node::as_object),
+    );
+    let (Some(our_members), Some(their_members)) = objects else {
+        conflicts.push(Conflict {
+            selector: selector.clone(),
+            ours: ours.cloned(),
+            theirs: theirs.cloned(),
+        });
+        return ours.cloned();
+    };
+    let base_members = base.and_then(Node::as_object);
+
+    let keys = our_members
+        .keys()
+        .chain(
+            their_members
+                .keys()
+                .filter(|key| !our_members.contains_key(*key)),
+        )
+        .cloned()
+        .collect::<Vec<_>>();
+    let mut members = IndexMap::new();
+    for key in keys {
+        selector.push(key.clone());
+        let member = merge_at(
+            selector,
+            base_members.and_then(|members| members.get(&key)),
+            our_members.get(&key),
+            their_members.get(&key),
+            conflicts,
+        );
+        selector.pop();
+        if let Some(member) = member {
+            members.insert(key, member);
+        }
+    }
+    Some(Node::from_object(members))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn load(input: &str) -> Node {
+        Node::load(input.as_bytes()).unwrap()
+    }
+
+    #[test]
+    fn merge_test() {
+        let base = load(r#"{"name": "a", "port": 80, "tags": [1], "old": true}"#);
+        let ours = load(r#"{"name": "b", "port": 81, "tags": [1, 2], "old": true}"#);
+        let theirs = load(r#"{"name": "a", "port": 82, "tags": [1], "new": 1}"#);
+
+        let (merged, conflicts) = merge(&base, &ours, &theirs);
+        assert_eq!(
+            merged.to_string_compact().unwrap(),
+            r#"{"name":"b","port":81,"tags":[1,2],"new":1}"#
+        );
+        assert_eq!(
+            conflicts,
+            [Conflict {
+                selector: vec![String::from("port")],
+                ours: Some(load("81")),
+                theirs: Some(load("82")),
+            }]
+        );
+    }
+
+    #[test]
+    fn merge_delete_test() {
+        let base = load(r#"{"a": {"b": 1}, "c": 1}"#);
+        let ours = load(r#"{"a": {"b": 2}, "c": 1}"#);
+        let theirs = load(r#"{"c": 2}"#);
+
+        let (merged, conflicts) = merge(&base, &ours, &theirs);
+        assert_eq!(
+            merged.to_string_compact().unwrap(),
+            r#"{"a":{"b":2},"c":2}"#
+        );
+        assert_eq!(
+            conflicts,
+            [Conflict {
+                selector: vec![String::from("a")],
+                ours: Some(load(r#"{"b": 2}"#)),
+                theirs: None,
+            }]
+        );
+    }
+
+    #[test]
+    fn same_value_test() {
+        assert!(load(r#"{"a": 1, "b": [2]}"#).same_value(&load(r#"{"b": [2], "a": 1}"#)));
+        assert!(!load("[1, 2]").same_value(&load("[2, 1]")));
+        assert!(!load("1").same_value(&load("1.0")));
+    }
+}
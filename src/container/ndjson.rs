@@ -0,0 +1,81 @@
+use rayon::iter::{IntoParallelIterator, ParallelIterator};
+
+use crate::error::LoadError;
+
+use super::{node::Node, parser::ParseOptions};
+
+/// Position in a newline-delimited JSON file that is read as it grows.
+#[derive(Debug, Default, Clone, Copy)]
+#[cfg_attr(test, derive(PartialEq))]
+pub struct RecordReader {
+    offset: u64,
+    line: usize,
+}
+
+impl RecordReader {
+    /// Byte offset of the first record not read yet.
+    pub fn offset(&self) -> u64 {
+        self.offset
+    }
+
+    /// Parses the complete records of `input`, which starts at [`RecordReader::offset`]. A
+    /// trailing record without its newline is left for the next read, since the writer may not
+    /// have finished it.
+    pub fn parse(&mut self, input: &[u8], options: &ParseOptions) -> Result<Vec<Node>, LoadError> {
+        let Some(end) = input.iter().rposition(|&c| c == b'\n') else {
+            return Ok(Vec::new());
+        };
+
+        let lines = input[..end]
+            .split(|&c| c == b'\n')
+            .enumerate()
+            .map(|(i, line)| (self.line + i + 1, line.strip_suffix(b"\r").unwrap_or(line)))
+            .filter(|(_, line)| !line.iter().all(u8::is_ascii_whitespace))
+            .collect::<Vec<_>>();
+        let records = lines
+            .into_par_iter()
+            .map(|(line, content)| {
+                Node::load_with(content, options)
+                    .map_err(|error| LoadError::InLine(line, Box::new(error)))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        self.offset += end as u64 + 1;
+        self.line += input[..=end].iter().filter(|&&c| c == b'\n').count();
+        Ok(records)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parse_test() {
+        let options = ParseOptions::default();
+        let mut reader = RecordReader::default();
+
+        let records = reader
+            .parse(b"{\"a\": 1}\r\n\n[2]\n{\"b\"", &options)
+            .unwrap();
+        assert_eq!(
+            records,
+            [
+                Node::load(r#"{"a": 1}"#.as_bytes()).unwrap(),
+                Node::load("[2]".as_bytes()).unwrap(),
+            ]
+        );
+        assert_eq!(reader.offset(), 15);
+
+        assert_eq!(reader.parse(b"{\"b\"", &options).unwrap(), []);
+        assert_eq!(reader.offset(), 15);
+
+        let records = reader.parse(b"{\"b\": 3}\n", &options).unwrap();
+        assert_eq!(records, [Node::load(r#"{"b": 3}"#.as_bytes()).unwrap()]);
+        assert_eq!(reader.offset(), 24);
+
+        let error = reader.parse(b"{}\n{\n", &options).unwrap_err();
+        assert!(matches!(error, LoadError::InLine(6, _)), "{error}");
+        assert_eq!(reader.offset(), 24);
+    }
+}
@@ -1,11 +1,30 @@
-use std::{fmt::Display, ops::Deref};
+use std::{
+    cmp::{Ordering, Reverse},
+    collections::BinaryHeap,
+    fmt::Display,
+    fs::File,
+    ops::Deref,
+    path::Path,
+    sync::{Arc, OnceLock},
+};
 
 use indexmap::IndexMap;
-use rayon::iter::{IntoParallelIterator, IntoParallelRefIterator, ParallelIterator};
-use serde::Serialize;
-
-use super::INDENT;
-use crate::error::{DeserializationError, DumpError, IndexingError, LoadError, MutationError};
+use rayon::iter::{
+    IntoParallelIterator, IntoParallelRefIterator, IntoParallelRefMutIterator, ParallelIterator,
+};
+use serde::{Serialize, ser::SerializeStruct};
+use sonic_rs::{JsonContainerTrait, JsonType, JsonValueTrait};
+
+use super::{
+    INDENT,
+    encoding::{Encoding, Indent},
+    parser::{self, ParseOptions, Repairs},
+    pointer,
+};
+use crate::error::{
+    ConversionError, DeserializationError, DumpError, IndexingError, LoadError, MutationError,
+    PointerError,
+};
 
 struct Selector<'a, T> {
     keys: &'a [T],
@@ -20,11 +39,15 @@ impl<'a, T: Deref<Target = str>> Selector<'a, T> {
         }
     }
 
-    fn next(&mut self) -> Option<&str> {
+    fn next(&mut self) -> Option<&'a str> {
         let res = self.keys.get(self.next_key_pos);
         self.next_key_pos = (self.next_key_pos + 1).min(self.keys.len());
         res.map(Deref::deref)
     }
+
+    fn peek(&self) -> Option<&'a str> {
+        self.keys.get(self.next_key_pos).map(Deref::deref)
+    }
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -32,6 +55,8 @@ impl<'a, T: Deref<Target = str>> Selector<'a, T> {
 pub struct NodeMeta {
     pub n_lines: usize,
     pub n_bytes: usize,
+    /// Indentation levels summed over the lines, to size the node for any [`Indent`].
+    pub n_indents: usize,
     pub kind: NodeKind,
 }
 
@@ -43,14 +68,62 @@ pub enum NodeKind {
     Array,
 }
 
+/// Size of a container summed child by child, as [`Node::from_array`] and [`Node::from_object`]
+/// size it.
+#[derive(Debug, Default)]
+pub(super) struct ContainerSize {
+    n_children: usize,
+    n_lines: usize,
+    n_bytes: usize,
+    n_indents: usize,
+}
+
+impl ContainerSize {
+    /// Adds a child, with its key when in an object.
+    pub(super) fn push(&mut self, key: Option<&str>, child: &NodeMeta) {
+        self.n_children += 1;
+        self.n_lines += child.n_lines;
+        self.n_bytes += child.n_bytes + INDENT * child.n_lines + key.map_or(0, |key| key.len() + 4);
+        self.n_indents += child.n_indents + child.n_lines;
+    }
+
+    pub(super) fn meta(&self, kind: NodeKind) -> NodeMeta {
+        if self.n_children == 0 {
+            return NodeMeta {
+                n_lines: 1,
+                n_bytes: 2,
+                n_indents: 0,
+                kind,
+            };
+        }
+        NodeMeta {
+            n_lines: self.n_lines + 2,
+            n_bytes: self.n_bytes + 2 * self.n_children + 2,
+            n_indents: self.n_indents,
+            kind,
+        }
+    }
+}
+
 impl NodeMeta {
     pub fn null() -> Self {
         NodeMeta {
             n_lines: 1,
             n_bytes: 4,
+            n_indents: 0,
             kind: NodeKind::Terminal,
         }
     }
+
+    /// Bytes taken when indented with `indent` rather than [`INDENT`] spaces.
+    pub fn n_bytes_with(&self, indent: Indent) -> usize {
+        let width = match indent {
+            Indent::Auto => INDENT,
+            Indent::Spaces(n) => n,
+            Indent::Tab => 1,
+        };
+        self.n_bytes - INDENT * self.n_indents + width * self.n_indents
+    }
 }
 
 #[derive(Debug)]
@@ -68,39 +141,232 @@ pub enum IndexKind {
     Array(usize),
 }
 
-#[derive(Debug)]
-#[cfg_attr(test, derive(PartialEq, Clone))]
+/// A JSON value with the size it takes when pretty printed. Clones share their containers until
+/// either side changes them, so snapshots of a whole document are cheap.
+#[derive(Debug, Clone)]
+#[cfg_attr(test, derive(PartialEq))]
 pub struct Node {
     n_lines: usize,
     n_bytes: usize,
-    data: Kind,
+    n_indents: usize,
+    data: Data,
 }
 
 #[derive(Debug, Clone, Copy)]
 #[cfg_attr(test, derive(PartialEq))]
-enum Number {
+enum NumberValue {
     Int(i64),
+    /// Integers above `i64::MAX`, kept exact instead of rounding to a float.
+    UInt(u64),
     Float(f64),
 }
 
-impl Display for Number {
+impl NumberValue {
+    fn as_f64(self) -> f64 {
+        match self {
+            NumberValue::Int(value) => value as f64,
+            NumberValue::UInt(value) => value as f64,
+            NumberValue::Float(value) => value,
+        }
+    }
+}
+
+impl Display for NumberValue {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            Number::Int(value) => write!(f, "{value}"),
-            Number::Float(value) => write!(f, "{value}"),
+            NumberValue::Int(value) => write!(f, "{value}"),
+            NumberValue::UInt(value) => write!(f, "{value}"),
+            NumberValue::Float(value) if !value.is_finite() => {
+                f.write_str(non_finite_token(*value))
+            }
+            // Debug keeps the `.0` of integral floats, matching what gets saved.
+            NumberValue::Float(value) => write!(f, "{value:?}"),
         }
     }
 }
 
-#[derive(Debug)]
-#[cfg_attr(test, derive(PartialEq, Clone))]
+#[derive(Debug, Clone)]
+#[cfg_attr(test, derive(PartialEq))]
+struct Number {
+    value: NumberValue,
+    /// The number as written in the input, saved verbatim. Only kept for floats, the only
+    /// numbers `value` may write differently, such as `1.50`, `1e3` or integers beyond `u64`.
+    literal: Option<Box<str>>,
+}
+
+impl Number {
+    fn new(value: NumberValue) -> Self {
+        Self {
+            value,
+            literal: None,
+        }
+    }
+
+    /// Parses a JSON number literal, keeping it as written. Floats beyond the range of `f64` are
+    /// kept too, valued as an infinity.
+    fn parse(literal: &str) -> Option<Self> {
+        if !is_number_literal(literal) {
+            return None;
+        }
+        let value = match (literal.parse::<i64>(), literal.parse::<u64>()) {
+            // As an integer, `-0` would lose its sign.
+            (Ok(value), _) if literal != "-0" => NumberValue::Int(value),
+            (_, Ok(value)) => NumberValue::UInt(value),
+            _ => NumberValue::Float(literal.parse().ok()?),
+        };
+        Some(Self {
+            value,
+            literal: matches!(value, NumberValue::Float(_)).then(|| Box::from(literal)),
+        })
+    }
+
+    fn as_f64(&self) -> f64 {
+        self.value.as_f64()
+    }
+
+    /// The literal of an integer too large for `u64`, whose float value is only approximate.
+    fn big_integer(&self) -> Option<&str> {
+        self.literal
+            .as_deref()
+            .filter(|literal| !literal.contains(['.', 'e', 'E']))
+    }
+
+    fn same_value(&self, other: &Number) -> bool {
+        match (self.value, other.value) {
+            (NumberValue::Int(a), NumberValue::Int(b)) => a == b,
+            (NumberValue::UInt(a), NumberValue::UInt(b)) => a == b,
+            (NumberValue::Float(a), NumberValue::Float(b)) => {
+                match (self.big_integer(), other.big_integer()) {
+                    (Some(a), Some(b)) => a == b,
+                    _ => a == b,
+                }
+            }
+            _ => false,
+        }
+    }
+}
+
+impl Display for Number {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.literal {
+            Some(literal) => f.write_str(literal),
+            None => self.value.fmt(f),
+        }
+    }
+}
+
+/// Whether `literal` follows the JSON number grammar: an optional minus, an integer part without
+/// leading zeros, then an optional fraction and exponent.
+fn is_number_literal(literal: &str) -> bool {
+    fn digits(bytes: &[u8]) -> (usize, &[u8]) {
+        let n = bytes
+            .iter()
+            .take_while(|byte| byte.is_ascii_digit())
+            .count();
+        (n, &bytes[n..])
+    }
+
+    let bytes = literal.as_bytes();
+    let bytes = bytes.strip_prefix(b"-").unwrap_or(bytes);
+    let (n, mut rest) = digits(bytes);
+    if n == 0 || (n > 1 && bytes[0] == b'0') {
+        return false;
+    }
+    if let Some(fraction) = rest.strip_prefix(b".") {
+        let n;
+        (n, rest) = digits(fraction);
+        if n == 0 {
+            return false;
+        }
+    }
+    if let Some(exponent) = rest.strip_prefix(b"e").or_else(|| rest.strip_prefix(b"E")) {
+        let exponent = exponent
+            .strip_prefix(b"+")
+            .or_else(|| exponent.strip_prefix(b"-"))
+            .unwrap_or(exponent);
+        let n;
+        (n, rest) = digits(exponent);
+        if n == 0 {
+            return false;
+        }
+    }
+    rest.is_empty()
+}
+
+/// Orders scalars as null, booleans, numbers and then strings.
+fn compare_scalars(a: &Kind, b: &Kind, compare_str: impl Fn(&str, &str) -> Ordering) -> Ordering {
+    let rank = |kind: &Kind| match kind {
+        Kind::Null => 0,
+        Kind::Bool(_) => 1,
+        Kind::Number(_) => 2,
+        Kind::String(_) => 3,
+        Kind::Array(_) | Kind::Object(_) => 4,
+    };
+    match (a, b) {
+        (Kind::Bool(a), Kind::Bool(b)) => a.cmp(b),
+        (Kind::Number(a), Kind::Number(b)) => match (a.value, b.value) {
+            (NumberValue::Int(a), NumberValue::Int(b)) => a.cmp(&b),
+            (NumberValue::UInt(a), NumberValue::UInt(b)) => a.cmp(&b),
+            (a, b) => a.as_f64().total_cmp(&b.as_f64()),
+        },
+        (Kind::String(a), Kind::String(b)) => compare_str(a, b),
+        _ => rank(a).cmp(&rank(b)),
+    }
+}
+
+/// Compares strings with runs of digits ordered by their value.
+fn natural_cmp(mut a: &str, mut b: &str) -> Ordering {
+    fn split_digits(s: &str) -> (&str, &str) {
+        let end = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+        let (digits, rest) = s.split_at(end);
+        (digits.trim_start_matches('0'), rest)
+    }
+    loop {
+        match (a.chars().next(), b.chars().next()) {
+            (None, None) => return Ordering::Equal,
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
+            (Some(x), Some(y)) if x.is_ascii_digit() && y.is_ascii_digit() => {
+                let (a_digits, a_rest) = split_digits(a);
+                let (b_digits, b_rest) = split_digits(b);
+                let ordering = a_digits
+                    .len()
+                    .cmp(&b_digits.len())
+                    .then_with(|| a_digits.cmp(b_digits));
+                if ordering.is_ne() {
+                    return ordering;
+                }
+                (a, b) = (a_rest, b_rest);
+            }
+            (Some(x), Some(y)) => {
+                if x != y {
+                    return x.cmp(&y);
+                }
+                (a, b) = (&a[x.len_utf8()..], &b[y.len_utf8()..]);
+            }
+        }
+    }
+}
+
+fn non_finite_token(value: f64) -> &'static str {
+    if value.is_nan() {
+        "NaN"
+    } else if value > 0.0 {
+        "Infinity"
+    } else {
+        "-Infinity"
+    }
+}
+
+#[derive(Debug, Clone)]
+#[cfg_attr(test, derive(PartialEq))]
 enum Kind {
     Null,
     Bool(bool),
     Number(Number),
     String(String),
-    Array(Vec<Node>),
-    Object(IndexMap<String, Node>),
+    Array(Arc<Vec<Node>>),
+    Object(Arc<IndexMap<String, Node>>),
 }
 
 impl Kind {
@@ -113,6 +379,117 @@ impl Kind {
     }
 }
 
+#[derive(Debug, Clone)]
+enum Data {
+    Parsed(Kind),
+    Lazy(Box<Lazy>),
+}
+
+impl Data {
+    /// The value, parsing it on first read when it is lazy.
+    fn kind(&self) -> &Kind {
+        match self {
+            Data::Parsed(kind) => kind,
+            Data::Lazy(lazy) => lazy.node().data.kind(),
+        }
+    }
+
+    /// The value, parsed for good when it is lazy.
+    fn kind_mut(&mut self) -> &mut Kind {
+        if matches!(self, Data::Lazy(_)) {
+            let Data::Lazy(lazy) = std::mem::replace(self, Data::Parsed(Kind::Null)) else {
+                unreachable!("data is lazy");
+            };
+            *self = lazy.into_node().data;
+        }
+        match self {
+            Data::Parsed(kind) => kind,
+            Data::Lazy(_) => unreachable!("lazy data was just parsed"),
+        }
+    }
+
+    fn node_kind(&self) -> NodeKind {
+        match self {
+            Data::Parsed(kind) => kind.node_kind(),
+            Data::Lazy(lazy) => lazy.kind,
+        }
+    }
+}
+
+#[cfg(test)]
+impl PartialEq for Data {
+    fn eq(&self, other: &Self) -> bool {
+        self.kind() == other.kind()
+    }
+}
+
+/// Container left unparsed on load, parsed from the shared source the first time it is read.
+#[derive(Clone)]
+struct Lazy {
+    source: Arc<[u8]>,
+    /// Offset of the opening bracket in `source`.
+    start: usize,
+    options: ParseOptions,
+    kind: NodeKind,
+    parsed: OnceLock<Node>,
+}
+
+impl Lazy {
+    fn parse(&self) -> Node {
+        parser::parse_at(&self.source, self.start, &self.options)
+    }
+
+    fn node(&self) -> &Node {
+        self.parsed.get_or_init(|| self.parse())
+    }
+
+    fn into_node(self) -> Node {
+        let parsed = self.parsed.into_inner();
+        parsed.unwrap_or_else(|| parser::parse_at(&self.source, self.start, &self.options))
+    }
+}
+
+impl std::fmt::Debug for Lazy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Lazy")
+            .field("start", &self.start)
+            .field("kind", &self.kind)
+            .field("parsed", &self.parsed.get().is_some())
+            .finish()
+    }
+}
+
+/// Type a terminal node can be converted to with [`Node::convert`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ScalarType {
+    String,
+    Number,
+    Boolean,
+    Null,
+}
+
+impl ScalarType {
+    pub const ALL: [Self; 4] = [Self::String, Self::Number, Self::Boolean, Self::Null];
+
+    /// Name as given by [`Node::type_name`].
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::String => "string",
+            Self::Number => "number",
+            Self::Boolean => "boolean",
+            Self::Null => "null",
+        }
+    }
+}
+
+/// How [`Node::sort`] orders object keys or array values.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct SortSpec {
+    /// Compares runs of digits by their value, so `a2` comes before `a10`.
+    pub natural: bool,
+    pub descending: bool,
+}
+
 #[derive(Debug)]
 pub enum AddNodeKey {
     Array,
@@ -124,7 +501,8 @@ pub enum NodeMutation<'a> {
     Replace(Node),
     Delete(&'a str),
     Append {
-        after: &'a str,
+        /// Sibling to insert after, or `None` to insert as the first child.
+        after: Option<&'a str>,
         key: AddNodeKey,
         node: Node,
     },
@@ -132,22 +510,84 @@ pub enum NodeMutation<'a> {
         before: &'a str,
         after: String,
     },
+    /// Exchanges the positions of two children.
+    Swap(&'a str, &'a str),
+    /// Reorders the children, listing their current positions in their new order.
+    Sort(&'a [usize]),
 }
 
 impl Node {
-    pub fn load(reader: impl std::io::Read) -> Result<Self, LoadError> {
-        let value: serde_json::Value = sonic_rs::from_reader(reader)?;
-        Self::from_serde_json(value).map_err(Into::into)
+    pub fn load(mut reader: impl std::io::Read) -> Result<Self, LoadError> {
+        let mut input = Vec::new();
+        reader.read_to_end(&mut input)?;
+        // Raw numbers keep their literals, which serde_json would round.
+        let mut deserializer = sonic_rs::Deserializer::from_slice(&input).use_rawnumber();
+        let value: sonic_rs::Value = deserializer.deserialize()?;
+        deserializer.end()?;
+        Self::from_sonic(&value).map_err(Into::into)
+    }
+
+    pub fn load_with(
+        reader: impl std::io::Read,
+        options: &ParseOptions,
+    ) -> Result<Self, LoadError> {
+        Self::load_with_repairs(reader, options).map(|(node, _)| node)
+    }
+
+    /// Like [`Node::load_with`], also returning what had to be repaired to load it.
+    pub fn load_with_repairs(
+        mut reader: impl std::io::Read,
+        options: &ParseOptions,
+    ) -> Result<(Self, Repairs), LoadError> {
+        let mut input = Vec::new();
+        reader.read_to_end(&mut input)?;
+        let input = Encoding::detect(&input).decode(&input, options.lossy_utf8)?;
+
+        if options.is_lazy(input.len()) {
+            let node = parser::parse_lazy(Arc::from(input), options)?;
+            return Ok((node, Repairs::default()));
+        }
+        if options.is_strict() {
+            return Self::load(input.as_ref()).map(|node| (node, Repairs::default()));
+        }
+        parser::parse(&input, options)
+    }
+
+    pub fn load_file(path: impl AsRef<Path>, options: &ParseOptions) -> Result<Self, LoadError> {
+        Self::load_with(File::open(path)?, options)
     }
 
     pub fn to_string_pretty(&self) -> Result<String, DumpError> {
         sonic_rs::to_string_pretty(self).map_err(Into::into)
     }
 
+    pub fn to_string_indented(&self, indent: Indent) -> Result<String, DumpError> {
+        let unit = indent.unit();
+        let mut writer = Vec::with_capacity(self.as_meta().n_bytes_with(indent));
+        let mut serializer = sonic_rs::Serializer::with_formatter(
+            &mut writer,
+            sonic_rs::format::PrettyFormatter::with_indent(unit.as_bytes()),
+        );
+        self.serialize(&mut serializer)?;
+        Ok(String::from_utf8(writer).expect("serialized JSON is UTF-8"))
+    }
+
+    pub fn to_string_compact(&self) -> Result<String, DumpError> {
+        sonic_rs::to_string(self).map_err(Into::into)
+    }
+
     pub fn subtree<T: Deref<Target = str>>(&self, selector: &[T]) -> Result<&Node, IndexingError> {
         self.subtree_inner(Selector::new(selector))
     }
 
+    /// Selector of the node named by a JSON Pointer, or by a JSONPath when `query` starts with
+    /// `$`.
+    pub fn resolve_pointer(&self, query: &str) -> Result<Vec<String>, PointerError> {
+        let selector = pointer::from_query(query)?;
+        self.subtree(&selector)?;
+        Ok(selector)
+    }
+
     pub fn metas<T: Deref<Target = str>>(
         &self,
         selector: &[T],
@@ -196,7 +636,7 @@ impl Node {
         self.mutate(
             Selector::new(&selector[..len - 1]),
             NodeMutation::Append {
-                after: selector[len - 1].deref(),
+                after: Some(selector[len - 1].deref()),
                 key,
                 node,
             },
@@ -204,6 +644,59 @@ impl Node {
         .map(|_| ())
     }
 
+    /// Inserts `node` as the first child of the container at `selector`.
+    pub fn insert_first<T: Deref<Target = str>>(
+        &mut self,
+        selector: &[T],
+        key: AddNodeKey,
+        node: Node,
+    ) -> Result<(), MutationError> {
+        self.mutate(
+            Selector::new(selector),
+            NodeMutation::Append {
+                after: None,
+                key,
+                node,
+            },
+        )
+        .map(|_| ())
+    }
+
+    /// Appends `nodes` to this array.
+    pub fn extend(&mut self, nodes: Vec<Node>) -> Result<(), MutationError> {
+        let Kind::Array(child) = self.data.kind_mut() else {
+            return Err(IndexingError::NotIndexable.into());
+        };
+        let child = Arc::make_mut(child);
+        for node in nodes {
+            if child.is_empty() {
+                self.n_lines = 2 + node.n_lines;
+                self.n_bytes = 4 + node.indented_n_bytes();
+                self.n_indents = node.nested_indents();
+            } else {
+                self.n_lines += node.n_lines;
+                self.n_bytes += node.indented_n_bytes() + 2;
+                self.n_indents += node.nested_indents();
+            }
+            child.push(node);
+        }
+        Ok(())
+    }
+
+    /// Sets the node at `selector`, adding the last key if it is missing.
+    ///
+    /// With `create_parents`, missing intermediate keys are created as well: an array when the
+    /// following key is an index, an object otherwise. Arrays are padded with `null` up to the
+    /// requested index.
+    pub fn set<T: Deref<Target = str>>(
+        &mut self,
+        selector: &[T],
+        node: Node,
+        create_parents: bool,
+    ) -> Result<Option<Node>, MutationError> {
+        self.set_inner(Selector::new(selector), node, create_parents)
+    }
+
     pub fn rename<T: Deref<Target = str>>(
         &mut self,
         selector: &[T],
@@ -224,9 +717,98 @@ impl Node {
         .map(|_| ())
     }
 
+    /// Sorts the keys of the object or the scalar values of the array at `selector`, returning
+    /// the previous positions of the children in their new order.
+    pub fn sort<T: Deref<Target = str>>(
+        &mut self,
+        selector: &[T],
+        spec: SortSpec,
+    ) -> Result<Vec<usize>, MutationError> {
+        let compare_str = |a: &str, b: &str| {
+            if spec.natural {
+                natural_cmp(a, b)
+            } else {
+                a.cmp(b)
+            }
+        };
+        let directed = |ordering: Ordering| {
+            if spec.descending {
+                ordering.reverse()
+            } else {
+                ordering
+            }
+        };
+        let node = self.subtree(selector)?;
+        let mut order = (0..node.children().count()).collect::<Vec<_>>();
+        match node.data.kind() {
+            Kind::Object(index_map) => order.sort_by(|&a, &b| {
+                let (a, _) = index_map.get_index(a).expect("position in range");
+                let (b, _) = index_map.get_index(b).expect("position in range");
+                directed(compare_str(a, b))
+            }),
+            Kind::Array(nodes) => {
+                if nodes
+                    .iter()
+                    .any(|node| matches!(node.data.kind(), Kind::Array(_) | Kind::Object(_)))
+                {
+                    return Err(MutationError::NotSortable);
+                }
+                order.sort_by(|&a, &b| {
+                    directed(compare_scalars(
+                        nodes[a].data.kind(),
+                        nodes[b].data.kind(),
+                        compare_str,
+                    ))
+                });
+            }
+            Kind::Null | Kind::Bool(_) | Kind::Number(_) | Kind::String(_) => {
+                return Err(IndexingError::NotIndexable.into());
+            }
+        }
+
+        self.mutate(Selector::new(selector), NodeMutation::Sort(&order))?;
+        Ok(order)
+    }
+
+    /// Copy of the node with the keys of every object in alphabetical order, as `jq -S` writes
+    /// them.
+    pub fn sorted_keys(&self) -> Node {
+        if let Some(items) = self.as_array() {
+            Node::from_array(items.iter().map(Node::sorted_keys).collect())
+        } else if let Some(members) = self.as_object() {
+            let mut members = members
+                .iter()
+                .map(|(key, member)| (key.clone(), member.sorted_keys()))
+                .collect::<IndexMap<_, _>>();
+            members.sort_keys();
+            Node::from_object(members)
+        } else {
+            self.clone()
+        }
+    }
+
+    /// Exchanges the node at `selector` with its sibling `other`. Array elements keep their
+    /// indices, so the moved node is found at `other` afterwards.
+    pub fn swap<T: Deref<Target = str>>(
+        &mut self,
+        selector: &[T],
+        other: &str,
+    ) -> Result<(), MutationError> {
+        let len = selector.len();
+        if len == 0 {
+            return Err(IndexingError::NotIndexable.into());
+        }
+
+        self.mutate(
+            Selector::new(&selector[..len - 1]),
+            NodeMutation::Swap(selector[len - 1].deref(), other),
+        )
+        .map(|_| ())
+    }
+
     pub fn as_index(&self) -> Index {
         let meta = self.as_meta();
-        let kind = match &self.data {
+        let kind = match self.data.kind() {
             Kind::Array(nodes) => IndexKind::Array(nodes.len()),
             Kind::Object(index_map) => IndexKind::Object(index_map.keys().cloned().collect()),
             Kind::Null | Kind::Bool(_) | Kind::Number(_) | Kind::String(_) => IndexKind::Terminal,
@@ -234,10 +816,385 @@ impl Node {
         Index { meta, kind }
     }
 
-    fn as_meta(&self) -> NodeMeta {
+    /// Selectors and byte sizes of the `n` largest descendants, largest first.
+    pub fn largest_subtrees(&self, n: usize) -> Vec<(Vec<String>, usize)> {
+        let mut heap = BinaryHeap::new();
+        let mut path = Vec::new();
+        self.collect_largest(n, &mut path, &mut heap);
+
+        let mut res = heap
+            .into_iter()
+            .map(|Reverse((n_bytes, path))| (path, n_bytes))
+            .collect::<Vec<_>>();
+        res.sort_by(|(_, a), (_, b)| b.cmp(a));
+        res
+    }
+
+    fn collect_largest(
+        &self,
+        n: usize,
+        path: &mut Vec<String>,
+        heap: &mut BinaryHeap<Reverse<(usize, Vec<String>)>>,
+    ) {
+        let children: Box<dyn Iterator<Item = (String, &Node)>> = match self.data.kind() {
+            Kind::Array(nodes) => Box::new(
+                nodes
+                    .iter()
+                    .enumerate()
+                    .map(|(index, node)| (index.to_string(), node)),
+            ),
+            Kind::Object(index_map) => {
+                Box::new(index_map.iter().map(|(key, node)| (key.clone(), node)))
+            }
+            Kind::Null | Kind::Bool(_) | Kind::Number(_) | Kind::String(_) => return,
+        };
+
+        for (key, child) in children {
+            path.push(key);
+            let is_candidate = heap.len() < n
+                || heap
+                    .peek()
+                    .is_some_and(|Reverse((n_bytes, _))| child.n_bytes > *n_bytes);
+            if is_candidate {
+                heap.push(Reverse((child.n_bytes, path.clone())));
+                if heap.len() > n {
+                    heap.pop();
+                }
+                child.collect_largest(n, path, heap);
+            }
+            path.pop();
+        }
+    }
+
+    /// Terminal values and empty containers below this node, with their selectors, in document
+    /// order. A terminal node is its own only leaf.
+    pub fn leaves(&self) -> Leaves<'_> {
+        Leaves {
+            root: Some(self),
+            levels: Vec::new(),
+            path: Vec::new(),
+        }
+    }
+
+    /// Children with their keys, array indices being keys as well.
+    fn keyed_children(&self) -> KeyedChildren<'_> {
+        match self.data.kind() {
+            Kind::Array(nodes) => Box::new(
+                nodes
+                    .iter()
+                    .enumerate()
+                    .map(|(index, node)| (index.to_string(), node)),
+            ),
+            Kind::Object(index_map) => {
+                Box::new(index_map.iter().map(|(key, node)| (key.clone(), node)))
+            }
+            Kind::Null | Kind::Bool(_) | Kind::Number(_) | Kind::String(_) => {
+                Box::new(std::iter::empty())
+            }
+        }
+    }
+
+    /// Selectors of the descendants whose key contains `query`, or whose terminal value does
+    /// when `values` is set, in document order. Matching ignores ASCII case.
+    pub fn search(&self, query: &str, values: bool) -> Vec<Vec<String>> {
+        let mut matches = Vec::new();
+        if !query.is_empty() {
+            self.collect_matches(
+                &query.to_ascii_lowercase(),
+                values,
+                &mut Vec::new(),
+                &mut matches,
+            );
+        }
+        matches
+    }
+
+    fn collect_matches(
+        &self,
+        query: &str,
+        values: bool,
+        path: &mut Vec<String>,
+        matches: &mut Vec<Vec<String>>,
+    ) {
+        let contains = |text: &str| text.to_ascii_lowercase().contains(query);
+        let children: Box<dyn Iterator<Item = (String, bool, &Node)>> = match self.data.kind() {
+            Kind::Array(nodes) => Box::new(
+                nodes
+                    .iter()
+                    .enumerate()
+                    .map(|(index, node)| (index.to_string(), false, node)),
+            ),
+            Kind::Object(index_map) => Box::new(
+                index_map
+                    .iter()
+                    .map(|(key, node)| (key.clone(), contains(key), node)),
+            ),
+            Kind::Null | Kind::Bool(_) | Kind::Number(_) | Kind::String(_) => return,
+        };
+
+        for (key, key_matches, child) in children {
+            path.push(key);
+            let value_matches = values
+                && match child.data.kind() {
+                    Kind::Null => contains("null"),
+                    Kind::Bool(value) => contains(&value.to_string()),
+                    Kind::Number(number) => contains(&number.to_string()),
+                    Kind::String(value) => contains(value),
+                    Kind::Array(_) | Kind::Object(_) => false,
+                };
+            if key_matches || value_matches {
+                matches.push(path.clone());
+            }
+            child.collect_matches(query, values, path, matches);
+            path.pop();
+        }
+    }
+
+    pub fn type_name(&self) -> &'static str {
+        match self.data.kind() {
+            Kind::Null => "null",
+            Kind::Bool(_) => "boolean",
+            Kind::Number(_) => "number",
+            Kind::String(_) => "string",
+            Kind::Array(_) => "array",
+            Kind::Object(_) => "object",
+        }
+    }
+
+    /// This terminal node as `to`, such as `"42"` as `42` or `true` as `"true"`. Strings convert to
+    /// numbers and booleans only when they hold one, and anything converts to null.
+    pub fn convert(&self, to: ScalarType) -> Result<Self, ConversionError> {
+        let invalid = || ConversionError::Invalid {
+            value: self
+                .to_string_compact()
+                .unwrap_or_else(|_| String::from(self.type_name())),
+            to: to.name(),
+        };
+        match (self.data.kind(), to) {
+            (Kind::Array(_) | Kind::Object(_), _) => Err(ConversionError::NotScalar),
+            (_, ScalarType::Null) => Ok(Self::null()),
+            (Kind::String(_), ScalarType::String)
+            | (Kind::Number(_), ScalarType::Number)
+            | (Kind::Bool(_), ScalarType::Boolean) => Ok(self.clone()),
+            (Kind::Number(number), ScalarType::String) if !number.as_f64().is_finite() => {
+                Ok(Self::string(number.to_string()))
+            }
+            (_, ScalarType::String) => Ok(Self::string(
+                self.to_string_compact()
+                    .expect("broken internal representation"),
+            )),
+            (Kind::String(value), ScalarType::Number) => Self::load(value.trim().as_bytes())
+                .ok()
+                .filter(|node| matches!(node.data.kind(), Kind::Number(_)))
+                .ok_or_else(invalid),
+            (Kind::Bool(value), ScalarType::Number) => Ok(Self {
+                n_lines: 1,
+                n_bytes: 1,
+                n_indents: 0,
+                data: Data::Parsed(Kind::Number(Number::new(NumberValue::Int(i64::from(
+                    *value,
+                ))))),
+            }),
+            (Kind::String(value), ScalarType::Boolean) => match value.trim() {
+                "true" => Ok(Self::bool(true)),
+                "false" => Ok(Self::bool(false)),
+                _ => Err(invalid()),
+            },
+            (Kind::Number(number), ScalarType::Boolean) => Ok(Self::bool(match number.value {
+                NumberValue::Int(value) => value != 0,
+                NumberValue::UInt(value) => value != 0,
+                NumberValue::Float(value) => value != 0.0,
+            })),
+            (Kind::Null, ScalarType::Number | ScalarType::Boolean) => Err(invalid()),
+        }
+    }
+
+    /// Whether this is a string that looks like an embedded JSON object or array, judging only by
+    /// its first and last characters.
+    pub fn looks_like_json(&self) -> bool {
+        self.as_str().is_some_and(|value| {
+            let value = value.trim();
+            (value.starts_with('{') && value.ends_with('}'))
+                || (value.starts_with('[') && value.ends_with(']'))
+        })
+    }
+
+    /// The JSON document held by this string, such as `"{\"a\": 1}"` as `{"a": 1}`.
+    pub fn parse_embedded(&self) -> Result<Self, ConversionError> {
+        let value = self.as_str().ok_or(ConversionError::NotString)?;
+        Self::load(value.as_bytes()).map_err(|error| ConversionError::NotJson(error.to_string()))
+    }
+
+    /// This node serialized into a string, undoing [`Node::parse_embedded`].
+    pub fn stringify(&self) -> Self {
+        Self::string(
+            self.to_string_compact()
+                .expect("broken internal representation"),
+        )
+    }
+
+    pub fn as_array(&self) -> Option<&[Node]> {
+        match self.data.kind() {
+            Kind::Array(nodes) => Some(nodes),
+            _ => None,
+        }
+    }
+
+    pub fn as_object(&self) -> Option<&IndexMap<String, Node>> {
+        match self.data.kind() {
+            Kind::Object(index_map) => Some(index_map),
+            _ => None,
+        }
+    }
+
+    /// Whether both nodes hold the same JSON value, ignoring the order of object keys.
+    pub fn same_value(&self, other: &Node) -> bool {
+        if self.shares_container(other) {
+            return true;
+        }
+        match (self.data.kind(), other.data.kind()) {
+            (Kind::Null, Kind::Null) => true,
+            (Kind::Bool(a), Kind::Bool(b)) => a == b,
+            (Kind::Number(a), Kind::Number(b)) => a.same_value(b),
+            (Kind::String(a), Kind::String(b)) => a == b,
+            (Kind::Array(a), Kind::Array(b)) => {
+                a.len() == b.len() && a.iter().zip(b.iter()).all(|(a, b)| a.same_value(b))
+            }
+            (Kind::Object(a), Kind::Object(b)) => {
+                a.len() == b.len()
+                    && a.iter()
+                        .all(|(key, a)| b.get(key).is_some_and(|b| a.same_value(b)))
+            }
+            _ => false,
+        }
+    }
+
+    /// Whether `other` shares this node's container, so that neither changed since one was cloned
+    /// from the other. Terminal values compare by value.
+    pub fn is_same(&self, other: &Node) -> bool {
+        let is_terminal = |node: &Node| matches!(node.data.node_kind(), NodeKind::Terminal);
+        self.shares_container(other)
+            || (is_terminal(self) && is_terminal(other) && self.same_value(other))
+    }
+
+    /// Whether both nodes are the same container, without parsing lazy ones: unread containers
+    /// are the same when they come from the same place of the same source.
+    fn shares_container(&self, other: &Node) -> bool {
+        match (&self.data, &other.data) {
+            (Data::Lazy(a), Data::Lazy(b)) => {
+                Arc::ptr_eq(&a.source, &b.source) && a.start == b.start
+            }
+            (Data::Parsed(Kind::Array(a)), Data::Parsed(Kind::Array(b))) => Arc::ptr_eq(a, b),
+            (Data::Parsed(Kind::Object(a)), Data::Parsed(Kind::Object(b))) => Arc::ptr_eq(a, b),
+            _ => false,
+        }
+    }
+
+    /// Converts an edited number back to the integer or float kind of `original`, when it can
+    /// without losing precision.
+    pub fn keep_number_kind(self, original: &Node) -> Self {
+        let (Kind::Number(number), Kind::Number(original)) =
+            (self.data.kind(), original.data.kind())
+        else {
+            return self;
+        };
+        let number = match (number.value, original.value) {
+            (NumberValue::Int(value), NumberValue::Float(_))
+                if value.unsigned_abs() <= 1 << f64::MANTISSA_DIGITS =>
+            {
+                Number::new(NumberValue::Float(value as f64))
+            }
+            (NumberValue::Float(value), NumberValue::Int(_))
+                if value.fract() == 0.0 && value.abs() <= (1u64 << f64::MANTISSA_DIGITS) as f64 =>
+            {
+                Number::new(NumberValue::Int(value as i64))
+            }
+            _ => return self,
+        };
+        let n_bytes = number.to_string().len();
+        Self {
+            n_lines: 1,
+            n_bytes,
+            n_indents: 0,
+            data: Data::Parsed(Kind::Number(number)),
+        }
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        match self.data.kind() {
+            Kind::Bool(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    pub fn as_f64(&self) -> Option<f64> {
+        match self.data.kind() {
+            Kind::Number(number) => Some(number.as_f64()),
+            _ => None,
+        }
+    }
+
+    /// Whether this is a number without a fractional part, such as `2` or `2.0`.
+    pub fn is_integer(&self) -> bool {
+        match self.data.kind() {
+            Kind::Number(number) => match number.value {
+                NumberValue::Int(_) | NumberValue::UInt(_) => true,
+                NumberValue::Float(value) => value.fract() == 0.0,
+            },
+            _ => false,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self.data.kind() {
+            Kind::String(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    pub fn child(&self, key: &str) -> Option<&Node> {
+        match self.data.kind() {
+            Kind::Array(nodes) => nodes.get(key.parse::<usize>().ok()?),
+            Kind::Object(index_map) => index_map.get(key),
+            Kind::Null | Kind::Bool(_) | Kind::Number(_) | Kind::String(_) => None,
+        }
+    }
+
+    /// Position of the child `key` within this container.
+    pub fn position(&self, key: &str) -> Option<usize> {
+        match self.data.kind() {
+            Kind::Array(nodes) => key.parse::<usize>().ok().filter(|&i| i < nodes.len()),
+            Kind::Object(index_map) => index_map.get_index_of(key),
+            Kind::Null | Kind::Bool(_) | Kind::Number(_) | Kind::String(_) => None,
+        }
+    }
+
+    /// Key of the child at `position` within this container.
+    pub fn key_at(&self, position: usize) -> Option<String> {
+        match self.data.kind() {
+            Kind::Array(nodes) => (position < nodes.len()).then(|| position.to_string()),
+            Kind::Object(index_map) => index_map.get_index(position).map(|(key, _)| key.clone()),
+            Kind::Null | Kind::Bool(_) | Kind::Number(_) | Kind::String(_) => None,
+        }
+    }
+
+    pub fn children(&self) -> impl Iterator<Item = &Node> {
+        let (array, object) = match self.data.kind() {
+            Kind::Array(nodes) => (Some(nodes.iter()), None),
+            Kind::Object(index_map) => (None, Some(index_map.values())),
+            Kind::Null | Kind::Bool(_) | Kind::Number(_) | Kind::String(_) => (None, None),
+        };
+        array
+            .into_iter()
+            .flatten()
+            .chain(object.into_iter().flatten())
+    }
+
+    pub fn as_meta(&self) -> NodeMeta {
         NodeMeta {
             n_lines: self.n_lines,
             n_bytes: self.n_bytes,
+            n_indents: self.n_indents,
             kind: self.data.node_kind(),
         }
     }
@@ -248,78 +1205,114 @@ impl Node {
         Self {
             n_lines: 1,
             n_bytes: 4,
-            data: Kind::Null,
+            n_indents: 0,
+            data: Data::Parsed(Kind::Null),
         }
     }
 
-    fn bool(value: bool) -> Self {
+    pub(super) fn bool(value: bool) -> Self {
         Self {
             n_lines: 1,
             n_bytes: if value { 4 } else { 5 },
-            data: Kind::Bool(value),
+            n_indents: 0,
+            data: Data::Parsed(Kind::Bool(value)),
         }
     }
 
-    fn number(value: serde_json::Number) -> Result<Self, DeserializationError> {
-        let n_bytes = serde_json::to_vec(&value).unwrap().len();
-        let data = value
-            .as_i64()
-            .map(Number::Int)
-            .or_else(|| value.as_f64().map(Number::Float))
-            .ok_or(DeserializationError::InvalidNumber(value))?;
+    /// The number written as `literal`, saved back exactly as written.
+    pub(super) fn number_literal(literal: &str) -> Result<Self, DeserializationError> {
+        let number = Number::parse(literal)
+            .ok_or_else(|| DeserializationError::InvalidNumber(literal.to_string()))?;
         Ok(Self {
             n_lines: 1,
-            n_bytes,
-            data: Kind::Number(data),
+            n_bytes: literal.len(),
+            n_indents: 0,
+            data: Data::Parsed(Kind::Number(number)),
         })
     }
 
-    fn string(value: String) -> Self {
+    /// Container starting at `start` in `source` and sized `meta`, parsed the first time it is
+    /// read.
+    pub(super) fn lazy(
+        source: &Arc<[u8]>,
+        start: usize,
+        options: &ParseOptions,
+        meta: NodeMeta,
+    ) -> Self {
+        Self {
+            n_lines: meta.n_lines,
+            n_bytes: meta.n_bytes,
+            n_indents: meta.n_indents,
+            data: Data::Lazy(Box::new(Lazy {
+                source: source.clone(),
+                start,
+                options: *options,
+                kind: meta.kind,
+                parsed: OnceLock::new(),
+            })),
+        }
+    }
+
+    /// `NaN` or an infinity, kept as a non-standard literal.
+    pub(super) fn non_finite(value: f64) -> Self {
+        Self {
+            n_lines: 1,
+            n_bytes: non_finite_token(value).len(),
+            n_indents: 0,
+            data: Data::Parsed(Kind::Number(Number::new(NumberValue::Float(value)))),
+        }
+    }
+
+    pub fn string(value: String) -> Self {
         Self {
             n_lines: 1,
             n_bytes: value.len() + 2,
-            data: Kind::String(value),
+            n_indents: 0,
+            data: Data::Parsed(Kind::String(value)),
         }
     }
 
-    fn array(values: Vec<serde_json::Value>) -> Result<Self, DeserializationError> {
-        if values.is_empty() {
-            return Ok(Self {
+    pub fn from_array(nodes: Vec<Self>) -> Self {
+        Self::array_of(Arc::new(nodes))
+    }
+
+    pub fn from_object(nodes: IndexMap<String, Self>) -> Self {
+        Self::object_of(Arc::new(nodes))
+    }
+
+    /// Array sharing `nodes` with the other documents holding them.
+    fn array_of(nodes: Arc<Vec<Self>>) -> Self {
+        if nodes.is_empty() {
+            return Self {
                 n_lines: 1,
                 n_bytes: 2,
-                data: Kind::Array(Vec::new()),
-            });
+                n_indents: 0,
+                data: Data::Parsed(Kind::Array(nodes)),
+            };
         }
 
-        let nodes: Vec<Self> = values
-            .into_par_iter()
-            .map(Self::from_serde_json)
-            .collect::<Result<_, _>>()?;
-
-        Ok(Self {
+        Self {
             n_lines: nodes.par_iter().map(|node| node.n_lines).sum::<usize>() + 2,
             n_bytes: nodes.par_iter().map(Self::indented_n_bytes).sum::<usize>()
                 + nodes.len()
                 + nodes.len().saturating_sub(1)
                 + 3,
-            data: Kind::Array(nodes),
-        })
+            n_indents: nodes.par_iter().map(Self::nested_indents).sum::<usize>(),
+            data: Data::Parsed(Kind::Array(nodes)),
+        }
     }
 
-    fn object(values: IndexMap<String, serde_json::Value>) -> Result<Self, DeserializationError> {
-        if values.is_empty() {
-            return Ok(Self {
+    fn object_of(nodes: Arc<IndexMap<String, Self>>) -> Self {
+        if nodes.is_empty() {
+            return Self {
                 n_lines: 1,
                 n_bytes: 2,
-                data: Kind::Object(IndexMap::new()),
-            });
+                n_indents: 0,
+                data: Data::Parsed(Kind::Object(nodes)),
+            };
         }
 
-        let nodes: IndexMap<String, Self> = values
-            .into_par_iter()
-            .map(|(key, value)| Ok((key, Self::from_serde_json(value)?)))
-            .collect::<Result<_, _>>()?;
-        Ok(Self {
+        Self {
             n_lines: nodes.par_values().map(|node| node.n_lines).sum::<usize>() + 2,
             n_bytes: nodes
                 .par_iter()
@@ -328,14 +1321,20 @@ impl Node {
                 + nodes.len()
                 + nodes.len().saturating_sub(1)
                 + 3,
-            data: Kind::Object(nodes),
-        })
+            n_indents: nodes.par_values().map(Self::nested_indents).sum::<usize>(),
+            data: Data::Parsed(Kind::Object(nodes)),
+        }
     }
 
     fn indented_n_bytes(&self) -> usize {
         self.n_bytes + INDENT * self.n_lines
     }
 
+    /// Indentation levels of the node once nested one level deeper.
+    fn nested_indents(&self) -> usize {
+        self.n_indents + self.n_lines
+    }
+
     fn metas_inner<T: Deref<Target = str>>(
         &self,
         mut selector: Selector<'_, T>,
@@ -345,7 +1344,7 @@ impl Node {
 
         if let Some(next_key) = selector.next() {
             let missing_key = || IndexingError::MissingKey(next_key.to_string());
-            let next_node = match &self.data {
+            let next_node = match self.data.kind() {
                 Kind::Array(nodes) => {
                     let index = next_key.parse::<usize>().map_err(|_| missing_key())?;
                     nodes.get(index).ok_or_else(missing_key)?
@@ -368,7 +1367,7 @@ impl Node {
     ) -> Result<&Self, IndexingError> {
         if let Some(next_key) = selector.next() {
             let missing_key = || IndexingError::MissingKey(next_key.to_string());
-            let next_node = match &self.data {
+            let next_node = match self.data.kind() {
                 Kind::Array(nodes) => {
                     let index = next_key.parse::<usize>().map_err(|_| missing_key())?;
                     nodes.get(index).ok_or_else(missing_key)?
@@ -392,12 +1391,16 @@ impl Node {
     ) -> Result<Option<Self>, MutationError> {
         if let Some(next_key) = selector.next() {
             let missing_key = || IndexingError::MissingKey(next_key.to_string());
-            let next_node = match &mut self.data {
+            let next_node = match self.data.kind_mut() {
                 Kind::Array(nodes) => {
                     let index = next_key.parse::<usize>().map_err(|_| missing_key())?;
-                    nodes.get_mut(index).ok_or_else(missing_key)?
+                    Arc::make_mut(nodes)
+                        .get_mut(index)
+                        .ok_or_else(missing_key)?
                 }
-                Kind::Object(index_map) => index_map.get_mut(next_key).ok_or_else(missing_key)?,
+                Kind::Object(index_map) => Arc::make_mut(index_map)
+                    .get_mut(next_key)
+                    .ok_or_else(missing_key)?,
                 Kind::Null | Kind::Bool(_) | Kind::Number(_) | Kind::String(_) => {
                     return Err(IndexingError::NotIndexable.into());
                 }
@@ -405,10 +1408,12 @@ impl Node {
 
             let old_n_lines = next_node.n_lines;
             let old_n_bytes = next_node.indented_n_bytes();
+            let old_n_indents = next_node.nested_indents();
             let old_node = next_node.mutate(selector, mutation)?;
 
             self.n_lines = self.n_lines - old_n_lines + next_node.n_lines;
             self.n_bytes = self.n_bytes - old_n_bytes + next_node.indented_n_bytes();
+            self.n_indents = self.n_indents - old_n_indents + next_node.nested_indents();
 
             Ok(old_node)
         } else {
@@ -421,19 +1426,30 @@ impl Node {
                     after,
                     key: AddNodeKey::Array,
                     node,
-                } => match &mut self.data {
+                } => match self.data.kind_mut() {
                     Kind::Array(child) => {
-                        let index = after
-                            .parse::<usize>()
-                            .map_err(|_| IndexingError::MissingKey(after.to_string()))?;
+                        let child = Arc::make_mut(child);
+                        let index = match after {
+                            Some(after) => {
+                                after
+                                    .parse::<usize>()
+                                    .ok()
+                                    .filter(|&index| index < child.len())
+                                    .ok_or_else(|| IndexingError::MissingKey(after.to_string()))?
+                                    + 1
+                            }
+                            None => 0,
+                        };
                         if child.is_empty() {
                             self.n_lines = 2 + node.n_lines;
-                            self.n_bytes = 3 + node.indented_n_bytes();
+                            self.n_bytes = 4 + node.indented_n_bytes();
+                            self.n_indents = node.nested_indents();
                         } else {
                             self.n_lines += node.n_lines;
                             self.n_bytes += node.indented_n_bytes() + 2;
+                            self.n_indents += node.nested_indents();
                         }
-                        child.insert(index + 1, node);
+                        child.insert(index, node);
                         Ok(None)
                     }
                     Kind::Object(_)
@@ -446,22 +1462,31 @@ impl Node {
                     after,
                     key: AddNodeKey::Object(new_key),
                     node,
-                } => match &mut self.data {
+                } => match self.data.kind_mut() {
                     Kind::Object(index_map) => {
+                        let index_map = Arc::make_mut(index_map);
                         if index_map.contains_key(&new_key) {
                             return Err(MutationError::DuplicateKey);
                         }
-                        let Some(index) = index_map.get_index_of(after) else {
-                            return Err(IndexingError::MissingKey(after.to_string()).into());
+                        let index = match after {
+                            Some(after) => {
+                                index_map
+                                    .get_index_of(after)
+                                    .ok_or_else(|| IndexingError::MissingKey(after.to_string()))?
+                                    + 1
+                            }
+                            None => 0,
                         };
                         if index_map.is_empty() {
                             self.n_lines = 2 + node.n_lines;
-                            self.n_bytes = 7 + new_key.len() + node.indented_n_bytes();
+                            self.n_bytes = 8 + new_key.len() + node.indented_n_bytes();
+                            self.n_indents = node.nested_indents();
                         } else {
                             self.n_lines += node.n_lines;
                             self.n_bytes += node.indented_n_bytes() + new_key.len() + 6;
+                            self.n_indents += node.nested_indents();
                         }
-                        index_map.insert_before(index + 1, new_key, node);
+                        index_map.insert_before(index, new_key, node);
                         Ok(None)
                     }
                     Kind::Array(_)
@@ -470,8 +1495,9 @@ impl Node {
                     | Kind::Number(_)
                     | Kind::String(_) => Err(IndexingError::NotIndexable.into()),
                 },
-                NodeMutation::Delete(key) => match &mut self.data {
+                NodeMutation::Delete(key) => match self.data.kind_mut() {
                     Kind::Array(child) => {
+                        let child = Arc::make_mut(child);
                         let index = key
                             .parse::<usize>()
                             .map_err(|_| IndexingError::MissingKey(key.to_string()))?;
@@ -479,22 +1505,27 @@ impl Node {
                         if child.is_empty() {
                             self.n_lines = 1;
                             self.n_bytes = 2;
+                            self.n_indents = 0;
                         } else {
                             self.n_lines -= deleted_node.n_lines;
                             self.n_bytes -= deleted_node.indented_n_bytes() + 2;
+                            self.n_indents -= deleted_node.nested_indents();
                         }
                         Ok(Some(deleted_node))
                     }
                     Kind::Object(index_map) => {
+                        let index_map = Arc::make_mut(index_map);
                         let deleted_node = index_map
                             .shift_remove(key)
                             .ok_or_else(|| IndexingError::MissingKey(key.to_string()))?;
                         if index_map.is_empty() {
                             self.n_lines = 1;
                             self.n_bytes = 2;
+                            self.n_indents = 0;
                         } else {
                             self.n_lines -= deleted_node.n_lines;
                             self.n_bytes -= deleted_node.indented_n_bytes() + key.len() + 6;
+                            self.n_indents -= deleted_node.nested_indents();
                         }
                         Ok(Some(deleted_node))
                     }
@@ -502,9 +1533,10 @@ impl Node {
                         Err(IndexingError::NotIndexable.into())
                     }
                 },
-                NodeMutation::Rename { before, after } => match &mut self.data {
+                NodeMutation::Rename { before, after } => match self.data.kind_mut() {
                     Kind::Array(_) => Err(MutationError::NotRenameable),
                     Kind::Object(index_map) => {
+                        let index_map = Arc::make_mut(index_map);
                         if index_map.contains_key(&after) {
                             return Err(MutationError::DuplicateKey);
                         };
@@ -520,18 +1552,233 @@ impl Node {
                         Err(IndexingError::NotIndexable.into())
                     }
                 },
+                // Sizes only depend on the set of children, so they stay as they are.
+                NodeMutation::Sort(order) => match self.data.kind_mut() {
+                    Kind::Array(child) => {
+                        let child = Arc::make_mut(child);
+                        let mut old = std::mem::take(child)
+                            .into_iter()
+                            .map(Some)
+                            .collect::<Vec<_>>();
+                        *child = order
+                            .iter()
+                            .map(|&index| old[index].take().expect("order is a permutation"))
+                            .collect();
+                        Ok(None)
+                    }
+                    Kind::Object(index_map) => {
+                        let index_map = Arc::make_mut(index_map);
+                        let mut old = std::mem::take(index_map)
+                            .into_iter()
+                            .map(Some)
+                            .collect::<Vec<_>>();
+                        *index_map = order
+                            .iter()
+                            .map(|&index| old[index].take().expect("order is a permutation"))
+                            .collect();
+                        Ok(None)
+                    }
+                    Kind::Null | Kind::Bool(_) | Kind::Number(_) | Kind::String(_) => {
+                        Err(IndexingError::NotIndexable.into())
+                    }
+                },
+                NodeMutation::Swap(a, b) => match self.data.kind_mut() {
+                    Kind::Array(child) => {
+                        let child = Arc::make_mut(child);
+                        let position = |key: &str| {
+                            key.parse::<usize>()
+                                .ok()
+                                .filter(|&index| index < child.len())
+                                .ok_or_else(|| IndexingError::MissingKey(key.to_string()))
+                        };
+                        let (a, b) = (position(a)?, position(b)?);
+                        child.swap(a, b);
+                        Ok(None)
+                    }
+                    Kind::Object(index_map) => {
+                        let index_map = Arc::make_mut(index_map);
+                        let position = |key: &str| {
+                            index_map
+                                .get_index_of(key)
+                                .ok_or_else(|| IndexingError::MissingKey(key.to_string()))
+                        };
+                        let (a, b) = (position(a)?, position(b)?);
+                        index_map.swap_indices(a, b);
+                        Ok(None)
+                    }
+                    Kind::Null | Kind::Bool(_) | Kind::Number(_) | Kind::String(_) => {
+                        Err(IndexingError::NotIndexable.into())
+                    }
+                },
+            }
+        }
+    }
+
+    fn set_inner<T: Deref<Target = str>>(
+        &mut self,
+        mut selector: Selector<'_, T>,
+        mut node: Node,
+        create_parents: bool,
+    ) -> Result<Option<Self>, MutationError> {
+        let Some(next_key) = selector.next() else {
+            std::mem::swap(self, &mut node);
+            return Ok(Some(node));
+        };
+        let following_key = selector.peek();
+        let can_create = following_key.is_none() || create_parents;
+        let new_child = || match following_key {
+            None => Self::null(),
+            Some(key) if key.parse::<usize>().is_ok() => Self::from_array(Vec::new()),
+            Some(_) => Self::from_object(IndexMap::new()),
+        };
+
+        let missing_key = || IndexingError::MissingKey(next_key.to_string());
+        let next_node = match self.data.kind_mut() {
+            Kind::Array(nodes) => {
+                let nodes = Arc::make_mut(nodes);
+                let index = next_key.parse::<usize>().map_err(|_| missing_key())?;
+                if index >= nodes.len() {
+                    if !can_create || (index > nodes.len() && !create_parents) {
+                        return Err(missing_key().into());
+                    }
+                    nodes.resize_with(index, Self::null);
+                    nodes.push(new_child());
+                }
+                &mut nodes[index]
+            }
+            Kind::Object(index_map) => {
+                let index_map = Arc::make_mut(index_map);
+                if !index_map.contains_key(next_key) {
+                    if !can_create {
+                        return Err(missing_key().into());
+                    }
+                    index_map.insert(next_key.to_string(), new_child());
+                }
+                index_map.get_mut(next_key).expect("key was just ensured")
+            }
+            Kind::Null | Kind::Bool(_) | Kind::Number(_) | Kind::String(_) => {
+                return Err(IndexingError::NotIndexable.into());
+            }
+        };
+
+        let res = next_node.set_inner(selector, node, create_parents);
+        self.refresh_meta();
+        res
+    }
+
+    /// Recomputes the sizes of the whole subtree in parallel, for after mutations that skipped
+    /// keeping them up to date.
+    pub fn restat(self) -> Self {
+        match self.data {
+            Data::Parsed(Kind::Array(nodes)) => Self::from_array(
+                Arc::unwrap_or_clone(nodes)
+                    .into_par_iter()
+                    .map(Self::restat)
+                    .collect(),
+            ),
+            Data::Parsed(Kind::Object(index_map)) => Self::from_object(
+                Arc::unwrap_or_clone(index_map)
+                    .into_par_iter()
+                    .map(|(key, node)| (key, node.restat()))
+                    .collect(),
+            ),
+            data => Self { data, ..self },
+        }
+    }
+
+    /// Shrinks the capacity of every container and string down to its length, returning roughly
+    /// how many bytes that freed. Containers shared with another document are copied, as copies
+    /// hold no spare capacity, but only when something below them has some.
+    pub fn compact(&mut self) -> usize {
+        if !self.has_spare_capacity() {
+            return 0;
+        }
+        let Data::Parsed(kind) = &mut self.data else {
+            return 0;
+        };
+        match kind {
+            Kind::String(value) => {
+                let capacity = value.capacity();
+                value.shrink_to_fit();
+                capacity - value.capacity()
+            }
+            Kind::Array(nodes) => {
+                let capacity = nodes.capacity();
+                let nodes = Arc::make_mut(nodes);
+                nodes.shrink_to_fit();
+                (capacity - nodes.capacity()) * size_of::<Node>()
+                    + nodes.par_iter_mut().map(Node::compact).sum::<usize>()
+            }
+            Kind::Object(index_map) => {
+                let capacity = index_map.capacity();
+                let index_map = Arc::make_mut(index_map);
+                index_map.shrink_to_fit();
+                (capacity - index_map.capacity()) * size_of::<(String, Node)>()
+                    + index_map.par_values_mut().map(Node::compact).sum::<usize>()
+            }
+            Kind::Null | Kind::Bool(_) | Kind::Number(_) => 0,
+        }
+    }
+
+    /// Whether [`Node::compact`] would free anything.
+    fn has_spare_capacity(&self) -> bool {
+        // Unread parts hold no spare capacity, and are not worth parsing for it.
+        let Data::Parsed(kind) = &self.data else {
+            return false;
+        };
+        match kind {
+            Kind::String(value) => value.capacity() > value.len(),
+            Kind::Array(nodes) => {
+                nodes.capacity() > nodes.len() || nodes.par_iter().any(Node::has_spare_capacity)
+            }
+            Kind::Object(index_map) => {
+                index_map.capacity() > index_map.len()
+                    || index_map.par_values().any(Node::has_spare_capacity)
             }
+            Kind::Null | Kind::Bool(_) | Kind::Number(_) => false,
         }
     }
 
-    fn from_serde_json(value: serde_json::Value) -> Result<Self, DeserializationError> {
-        let res = match value {
-            serde_json::Value::Null => Self::null(),
-            serde_json::Value::Bool(value) => Self::bool(value),
-            serde_json::Value::Number(number) => Self::number(number)?,
-            serde_json::Value::String(value) => Self::string(value),
-            serde_json::Value::Array(values) => Self::array(values)?,
-            serde_json::Value::Object(map) => Self::object(map.into_iter().collect())?,
+    /// Recomputes the size of a container from its children.
+    fn refresh_meta(&mut self) {
+        let data = std::mem::replace(&mut self.data, Data::Parsed(Kind::Null));
+        *self = match data {
+            Data::Parsed(Kind::Array(nodes)) => Self::array_of(nodes),
+            Data::Parsed(Kind::Object(index_map)) => Self::object_of(index_map),
+            data => Self {
+                n_lines: self.n_lines,
+                n_bytes: self.n_bytes,
+                n_indents: self.n_indents,
+                data,
+            },
+        };
+    }
+
+    fn from_sonic(value: &sonic_rs::Value) -> Result<Self, DeserializationError> {
+        let res = match value.get_type() {
+            JsonType::Null => Self::null(),
+            JsonType::Boolean => Self::bool(value.is_true()),
+            JsonType::Number => match value.as_raw_number() {
+                Some(number) => Self::number_literal(number.as_str())?,
+                None => Self::number_literal(&value.to_string())?,
+            },
+            JsonType::String => Self::string(value.as_str().unwrap_or_default().to_string()),
+            JsonType::Array => {
+                let values = value.as_array().map_or(&[][..], |array| array.as_slice());
+                let nodes: Vec<Self> = values
+                    .par_iter()
+                    .map(Self::from_sonic)
+                    .collect::<Result<_, _>>()?;
+                Self::from_array(nodes)
+            }
+            JsonType::Object => {
+                let entries: Vec<_> = value.as_object().into_iter().flatten().collect();
+                let nodes: IndexMap<String, Self> = entries
+                    .into_par_iter()
+                    .map(|(key, value)| Ok((key.to_string(), Self::from_sonic(value)?)))
+                    .collect::<Result<_, _>>()?;
+                Self::from_object(nodes)
+            }
         };
         Ok(res)
     }
@@ -542,7 +1789,14 @@ impl Serialize for Node {
     where
         S: serde::Serializer,
     {
-        self.data.serialize(serializer)
+        match &self.data {
+            Data::Parsed(kind) => kind.serialize(serializer),
+            // Parses unread containers only for as long as they are written.
+            Data::Lazy(lazy) => match lazy.parsed.get() {
+                Some(node) => node.serialize(serializer),
+                None => lazy.parse().serialize(serializer),
+            },
+        }
     }
 }
 
@@ -567,9 +1821,76 @@ impl Serialize for Number {
     where
         S: serde::Serializer,
     {
-        match self {
-            Number::Int(value) => value.serialize(serializer),
-            Number::Float(value) => value.serialize(serializer),
+        if let Some(literal) = &self.literal {
+            return RawToken(literal).serialize(serializer);
+        }
+        match self.value {
+            NumberValue::Int(value) => value.serialize(serializer),
+            NumberValue::UInt(value) => value.serialize(serializer),
+            NumberValue::Float(value) if !value.is_finite() => {
+                RawToken(non_finite_token(value)).serialize(serializer)
+            }
+            NumberValue::Float(value) => value.serialize(serializer),
+        }
+    }
+}
+
+/// Written to the output verbatim, bypassing JSON validation.
+struct RawToken<'a>(&'a str);
+
+impl Serialize for RawToken<'_> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        // sonic_rs emits the field of this private struct as is, the same way it writes
+        // `sonic_rs::RawNumber`.
+        const TOKEN: &str = "$sonic_rs::private::JsonNumber";
+        let mut s = serializer.serialize_struct(TOKEN, 1)?;
+        s.serialize_field(TOKEN, self.0)?;
+        s.end()
+    }
+}
+
+type KeyedChildren<'a> = Box<dyn Iterator<Item = (String, &'a Node)> + 'a>;
+
+/// Depth-first walk returned by [`Node::leaves`].
+pub struct Leaves<'a> {
+    /// The node the walk starts from, until it is visited.
+    root: Option<&'a Node>,
+    /// Remaining children of each container on the way to the current node.
+    levels: Vec<KeyedChildren<'a>>,
+    path: Vec<String>,
+}
+
+impl<'a> Iterator for Leaves<'a> {
+    type Item = (Vec<String>, &'a Node);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut node = match self.root.take() {
+            Some(root) => root,
+            None => loop {
+                let level = self.levels.last_mut()?;
+                match level.next() {
+                    Some((key, child)) => {
+                        self.path.truncate(self.levels.len() - 1);
+                        self.path.push(key);
+                        break child;
+                    }
+                    None => {
+                        self.levels.pop();
+                    }
+                }
+            },
+        };
+        loop {
+            let mut children = node.keyed_children();
+            let Some((key, child)) = children.next() else {
+                return Some((self.path.clone(), node));
+            };
+            self.levels.push(children);
+            self.path.push(key);
+            node = child;
         }
     }
 }
@@ -595,9 +1916,14 @@ const RAW_JSON: &str = r#"{
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::fixtures::SAMPLE_JSON;
     use serde_json::json;
 
     impl Node {
+        fn from_serde_json(value: serde_json::Value) -> Result<Self, LoadError> {
+            Self::load(value.to_string().as_bytes())
+        }
+
         fn assert_meta(&self) {
             assert_eq!(
                 self.to_string_pretty()
@@ -608,11 +1934,17 @@ mod test {
                 self.n_lines
             );
             assert_eq!(self.to_string_pretty().unwrap().len(), self.n_bytes);
+            for indent in [Indent::Spaces(4), Indent::Tab] {
+                assert_eq!(
+                    self.to_string_indented(indent).unwrap().len(),
+                    self.as_meta().n_bytes_with(indent)
+                );
+            }
         }
 
         fn assert_all_meta(&self) {
             self.assert_meta();
-            match &self.data {
+            match self.data.kind() {
                 Kind::Array(nodes) => nodes.iter().for_each(Self::assert_meta),
                 Kind::Object(index_map) => index_map.values().for_each(Self::assert_meta),
                 Kind::Null | Kind::Bool(_) | Kind::Number(_) | Kind::String(_) => {}
@@ -673,7 +2005,7 @@ mod test {
         let node = Node::from_serde_json(json_value.clone()).unwrap();
         node.assert_all_meta();
 
-        let Kind::Object(fields) = node.data else {
+        let Kind::Object(fields) = node.data.kind() else {
             unreachable!()
         };
 
@@ -716,6 +2048,7 @@ mod test {
                 meta: NodeMeta {
                     n_lines: 16,
                     n_bytes: 199,
+                    n_indents: 18,
                     kind: NodeKind::Object,
                 },
                 kind: IndexKind::Object(vec![
@@ -737,6 +2070,7 @@ mod test {
                 meta: NodeMeta {
                     n_lines: 5,
                     n_bytes: 19,
+                    n_indents: 3,
                     kind: NodeKind::Array,
                 },
                 kind: IndexKind::Array(3)
@@ -748,6 +2082,7 @@ mod test {
                 meta: NodeMeta {
                     n_lines: 1,
                     n_bytes: 1,
+                    n_indents: 0,
                     kind: NodeKind::Terminal,
                 },
                 kind: IndexKind::Terminal
@@ -759,6 +2094,7 @@ mod test {
                 meta: NodeMeta {
                     n_lines: 3,
                     n_bytes: 20,
+                    n_indents: 1,
                     kind: NodeKind::Object,
                 },
                 kind: IndexKind::Object(vec![String::from("key")])
@@ -770,6 +2106,7 @@ mod test {
                 meta: NodeMeta {
                     n_lines: 1,
                     n_bytes: 7,
+                    n_indents: 0,
                     kind: NodeKind::Terminal,
                 },
                 kind: IndexKind::Terminal
@@ -782,6 +2119,7 @@ mod test {
                 meta: NodeMeta {
                     n_lines: 1,
                     n_bytes: 3,
+                    n_indents: 0,
                     kind: NodeKind::Terminal,
                 },
                 kind: IndexKind::Terminal
@@ -797,6 +2135,107 @@ mod test {
         );
     }
 
+    #[test]
+    fn largest_subtrees_test() {
+        let node = Node::load(RAW_JSON.as_bytes()).unwrap();
+        let largest = node.largest_subtrees(3).into_iter().collect::<Vec<_>>();
+        assert_eq!(
+            largest,
+            vec![
+                (vec![String::from("nested_object")], 20),
+                (vec![String::from("array")], 19),
+                (vec![String::from("string")], 11),
+            ]
+        );
+    }
+
+    #[test]
+    fn resolve_pointer_test() {
+        let node = Node::load(r#"{"a": [{"b/c": 1}]}"#.as_bytes()).unwrap();
+        let selector = vec![String::from("a"), String::from("0"), String::from("b/c")];
+        assert_eq!(node.resolve_pointer("/a/0/b~1c"), Ok(selector.clone()));
+        assert_eq!(node.resolve_pointer("$.a[0]['b/c']"), Ok(selector));
+        assert_eq!(node.resolve_pointer(""), Ok(vec![]));
+        assert_eq!(
+            node.resolve_pointer("/a/1"),
+            Err(PointerError::Indexing(IndexingError::MissingKey(
+                String::from("1")
+            )))
+        );
+        assert_eq!(
+            node.resolve_pointer("a"),
+            Err(PointerError::InvalidPointer(String::from("a")))
+        );
+        assert_eq!(
+            node.resolve_pointer("$.a[*]"),
+            Err(PointerError::InvalidPath(String::from("$.a[*]")))
+        );
+    }
+
+    #[test]
+    fn search_test() {
+        let node =
+            Node::load(r#"{"Name": "a", "list": [{"name": "b"}, "names"], "x": 10}"#.as_bytes())
+                .unwrap();
+        let selectors = |query, values| {
+            node.search(query, values)
+                .into_iter()
+                .map(|selector| selector.join("/"))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(selectors("name", false), ["Name", "list/0/name"]);
+        assert_eq!(selectors("name", true), ["Name", "list/0/name", "list/1"]);
+        assert_eq!(selectors("1", true), ["x"]);
+        assert!(selectors("", true).is_empty());
+    }
+
+    #[test]
+    fn leaves_test() {
+        let node =
+            Node::load(r#"{"a": [1, {"b": null}, []], "c": {}, "d": "x"}"#.as_bytes()).unwrap();
+        let leaves = node
+            .leaves()
+            .map(|(selector, leaf)| {
+                let value = leaf.to_string_compact().unwrap();
+                format!("{} = {value}", selector.join("/"))
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            leaves,
+            ["a/0 = 1", "a/1/b = null", "a/2 = []", "c = {}", "d = \"x\""]
+        );
+
+        let node = Node::load("1".as_bytes()).unwrap();
+        assert_eq!(node.leaves().count(), 1);
+    }
+
+    #[test]
+    fn is_same_test() {
+        let mut node = Node::load(r#"{"a": {"b": 1}, "c": 2}"#.as_bytes()).unwrap();
+        let clone = node.clone();
+        assert!(node.is_same(&clone));
+        node.set(&["a", "b"], Node::null(), false).unwrap();
+        assert!(!node.is_same(&clone));
+        assert!(node.child("c").unwrap().is_same(clone.child("c").unwrap()));
+
+        // Unread containers compare without being parsed.
+        let options = ParseOptions {
+            lazy_size: Some(0),
+            ..Default::default()
+        };
+        let input = format!(r#"{{"a": {{"b": {:?}, "c": 1}}}}"#, vec![0; 2048]);
+        let mut node = Node::load_with(input.as_bytes(), &options).unwrap();
+        let clone = node.clone();
+        node.delete(&["a", "c"]).unwrap();
+        let b = node.subtree(&["a", "b"]).unwrap();
+        assert!(b.is_same(clone.subtree(&["a", "b"]).unwrap()));
+        assert!(b.same_value(clone.subtree(&["a", "b"]).unwrap()));
+        assert!(matches!(&b.data, Data::Lazy(lazy) if lazy.parsed.get().is_none()));
+        let other = Node::load_with(input.as_bytes(), &options).unwrap();
+        assert!(!b.is_same(other.subtree(&["a", "b"]).unwrap()));
+        assert!(b.same_value(other.subtree(&["a", "b"]).unwrap()));
+    }
+
     #[test]
     fn replace_test() {
         let original = json!({
@@ -1131,4 +2570,460 @@ mod test {
 
         node.assert_all_meta();
     }
+
+    #[test]
+    fn swap_test() {
+        let mut node = Node::from_serde_json(json!({
+            "array": [1, [2, 3], 4],
+            "object": {"a": 1, "b": {"c": 2}, "d": 3}
+        }))
+        .unwrap();
+
+        node.swap(&["array", "0"], "1").unwrap();
+        node.swap(&["object", "b"], "a").unwrap();
+        node.swap(&["object", "b"], "d").unwrap();
+        assert_eq!(
+            node.to_string_compact().unwrap(),
+            r#"{"array":[[2,3],1,4],"object":{"d":3,"a":1,"b":{"c":2}}}"#
+        );
+        node.assert_all_meta();
+
+        assert!(matches!(
+            node.swap(&["array", "0"], "3"),
+            Err(MutationError::Indexing(IndexingError::MissingKey(_)))
+        ));
+    }
+
+    #[test]
+    fn sorted_keys_test() {
+        let node =
+            Node::load(r#"{"b": [{"d": 1, "c": 2}], "a": {"z": null, "y": [3, 1]}}"#.as_bytes())
+                .unwrap();
+        let sorted = node.sorted_keys();
+        assert_eq!(
+            sorted.to_string_compact().unwrap(),
+            r#"{"a":{"y":[3,1],"z":null},"b":[{"c":2,"d":1}]}"#
+        );
+        assert_eq!(sorted.as_meta().n_bytes, node.as_meta().n_bytes);
+    }
+
+    #[test]
+    fn sort_test() {
+        let mut node = Node::from_serde_json(json!({
+            "object": {"b10": 1, "a": {"x": 2}, "b2": 3},
+            "array": ["b", 10, null, 2.5, true, "a"],
+            "nested": [[1]]
+        }))
+        .unwrap();
+
+        assert_eq!(
+            node.sort(&["object"], SortSpec::default()).unwrap(),
+            [1, 0, 2]
+        );
+        node.sort(
+            &["array"],
+            SortSpec {
+                natural: false,
+                descending: true,
+            },
+        )
+        .unwrap();
+        assert_eq!(
+            node.to_string_compact().unwrap(),
+            r#"{"object":{"a":{"x":2},"b10":1,"b2":3},"array":["b","a",10,2.5,true,null],"nested":[[1]]}"#
+        );
+        node.sort(
+            &["object"],
+            SortSpec {
+                natural: true,
+                descending: false,
+            },
+        )
+        .unwrap();
+        assert_eq!(
+            node.subtree(&["object"])
+                .unwrap()
+                .to_string_compact()
+                .unwrap(),
+            r#"{"a":{"x":2},"b2":3,"b10":1}"#
+        );
+        node.assert_all_meta();
+
+        assert!(matches!(
+            node.sort(&["nested"], SortSpec::default()),
+            Err(MutationError::NotSortable)
+        ));
+    }
+
+    #[test]
+    fn natural_cmp_test() {
+        assert_eq!(natural_cmp("a2", "a10"), Ordering::Less);
+        assert_eq!(natural_cmp("a02", "a2b"), Ordering::Less);
+        assert_eq!(natural_cmp("10", "9"), Ordering::Greater);
+        assert_eq!(natural_cmp("b1", "a2"), Ordering::Greater);
+        assert_eq!(natural_cmp("x1y", "x1y"), Ordering::Equal);
+    }
+
+    #[test]
+    fn convert_test() {
+        let convert = |json: &str, to| {
+            Node::load(json.as_bytes())
+                .unwrap()
+                .convert(to)
+                .map(|node| node.to_string_compact().unwrap())
+        };
+
+        assert_eq!(convert("42", ScalarType::String).unwrap(), r#""42""#);
+        assert_eq!(convert("null", ScalarType::String).unwrap(), r#""null""#);
+        assert_eq!(convert(r#"" 4.5 ""#, ScalarType::Number).unwrap(), "4.5");
+        assert_eq!(convert("true", ScalarType::Number).unwrap(), "1");
+        assert_eq!(convert(r#""false""#, ScalarType::Boolean).unwrap(), "false");
+        assert_eq!(convert("2", ScalarType::Boolean).unwrap(), "true");
+        assert_eq!(convert(r#""x""#, ScalarType::Null).unwrap(), "null");
+        assert_eq!(
+            convert(r#""4 2""#, ScalarType::Number),
+            Err(ConversionError::Invalid {
+                value: String::from(r#""4 2""#),
+                to: "number"
+            })
+        );
+        assert_eq!(
+            convert("null", ScalarType::Boolean),
+            Err(ConversionError::Invalid {
+                value: String::from("null"),
+                to: "boolean"
+            })
+        );
+        assert_eq!(
+            convert("[1]", ScalarType::String),
+            Err(ConversionError::NotScalar)
+        );
+
+        let node = Node::load("\"1e3\"".as_bytes())
+            .unwrap()
+            .convert(ScalarType::Number)
+            .unwrap();
+        node.assert_all_meta();
+    }
+
+    #[test]
+    fn embedded_json_test() {
+        let node = Node::load(r#""{\"a\": [1, \"x\"]}""#.as_bytes()).unwrap();
+        assert!(node.looks_like_json());
+        let parsed = node.parse_embedded().unwrap();
+        assert_eq!(parsed.to_string_compact().unwrap(), r#"{"a":[1,"x"]}"#);
+        parsed.assert_all_meta();
+        assert_eq!(
+            parsed.stringify().to_string_compact().unwrap(),
+            r#""{\"a\":[1,\"x\"]}""#
+        );
+
+        assert!(!Node::string(String::from("[x")).looks_like_json());
+        assert!(!parsed.looks_like_json());
+        assert!(matches!(
+            Node::string(String::from("[x]")).parse_embedded(),
+            Err(ConversionError::NotJson(_))
+        ));
+        assert_eq!(parsed.parse_embedded(), Err(ConversionError::NotString));
+    }
+
+    #[test]
+    fn insert_first_test() {
+        let mut node = Node::from_serde_json(json!({
+            "object": {},
+            "array": [1],
+            "empty_array": []
+        }))
+        .unwrap();
+
+        node.insert_first(&["empty_array"], AddNodeKey::Array, Node::null())
+            .unwrap();
+        node.insert_first(
+            &["object"],
+            AddNodeKey::Object(String::from("a")),
+            Node::bool(true),
+        )
+        .unwrap();
+        node.insert_first(
+            &["object"],
+            AddNodeKey::Object(String::from("b")),
+            Node::null(),
+        )
+        .unwrap();
+        node.insert_first(&["array"], AddNodeKey::Array, Node::null())
+            .unwrap();
+        node.insert_first::<&str>(&[], AddNodeKey::Object(String::from("c")), Node::null())
+            .unwrap();
+        assert_eq!(
+            node.insert_first(
+                &["array"],
+                AddNodeKey::Object(String::from("d")),
+                Node::null()
+            )
+            .unwrap_err(),
+            IndexingError::NotIndexable.into()
+        );
+
+        assert_eq!(
+            node,
+            Node::from_serde_json(json!({
+                "c": null,
+                "object": {"b": null, "a": true},
+                "array": [null, 1],
+                "empty_array": [null]
+            }))
+            .unwrap()
+        );
+
+        node.assert_all_meta();
+    }
+
+    #[test]
+    fn set_test() {
+        let mut node = Node::from_serde_json(json!({
+            "object": {"a": 1},
+            "array": [1]
+        }))
+        .unwrap();
+
+        assert_eq!(
+            node.set(&["object", "a"], Node::bool(true), false).unwrap(),
+            Some(Node::from_serde_json(json!(1)).unwrap())
+        );
+        assert_eq!(
+            node.set(&["object", "b"], Node::null(), false).unwrap(),
+            Some(Node::null())
+        );
+        node.set(&["array", "1"], Node::bool(false), false).unwrap();
+        assert_eq!(
+            node.set(&["array", "3"], Node::null(), false).unwrap_err(),
+            IndexingError::MissingKey(String::from("3")).into()
+        );
+        assert_eq!(
+            node.set(&["missing", "a"], Node::null(), false)
+                .unwrap_err(),
+            IndexingError::MissingKey(String::from("missing")).into()
+        );
+        assert_eq!(
+            node.set(&["object", "a", "b"], Node::null(), true)
+                .unwrap_err(),
+            IndexingError::NotIndexable.into()
+        );
+
+        node.set(&["new", "list", "2", "key"], Node::bool(true), true)
+            .unwrap();
+        node.set(&["array", "3"], Node::bool(true), true).unwrap();
+
+        assert_eq!(
+            node,
+            Node::from_serde_json(json!({
+                "object": {"a": true, "b": null},
+                "array": [1, false, null, true],
+                "new": {"list": [null, null, {"key": true}]}
+            }))
+            .unwrap()
+        );
+        node.assert_all_meta();
+    }
+
+    #[test]
+    fn extend_test() {
+        let mut node = Node::from_array(Vec::new());
+        node.extend(vec![Node::null()]).unwrap();
+        node.extend(vec![Node::bool(true), Node::null()]).unwrap();
+        assert_eq!(
+            node,
+            Node::from_serde_json(json!([null, true, null])).unwrap()
+        );
+        node.assert_all_meta();
+
+        assert_eq!(
+            Node::null().extend(Vec::new()).unwrap_err(),
+            IndexingError::NotIndexable.into()
+        );
+    }
+
+    #[test]
+    fn number_kind_test() {
+        let load = |input: &str| Node::load(input.as_bytes()).unwrap();
+        assert_eq!(load("2.0").to_string_compact().unwrap(), "2.0");
+        assert_eq!(
+            load("18446744073709551615").to_string_compact().unwrap(),
+            "18446744073709551615"
+        );
+
+        let float = load("1.5");
+        let int = load("1");
+        let compact = |node: Node| node.to_string_compact().unwrap();
+        assert_eq!(compact(load("2").keep_number_kind(&float)), "2.0");
+        assert_eq!(load("2.0").keep_number_kind(&int), load("2"));
+        assert_eq!(compact(load("2.5").keep_number_kind(&int)), "2.5");
+        assert_eq!(compact(load("1e3").keep_number_kind(&float)), "1e3");
+        assert_eq!(load("\"2\"").keep_number_kind(&int), load("\"2\""));
+        load("2").keep_number_kind(&float).assert_all_meta();
+    }
+
+    #[test]
+    fn number_literal_test() {
+        let input =
+            "[1.50, 1e3, -0, 1E-7, 18446744073709551616, 170141183460469231731687303715884105727]";
+        let lenient = ParseOptions {
+            non_finite: parser::NonFinite::Keep,
+            ..Default::default()
+        };
+        for node in [
+            Node::load(input.as_bytes()).unwrap(),
+            Node::load_with(input.as_bytes(), &lenient).unwrap(),
+        ] {
+            assert_eq!(node.to_string_compact().unwrap(), input.replace(", ", ","));
+            node.assert_all_meta();
+        }
+
+        let load = |input: &str| Node::load(input.as_bytes()).unwrap();
+        assert!(load("1.50").same_value(&load("1.5")));
+        assert!(load("18446744073709551616").same_value(&load("18446744073709551616")));
+        assert!(!load("18446744073709551616").same_value(&load("18446744073709551617")));
+    }
+
+    #[test]
+    fn number_parse_test() {
+        for literal in ["0", "-0", "12", "-1.5", "1e400", "-1E-400", "2.50e+3"] {
+            let number = Number::parse(literal).unwrap();
+            assert_eq!(number.to_string(), literal);
+        }
+        assert_eq!(Number::parse("-7").unwrap().value, NumberValue::Int(-7));
+        assert_eq!(
+            Number::parse("18446744073709551615").unwrap().value,
+            NumberValue::UInt(u64::MAX)
+        );
+        assert_eq!(
+            Number::parse("1e400").unwrap().value,
+            NumberValue::Float(f64::INFINITY)
+        );
+        for literal in [
+            "", "-", "01", "1.", ".5", "+1", "1e", "1e+", "0x1", "1 ", "NaN", "inf",
+        ] {
+            assert!(Number::parse(literal).is_none(), "{literal}");
+        }
+
+        let node = Node::load(b"[1e400, -1e400]".as_slice()).unwrap();
+        assert_eq!(node.to_string_compact().unwrap(), "[1e400,-1e400]");
+        node.assert_all_meta();
+    }
+
+    #[test]
+    fn lazy_load_test() {
+        let records = (0..100)
+            .map(|i| {
+                format!(r#"{{"id": -1, "id": {i}, "name": "n\u00e9{i}", "tags": [1.50, null]}}"#)
+            })
+            .collect::<Vec<_>>()
+            .join(", ");
+        let input = format!(r#"{{"meta": {{"count": 100}}, "data": {{"records": [{records}]}}}}"#);
+        let options = ParseOptions {
+            lazy_size: Some(0),
+            ..Default::default()
+        };
+        let eager = Node::load(input.as_bytes()).unwrap();
+        let lazy = Node::load_with(input.as_bytes(), &options).unwrap();
+
+        let selector = ["data", "records"];
+        let records = lazy.subtree(&selector).unwrap();
+        assert!(matches!(&records.data, Data::Lazy(lazy) if lazy.parsed.get().is_none()));
+        assert_eq!(
+            records.as_meta(),
+            eager.subtree(&selector).unwrap().as_meta()
+        );
+        assert_eq!(lazy.as_meta(), eager.as_meta());
+        assert_eq!(
+            lazy.to_string_pretty().unwrap(),
+            eager.to_string_pretty().unwrap()
+        );
+        assert!(matches!(&records.data, Data::Lazy(lazy) if lazy.parsed.get().is_none()));
+        assert_eq!(lazy, eager);
+        lazy.assert_all_meta();
+
+        let mut lazy = Node::load_with(input.as_bytes(), &options).unwrap();
+        let mut eager = eager;
+        let selector = ["data", "records", "7"];
+        assert_eq!(
+            lazy.delete(&selector).unwrap(),
+            eager.delete(&selector).unwrap()
+        );
+        assert_eq!(lazy.as_meta(), eager.as_meta());
+        assert!(matches!(
+            &lazy.subtree(&["data", "records"]).unwrap().data,
+            Data::Parsed(_)
+        ));
+
+        let broken = input.replace("[1.50, null]}]", "[1.50, null],]");
+        assert!(Node::load_with(broken.as_bytes(), &options).is_err());
+    }
+
+    #[test]
+    fn shared_clone_test() {
+        let mut node = Node::load(r#"{"a": [1, 2], "b": {"c": 1}}"#.as_bytes()).unwrap();
+        let mut snapshot = node.clone();
+        let shared = |a: &Node, b: &Node, key: &str| match (
+            a.subtree(&[key]).unwrap().data.kind(),
+            b.subtree(&[key]).unwrap().data.kind(),
+        ) {
+            (Kind::Array(a), Kind::Array(b)) => Arc::ptr_eq(a, b),
+            (Kind::Object(a), Kind::Object(b)) => Arc::ptr_eq(a, b),
+            _ => false,
+        };
+        assert!(shared(&node, &snapshot, "a"));
+
+        node.set(&["a", "0"], Node::null(), false).unwrap();
+        node.delete(&["b", "c"]).unwrap();
+        assert!(!shared(&node, &snapshot, "a"));
+        assert_eq!(
+            node.to_string_compact().unwrap(),
+            r#"{"a":[null,2],"b":{}}"#
+        );
+        assert_eq!(
+            snapshot.to_string_compact().unwrap(),
+            r#"{"a":[1,2],"b":{"c":1}}"#
+        );
+
+        // Compacting leaves containers with nothing to reclaim shared.
+        snapshot.compact();
+        let mut node = snapshot.clone();
+        assert_eq!(node.compact(), 0);
+        node.set(&["b", "d"], Node::null(), false).unwrap();
+        assert!(shared(&node, &snapshot, "a"));
+    }
+
+    #[test]
+    fn compact_test() {
+        let mut node =
+            Node::load(r#"{"a": [1, 2, 3, 4], "b": {"c": 1, "d": 2}}"#.as_bytes()).unwrap();
+        node.delete(&["a", "3"]).unwrap();
+        node.delete(&["b", "d"]).unwrap();
+        let expected = Node::load(node.to_string_compact().unwrap().as_bytes()).unwrap();
+        let shared = node.clone();
+
+        assert!(node.compact() > 0);
+        assert_eq!(node.compact(), 0);
+        assert_eq!(node, expected);
+        // The containers shared with the clone were copied rather than shrunk in place.
+        assert!(shared.has_spare_capacity());
+        assert_eq!(shared, expected);
+    }
+
+    #[test]
+    fn restat_test() {
+        let node = Node::load(SAMPLE_JSON.as_bytes()).unwrap();
+        let mut stale = node.clone();
+        stale.n_bytes = 0;
+        if let Kind::Object(index_map) = stale.data.kind_mut() {
+            Arc::make_mut(index_map)
+                .values_mut()
+                .for_each(|child| child.n_lines = 0);
+        }
+
+        let restated = stale.restat();
+        restated.assert_all_meta();
+        assert_eq!(restated, node);
+    }
 }
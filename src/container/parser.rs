@@ -0,0 +1,639 @@
+use std::sync::Arc;
+
+use indexmap::IndexMap;
+use serde::Deserialize;
+
+use super::{
+    node::{ContainerSize, Node, NodeKind, NodeMeta},
+    pointer::to_pointer,
+};
+use crate::error::LoadError;
+
+const MAX_DEPTH: usize = 1024;
+/// Levels of containers built on a lazy load, and whenever a lazy container is read.
+const LAZY_DEPTH: usize = 2;
+/// Smallest container left unparsed on a lazy load, as smaller ones cost less built.
+const LAZY_MIN_BYTES: usize = 4096;
+
+/// How `NaN`, `Infinity` and `-Infinity` literals are handled on load.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+#[cfg_attr(test, derive(serde::Serialize))]
+#[serde(rename_all = "lowercase")]
+pub enum NonFinite {
+    /// Reject the document, as strict JSON does.
+    #[default]
+    Error,
+    /// Load them as `null`.
+    Null,
+    /// Keep them as numbers and write them back verbatim on save.
+    Keep,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+#[cfg_attr(test, derive(serde::Serialize))]
+#[serde(rename_all = "lowercase")]
+pub enum DuplicateKeys {
+    /// Keep the last value silently, as strict JSON parsers do.
+    #[default]
+    Last,
+    /// Keep the last value, listing the keys in [`Repairs::duplicate_keys`].
+    Report,
+    /// Keep every value, renaming repeats to `key__2`, `key__3` and so on.
+    Rename,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ParseOptions {
+    pub non_finite: NonFinite,
+    /// Replace invalid UTF-8 in strings with U+FFFD instead of rejecting the document.
+    pub lossy_utf8: bool,
+    pub duplicate_keys: DuplicateKeys,
+    /// Inputs at least this large are loaded lazily, see [`parse_lazy`].
+    pub lazy_size: Option<usize>,
+}
+
+/// What loading changed in the document, as JSON Pointers.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Repairs {
+    /// Strings and keys whose invalid UTF-8 was replaced.
+    pub utf8: Vec<String>,
+    /// Keys repeated within their object, or their new names with [`DuplicateKeys::Rename`].
+    pub duplicate_keys: Vec<String>,
+}
+
+impl Repairs {
+    pub fn is_empty(&self) -> bool {
+        self.utf8.is_empty() && self.duplicate_keys.is_empty()
+    }
+}
+
+impl ParseOptions {
+    /// Whether these options accept exactly standard JSON, which takes the fast load path.
+    pub fn is_strict(&self) -> bool {
+        Self {
+            lazy_size: None,
+            ..*self
+        } == Self::default()
+    }
+
+    /// Whether to load `input_len` bytes with [`parse_lazy`]. Repairs are only found by parsing
+    /// everything, so asking for them loads eagerly.
+    pub fn is_lazy(&self, input_len: usize) -> bool {
+        self.lazy_size.is_some_and(|size| input_len >= size)
+            && !self.lossy_utf8
+            && self.duplicate_keys == DuplicateKeys::Last
+    }
+}
+
+/// Permissive JSON parser backing the non-strict [`ParseOptions`].
+///
+/// Also returns what had to be repaired to load the document.
+pub fn parse(input: &[u8], options: &ParseOptions) -> Result<(Node, Repairs), LoadError> {
+    let mut parser = Parser::new(input, options, None);
+    parser.skip_whitespace();
+    let node = parser.value(0)?;
+    parser.skip_whitespace();
+    if parser.pos != input.len() {
+        return Err(parser.error("trailing characters"));
+    }
+    Ok((node, parser.repaired))
+}
+
+/// Like [`parse`], only building the top levels of containers. Deeper large containers are
+/// checked and sized, then left unparsed in `source` until they are first read.
+pub fn parse_lazy(source: Arc<[u8]>, options: &ParseOptions) -> Result<Node, LoadError> {
+    let mut parser = Parser::new(&source, options, Some(&source));
+    parser.skip_whitespace();
+    let node = parser.value(0)?;
+    parser.skip_whitespace();
+    if parser.pos != source.len() {
+        return Err(parser.error("trailing characters"));
+    }
+    Ok(node)
+}
+
+/// Parses the lazy container at `start`, which [`parse_lazy`] already checked.
+pub(super) fn parse_at(source: &Arc<[u8]>, start: usize, options: &ParseOptions) -> Node {
+    let mut parser = Parser::new(source, options, Some(source));
+    parser.pos = start;
+    parser.value(0).expect("lazy container was checked on load")
+}
+
+struct Parser<'a> {
+    input: &'a [u8],
+    pos: usize,
+    options: &'a ParseOptions,
+    path: Vec<String>,
+    repaired: Repairs,
+    /// The input shared with lazy containers, when loading lazily.
+    source: Option<&'a Arc<[u8]>>,
+}
+
+impl<'a> Parser<'a> {
+    fn new(input: &'a [u8], options: &'a ParseOptions, source: Option<&'a Arc<[u8]>>) -> Self {
+        Self {
+            input,
+            pos: 0,
+            options,
+            path: Vec::new(),
+            repaired: Repairs::default(),
+            source,
+        }
+    }
+
+    fn value(&mut self, depth: usize) -> Result<Node, LoadError> {
+        if depth > MAX_DEPTH {
+            return Err(self.error("recursion limit exceeded"));
+        }
+
+        if let Some(source) = self.source
+            && depth >= LAZY_DEPTH
+            && matches!(self.peek(), Some(b'{' | b'['))
+        {
+            let start = self.pos;
+            let meta = self.measure(depth)?;
+            if self.pos - start >= LAZY_MIN_BYTES {
+                return Ok(Node::lazy(source, start, self.options, meta));
+            }
+            self.pos = start;
+        }
+
+        match self.peek() {
+            Some(b'{') => self.object(depth),
+            Some(b'[') => self.array(depth),
+            Some(b'"') => {
+                let (string, is_repaired) = self.string()?;
+                if is_repaired {
+                    self.repaired.utf8.push(to_pointer(&self.path));
+                }
+                Ok(Node::string(string))
+            }
+            Some(b't') => self.literal("true", Node::bool(true)),
+            Some(b'f') => self.literal("false", Node::bool(false)),
+            Some(b'n') => self.literal("null", Node::null()),
+            Some(b'N') => self.non_finite("NaN", f64::NAN),
+            Some(b'I') => self.non_finite("Infinity", f64::INFINITY),
+            Some(b'-') if self.input[self.pos..].starts_with(b"-I") => {
+                self.non_finite("-Infinity", f64::NEG_INFINITY)
+            }
+            Some(b'-' | b'0'..=b'9') => self.number(),
+            Some(_) => Err(self.error("expected value")),
+            None => Err(self.error("EOF while parsing a value")),
+        }
+    }
+
+    fn object(&mut self, depth: usize) -> Result<Node, LoadError> {
+        self.pos += 1;
+        let mut index_map = IndexMap::new();
+        self.skip_whitespace();
+        if self.eat(b'}') {
+            return Ok(Node::from_object(index_map));
+        }
+
+        loop {
+            self.skip_whitespace();
+            if self.peek() != Some(b'"') {
+                return Err(self.error("expected object key"));
+            }
+            let (key, is_repaired) = self.string()?;
+            self.path.push(key);
+            if is_repaired {
+                self.repaired.utf8.push(to_pointer(&self.path));
+            }
+            self.skip_whitespace();
+            if !self.eat(b':') {
+                return Err(self.error("expected `:`"));
+            }
+            self.skip_whitespace();
+            let value = self.value(depth + 1)?;
+            let mut key = self.path.pop().expect("key was pushed");
+            if index_map.contains_key(&key) && self.options.duplicate_keys != DuplicateKeys::Last {
+                if self.options.duplicate_keys == DuplicateKeys::Rename {
+                    key = (2..)
+                        .map(|n| format!("{key}__{n}"))
+                        .find(|renamed| !index_map.contains_key(renamed))
+                        .expect("some name is free");
+                }
+                self.path.push(key);
+                self.repaired.duplicate_keys.push(to_pointer(&self.path));
+                key = self.path.pop().expect("key was pushed");
+            }
+            index_map.insert(key, value);
+
+            self.skip_whitespace();
+            if self.eat(b',') {
+                continue;
+            }
+            if self.eat(b'}') {
+                return Ok(Node::from_object(index_map));
+            }
+            return Err(self.error("expected `,` or `}`"));
+        }
+    }
+
+    fn array(&mut self, depth: usize) -> Result<Node, LoadError> {
+        self.pos += 1;
+        let mut nodes = Vec::new();
+        self.skip_whitespace();
+        if self.eat(b']') {
+            return Ok(Node::from_array(nodes));
+        }
+
+        loop {
+            self.skip_whitespace();
+            self.path.push(nodes.len().to_string());
+            nodes.push(self.value(depth + 1)?);
+            self.path.pop();
+
+            self.skip_whitespace();
+            if self.eat(b',') {
+                continue;
+            }
+            if self.eat(b']') {
+                return Ok(Node::from_array(nodes));
+            }
+            return Err(self.error("expected `,` or `]`"));
+        }
+    }
+
+    /// Checks the value at the cursor and sizes it as it would be built, without building
+    /// containers.
+    fn measure(&mut self, depth: usize) -> Result<NodeMeta, LoadError> {
+        if depth > MAX_DEPTH {
+            return Err(self.error("recursion limit exceeded"));
+        }
+
+        let mut size = ContainerSize::default();
+        match self.peek() {
+            Some(b'{') => {
+                self.pos += 1;
+                self.skip_whitespace();
+                if self.eat(b'}') {
+                    return Ok(size.meta(NodeKind::Object));
+                }
+                // Repeated keys keep their last value, so children are only summed at the end.
+                let mut children = IndexMap::new();
+                loop {
+                    self.skip_whitespace();
+                    if self.peek() != Some(b'"') {
+                        return Err(self.error("expected object key"));
+                    }
+                    let (key, _) = self.string()?;
+                    self.skip_whitespace();
+                    if !self.eat(b':') {
+                        return Err(self.error("expected `:`"));
+                    }
+                    self.skip_whitespace();
+                    children.insert(key, self.measure(depth + 1)?);
+
+                    self.skip_whitespace();
+                    if self.eat(b',') {
+                        continue;
+                    }
+                    if self.eat(b'}') {
+                        for (key, child) in &children {
+                            size.push(Some(key), child);
+                        }
+                        return Ok(size.meta(NodeKind::Object));
+                    }
+                    return Err(self.error("expected `,` or `}`"));
+                }
+            }
+            Some(b'[') => {
+                self.pos += 1;
+                self.skip_whitespace();
+                if self.eat(b']') {
+                    return Ok(size.meta(NodeKind::Array));
+                }
+                loop {
+                    self.skip_whitespace();
+                    size.push(None, &self.measure(depth + 1)?);
+
+                    self.skip_whitespace();
+                    if self.eat(b',') {
+                        continue;
+                    }
+                    if self.eat(b']') {
+                        return Ok(size.meta(NodeKind::Array));
+                    }
+                    return Err(self.error("expected `,` or `]`"));
+                }
+            }
+            _ => Ok(self.value(depth)?.as_meta()),
+        }
+    }
+
+    /// Returns the string and whether invalid UTF-8 had to be replaced.
+    fn string(&mut self) -> Result<(String, bool), LoadError> {
+        self.pos += 1;
+        let mut bytes = Vec::new();
+        loop {
+            let Some(c) = self.peek() else {
+                return Err(self.error("EOF while parsing a string"));
+            };
+            self.pos += 1;
+            match c {
+                b'"' => break,
+                b'\\' => self.escape(&mut bytes)?,
+                0x00..=0x1f => {
+                    self.pos -= 1;
+                    return Err(self.error("control character in string"));
+                }
+                c => bytes.push(c),
+            }
+        }
+
+        match String::from_utf8(bytes) {
+            Ok(string) => Ok((string, false)),
+            Err(error) if self.options.lossy_utf8 => {
+                Ok((String::from_utf8_lossy(error.as_bytes()).into_owned(), true))
+            }
+            Err(_) => Err(self.error("invalid UTF-8 in string")),
+        }
+    }
+
+    fn escape(&mut self, bytes: &mut Vec<u8>) -> Result<(), LoadError> {
+        let Some(c) = self.peek() else {
+            return Err(self.error("EOF while parsing a string"));
+        };
+        self.pos += 1;
+        let c = match c {
+            b'"' => '"',
+            b'\\' => '\\',
+            b'/' => '/',
+            b'b' => '\u{8}',
+            b'f' => '\u{c}',
+            b'n' => '\n',
+            b'r' => '\r',
+            b't' => '\t',
+            b'u' => {
+                let high = self.hex4()?;
+                let code = if (0xd800..0xdc00).contains(&high) && self.eat_str("\\u") {
+                    let low = self.hex4()?;
+                    if !(0xdc00..0xe000).contains(&low) {
+                        return Err(self.error("invalid surrogate pair"));
+                    }
+                    0x10000 + ((high - 0xd800) << 10) + (low - 0xdc00)
+                } else {
+                    high
+                };
+                char::from_u32(code).ok_or_else(|| self.error("invalid unicode escape"))?
+            }
+            _ => return Err(self.error("invalid escape")),
+        };
+        bytes.extend_from_slice(c.encode_utf8(&mut [0; 4]).as_bytes());
+        Ok(())
+    }
+
+    fn hex4(&mut self) -> Result<u32, LoadError> {
+        let digits = self
+            .input
+            .get(self.pos..self.pos + 4)
+            .and_then(|digits| std::str::from_utf8(digits).ok())
+            .and_then(|digits| u32::from_str_radix(digits, 16).ok())
+            .ok_or_else(|| self.error("invalid unicode escape"))?;
+        self.pos += 4;
+        Ok(digits)
+    }
+
+    fn number(&mut self) -> Result<Node, LoadError> {
+        let start = self.pos;
+        self.eat(b'-');
+        if !self.eat(b'0') && self.digits() == 0 {
+            return Err(self.error("invalid number"));
+        }
+        if self.eat(b'.') && self.digits() == 0 {
+            return Err(self.error("invalid number"));
+        }
+        if matches!(self.peek(), Some(b'e' | b'E')) {
+            self.pos += 1;
+            if !self.eat(b'+') {
+                self.eat(b'-');
+            }
+            if self.digits() == 0 {
+                return Err(self.error("invalid number"));
+            }
+        }
+
+        let text = std::str::from_utf8(&self.input[start..self.pos]).expect("number is ASCII");
+        Node::number_literal(text).map_err(|_| self.error("invalid number"))
+    }
+
+    fn non_finite(&mut self, token: &str, value: f64) -> Result<Node, LoadError> {
+        if !self.eat_str(token) {
+            return Err(self.error("expected value"));
+        }
+        match self.options.non_finite {
+            NonFinite::Error => {
+                self.pos -= token.len();
+                Err(self.error("non-finite number"))
+            }
+            NonFinite::Null => Ok(Node::null()),
+            NonFinite::Keep => Ok(Node::non_finite(value)),
+        }
+    }
+
+    fn literal(&mut self, token: &str, node: Node) -> Result<Node, LoadError> {
+        if self.eat_str(token) {
+            Ok(node)
+        } else {
+            Err(self.error("expected value"))
+        }
+    }
+
+    fn digits(&mut self) -> usize {
+        let start = self.pos;
+        while matches!(self.peek(), Some(b'0'..=b'9')) {
+            self.pos += 1;
+        }
+        self.pos - start
+    }
+
+    fn skip_whitespace(&mut self) {
+        while matches!(self.peek(), Some(b' ' | b'\n' | b'\r' | b'\t')) {
+            self.pos += 1;
+        }
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.input.get(self.pos).copied()
+    }
+
+    fn eat(&mut self, c: u8) -> bool {
+        let res = self.peek() == Some(c);
+        if res {
+            self.pos += 1;
+        }
+        res
+    }
+
+    fn eat_str(&mut self, s: &str) -> bool {
+        let res = self.input[self.pos..].starts_with(s.as_bytes());
+        if res {
+            self.pos += s.len();
+        }
+        res
+    }
+
+    fn error(&self, message: &str) -> LoadError {
+        let consumed = &self.input[..self.pos.min(self.input.len())];
+        let line = consumed.iter().filter(|&&c| c == b'\n').count() + 1;
+        let column = self.pos
+            - consumed
+                .iter()
+                .rposition(|&c| c == b'\n')
+                .map_or(0, |i| i + 1)
+            + 1;
+        LoadError::Syntax {
+            message: message.to_string(),
+            line,
+            column,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::fixtures::SAMPLE_JSON;
+
+    fn keep() -> ParseOptions {
+        ParseOptions {
+            non_finite: NonFinite::Keep,
+            ..ParseOptions::default()
+        }
+    }
+
+    #[test]
+    fn parse_matches_strict_load_test() {
+        for input in [
+            SAMPLE_JSON,
+            r#"{"a": [1, -2.5e3, 0.1, 18446744073709551615], "b": "é😀\n\"", "c": {}}"#,
+            "[]",
+            " 12 ",
+        ] {
+            assert_eq!(
+                parse(input.as_bytes(), &keep()).unwrap().0,
+                Node::load(input.as_bytes()).unwrap(),
+                "{input}"
+            );
+        }
+    }
+
+    #[test]
+    fn parse_error_test() {
+        for (input, line, column) in [
+            ("{\"a\": 1,}", 1, 9),
+            ("[1,\n  tru]", 2, 3),
+            ("[1] 2", 1, 5),
+            ("\"abc", 1, 5),
+            ("[01]", 1, 3),
+        ] {
+            let Err(LoadError::Syntax {
+                line: actual_line,
+                column: actual_column,
+                ..
+            }) = parse(input.as_bytes(), &keep())
+            else {
+                panic!("expected syntax error for {input}");
+            };
+            assert_eq!((actual_line, actual_column), (line, column), "{input}");
+        }
+    }
+
+    #[test]
+    fn parse_non_finite_test() {
+        let input = r#"{"a": NaN, "b": [Infinity, -Infinity, -1]}"#;
+
+        assert!(matches!(
+            parse(input.as_bytes(), &ParseOptions::default()),
+            Err(LoadError::Syntax { column: 7, .. })
+        ));
+
+        let node = parse(
+            input.as_bytes(),
+            &ParseOptions {
+                non_finite: NonFinite::Null,
+                ..ParseOptions::default()
+            },
+        )
+        .unwrap()
+        .0;
+        assert_eq!(
+            node,
+            Node::load(r#"{"a": null, "b": [null, null, -1]}"#.as_bytes()).unwrap()
+        );
+
+        let (node, _) = parse(input.as_bytes(), &keep()).unwrap();
+        let output = node.to_string_pretty().unwrap();
+        assert_eq!(
+            output,
+            "{\n  \"a\": NaN,\n  \"b\": [\n    Infinity,\n    -Infinity,\n    -1\n  ]\n}"
+        );
+        assert_eq!(output.len(), node.as_meta().n_bytes);
+    }
+
+    #[test]
+    fn parse_lossy_utf8_test() {
+        let input = b"{\"a\": [\"ok\", \"bad\xff\"], \"k\xc3\": 1}";
+
+        assert!(matches!(
+            parse(input, &ParseOptions::default()),
+            Err(LoadError::Syntax { .. })
+        ));
+
+        let (node, repaired) = parse(
+            input,
+            &ParseOptions {
+                lossy_utf8: true,
+                ..ParseOptions::default()
+            },
+        )
+        .unwrap();
+        assert_eq!(
+            node.to_string_compact().unwrap(),
+            "{\"a\":[\"ok\",\"bad\u{fffd}\"],\"k\u{fffd}\":1}"
+        );
+        assert_eq!(repaired.utf8, vec!["/a/1", "/k\u{fffd}"]);
+    }
+
+    #[test]
+    fn parse_duplicate_keys_test() {
+        let input = br#"{"a": 1, "b": {"c": 2, "c": 3}, "a": 4, "a__2": 5}"#;
+        let parse_with = |duplicate_keys| {
+            parse(
+                input,
+                &ParseOptions {
+                    duplicate_keys,
+                    ..ParseOptions::default()
+                },
+            )
+            .unwrap()
+        };
+
+        let (node, repaired) = parse_with(DuplicateKeys::Last);
+        assert_eq!(node, Node::load(&input[..]).unwrap());
+        assert_eq!(
+            node.to_string_compact().unwrap(),
+            r#"{"a":4,"b":{"c":3},"a__2":5}"#
+        );
+        assert!(repaired.is_empty());
+
+        let (node, repaired) = parse_with(DuplicateKeys::Report);
+        assert_eq!(
+            node.to_string_compact().unwrap(),
+            r#"{"a":4,"b":{"c":3},"a__2":5}"#
+        );
+        assert_eq!(repaired.duplicate_keys, vec!["/b/c", "/a"]);
+
+        let (node, repaired) = parse_with(DuplicateKeys::Rename);
+        assert_eq!(
+            node.to_string_compact().unwrap(),
+            r#"{"a":1,"b":{"c":2,"c__2":3},"a__2":4,"a__2__2":5}"#
+        );
+        assert_eq!(
+            repaired.duplicate_keys,
+            vec!["/b/c__2", "/a__2", "/a__2__2"]
+        );
+    }
+}
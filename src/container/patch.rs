@@ -0,0 +1,438 @@
+use std::ops::Deref;
+
+use indexmap::IndexMap;
+
+use super::{
+    node::{AddNodeKey, Node},
+    pointer::{from_pointer, to_pointer},
+};
+use crate::error::{IndexingError, PatchError};
+
+/// An RFC 6902 JSON Patch operation.
+#[derive(Debug, Clone)]
+#[cfg_attr(test, derive(PartialEq))]
+pub enum Operation {
+    Add {
+        path: Vec<String>,
+        value: Node,
+    },
+    Remove {
+        path: Vec<String>,
+    },
+    Replace {
+        path: Vec<String>,
+        value: Node,
+    },
+    Move {
+        from: Vec<String>,
+        path: Vec<String>,
+    },
+    Copy {
+        from: Vec<String>,
+        path: Vec<String>,
+    },
+    Test {
+        path: Vec<String>,
+        value: Node,
+    },
+}
+
+impl Operation {
+    fn to_node(&self) -> Node {
+        let (op, from, path, value) = match self {
+            Operation::Add { path, value } => ("add", None, path, Some(value)),
+            Operation::Remove { path } => ("remove", None, path, None),
+            Operation::Replace { path, value } => ("replace", None, path, Some(value)),
+            Operation::Move { from, path } => ("move", Some(from), path, None),
+            Operation::Copy { from, path } => ("copy", Some(from), path, None),
+            Operation::Test { path, value } => ("test", None, path, Some(value)),
+        };
+        let mut members = IndexMap::new();
+        members.insert(String::from("op"), Node::string(String::from(op)));
+        if let Some(from) = from {
+            members.insert(String::from("from"), Node::string(to_pointer(from)));
+        }
+        members.insert(String::from("path"), Node::string(to_pointer(path)));
+        if let Some(value) = value {
+            members.insert(String::from("value"), value.clone());
+        }
+        Node::from_object(members)
+    }
+
+    fn from_node(node: &Node) -> Result<Self, PatchError> {
+        let member = |key: &str| {
+            node.child(key)
+                .ok_or_else(|| PatchError::Invalid(format!("missing {key}")))
+        };
+        let pointer = |key: &str| {
+            let pointer = member(key)?
+                .as_str()
+                .ok_or_else(|| PatchError::Invalid(format!("{key} is not a string")))?;
+            from_pointer(pointer)
+                .ok_or_else(|| PatchError::Invalid(format!("invalid JSON Pointer {pointer}")))
+        };
+        if node.as_object().is_none() {
+            return Err(PatchError::Invalid(String::from("not an object")));
+        }
+        let path = pointer("path")?;
+        match member("op")?.as_str() {
+            Some("add") => Ok(Operation::Add {
+                path,
+                value: member("value")?.clone(),
+            }),
+            Some("remove") => Ok(Operation::Remove { path }),
+            Some("replace") => Ok(Operation::Replace {
+                path,
+                value: member("value")?.clone(),
+            }),
+            Some("move") => Ok(Operation::Move {
+                from: pointer("from")?,
+                path,
+            }),
+            Some("copy") => Ok(Operation::Copy {
+                from: pointer("from")?,
+                path,
+            }),
+            Some("test") => Ok(Operation::Test {
+                path,
+                value: member("value")?.clone(),
+            }),
+            _ => Err(PatchError::Invalid(String::from("unknown op"))),
+        }
+    }
+
+    fn apply(&self, root: &mut Node) -> Result<(), PatchError> {
+        match self {
+            Operation::Add { path, value } => add(root, path, value.clone()),
+            Operation::Remove { path } => {
+                root.delete(path)?;
+                Ok(())
+            }
+            Operation::Replace { path, value } => {
+                root.replace(path, value.clone())?;
+                Ok(())
+            }
+            Operation::Move { from, path } => {
+                if path.len() > from.len() && path.starts_with(from) {
+                    return Err(PatchError::Invalid(String::from(
+                        "cannot move a node into itself",
+                    )));
+                }
+                let value = root.delete(from)?;
+                add(root, path, value)
+            }
+            Operation::Copy { from, path } => {
+                let value = root.subtree(from)?.clone();
+                add(root, path, value)
+            }
+            Operation::Test { path, value } => {
+                if root.subtree(path)?.same_value(value) {
+                    Ok(())
+                } else {
+                    Err(PatchError::TestFailed(to_pointer(path)))
+                }
+            }
+        }
+    }
+}
+
+/// Adds `value` at `path`, inserting it before the element at an array index or after the last
+/// one for `-`, and replacing an existing object member.
+fn add(root: &mut Node, path: &[String], value: Node) -> Result<(), PatchError> {
+    let Some((key, parent_path)) = path.split_last() else {
+        *root = value;
+        return Ok(());
+    };
+    let parent = root.subtree(parent_path)?;
+    let Some(elements) = parent.as_array() else {
+        root.set(path, value, false)?;
+        return Ok(());
+    };
+
+    let index = match key.as_str() {
+        "-" => elements.len(),
+        key => key
+            .parse::<usize>()
+            .ok()
+            .filter(|index| *index <= elements.len())
+            .ok_or_else(|| IndexingError::MissingKey(key.to_string()))?,
+    };
+    if index == 0 {
+        root.insert_first(parent_path, AddNodeKey::Array, value)?;
+    } else {
+        let mut previous = parent_path.to_vec();
+        previous.push((index - 1).to_string());
+        root.append_after(&previous, AddNodeKey::Array, value)?;
+    }
+    Ok(())
+}
+
+/// Parses a JSON Patch document.
+pub fn parse(node: &Node) -> Result<Vec<Operation>, PatchError> {
+    let operations = node.as_array().ok_or(PatchError::NotAPatch)?;
+    operations
+        .iter()
+        .enumerate()
+        .map(|(i, operation)| {
+            Operation::from_node(operation)
+                .map_err(|error| PatchError::InOperation(i, Box::new(error)))
+        })
+        .collect()
+}
+
+/// Applies `patch` to `root` in order, stopping at the first operation that fails or whose test
+/// does not hold.
+pub fn apply(root: &mut Node, patch: &[Operation]) -> Result<(), PatchError> {
+    for (i, operation) in patch.iter().enumerate() {
+        operation
+            .apply(root)
+            .map_err(|error| PatchError::InOperation(i, Box::new(error)))?;
+    }
+    Ok(())
+}
+
+/// The operations as a JSON Patch document.
+pub fn to_node(patch: &[Operation]) -> Node {
+    Node::from_array(patch.iter().map(Operation::to_node).collect())
+}
+
+/// Appends to `patch` the operations turning `old`, found at `selector`, into `new`, which is
+/// `None` where the node was removed. Objects and arrays are compared member by member.
+pub fn diff<T: Deref<Target = str>>(
+    selector: &[T],
+    old: &Node,
+    new: Option<&Node>,
+    patch: &mut Vec<Operation>,
+) {
+    let mut selector = selector.iter().map(|key| key.to_string()).collect();
+    match new {
+        Some(new) => diff_at(&mut selector, old, new, patch),
+        None => patch.push(Operation::Remove { path: selector }),
+    }
+}
+
+fn diff_at(selector: &mut Vec<String>, old: &Node, new: &Node, patch: &mut Vec<Operation>) {
+    // Subtrees shared with the old document are unchanged, which saves comparing, and parsing,
+    // every sibling of an edit.
+    if old.is_same(new) {
+        return;
+    }
+    if let (Some(old), Some(new)) = (old.as_object(), new.as_object()) {
+        for (key, old_member) in old {
+            selector.push(key.clone());
+            match new.get(key) {
+                Some(new_member) => diff_at(selector, old_member, new_member, patch),
+                None => patch.push(Operation::Remove {
+                    path: selector.clone(),
+                }),
+            }
+            selector.pop();
+        }
+        for (key, new_member) in new.iter().filter(|(key, _)| !old.contains_key(*key)) {
+            selector.push(key.clone());
+            patch.push(Operation::Add {
+                path: selector.clone(),
+                value: new_member.clone(),
+            });
+            selector.pop();
+        }
+    } else if let (Some(old), Some(new)) = (old.as_array(), new.as_array()) {
+        diff_array(selector, old, new, patch);
+    } else if !old.same_value(new) {
+        patch.push(Operation::Replace {
+            path: selector.clone(),
+            value: new.clone(),
+        });
+    }
+}
+
+/// Compares the elements between the common prefix and suffix index by index, so that inserting
+/// or removing one element shifts the following ones instead of replacing them all.
+fn diff_array(selector: &[String], old: &[Node], new: &[Node], patch: &mut Vec<Operation>) {
+    let prefix = old
+        .iter()
+        .zip(new)
+        .take_while(|(old, new)| old.same_value(new))
+        .count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(old, new)| old.same_value(new))
+        .count();
+    let old = &old[prefix..old.len() - suffix];
+    let new = &new[prefix..new.len() - suffix];
+    let common = old.len().min(new.len());
+
+    let path = |selector: &[String], position: usize| {
+        let mut path = selector.to_vec();
+        path.push((prefix + position).to_string());
+        path
+    };
+    for position in 0..common {
+        let mut path = path(selector, position);
+        diff_at(&mut path, &old[position], &new[position], patch);
+    }
+    // From the back, so that each index is still valid when its removal is applied.
+    for position in (common..old.len()).rev() {
+        patch.push(Operation::Remove {
+            path: path(selector, position),
+        });
+    }
+    for (position, value) in new.iter().enumerate().skip(common) {
+        patch.push(Operation::Add {
+            path: path(selector, position),
+            value: value.clone(),
+        });
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn load(input: &str) -> Node {
+        Node::load(input.as_bytes()).unwrap()
+    }
+
+    fn patch(old: &str, new: &str) -> String {
+        let mut patch = Vec::new();
+        diff::<&str>(&[], &load(old), Some(&load(new)), &mut patch);
+        to_node(&patch).to_string_compact().unwrap()
+    }
+
+    #[test]
+    fn diff_test() {
+        assert_eq!(patch(r#"{"a": 1}"#, r#"{"a": 1}"#), "[]");
+        assert_eq!(
+            patch(
+                r#"{"a": {"b": 1, "c": 2}, "d": "x"}"#,
+                r#"{"a": {"b": 3}, "d": [], "e/f": null}"#
+            ),
+            concat!(
+                r#"[{"op":"replace","path":"/a/b","value":3},"#,
+                r#"{"op":"remove","path":"/a/c"},"#,
+                r#"{"op":"replace","path":"/d","value":[]},"#,
+                r#"{"op":"add","path":"/e~1f","value":null}]"#
+            )
+        );
+
+        // Edits of a clone only differ where the clone stopped sharing the original.
+        let old = load(r#"{"a": [[1], [2]], "b": {"c": 1}}"#);
+        let mut new = old.clone();
+        new.delete(&["b"]).unwrap();
+        new.set(&["a", "1", "0"], Node::null(), false).unwrap();
+        let mut patch = Vec::new();
+        diff::<&str>(&[], &old, Some(&new), &mut patch);
+        assert_eq!(
+            to_node(&patch).to_string_compact().unwrap(),
+            r#"[{"op":"replace","path":"/a/1/0","value":null},{"op":"remove","path":"/b"}]"#
+        );
+    }
+
+    fn apply_str(input: &str, patch: &str) -> Result<String, PatchError> {
+        let mut root = load(input);
+        apply(&mut root, &parse(&load(patch))?)?;
+        Ok(root.to_string_compact().unwrap())
+    }
+
+    #[test]
+    fn apply_test() {
+        assert_eq!(
+            apply_str(
+                r#"{"a": [1, 3], "b": {"c": 1}}"#,
+                r#"[
+                    {"op": "test", "path": "/b/c", "value": 1},
+                    {"op": "add", "path": "/a/1", "value": 2},
+                    {"op": "add", "path": "/a/0", "value": 0},
+                    {"op": "add", "path": "/a/-", "value": 4},
+                    {"op": "replace", "path": "/b/c", "value": [5]},
+                    {"op": "copy", "from": "/b/c", "path": "/d"},
+                    {"op": "move", "from": "/b", "path": "/e"},
+                    {"op": "remove", "path": "/a/4"},
+                    {"op": "add", "path": "/e/c", "value": null}
+                ]"#
+            )
+            .unwrap(),
+            r#"{"a":[0,1,2,3],"d":[5],"e":{"c":null}}"#
+        );
+        assert_eq!(
+            apply_str("1", r#"[{"op": "add", "path": "", "value": {"a": 1}}]"#).unwrap(),
+            r#"{"a":1}"#
+        );
+    }
+
+    #[test]
+    fn apply_error_test() {
+        let error = |input: &str, patch: &str| apply_str(input, patch).unwrap_err().to_string();
+        assert_eq!(
+            error(r#"{"a": 1}"#, r#"{"op": "remove", "path": "/a"}"#),
+            "A JSON Patch is an array of operations"
+        );
+        assert_eq!(
+            error(r#"{"a": 1}"#, r#"[{"op": "remove", "path": "a"}]"#),
+            "Operation 0: invalid JSON Pointer a"
+        );
+        assert_eq!(
+            error(r#"{"a": 1}"#, r#"[{"op": "frob", "path": "/a"}]"#),
+            "Operation 0: unknown op"
+        );
+        assert_eq!(
+            error(
+                r#"{"a": 1}"#,
+                r#"[{"op": "remove", "path": "/a"}, {"op": "test", "path": "/a", "value": 1}]"#
+            ),
+            "Operation 1: Missing key: a"
+        );
+        assert_eq!(
+            error(
+                r#"{"a": 1}"#,
+                r#"[{"op": "test", "path": "/a", "value": 2}]"#
+            ),
+            "Operation 0: Test failed at /a"
+        );
+        assert_eq!(
+            error(
+                r#"{"a": [1]}"#,
+                r#"[{"op": "add", "path": "/a/2", "value": 2}]"#
+            ),
+            "Operation 0: Missing key: 2"
+        );
+        assert_eq!(
+            error(
+                r#"{"a": {}}"#,
+                r#"[{"op": "move", "from": "/a", "path": "/a/b"}]"#
+            ),
+            "Operation 0: cannot move a node into itself"
+        );
+    }
+
+    #[test]
+    fn diff_array_test() {
+        assert_eq!(
+            patch("[1, 2, 3, 4]", "[1, 3, 4]"),
+            r#"[{"op":"remove","path":"/1"}]"#
+        );
+        assert_eq!(
+            patch("[1, 3]", "[1, 2, 3]"),
+            r#"[{"op":"add","path":"/1","value":2}]"#
+        );
+        assert_eq!(
+            patch("[1, 2, 3]", "[1]"),
+            r#"[{"op":"remove","path":"/2"},{"op":"remove","path":"/1"}]"#
+        );
+        assert_eq!(
+            patch(r#"[{"a": 1}, 2]"#, r#"[{"a": 2}, 1]"#),
+            r#"[{"op":"replace","path":"/0/a","value":2},{"op":"replace","path":"/1","value":1}]"#
+        );
+
+        let mut patch = Vec::new();
+        diff(&[String::from("a")], &load("1"), None, &mut patch);
+        assert_eq!(
+            patch,
+            [Operation::Remove {
+                path: vec![String::from("a")]
+            }]
+        );
+    }
+}
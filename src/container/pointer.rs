@@ -0,0 +1,346 @@
+use std::{
+    collections::HashSet,
+    hash::{Hash, Hasher},
+    ops::Deref,
+    sync::{Arc, LazyLock, Mutex},
+};
+
+use smallvec::SmallVec;
+
+use super::node::Node;
+use crate::error::PointerError;
+
+/// Key of a node. Equal keys interned with [`intern`] share one allocation, so cloning one only
+/// bumps a count and comparing two usually stops at their addresses.
+pub type Key = Arc<str>;
+
+/// Fewest interned keys before unused ones get dropped.
+const MIN_PURGE_AT: usize = 1024;
+
+struct Interner {
+    keys: HashSet<Key>,
+    /// Number of keys at which the ones nothing else holds are dropped.
+    purge_at: usize,
+}
+
+static INTERNER: LazyLock<Mutex<Interner>> = LazyLock::new(|| {
+    Mutex::new(Interner {
+        keys: HashSet::new(),
+        purge_at: MIN_PURGE_AT,
+    })
+});
+
+/// The shared copy of `key`, allocated on its first use.
+pub fn intern(key: &str) -> Key {
+    let mut interner = INTERNER.lock().unwrap_or_else(|error| error.into_inner());
+    if let Some(key) = interner.keys.get(key) {
+        return key.clone();
+    }
+    if interner.keys.len() >= interner.purge_at {
+        interner.keys.retain(|key| Arc::strong_count(key) > 1);
+        interner.purge_at = (interner.keys.len() * 2).max(MIN_PURGE_AT);
+    }
+    let key = Key::from(key);
+    interner.keys.insert(key.clone());
+    key
+}
+
+/// Keys from the root down to a node. Cloning one shares its keys, and paths up to eight keys
+/// deep, which covers most documents, are held without allocating.
+#[derive(Debug, Clone, Default, Eq)]
+pub struct KeyPath(SmallVec<[Key; 8]>);
+
+impl KeyPath {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn push(&mut self, key: &str) {
+        self.0.push(intern(key));
+    }
+
+    pub fn push_key(&mut self, key: Key) {
+        self.0.push(key);
+    }
+
+    pub fn pop(&mut self) -> Option<Key> {
+        self.0.pop()
+    }
+
+    /// Whether `selector` is this path or goes through it.
+    pub fn is_prefix_of<T: Deref<Target = str>>(&self, selector: &[T]) -> bool {
+        self.len() <= selector.len()
+            && self
+                .iter()
+                .zip(selector)
+                .all(|(key, other)| **key == **other)
+    }
+}
+
+impl Deref for KeyPath {
+    type Target = [Key];
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<T: Deref<Target = str>> From<&[T]> for KeyPath {
+    fn from(selector: &[T]) -> Self {
+        selector.iter().map(|key| intern(key)).collect()
+    }
+}
+
+impl<T: Deref<Target = str>, const N: usize> From<[T; N]> for KeyPath {
+    fn from(selector: [T; N]) -> Self {
+        Self::from(&selector[..])
+    }
+}
+
+impl From<Vec<String>> for KeyPath {
+    fn from(selector: Vec<String>) -> Self {
+        Self::from(selector.as_slice())
+    }
+}
+
+impl FromIterator<Key> for KeyPath {
+    fn from_iter<I: IntoIterator<Item = Key>>(iter: I) -> Self {
+        Self(iter.into_iter().collect())
+    }
+}
+
+impl PartialEq for KeyPath {
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len()
+            && self
+                .iter()
+                .zip(other.iter())
+                .all(|(a, b)| Arc::ptr_eq(a, b) || a == b)
+    }
+}
+
+impl<T: Deref<Target = str>, const N: usize> PartialEq<[T; N]> for KeyPath {
+    fn eq(&self, other: &[T; N]) -> bool {
+        self.len() == N && self.is_prefix_of(other)
+    }
+}
+
+impl Hash for KeyPath {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.0.hash(state);
+    }
+}
+
+/// Formats a selector as an RFC 6901 JSON Pointer.
+pub fn to_pointer<T: Deref<Target = str>>(selector: &[T]) -> String {
+    let mut pointer = String::new();
+    for key in selector {
+        pointer.push('/');
+        for c in key.chars() {
+            match c {
+                '~' => pointer.push_str("~0"),
+                '/' => pointer.push_str("~1"),
+                c => pointer.push(c),
+            }
+        }
+    }
+    pointer
+}
+
+/// Formats a selector into `root` as a JSONPath such as `$.servlet[2]['init-param']`.
+pub fn to_path<T: Deref<Target = str>>(root: &Node, selector: &[T]) -> String {
+    let mut path = String::from("$");
+    let mut node = Some(root);
+    for key in selector {
+        let is_index = node.is_some_and(|node| node.as_array().is_some());
+        let is_identifier =
+            key.chars().enumerate().all(|(i, c)| {
+                c == '_' || c == '$' || c.is_alphabetic() || (i > 0 && c.is_numeric())
+            }) && !key.is_empty();
+        if is_index {
+            path.push_str(&format!("[{}]", &**key));
+        } else if is_identifier {
+            path.push('.');
+            path.push_str(key);
+        } else {
+            path.push_str("['");
+            for c in key.chars() {
+                if c == '\'' || c == '\\' {
+                    path.push('\\');
+                }
+                path.push(c);
+            }
+            path.push_str("']");
+        }
+        node = node.and_then(|node| node.child(key));
+    }
+    path
+}
+
+/// Parses a JSON Pointer, or a JSONPath when `query` starts with `$`, into a selector.
+pub fn from_query(query: &str) -> Result<Vec<String>, PointerError> {
+    if query.starts_with('$') {
+        from_path(query).ok_or_else(|| PointerError::InvalidPath(query.to_string()))
+    } else {
+        from_pointer(query).ok_or_else(|| PointerError::InvalidPointer(query.to_string()))
+    }
+}
+
+/// Parses an RFC 6901 JSON Pointer into a selector.
+pub fn from_pointer(pointer: &str) -> Option<Vec<String>> {
+    if pointer.is_empty() {
+        return Some(Vec::new());
+    }
+    pointer
+        .strip_prefix('/')?
+        .split('/')
+        .map(|key| {
+            let mut unescaped = String::with_capacity(key.len());
+            let mut chars = key.chars();
+            while let Some(c) = chars.next() {
+                match c {
+                    '~' => match chars.next()? {
+                        '0' => unescaped.push('~'),
+                        '1' => unescaped.push('/'),
+                        _ => return None,
+                    },
+                    c => unescaped.push(c),
+                }
+            }
+            Some(unescaped)
+        })
+        .collect()
+}
+
+/// Parses a JSONPath naming a single node, such as `$.a["b c"][0]`, into a selector. Wildcards,
+/// slices, filters and recursive descent are not supported.
+pub fn from_path(path: &str) -> Option<Vec<String>> {
+    let mut chars = path.strip_prefix('$')?.chars().peekable();
+    let mut selector = Vec::new();
+    while let Some(c) = chars.next() {
+        match c {
+            '.' => {
+                let mut key = String::new();
+                while let Some(&c) = chars.peek().filter(|&&c| c != '.' && c != '[') {
+                    key.push(c);
+                    chars.next();
+                }
+                if key.is_empty() || key == "*" {
+                    return None;
+                }
+                selector.push(key);
+            }
+            '[' => {
+                let mut key = String::new();
+                match chars.next()? {
+                    quote @ ('\'' | '"') => loop {
+                        match chars.next()? {
+                            '\\' => key.push(chars.next()?),
+                            c if c == quote => break,
+                            c => key.push(c),
+                        }
+                    },
+                    c if c.is_ascii_digit() => {
+                        key.push(c);
+                        while let Some(c) = chars.next_if(char::is_ascii_digit) {
+                            key.push(c);
+                        }
+                    }
+                    _ => return None,
+                }
+                if chars.next()? != ']' {
+                    return None;
+                }
+                selector.push(key);
+            }
+            _ => return None,
+        }
+    }
+    Some(selector)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn key_path_test() {
+        let a = KeyPath::from(&["servers", "0"][..]);
+        let mut b = KeyPath::new();
+        b.push("servers");
+        b.push(&0.to_string());
+        assert_eq!(a, b);
+        assert!(Arc::ptr_eq(&a[1], &b[1]));
+        assert_eq!(to_pointer(&a), "/servers/0");
+
+        b.pop();
+        b.push("1");
+        assert_ne!(a, b);
+        assert_eq!(b[..], ["servers", "1"].map(Key::from));
+    }
+
+    #[test]
+    fn to_pointer_test() {
+        assert_eq!(to_pointer::<&str>(&[]), "");
+        assert_eq!(to_pointer(&["a", "0"]), "/a/0");
+        assert_eq!(to_pointer(&["a/b", "m~n", ""]), "/a~1b/m~0n/");
+    }
+
+    #[test]
+    fn to_path_test() {
+        let root = Node::load(r#"{"servlet": [{"init-param": {"it's": 1, "_a1": 2}}]}"#.as_bytes())
+            .unwrap();
+        assert_eq!(to_path::<&str>(&root, &[]), "$");
+        assert_eq!(
+            to_path(&root, &["servlet", "0", "init-param", "it's"]),
+            r"$.servlet[0]['init-param']['it\'s']"
+        );
+        assert_eq!(
+            to_path(&root, &["servlet", "0", "init-param", "_a1"]),
+            "$.servlet[0]['init-param']._a1"
+        );
+        let selector = ["servlet", "0", "init-param", "it's"];
+        assert_eq!(
+            from_path(&to_path(&root, &selector)).unwrap(),
+            selector.map(String::from)
+        );
+    }
+
+    #[test]
+    fn from_pointer_test() {
+        assert_eq!(from_pointer(""), Some(vec![]));
+        assert_eq!(
+            from_pointer("/a/0"),
+            Some(vec![String::from("a"), String::from("0")])
+        );
+        assert_eq!(
+            from_pointer("/a~1b/m~0n/"),
+            Some(vec![
+                String::from("a/b"),
+                String::from("m~n"),
+                String::new()
+            ])
+        );
+        assert_eq!(from_pointer("a"), None);
+        assert_eq!(from_pointer("/a~2"), None);
+    }
+
+    #[test]
+    fn from_path_test() {
+        assert_eq!(from_path("$"), Some(vec![]));
+        assert_eq!(
+            from_path(r#"$.a['b c']["d\"e"][10]"#),
+            Some(vec![
+                String::from("a"),
+                String::from("b c"),
+                String::from("d\"e"),
+                String::from("10"),
+            ])
+        );
+        assert_eq!(from_path("a.b"), None);
+        assert_eq!(from_path("$..a"), None);
+        assert_eq!(from_path("$.*"), None);
+        assert_eq!(from_path("$[*]"), None);
+        assert_eq!(from_path("$['a'"), None);
+    }
+}
@@ -0,0 +1,480 @@
+use std::{fs::File, io::BufReader, path::Path};
+
+use serde_json::{Map, Value};
+
+use super::{node::Node, pointer};
+use crate::error::LoadError;
+
+/// Errors kept per validation, so that a document breaking a rule everywhere stays browsable.
+const MAX_ERRORS: usize = 1000;
+/// Nested `$ref`s followed before giving up on a recursive schema.
+const MAX_REF_DEPTH: usize = 64;
+
+/// A JSON Schema. The type, enum, const, numeric, string length, object, array and combining
+/// keywords are checked, along with local `$ref`s; `pattern` and `format` are not.
+#[derive(Debug)]
+pub struct Schema {
+    root: Value,
+}
+
+/// A place where a document breaks its schema.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ValidationError {
+    pub selector: Vec<String>,
+    pub message: String,
+}
+
+impl Schema {
+    pub fn new(root: Value) -> Self {
+        Self { root }
+    }
+
+    pub fn load_file(path: impl AsRef<Path>) -> Result<Self, LoadError> {
+        let reader = BufReader::new(File::open(path)?);
+        Ok(Self::new(sonic_rs::from_reader(reader)?))
+    }
+
+    /// Where `node` breaks the schema, in document order.
+    pub fn validate(&self, node: &Node) -> Vec<ValidationError> {
+        let mut validator = Validator {
+            root: &self.root,
+            selector: Vec::new(),
+            errors: Vec::new(),
+        };
+        validator.validate(&self.root, node, 0);
+        validator.errors
+    }
+}
+
+struct Validator<'a> {
+    root: &'a Value,
+    selector: Vec<String>,
+    errors: Vec<ValidationError>,
+}
+
+impl<'a> Validator<'a> {
+    fn fail(&mut self, message: String) {
+        if self.errors.len() < MAX_ERRORS {
+            self.errors.push(ValidationError {
+                selector: self.selector.clone(),
+                message,
+            });
+        }
+    }
+
+    fn is_valid(&self, schema: &'a Value, node: &Node, depth: usize) -> bool {
+        let mut validator = Validator {
+            root: self.root,
+            selector: Vec::new(),
+            errors: Vec::new(),
+        };
+        validator.validate(schema, node, depth);
+        validator.errors.is_empty()
+    }
+
+    fn validate_child(&mut self, key: String, schema: &'a Value, node: &Node, depth: usize) {
+        self.selector.push(key);
+        self.validate(schema, node, depth);
+        self.selector.pop();
+    }
+
+    fn validate(&mut self, schema: &'a Value, node: &Node, depth: usize) {
+        let schema = match schema {
+            Value::Object(schema) => schema,
+            Value::Bool(false) => return self.fail(String::from("No value is allowed here")),
+            _ => return,
+        };
+
+        if let Some(Value::String(reference)) = schema.get("$ref") {
+            match self.resolve(reference) {
+                Some(_) if depth >= MAX_REF_DEPTH => {
+                    self.fail(format!("$ref {reference} nests too deeply"))
+                }
+                Some(resolved) => self.validate(resolved, node, depth + 1),
+                None => self.fail(format!("Cannot resolve $ref {reference}")),
+            }
+        }
+
+        self.validate_type(schema, node);
+        self.validate_number(schema, node);
+        self.validate_string(schema, node);
+        if let Some(object) = node.as_object() {
+            self.validate_object(schema, object, depth);
+        }
+        if let Some(array) = node.as_array() {
+            self.validate_array(schema, array, depth);
+        }
+        self.validate_combinators(schema, node, depth);
+    }
+
+    /// The schema at a local reference such as `#/$defs/user`.
+    fn resolve(&self, reference: &str) -> Option<&'a Value> {
+        let selector = pointer::from_pointer(reference.strip_prefix('#')?)?;
+        selector
+            .iter()
+            .try_fold(self.root, |value, key| match value {
+                Value::Object(map) => map.get(key),
+                Value::Array(values) => values.get(key.parse::<usize>().ok()?),
+                _ => None,
+            })
+    }
+
+    fn validate_type(&mut self, schema: &Map<String, Value>, node: &Node) {
+        let types = match schema.get("type") {
+            Some(Value::String(name)) => vec![name.as_str()],
+            Some(Value::Array(names)) => names.iter().filter_map(Value::as_str).collect(),
+            _ => Vec::new(),
+        };
+        let is_type = |name: &str| match name {
+            "integer" => node.is_integer(),
+            name => name == node.type_name(),
+        };
+        if !types.is_empty() && !types.iter().any(|name| is_type(name)) {
+            self.fail(format!(
+                "Expected {}, found {}",
+                types.join(" or "),
+                node.type_name()
+            ));
+        }
+
+        if let Some(Value::Array(values)) = schema.get("enum")
+            && !values.iter().any(|value| equals(node, value))
+        {
+            self.fail(format!("Expected one of {}", Value::Array(values.clone())));
+        }
+        if let Some(value) = schema.get("const")
+            && !equals(node, value)
+        {
+            self.fail(format!("Expected {value}"));
+        }
+    }
+
+    fn validate_number(&mut self, schema: &Map<String, Value>, node: &Node) {
+        let Some(value) = node.as_f64() else {
+            return;
+        };
+        let limit = |keyword: &str| schema.get(keyword).and_then(Value::as_f64);
+        if let Some(minimum) = limit("minimum")
+            && value < minimum
+        {
+            self.fail(format!("Expected at least {minimum}, found {value}"));
+        }
+        if let Some(maximum) = limit("maximum")
+            && value > maximum
+        {
+            self.fail(format!("Expected at most {maximum}, found {value}"));
+        }
+        if let Some(minimum) = limit("exclusiveMinimum")
+            && value <= minimum
+        {
+            self.fail(format!("Expected more than {minimum}, found {value}"));
+        }
+        if let Some(maximum) = limit("exclusiveMaximum")
+            && value >= maximum
+        {
+            self.fail(format!("Expected less than {maximum}, found {value}"));
+        }
+        if let Some(divisor) = limit("multipleOf")
+            && divisor > 0.0
+            && (value / divisor).fract() != 0.0
+        {
+            self.fail(format!("Expected a multiple of {divisor}, found {value}"));
+        }
+    }
+
+    fn validate_string(&mut self, schema: &Map<String, Value>, node: &Node) {
+        let Some(value) = node.as_str() else {
+            return;
+        };
+        let len = value.chars().count() as u64;
+        if let Some(min) = schema.get("minLength").and_then(Value::as_u64)
+            && len < min
+        {
+            self.fail(format!("Expected at least {min} characters, found {len}"));
+        }
+        if let Some(max) = schema.get("maxLength").and_then(Value::as_u64)
+            && len > max
+        {
+            self.fail(format!("Expected at most {max} characters, found {len}"));
+        }
+    }
+
+    fn validate_object(
+        &mut self,
+        schema: &'a Map<String, Value>,
+        object: &indexmap::IndexMap<String, Node>,
+        depth: usize,
+    ) {
+        if let Some(Value::Array(required)) = schema.get("required") {
+            for key in required.iter().filter_map(Value::as_str) {
+                if !object.contains_key(key) {
+                    self.fail(format!("Missing required key {key}"));
+                }
+            }
+        }
+        let len = object.len() as u64;
+        if let Some(min) = schema.get("minProperties").and_then(Value::as_u64)
+            && len < min
+        {
+            self.fail(format!("Expected at least {min} keys, found {len}"));
+        }
+        if let Some(max) = schema.get("maxProperties").and_then(Value::as_u64)
+            && len > max
+        {
+            self.fail(format!("Expected at most {max} keys, found {len}"));
+        }
+
+        let properties = schema.get("properties").and_then(Value::as_object);
+        // Keys matching `patternProperties` cannot be told apart without regexes, so additional
+        // keys are only checked without them.
+        let additional = schema
+            .get("additionalProperties")
+            .filter(|_| !schema.contains_key("patternProperties"));
+        for (key, child) in object {
+            match properties.and_then(|properties| properties.get(key)) {
+                Some(property) => self.validate_child(key.clone(), property, child, depth),
+                None => match additional {
+                    Some(Value::Bool(false)) => self.fail(format!("Unexpected key {key}")),
+                    Some(additional) => self.validate_child(key.clone(), additional, child, depth),
+                    None => {}
+                },
+            }
+        }
+    }
+
+    fn validate_array(&mut self, schema: &'a Map<String, Value>, array: &[Node], depth: usize) {
+        let len = array.len() as u64;
+        if let Some(min) = schema.get("minItems").and_then(Value::as_u64)
+            && len < min
+        {
+            self.fail(format!("Expected at least {min} items, found {len}"));
+        }
+        if let Some(max) = schema.get("maxItems").and_then(Value::as_u64)
+            && len > max
+        {
+            self.fail(format!("Expected at most {max} items, found {len}"));
+        }
+        if schema.get("uniqueItems") == Some(&Value::Bool(true))
+            && let Some((i, j)) = (0..array.len()).find_map(|j| {
+                (0..j)
+                    .find(|&i| array[i].same_value(&array[j]))
+                    .map(|i| (i, j))
+            })
+        {
+            self.fail(format!("Items {i} and {j} are equal"));
+        }
+
+        // Tuples are `prefixItems` since 2020-12, and an array of `items` before.
+        let (prefix, rest) = match (schema.get("prefixItems"), schema.get("items")) {
+            (Some(Value::Array(prefix)), items) => (prefix.as_slice(), items),
+            (_, Some(Value::Array(prefix))) => (prefix.as_slice(), schema.get("additionalItems")),
+            (_, items) => (&[][..], items),
+        };
+        for (i, child) in array.iter().enumerate() {
+            match prefix.get(i).or(rest) {
+                Some(Value::Bool(false)) if prefix.get(i).is_none() => {
+                    self.fail(format!(
+                        "Expected at most {} items, found {len}",
+                        prefix.len()
+                    ));
+                    break;
+                }
+                Some(item) => self.validate_child(i.to_string(), item, child, depth),
+                None => {}
+            }
+        }
+
+        if let Some(contains) = schema.get("contains")
+            && !array
+                .iter()
+                .any(|child| self.is_valid(contains, child, depth))
+        {
+            self.fail(String::from("No item matches contains"));
+        }
+    }
+
+    fn validate_combinators(&mut self, schema: &'a Map<String, Value>, node: &Node, depth: usize) {
+        if let Some(Value::Array(schemas)) = schema.get("allOf") {
+            for schema in schemas {
+                self.validate(schema, node, depth);
+            }
+        }
+        if let Some(Value::Array(schemas)) = schema.get("anyOf")
+            && !schemas
+                .iter()
+                .any(|schema| self.is_valid(schema, node, depth))
+        {
+            self.fail(String::from("Matches none of the anyOf schemas"));
+        }
+        if let Some(Value::Array(schemas)) = schema.get("oneOf") {
+            let n_valid = schemas
+                .iter()
+                .filter(|schema| self.is_valid(schema, node, depth))
+                .count();
+            if n_valid != 1 {
+                self.fail(format!(
+                    "Matches {n_valid} of the oneOf schemas, expected exactly one"
+                ));
+            }
+        }
+        if let Some(not) = schema.get("not")
+            && self.is_valid(not, node, depth)
+        {
+            self.fail(String::from("Matches a schema it must not"));
+        }
+        if let Some(condition) = schema.get("if") {
+            let branch = if self.is_valid(condition, node, depth) {
+                schema.get("then")
+            } else {
+                schema.get("else")
+            };
+            if let Some(branch) = branch {
+                self.validate(branch, node, depth);
+            }
+        }
+    }
+}
+
+/// Whether `node` holds `value`, comparing numbers by value so that `1` equals `1.0`.
+fn equals(node: &Node, value: &Value) -> bool {
+    match value {
+        Value::Null => node.type_name() == "null",
+        Value::Bool(value) => node.as_bool() == Some(*value),
+        Value::Number(value) => node.as_f64().is_some() && node.as_f64() == value.as_f64(),
+        Value::String(value) => node.as_str() == Some(value.as_str()),
+        Value::Array(values) => node.as_array().is_some_and(|array| {
+            array.len() == values.len()
+                && array
+                    .iter()
+                    .zip(values)
+                    .all(|(node, value)| equals(node, value))
+        }),
+        Value::Object(map) => node.as_object().is_some_and(|object| {
+            object.len() == map.len()
+                && map
+                    .iter()
+                    .all(|(key, value)| object.get(key).is_some_and(|node| equals(node, value)))
+        }),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+
+    use super::*;
+
+    fn validate(schema: Value, input: &str) -> Vec<(String, String)> {
+        Schema::new(schema)
+            .validate(&Node::load(input.as_bytes()).unwrap())
+            .into_iter()
+            .map(|error| (pointer::to_pointer(&error.selector), error.message))
+            .collect()
+    }
+
+    fn error(pointer: &str, message: &str) -> (String, String) {
+        (String::from(pointer), String::from(message))
+    }
+
+    #[test]
+    fn validate_test() {
+        let schema = json!({
+            "type": "object",
+            "required": ["name", "port"],
+            "properties": {
+                "name": {"type": "string", "minLength": 2},
+                "port": {"type": "integer", "minimum": 1, "maximum": 65535},
+                "tags": {"type": "array", "items": {"$ref": "#/$defs/tag"}, "uniqueItems": true},
+                "mode": {"enum": ["dev", "prod"]}
+            },
+            "additionalProperties": false,
+            "$defs": {"tag": {"type": "string", "maxLength": 3}}
+        });
+
+        assert_eq!(
+            validate(
+                schema.clone(),
+                r#"{"name": "api", "port": 80, "tags": ["a", "b"], "mode": "dev"}"#
+            ),
+            []
+        );
+        assert_eq!(
+            validate(
+                schema,
+                r#"{"name": "a", "port": 8.5, "tags": ["a", "long", "a"], "mode": "x", "extra": 1}"#
+            ),
+            [
+                error("/name", "Expected at least 2 characters, found 1"),
+                error("/port", "Expected integer, found number"),
+                error("/tags", "Items 0 and 2 are equal"),
+                error("/tags/1", "Expected at most 3 characters, found 4"),
+                error("/mode", r#"Expected one of ["dev","prod"]"#),
+                error("", "Unexpected key extra"),
+            ]
+        );
+    }
+
+    #[test]
+    fn validate_combinators_test() {
+        let schema = json!({
+            "type": "array",
+            "prefixItems": [{"const": 1}],
+            "items": {"oneOf": [{"type": "number"}, {"type": "integer"}, {"type": "string"}]},
+            "contains": {"type": "string"},
+            "maxItems": 3
+        });
+
+        assert_eq!(validate(schema.clone(), r#"[1, "a", 2.5]"#), []);
+        assert_eq!(
+            validate(schema, "[2, 3, null, 4]"),
+            [
+                error("", "Expected at most 3 items, found 4"),
+                error("/0", "Expected 1"),
+                error("/1", "Matches 2 of the oneOf schemas, expected exactly one"),
+                error("/2", "Matches 0 of the oneOf schemas, expected exactly one"),
+                error("/3", "Matches 2 of the oneOf schemas, expected exactly one"),
+                error("", "No item matches contains"),
+            ]
+        );
+
+        let schema = json!({
+            "if": {"properties": {"kind": {"const": "a"}}},
+            "then": {"required": ["a"]},
+            "else": {"not": {"required": ["a"]}}
+        });
+        assert_eq!(validate(schema.clone(), r#"{"kind": "a", "a": 1}"#), []);
+        assert_eq!(
+            validate(schema.clone(), r#"{"kind": "a"}"#),
+            [error("", "Missing required key a")]
+        );
+        assert_eq!(
+            validate(schema, r#"{"kind": "b", "a": 1}"#),
+            [error("", "Matches a schema it must not")]
+        );
+    }
+
+    #[test]
+    fn validate_ref_test() {
+        let schema = json!({"$ref": "#/definitions/missing"});
+        assert_eq!(
+            validate(schema, "1"),
+            [error("", "Cannot resolve $ref #/definitions/missing")]
+        );
+
+        let schema = json!({"$ref": "#"});
+        assert_eq!(
+            validate(schema, "1"),
+            [error("", "$ref # nests too deeply")]
+        );
+
+        let schema = json!({
+            "type": "object",
+            "properties": {"child": {"$ref": "#"}},
+            "additionalProperties": {"type": "number"}
+        });
+        assert_eq!(
+            validate(schema, r#"{"child": {"child": {"x": "1"}}}"#),
+            [error("/child/child/x", "Expected number, found string")]
+        );
+    }
+}
@@ -15,7 +15,7 @@ pub enum DumpError {
 #[derive(Debug, thiserror::Error)]
 pub enum DeserializationError {
     #[error("Invalid number: {0}")]
-    InvalidNumber(serde_json::Number),
+    InvalidNumber(String),
 }
 
 #[derive(Debug, thiserror::Error)]
@@ -26,6 +26,20 @@ pub enum LoadError {
     DeserializationError(#[from] DeserializationError),
     #[error(transparent)]
     IO(#[from] std::io::Error),
+    #[error("Syntax error: {message} at line {line} column {column}")]
+    Syntax {
+        message: String,
+        line: usize,
+        column: usize,
+    },
+    #[error("Duplicate key: {0}")]
+    DuplicateKey(String),
+    #[error("Invalid UTF-16")]
+    InvalidUtf16,
+    #[error("{0}: {1}")]
+    InFile(String, Box<LoadError>),
+    #[error("Line {0}: {1}")]
+    InLine(usize, Box<LoadError>),
 }
 
 // TODO: add error path
@@ -38,6 +52,17 @@ pub enum IndexingError {
     MissingKey(String),
 }
 
+#[derive(Debug, thiserror::Error)]
+#[cfg_attr(test, derive(PartialEq))]
+pub enum PointerError {
+    #[error("Invalid JSON Pointer: {0}")]
+    InvalidPointer(String),
+    #[error("Invalid JSONPath: {0}")]
+    InvalidPath(String),
+    #[error(transparent)]
+    Indexing(#[from] IndexingError),
+}
+
 #[derive(Debug, thiserror::Error)]
 #[cfg_attr(test, derive(PartialEq))]
 pub enum MutationError {
@@ -45,6 +70,53 @@ pub enum MutationError {
     DuplicateKey,
     #[error("Not renameable")]
     NotRenameable,
+    #[error("Only arrays of strings, numbers, booleans and null can be sorted")]
+    NotSortable,
     #[error(transparent)]
     Indexing(#[from] IndexingError),
 }
+
+#[derive(Debug, thiserror::Error)]
+pub enum PatchError {
+    #[error("A JSON Patch is an array of operations")]
+    NotAPatch,
+    #[error("{0}")]
+    Invalid(String),
+    #[error("Test failed at {0}")]
+    TestFailed(String),
+    #[error(transparent)]
+    Mutation(#[from] MutationError),
+    #[error("Operation {0}: {1}")]
+    InOperation(usize, Box<PatchError>),
+}
+
+impl From<IndexingError> for PatchError {
+    fn from(error: IndexingError) -> Self {
+        Self::Mutation(error.into())
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+#[cfg_attr(test, derive(PartialEq))]
+pub enum ConversionError {
+    #[error("Only strings, numbers, booleans and null can change type")]
+    NotScalar,
+    #[error("Cannot convert {value} to {to}")]
+    Invalid { value: String, to: &'static str },
+    #[error("Only strings can hold embedded JSON")]
+    NotString,
+    #[error("The string does not hold JSON: {0}")]
+    NotJson(String),
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum JobError {
+    #[error(transparent)]
+    IO(#[from] std::io::Error),
+    #[error("{name} job #{id} panicked: {message}")]
+    Panicked {
+        name: &'static str,
+        id: usize,
+        message: String,
+    },
+}
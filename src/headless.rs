@@ -0,0 +1,648 @@
+use std::{
+    fs,
+    io::{self, Write},
+};
+
+use clap::Subcommand;
+
+use crate::{
+    app::{atomic_file::AtomicFile, back_up, config::Config, read_file_head},
+    container::{
+        encoding::{Encoding, Indent},
+        join::{self, JoinKind},
+        node::Node,
+        parser::ParseOptions,
+        patch, pointer,
+    },
+    error::{IndexingError, LoadError, MutationError},
+};
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Join multiple JSON files into one document
+    Join {
+        /// Build an object keyed by file name instead of an array
+        #[arg(long)]
+        object: bool,
+        /// File to write the joined document to
+        output: String,
+        /// JSON files to join
+        #[arg(required = true)]
+        inputs: Vec<String>,
+    },
+    /// Apply an RFC 6902 JSON Patch, failing without writing when an operation or test fails
+    Apply {
+        /// JSON Patch to apply
+        #[arg(long)]
+        patch: String,
+        /// JSON file to patch
+        input: String,
+        /// File to write the patched document to. Defaults to overwrite the input file
+        #[arg(short, long)]
+        output: Option<String>,
+    },
+    /// Three-way merge JSON files, resolving conflicting paths interactively. Usable as a git
+    /// mergetool
+    Merge {
+        /// Common ancestor of both versions
+        base: String,
+        /// Our version
+        ours: String,
+        /// Their version
+        theirs: String,
+        /// File to write the merged document to. Defaults to overwrite our version
+        #[arg(short, long)]
+        output: Option<String>,
+    },
+    /// Print the value at a JSON Pointer, or a JSONPath starting with `$`
+    Get {
+        /// Where the value is, such as `/servers/0/port`
+        pointer: String,
+        /// JSON file to read
+        input: String,
+    },
+    /// Set the value at a JSON Pointer, adding its last key if missing, and print the edited
+    /// document unless told where to write it
+    Set {
+        /// Where the value is, such as `/servers/0/port`
+        pointer: String,
+        /// New value as JSON, such as `8080` or `'"text"'`
+        value: String,
+        /// JSON file to edit
+        input: String,
+        /// Create the missing objects and arrays on the way to the value
+        #[arg(long)]
+        create_parents: bool,
+        #[command(flatten)]
+        target: Target,
+    },
+    /// Delete the value at a JSON Pointer, printing the edited document unless told where to
+    /// write it
+    Delete {
+        /// Where the value is, such as `/servers/0`
+        pointer: String,
+        /// JSON file to edit
+        input: String,
+        #[command(flatten)]
+        target: Target,
+    },
+    /// Rewrite JSON files in place, formatted as saves write them
+    Fmt {
+        /// Spaces per level, `tab` or `auto` to keep each file's own, even if `minify` is set.
+        /// Defaults to the `indent` setting
+        #[arg(long, conflicts_with = "compact")]
+        indent: Option<Indent>,
+        /// Order the keys of every object alphabetically
+        #[arg(long)]
+        sort_keys: bool,
+        /// Write without any whitespace
+        #[arg(long)]
+        compact: bool,
+        /// Only list the files that are not formatted, failing if there are any
+        #[arg(long)]
+        check: bool,
+        /// JSON files to format
+        #[arg(required = true)]
+        inputs: Vec<String>,
+    },
+}
+
+/// Where `set` and `delete` write the edited document, stdout by default.
+#[derive(Debug, clap::Args)]
+pub struct Target {
+    /// Overwrite the input file
+    #[arg(short, long, conflicts_with = "output")]
+    in_place: bool,
+    /// File to write the edited document to
+    #[arg(short, long)]
+    output: Option<String>,
+}
+
+/// Runs a subcommand without starting the interactive editor.
+pub fn run(command: Command) -> io::Result<()> {
+    run_with(command, Config::load())
+}
+
+/// Runs a subcommand with `config` giving the parse options and how files are written.
+fn run_with(command: Command, config: Config) -> io::Result<()> {
+    match command {
+        Command::Join {
+            object,
+            output,
+            inputs,
+        } => {
+            let kind = if object {
+                JoinKind::Object
+            } else {
+                JoinKind::Array
+            };
+            join(&inputs, &output, kind, &config)
+        }
+        Command::Apply {
+            patch,
+            input,
+            output,
+        } => apply(&patch, &input, output.as_deref().unwrap_or(&input), &config),
+        Command::Get { pointer, input } => {
+            let value = get(&pointer, &input, &config)?;
+            writeln!(io::stdout(), "{value}")
+        }
+        Command::Set {
+            pointer,
+            value,
+            input,
+            create_parents,
+            target,
+        } => {
+            let value = Node::load(value.as_bytes()).map_err(|error| {
+                io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("Invalid value: {error}"),
+                )
+            })?;
+            let selector = pointer::from_query(&pointer)
+                .map_err(|error| io::Error::new(io::ErrorKind::InvalidInput, error.to_string()))?;
+            edit(&input, &target, &config, |node| {
+                node.set(&selector, value, create_parents)
+                    .map(|_| ())
+                    .map_err(|error| {
+                        let kind = match error {
+                            MutationError::Indexing(IndexingError::MissingKey(_)) => {
+                                io::ErrorKind::NotFound
+                            }
+                            _ => io::ErrorKind::InvalidInput,
+                        };
+                        io::Error::new(kind, error.to_string())
+                    })
+            })
+        }
+        Command::Delete {
+            pointer,
+            input,
+            target,
+        } => edit(&input, &target, &config, |node| {
+            let selector = node
+                .resolve_pointer(&pointer)
+                .map_err(|error| io::Error::new(io::ErrorKind::NotFound, error.to_string()))?;
+            node.delete(&selector)
+                .map(|_| ())
+                .map_err(|error| io::Error::new(io::ErrorKind::InvalidInput, error.to_string()))
+        }),
+        Command::Fmt {
+            indent,
+            sort_keys,
+            compact,
+            check,
+            inputs,
+        } => {
+            let config = fmt_config(config, indent, compact);
+            fmt(&inputs, &config, sort_keys, check)
+        }
+        Command::Merge { .. } => unreachable!("merge runs interactively"),
+    }
+}
+
+/// Loads `path`, naming it in the errors that are not about reading it.
+fn load(path: &str, parse_options: &ParseOptions) -> io::Result<Node> {
+    Node::load_file(path, parse_options).map_err(|error| match error {
+        LoadError::IO(error) => error,
+        error => io::Error::new(
+            io::ErrorKind::InvalidData,
+            LoadError::InFile(path.to_string(), Box::new(error)).to_string(),
+        ),
+    })
+}
+
+/// Joins `inputs` into `output`, formatted the way the editor would save over it.
+fn join(inputs: &[String], output: &str, kind: JoinKind, config: &Config) -> io::Result<()> {
+    let node = join::join(inputs, kind, &config.parse_options()).map_err(|error| match error {
+        crate::error::LoadError::IO(error) => error,
+        error => io::Error::new(io::ErrorKind::InvalidData, error.to_string()),
+    })?;
+    write_output(output, &saved_content(&node, output, config), config)
+}
+
+/// Applies `patch` to `input` and writes it to `output`, formatted the way the editor saves
+/// `input`.
+fn apply(patch: &str, input: &str, output: &str, config: &Config) -> io::Result<()> {
+    let parse_options = config.parse_options();
+    let operations = patch::parse(&load(patch, &parse_options)?)
+        .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error.to_string()))?;
+    let mut node = load(input, &parse_options)?;
+    patch::apply(&mut node, &operations).map_err(io::Error::other)?;
+    write_output(output, &saved_content(&node, input, config), config)
+}
+
+fn get(pointer: &str, input: &str, config: &Config) -> io::Result<String> {
+    let node = load(input, &config.parse_options())?;
+    let selector = node
+        .resolve_pointer(pointer)
+        .map_err(|error| io::Error::new(io::ErrorKind::NotFound, error.to_string()))?;
+    Ok(node
+        .subtree(&selector)
+        .expect("resolved selector")
+        .to_string_pretty()
+        .expect("invalid internal representation"))
+}
+
+/// Applies `mutation` to `input` and writes the result to `target`, formatted the way the editor
+/// saves it. Nothing is written when `mutation` fails.
+fn edit(
+    input: &str,
+    target: &Target,
+    config: &Config,
+    mutation: impl FnOnce(&mut Node) -> io::Result<()>,
+) -> io::Result<()> {
+    let mut node = load(input, &config.parse_options())?;
+    mutation(&mut node)?;
+
+    let content = saved_content(&node, input, config);
+    let output = target
+        .output
+        .as_deref()
+        .or(target.in_place.then_some(input));
+    match output {
+        Some(output) => write_output(output, &content, config),
+        None => io::stdout().write_all(&content),
+    }
+}
+
+/// Writes `content` over `output` as interactive saves do: through a temporary file renamed over
+/// it, after copying it to `output.bak` when the `backup` setting is on.
+fn write_output(output: &str, content: &[u8], config: &Config) -> io::Result<()> {
+    if config.backup {
+        back_up(output)?;
+    }
+    let mut file = AtomicFile::create(output)?;
+    file.write_all(content)?;
+    file.commit()
+}
+
+/// `config` with the formatting given to `fmt`, where `--indent` wins over the `minify` setting.
+fn fmt_config(mut config: Config, indent: Option<Indent>, compact: bool) -> Config {
+    if let Some(indent) = indent {
+        config.indent = indent;
+        config.minify = false;
+    }
+    config.minify |= compact;
+    config
+}
+
+/// Rewrites each of `inputs` that is not formatted, or only lists them with `check`.
+fn fmt(inputs: &[String], config: &Config, sort_keys: bool, check: bool) -> io::Result<()> {
+    let parse_options = config.parse_options();
+    let mut n_unformatted = 0;
+    for input in inputs {
+        let mut node = load(input, &parse_options)?;
+        if sort_keys {
+            node = node.sorted_keys();
+        }
+        let content = saved_content(&node, input, config);
+        if fs::read(input)? == content {
+            continue;
+        }
+        if check {
+            writeln!(io::stdout(), "{input}")?;
+        } else {
+            write_output(input, &content, config)?;
+        }
+        n_unformatted += 1;
+    }
+    if check && n_unformatted > 0 {
+        return Err(io::Error::other(format!(
+            "Files not formatted: {n_unformatted}"
+        )));
+    }
+    Ok(())
+}
+
+/// `node` as interactive saves write it over `input`: with its indentation and line endings,
+/// and its encoding when `preserve_encoding` is set.
+fn saved_content(node: &Node, input: &str, config: &Config) -> Vec<u8> {
+    let (encoding, head) = read_file_head(input);
+    let encoding = if config.preserve_encoding {
+        encoding
+    } else {
+        Encoding::default()
+    };
+    let content = if config.minify {
+        node.to_string_compact()
+    } else {
+        node.to_string_indented(config.indent.resolve(&head))
+    }
+    .expect("invalid internal representation");
+    encoding.encode(&config.line_ending.resolve(&head).apply(&content))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn join_test() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = |name: &str| dir.path().join(name).to_str().unwrap().to_string();
+        fs::write(path("a.json"), "1").unwrap();
+        fs::write(path("b.json"), r#""b""#).unwrap();
+        let join = |object: bool| {
+            run_with(
+                Command::Join {
+                    object,
+                    output: path("out.json"),
+                    inputs: vec![path("a.json"), path("b.json")],
+                },
+                Config::default(),
+            )
+            .unwrap();
+            fs::read_to_string(path("out.json")).unwrap()
+        };
+
+        assert_eq!(join(true), "{\n  \"a\": 1,\n  \"b\": \"b\"\n}");
+        // An existing output keeps its indentation and line endings.
+        fs::write(path("out.json"), "[\r\n    0\r\n]").unwrap();
+        assert_eq!(join(false), "[\r\n    1,\r\n    \"b\"\r\n]");
+
+        let error = run_with(
+            Command::Join {
+                object: false,
+                output: path("out.json"),
+                inputs: vec![path("missing.json")],
+            },
+            Config::default(),
+        )
+        .unwrap_err();
+        assert!(error.to_string().contains("missing.json"));
+    }
+
+    #[test]
+    fn apply_test() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = |name: &str| dir.path().join(name).to_str().unwrap().to_string();
+        fs::write(path("input.json"), "{\r\n\t\"a\": 1\r\n}").unwrap();
+        fs::write(
+            path("patch.json"),
+            r#"[{"op": "test", "path": "/a", "value": 1}, {"op": "add", "path": "/b", "value": 2}]"#,
+        )
+        .unwrap();
+        fs::write(
+            path("failing.json"),
+            r#"[{"op": "remove", "path": "/a"}, {"op": "test", "path": "/a", "value": 1}]"#,
+        )
+        .unwrap();
+
+        run_with(
+            Command::Apply {
+                patch: path("patch.json"),
+                input: path("input.json"),
+                output: Some(path("out.json")),
+            },
+            Config::default(),
+        )
+        .unwrap();
+        assert_eq!(
+            fs::read_to_string(path("out.json")).unwrap(),
+            "{\r\n\t\"a\": 1,\r\n\t\"b\": 2\r\n}"
+        );
+
+        let error = run_with(
+            Command::Apply {
+                patch: path("failing.json"),
+                input: path("input.json"),
+                output: None,
+            },
+            Config::default(),
+        )
+        .unwrap_err();
+        assert_eq!(error.to_string(), "Operation 1: Missing key: a");
+        assert_eq!(
+            fs::read_to_string(path("input.json")).unwrap(),
+            "{\r\n\t\"a\": 1\r\n}"
+        );
+    }
+
+    #[test]
+    fn get_test() {
+        let dir = tempfile::tempdir().unwrap();
+        let input = dir.path().join("input.json").to_str().unwrap().to_string();
+        fs::write(&input, r#"{"servers": [{"port": 80, "tags": ["a"]}]}"#).unwrap();
+
+        assert_eq!(
+            get("/servers/0/port", &input, &Config::default()).unwrap(),
+            "80"
+        );
+        assert_eq!(
+            get("$.servers[0].tags", &input, &Config::default()).unwrap(),
+            "[\n  \"a\"\n]"
+        );
+        assert_eq!(
+            get("", &input, &Config::default()).unwrap(),
+            get("$", &input, &Config::default()).unwrap()
+        );
+
+        let error = get("/servers/1", &input, &Config::default()).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::NotFound);
+        assert_eq!(error.to_string(), "Missing key: 1");
+        let error = get("servers", &input, &Config::default()).unwrap_err();
+        assert_eq!(error.to_string(), "Invalid JSON Pointer: servers");
+    }
+
+    #[test]
+    fn set_delete_test() {
+        let dir = tempfile::tempdir().unwrap();
+        let input = dir.path().join("input.json").to_str().unwrap().to_string();
+        let output = dir.path().join("out.json").to_str().unwrap().to_string();
+        fs::write(&input, "{\r\n    \"a\": [1, 2],\r\n    \"b\": true\r\n}").unwrap();
+
+        run_with(
+            Command::Set {
+                pointer: String::from("/a/1"),
+                value: String::from(r#"{"c": null}"#),
+                input: input.clone(),
+                create_parents: false,
+                target: Target {
+                    in_place: false,
+                    output: Some(output.clone()),
+                },
+            },
+            Config::default(),
+        )
+        .unwrap();
+        assert_eq!(
+            fs::read_to_string(&output).unwrap(),
+            concat!(
+                "{\r\n",
+                "    \"a\": [\r\n",
+                "        1,\r\n",
+                "        {\r\n",
+                "            \"c\": null\r\n",
+                "        }\r\n",
+                "    ],\r\n",
+                "    \"b\": true\r\n",
+                "}"
+            )
+        );
+
+        run_with(
+            Command::Delete {
+                pointer: String::from("$.a"),
+                input: input.clone(),
+                target: Target {
+                    in_place: true,
+                    output: None,
+                },
+            },
+            Config::default(),
+        )
+        .unwrap();
+        assert_eq!(
+            fs::read_to_string(&input).unwrap(),
+            "{\r\n    \"b\": true\r\n}"
+        );
+
+        let set = |pointer: &str, value: &str, create_parents: bool| {
+            run_with(
+                Command::Set {
+                    pointer: String::from(pointer),
+                    value: String::from(value),
+                    input: input.clone(),
+                    create_parents,
+                    target: Target {
+                        in_place: false,
+                        output: Some(output.clone()),
+                    },
+                },
+                Config::default(),
+            )
+            .map(|()| fs::read_to_string(&output).unwrap())
+        };
+        assert_eq!(
+            set("/c", "1", false).unwrap(),
+            "{\r\n    \"b\": true,\r\n    \"c\": 1\r\n}"
+        );
+        assert_eq!(
+            set("$.a.b.c", "1", true).unwrap(),
+            concat!(
+                "{\r\n",
+                "    \"b\": true,\r\n",
+                "    \"a\": {\r\n",
+                "        \"b\": {\r\n",
+                "            \"c\": 1\r\n",
+                "        }\r\n",
+                "    }\r\n",
+                "}"
+            )
+        );
+        assert_eq!(
+            set("/a/b/c", "1", false).unwrap_err().kind(),
+            io::ErrorKind::NotFound
+        );
+        assert_eq!(
+            set("/b/c", "1", true).unwrap_err().kind(),
+            io::ErrorKind::InvalidInput
+        );
+        assert_eq!(
+            set("/b", "nope", false).unwrap_err().kind(),
+            io::ErrorKind::InvalidInput
+        );
+        assert_eq!(
+            set("b", "1", false).unwrap_err().kind(),
+            io::ErrorKind::InvalidInput
+        );
+        let error = run_with(
+            Command::Delete {
+                pointer: String::new(),
+                input: input.clone(),
+                target: Target {
+                    in_place: true,
+                    output: None,
+                },
+            },
+            Config::default(),
+        )
+        .unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidInput);
+        assert_eq!(
+            fs::read_to_string(&input).unwrap(),
+            "{\r\n    \"b\": true\r\n}"
+        );
+    }
+
+    #[test]
+    fn write_output_test() {
+        let dir = tempfile::tempdir().unwrap();
+        let output = dir.path().join("out.json").to_str().unwrap().to_string();
+        let backup = Config {
+            backup: true,
+            ..Config::default()
+        };
+
+        // Nothing to back up yet.
+        write_output(&output, b"1", &backup).unwrap();
+        assert_eq!(fs::read_to_string(&output).unwrap(), "1");
+        write_output(&output, b"2", &backup).unwrap();
+        assert_eq!(fs::read_to_string(&output).unwrap(), "2");
+        assert_eq!(fs::read_to_string(format!("{output}.bak")).unwrap(), "1");
+        write_output(&output, b"3", &Config::default()).unwrap();
+        assert_eq!(fs::read_to_string(format!("{output}.bak")).unwrap(), "1");
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 2);
+    }
+
+    #[test]
+    fn fmt_test() {
+        let dir = tempfile::tempdir().unwrap();
+        let a = dir.path().join("a.json").to_str().unwrap().to_string();
+        let b = dir.path().join("b.json").to_str().unwrap().to_string();
+        fs::write(&a, r#"{"b": 1, "a": [true]}"#).unwrap();
+        fs::write(&b, "{\n    \"b\": 1\n}").unwrap();
+        let fmt = |indent: Option<Indent>, sort_keys: bool, compact: bool, check: bool| {
+            run_with(
+                Command::Fmt {
+                    indent,
+                    sort_keys,
+                    compact,
+                    check,
+                    inputs: vec![a.clone(), b.clone()],
+                },
+                Config::default(),
+            )
+        };
+
+        let error = fmt(None, true, false, true).unwrap_err();
+        assert_eq!(error.to_string(), "Files not formatted: 1");
+        assert_eq!(fs::read_to_string(&a).unwrap(), r#"{"b": 1, "a": [true]}"#);
+
+        fmt(None, true, false, false).unwrap();
+        assert_eq!(
+            fs::read_to_string(&a).unwrap(),
+            "{\n  \"a\": [\n    true\n  ],\n  \"b\": 1\n}"
+        );
+        assert_eq!(fs::read_to_string(&b).unwrap(), "{\n    \"b\": 1\n}");
+        fmt(None, true, false, true).unwrap();
+
+        fmt(Some(Indent::Tab), false, false, false).unwrap();
+        assert_eq!(fs::read_to_string(&b).unwrap(), "{\n\t\"b\": 1\n}");
+        fmt(None, false, true, false).unwrap();
+        assert_eq!(fs::read_to_string(&a).unwrap(), r#"{"a":[true],"b":1}"#);
+
+        // Rewritten files are backed up when asked to, and no temporary file is left behind.
+        let backup = Config {
+            backup: true,
+            ..Config::default()
+        };
+        fs::write(&a, "[1,\n2]").unwrap();
+        super::fmt(std::slice::from_ref(&a), &backup, false, false).unwrap();
+        assert_eq!(fs::read_to_string(&a).unwrap(), "[\n  1,\n  2\n]");
+        assert_eq!(fs::read_to_string(format!("{a}.bak")).unwrap(), "[1,\n2]");
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 3);
+
+        let minify = || Config {
+            minify: true,
+            ..Config::default()
+        };
+        let config = fmt_config(minify(), Some(Indent::Spaces(3)), false);
+        assert!(!config.minify);
+        assert_eq!(config.indent, Indent::Spaces(3));
+        assert!(fmt_config(minify(), None, false).minify);
+        assert!(fmt_config(Config::default(), None, true).minify);
+    }
+}
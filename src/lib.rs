@@ -0,0 +1,11 @@
+mod app;
+mod container;
+mod error;
+pub mod headless;
+
+#[cfg(test)]
+mod fixtures;
+
+#[cfg(feature = "testing")]
+pub use app::testing;
+pub use app::{CliApp, embed};
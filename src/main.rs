@@ -1,30 +1,115 @@
-mod app;
-mod container;
-mod error;
+use std::{
+    io::{self, IsTerminal},
+    time::Duration,
+};
 
-#[cfg(test)]
-mod fixtures;
-
-use std::io;
-
-use app::CliApp;
 use clap::Parser;
+use jedit::{
+    CliApp,
+    headless::{self, Command},
+};
 
 /// View and edit JSON file
 #[derive(Debug, Parser)]
-#[command(version, about)]
+#[command(version, about, args_conflicts_with_subcommands = true)]
 struct Args {
-    /// Output file to write to. Defaults to overwrite the input file
+    #[command(subcommand)]
+    command: Option<Command>,
+    /// Output file to write to, or `-` for stdout on exit. Defaults to overwrite the input file
     #[arg(short, long)]
     output: Option<String>,
-    /// JSON file to edit
-    input: String,
+    /// Never run external programs such as `$EDITOR`; edit nodes inline instead
+    #[arg(long)]
+    no_shell: bool,
+    /// Save without copying the output file to `file.json.bak` first, even if `backup` is set
+    #[arg(long)]
+    no_backup: bool,
+    /// Save without any whitespace, even if `minify` is not set
+    #[arg(long)]
+    minify: bool,
+    /// Tail newline-delimited JSON into a root array as the input grows; never saves over it
+    #[arg(long, requires = "inputs")]
+    follow: bool,
+    /// Reload the input when another program changes it, asking first if there are unsaved edits
+    #[arg(long, requires = "inputs", conflicts_with = "follow")]
+    watch: bool,
+    /// Mark where the input differs from this JSON file, with a side-by-side preview
+    #[arg(long, value_name = "FILE")]
+    diff: Option<String>,
+    /// JSON Schema to validate the document against with `V`
+    #[arg(long, value_name = "FILE")]
+    schema: Option<String>,
+    /// Type the keys recorded in a JSON array such as `["j", "Enter", "Ctrl+d"]`
+    #[arg(long, value_name = "FILE")]
+    replay: Option<String>,
+    /// Milliseconds between replayed keys
+    #[arg(long, value_name = "MS", default_value_t = 500, requires = "replay")]
+    replay_interval: u64,
+    /// JSON file to edit, `-` for stdin, or `archive.zip!/inner.json` to open a member of a zip or
+    /// tar archive read-only. Reads stdin when it is piped, or else starts with an empty scratch
+    /// document when omitted. Further files open in tabs
+    #[arg(value_name = "INPUT")]
+    inputs: Vec<String>,
 }
 
 fn main() -> io::Result<()> {
     let args = Args::parse();
 
-    let output = args.output.unwrap_or(args.input.clone());
-    let app = Box::leak(Box::new(CliApp::new(args.input, output)?));
+    let app = match args.command {
+        Some(Command::Merge {
+            base,
+            ours,
+            theirs,
+            output,
+        }) => CliApp::merge(base, ours, theirs, output)?,
+        Some(command) => return headless::run(command),
+        None if args.inputs.len() > 1
+            && (args.output.is_some() || args.follow || args.diff.is_some()) =>
+        {
+            return Err(io::Error::other(
+                "--output, --follow and --diff need a single input",
+            ));
+        }
+        None if args.inputs.first().map(String::as_str) == Some("-")
+            || (args.inputs.is_empty() && !io::stdin().is_terminal()) =>
+        {
+            let Some(output) = args.output else {
+                return Err(io::Error::other("Reading from stdin needs --output"));
+            };
+            if args.follow || args.watch {
+                return Err(io::Error::other("--follow and --watch need a file"));
+            }
+            CliApp::from_reader(Box::new(io::stdin()), output, args.no_shell)?
+        }
+        None => {
+            let mut inputs = args.inputs.into_iter();
+            let input = inputs.next();
+            let watched = input.clone().filter(|_| args.watch);
+            let mut app = CliApp::new(input, args.output, args.no_shell, args.follow)?;
+            if let Some(input) = watched {
+                app.watch(input);
+            }
+            for input in inputs {
+                app.open(input, args.watch)?;
+            }
+            app
+        }
+    };
+    let app = Box::leak(Box::new(app));
+    if args.no_backup {
+        app.disable_backup();
+    }
+    if args.minify {
+        app.minify();
+    }
+    if let Some(other) = args.diff {
+        app.compare(other);
+    }
+    if let Some(schema) = &args.schema {
+        app.schema(schema)?;
+    }
+    if let Some(replay) = &args.replay {
+        app.replay(replay, Duration::from_millis(args.replay_interval))?;
+    }
     app.run()
 }